@@ -0,0 +1,91 @@
+//! Seeded graph generation shared by the benches. Everything is derived from
+//! a fixed seed so two runs measure the same graphs.
+
+use bit_graph::Graph;
+
+pub const SEED: u64 = 0x5eed_0b17_6a2f;
+
+/// splitmix64, enough randomness for picking edges and cheap to step
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// uniform enough below `n` for graphs far smaller than 2⁶⁴ nodes
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+}
+
+/// `count` node pairs picked uniformly, repeats and self loops included
+pub fn random_pairs(node_count: usize, count: usize, seed: u64) -> Vec<(usize, usize)> {
+    let mut rng = Rng::new(seed);
+    (0..count)
+        .map(|_| (rng.below(node_count), rng.below(node_count)))
+        .collect()
+}
+
+/// `degree` random edges out of every node, the shape of a sparse graph
+pub fn sparse_edges(node_count: usize, degree: usize, seed: u64) -> Vec<(usize, usize)> {
+    let mut rng = Rng::new(seed);
+    (0..node_count)
+        .flat_map(|from| (0..degree).map(move |_| from))
+        .map(|from| (from, rng.below(node_count)))
+        .collect()
+}
+
+/// `from -> (from + 1) * step` for every node and step, wrapping at the
+/// last node. Consecutive edges land far apart in the transpose's columns.
+pub fn strided_edges(node_count: usize, steps: usize) -> Vec<(usize, usize)> {
+    (1..=steps)
+        .flat_map(|step| (0..node_count).map(move |from| (from, (from + 1) * step % node_count)))
+        .collect()
+}
+
+/// every ordered pair of nodes, row by row
+pub fn all_pairs(node_count: usize) -> Vec<(usize, usize)> {
+    (0..node_count)
+        .flat_map(|from| (0..node_count).map(move |to| (from, to)))
+        .collect()
+}
+
+/// every pair kept with probability `percent / 100`
+pub fn dense_edges(node_count: usize, percent: u64, seed: u64) -> Vec<(usize, usize)> {
+    let mut rng = Rng::new(seed);
+    let mut edges = Vec::new();
+    for from in 0..node_count {
+        for to in 0..node_count {
+            if rng.next_u64() % 100 < percent {
+                edges.push((from, to));
+            }
+        }
+    }
+    edges
+}
+
+/// pushes `node_count` nodes valued by index onto `graph`
+pub fn with_nodes<G: Graph<u64, W>, W>(mut graph: G, node_count: usize) -> G {
+    for i in 0..node_count {
+        graph.push_node(i as u64);
+    }
+    graph
+}
+
+/// `with_nodes` and then every edge of `edges`
+pub fn build<G: Graph<u64, W>, W>(graph: G, node_count: usize, edges: &[(usize, usize)]) -> G {
+    let mut graph = with_nodes(graph, node_count);
+    for &(from, to) in edges {
+        graph.add_edge(from, to);
+    }
+    graph
+}
@@ -0,0 +1,272 @@
+//! Compares the graph implementations on the same seeded inputs.
+//!
+//! Besides `bit`, `adj` and `hash` a few `BitGraph` variants are measured
+//! next to each other:
+//!
+//! - `bit/aligned` is `with_capacity_aligned`, rows padded to whole words
+//! - `bit/directed_only` skips the transpose, so inserting shows what keeping
+//!   it up to date costs and incoming edges fall back to a column scan. The
+//!   `transpose` group repeats that on 8192 nodes, where the column writes
+//!   miss cache.
+//! - `bit/vec`, `bit/into` and `bit/lazy` walk the same rows through
+//!   `outgoing_edges_of`, `outgoing_edges_into` and the lazy
+//!   `outgoing_edges_unchecked`
+//!
+//! `has_edge/scan` asks about every pair of a 1000 node graph in order, whose
+//! packed rows straddle words and aligned ones do not. `AdjGraph` stores a
+//! byte per pair, so it is left out of the 100k node BFS.
+
+mod bench_util;
+
+use bench_util::{
+    all_pairs, build, dense_edges, random_pairs, sparse_edges, strided_edges, with_nodes, SEED,
+};
+use bit_graph::hash::HashGraph;
+use bit_graph::search::bfs::BFS;
+use bit_graph::{AdjGraph, BitGraph, Graph};
+use criterion::measurement::WallTime;
+use criterion::{
+    criterion_group, criterion_main, BatchSize, BenchmarkGroup, BenchmarkId, Criterion, Throughput,
+};
+use std::hint::black_box;
+
+const SIZES: [usize; 2] = [1_000, 4_000];
+const DEGREE: usize = 8;
+const PROBES: usize = 10_000;
+
+fn bench_insert<G, W>(
+    group: &mut BenchmarkGroup<WallTime>,
+    name: &str,
+    edges: &[(usize, usize)],
+    node_count: usize,
+    make: impl Fn(usize) -> G,
+) where
+    G: Graph<u64, W>,
+{
+    group.bench_with_input(BenchmarkId::new(name, node_count), edges, |b, edges| {
+        b.iter_batched(
+            || with_nodes(make(node_count), node_count),
+            |mut graph| {
+                for &(from, to) in edges {
+                    graph.add_edge(from, to);
+                }
+                graph
+            },
+            BatchSize::LargeInput,
+        )
+    });
+}
+
+fn add_edge(c: &mut Criterion) {
+    let mut group = c.benchmark_group("add_edge");
+    for &n in &SIZES {
+        let edges = sparse_edges(n, DEGREE, SEED);
+        group.throughput(Throughput::Elements(edges.len() as u64));
+
+        bench_insert(&mut group, "bit", &edges, n, BitGraph::with_capacity);
+        bench_insert(
+            &mut group,
+            "bit/aligned",
+            &edges,
+            n,
+            BitGraph::with_capacity_aligned,
+        );
+        bench_insert(
+            &mut group,
+            "bit/directed_only",
+            &edges,
+            n,
+            BitGraph::with_capacity_directed_only,
+        );
+        bench_insert(&mut group, "adj", &edges, n, AdjGraph::with_capacity);
+        bench_insert(&mut group, "hash", &edges, n, HashGraph::with_capacity);
+    }
+    group.finish();
+}
+
+fn transpose(c: &mut Criterion) {
+    let mut group = c.benchmark_group("transpose");
+    group.sample_size(10);
+
+    let n = 8192;
+    let edges = strided_edges(n, 63);
+    group.throughput(Throughput::Elements(edges.len() as u64));
+    bench_insert(&mut group, "bit", &edges, n, BitGraph::with_capacity);
+    bench_insert(
+        &mut group,
+        "bit/directed_only",
+        &edges,
+        n,
+        BitGraph::with_capacity_directed_only,
+    );
+    group.finish();
+}
+
+fn bench_probes<G: Graph<u64, W>, W>(
+    group: &mut BenchmarkGroup<WallTime>,
+    name: &str,
+    graph: &G,
+    probes: &[(usize, usize)],
+) {
+    let n = graph.node_count();
+    group.bench_with_input(BenchmarkId::new(name, n), probes, |b, probes| {
+        b.iter(|| {
+            probes
+                .iter()
+                .filter(|&&(from, to)| graph.has_edge(from, to))
+                .count()
+        })
+    });
+}
+
+fn has_edge(c: &mut Criterion) {
+    let mut group = c.benchmark_group("has_edge");
+    group.throughput(Throughput::Elements(PROBES as u64));
+    for &n in &SIZES {
+        let edges = sparse_edges(n, DEGREE, SEED);
+        let probes = random_pairs(n, PROBES, SEED + 1);
+
+        let bit = build(BitGraph::with_capacity(n), n, &edges);
+        bench_probes(&mut group, "bit", &bit, &probes);
+        let aligned = build(BitGraph::with_capacity_aligned(n), n, &edges);
+        bench_probes(&mut group, "bit/aligned", &aligned, &probes);
+        let adj = build(AdjGraph::with_capacity(n), n, &edges);
+        bench_probes(&mut group, "adj", &adj, &probes);
+        let hash = build(HashGraph::with_capacity(n), n, &edges);
+        bench_probes(&mut group, "hash", &hash, &probes);
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("has_edge/scan");
+    let n = 1_000;
+    let edges = sparse_edges(n, DEGREE, SEED);
+    let pairs = all_pairs(n);
+    group.throughput(Throughput::Elements(pairs.len() as u64));
+
+    let bit = build(BitGraph::with_capacity(n), n, &edges);
+    bench_probes(&mut group, "bit", &bit, &pairs);
+    let aligned = build(BitGraph::with_capacity_aligned(n), n, &edges);
+    bench_probes(&mut group, "bit/aligned", &aligned, &pairs);
+    group.finish();
+}
+
+/// sums the neighbours of every node through `outgoing_edges_into`
+fn bench_into<G: Graph<u64, W>, W>(
+    group: &mut BenchmarkGroup<WallTime>,
+    name: &str,
+    graph: &G,
+    incoming: bool,
+) {
+    let n = graph.node_count();
+    group.bench_function(BenchmarkId::new(name, n), |b| {
+        let mut out = Vec::new();
+        b.iter(|| {
+            let mut sum = 0;
+            for node in 0..n {
+                if incoming {
+                    graph.incoming_edges_into(node, &mut out);
+                } else {
+                    graph.outgoing_edges_into(node, &mut out);
+                }
+                sum += out.iter().sum::<usize>();
+            }
+            sum
+        })
+    });
+}
+
+fn neighbors(c: &mut Criterion) {
+    let mut group = c.benchmark_group("neighbors");
+    for &n in &SIZES {
+        let edges = sparse_edges(n, DEGREE, SEED);
+        let bit = build(BitGraph::with_capacity(n), n, &edges);
+        let aligned = build(BitGraph::with_capacity_aligned(n), n, &edges);
+
+        group.bench_function(BenchmarkId::new("bit/vec", n), |b| {
+            b.iter(|| {
+                (0..n)
+                    .map(|node| bit.outgoing_edges_of(node).iter().sum::<usize>())
+                    .sum::<usize>()
+            })
+        });
+        bench_into(&mut group, "bit/into", &bit, false);
+        for (name, graph) in [("bit/lazy", &bit), ("bit/aligned/lazy", &aligned)] {
+            // SAFETY: every node is below the capacity
+            let row = |node| unsafe { graph.outgoing_edges_unchecked(node) }.sum::<usize>();
+            group.bench_function(BenchmarkId::new(name, n), |b| {
+                b.iter(|| (0..n).map(row).sum::<usize>())
+            });
+        }
+
+        bench_into(
+            &mut group,
+            "adj/into",
+            &build(AdjGraph::with_capacity(n), n, &edges),
+            false,
+        );
+        bench_into(
+            &mut group,
+            "hash/into",
+            &build(HashGraph::with_capacity(n), n, &edges),
+            false,
+        );
+
+        // the transpose against a scan down the column
+        bench_into(&mut group, "bit/incoming", &bit, true);
+        let directed_only = build(BitGraph::with_capacity_directed_only(n), n, &edges);
+        bench_into(
+            &mut group,
+            "bit/directed_only/incoming",
+            &directed_only,
+            true,
+        );
+    }
+    group.finish();
+}
+
+fn bench_bfs<G: Graph<u64, W>, W>(group: &mut BenchmarkGroup<WallTime>, name: &str, graph: &G) {
+    let n = graph.node_count();
+    group.bench_function(BenchmarkId::new(name, n), |b| {
+        b.iter(|| BFS::new(graph, black_box(0)).distances(graph))
+    });
+}
+
+fn bfs(c: &mut Criterion) {
+    let mut group = c.benchmark_group("bfs");
+    group.sample_size(10);
+
+    // sparse, 100k nodes with 5 edges each, the bit matrix alone is 1.25 GB
+    let n = 100_000;
+    let edges = sparse_edges(n, 5, SEED);
+    let bit = build(BitGraph::with_capacity_directed_only(n), n, &edges);
+    bench_bfs(&mut group, "sparse/bit/directed_only", &bit);
+    drop(bit);
+    bench_bfs(
+        &mut group,
+        "sparse/hash",
+        &build(HashGraph::with_capacity(n), n, &edges),
+    );
+
+    // dense, 2k nodes with every pair kept 1 in 4 times
+    let n = 2_000;
+    let edges = dense_edges(n, 25, SEED);
+    bench_bfs(
+        &mut group,
+        "dense/bit",
+        &build(BitGraph::with_capacity(n), n, &edges),
+    );
+    bench_bfs(
+        &mut group,
+        "dense/adj",
+        &build(AdjGraph::with_capacity(n), n, &edges),
+    );
+    bench_bfs(
+        &mut group,
+        "dense/hash",
+        &build(HashGraph::with_capacity(n), n, &edges),
+    );
+    group.finish();
+}
+
+criterion_group!(benches, add_edge, transpose, has_edge, neighbors, bfs);
+criterion_main!(benches);
@@ -1,15 +1,79 @@
 const DEFAULT_CAPACITY: usize = 16;
 
-use crate::{EdgeMeta, Graph};
+use crate::integrity::{check_count, into_result, IntegrityError};
+use crate::memory::{vec_bytes, MemoryBreakdown};
+use crate::{assert_node, cover_nodes, EdgeMeta, Graph};
+use std::iter::FromIterator;
 
 pub struct AdjGraph {
     count: usize,
+    /// row length of both matrices, the most nodes that fit before a `grow`
+    dim: usize,
 
     nodes: Vec<u64>,
     edges: Vec<u8>,
     edges_transpose: Vec<u8>,
 }
 
+impl Default for AdjGraph {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl_node_access!(impl for AdjGraph, u64);
+
+/// Adds every `(from, to)` pair with weight 1, pushing nodes valued `0` until
+/// both ends are nodes. The matrices double in size when full.
+impl Extend<(usize, usize)> for AdjGraph {
+    fn extend<I: IntoIterator<Item = (usize, usize)>>(&mut self, iter: I) {
+        for (from, to) in iter {
+            cover_nodes(self, from, to);
+            self.add_edge(from, to);
+        }
+    }
+}
+
+/// same as the unweighted `extend`, a weight of 0 removes the edge
+impl Extend<(usize, usize, u8)> for AdjGraph {
+    fn extend<I: IntoIterator<Item = (usize, usize, u8)>>(&mut self, iter: I) {
+        for (from, to, weight) in iter {
+            cover_nodes(self, from, to);
+            self.set_edge((from, to), weight);
+        }
+    }
+}
+
+impl FromIterator<(usize, usize)> for AdjGraph {
+    fn from_iter<I: IntoIterator<Item = (usize, usize)>>(iter: I) -> Self {
+        let mut graph = AdjGraph::new();
+        graph.extend(iter);
+        graph
+    }
+}
+
+impl FromIterator<(usize, usize, u8)> for AdjGraph {
+    fn from_iter<I: IntoIterator<Item = (usize, usize, u8)>>(iter: I) -> Self {
+        let mut graph = AdjGraph::new();
+        graph.extend(iter);
+        graph
+    }
+}
+
+/// equal when node values and edge weights match, capacity is ignored
+impl PartialEq for AdjGraph {
+    fn eq(&self, other: &Self) -> bool {
+        self.count == other.count
+            && self.nodes == other.nodes
+            && (0..self.count).all(|node| {
+                let row = self.cell(node, 0);
+                let other_row = other.cell(node, 0);
+
+                self.edges[row..row + self.count] == other.edges[other_row..other_row + self.count]
+            })
+    }
+}
+
 impl AdjGraph {
     pub fn new() -> Self {
         Self::with_capacity(DEFAULT_CAPACITY)
@@ -18,6 +82,7 @@ impl AdjGraph {
     pub fn with_capacity(size: usize) -> Self {
         Self {
             count: 0,
+            dim: size,
 
             nodes: Vec::with_capacity(size),
             edges: vec![0; size * size],
@@ -25,44 +90,144 @@ impl AdjGraph {
         }
     }
 
-    fn set_edge_of_both(&mut self, from: usize, to: usize, val: u8) -> u8 {
-        // get proper word
-        let row = self.nodes.capacity() * from;
-        let column = to;
+    /// Checks that both matrices hold the same weight for every pair and
+    /// that no weight is set past the last node.
+    pub fn validate(&self) -> Result<(), Vec<IntegrityError>> {
+        let mut errors = Vec::new();
+        check_count(self.count, self.nodes.len(), &mut errors);
+
+        for from in 0..self.dim {
+            for to in 0..self.dim {
+                let weight = self.edges[from * self.dim + to];
+                let transposed = self.edges_transpose[to * self.dim + from];
+
+                if (weight != 0 || transposed != 0) && (from >= self.count || to >= self.count) {
+                    errors.push(IntegrityError::EdgeOutOfBounds {
+                        from,
+                        to,
+                        count: self.count,
+                    });
+                }
+                if weight != transposed {
+                    errors.push(IntegrityError::TransposeMismatch { from, to });
+                }
+            }
+        }
 
-        let prev = self.edges[row + column];
+        into_result(errors)
+    }
 
-        self.edges[row + column] = val;
+    /// Weight of the edge from `from` to `to`, `None` when there is no edge.
+    /// A weight of 0 is the same as no edge.
+    pub fn weight_of(&self, from: usize, to: usize) -> Option<u8> {
+        assert_node(from, self.count);
+        assert_node(to, self.count);
 
-        self.set_edge_of_tranpose(to, from, val);
+        match self.edges[self.cell(from, to)] {
+            0 => None,
+            weight => Some(weight),
+        }
+    }
 
-        prev
+    /// every node with an edge to `node_index` and that edge's weight, read
+    /// from the transpose
+    pub fn incoming_weights_of(&self, node_index: usize) -> Vec<(usize, u8)> {
+        assert_node(node_index, self.count);
+
+        let row = self.cell(node_index, 0);
+
+        self.edges_transpose[row..row + self.count]
+            .iter()
+            .enumerate()
+            .filter(|(_, weight)| **weight > 0)
+            .map(|(from, weight)| (from, *weight))
+            .collect()
     }
 
-    fn set_edge_of_tranpose(&mut self, from: usize, to: usize, val: u8) {
-        // get proper word
-        let row = self.nodes.capacity() * from;
-        let column = to;
+    /// most nodes that fit before the matrices have to grow
+    pub fn capacity(&self) -> usize {
+        self.dim
+    }
 
-        self.edges_transpose[row + column] = val;
+    /// the matrix under `edges`, its transpose under `auxiliary`
+    pub fn memory_bytes(&self) -> MemoryBreakdown {
+        MemoryBreakdown {
+            nodes: vec_bytes(&self.nodes),
+            edges: vec_bytes(&self.edges),
+            auxiliary: vec_bytes(&self.edges_transpose),
+        }
+    }
+
+    /// Reallocates both matrices with rows `new_dim` long, copying every
+    /// row over at the new stride. Does nothing unless `new_dim` is bigger
+    /// than the current size.
+    pub fn grow(&mut self, new_dim: usize) {
+        if new_dim <= self.dim {
+            return;
+        }
+
+        let used = self.nodes.len();
+        for matrix in [&mut self.edges, &mut self.edges_transpose] {
+            let mut grown = vec![0; new_dim * new_dim];
+            for row in 0..used {
+                grown[new_dim * row..new_dim * row + used]
+                    .copy_from_slice(&matrix[self.dim * row..self.dim * row + used]);
+            }
+            *matrix = grown;
+        }
+
+        self.dim = new_dim;
+        self.nodes.reserve_exact(new_dim - used);
+    }
+
+    /// position of `from -> to` in `edges`, and of `to -> from` in the
+    /// transpose
+    #[inline]
+    fn cell(&self, from: usize, to: usize) -> usize {
+        self.dim * from + to
+    }
+
+    /// Writes `val` to both matrices and returns the weight it replaced,
+    /// `None` if there was no edge.
+    fn replace_edge(&mut self, from: usize, to: usize, val: u8) -> Option<u8> {
+        // also checks both ends
+        let prev = self.weight_of(from, to);
+
+        let cell = self.cell(from, to);
+        self.edges[cell] = val;
+
+        let cell = self.cell(to, from);
+        self.edges_transpose[cell] = val;
+
+        prev
     }
 }
 
 impl Graph<u64, u8> for AdjGraph {
+    /// gives weight 1, or leaves the weight alone if the edge is there
     fn add_edge(&mut self, from: usize, to: usize) -> bool {
-        self.set_edge_of_both(from, to, 1) > 0
+        match self.weight_of(from, to) {
+            Some(_) => true,
+            None => self.replace_edge(from, to, 1).is_some(),
+        }
     }
 
     fn remove_edge(&mut self, from: usize, to: usize) -> bool {
-        self.set_edge_of_both(from, to, 0) > 0
+        self.replace_edge(from, to, 0).is_some()
     }
 
-    fn get_edge(&self, _from: usize, _to: usize) -> Option<EdgeMeta<u8>> {
-        unimplemented!()
+    fn get_edge(&self, from: usize, to: usize) -> Option<EdgeMeta<u8>> {
+        self.weight_of(from, to).map(|weight| EdgeMeta {
+            source: from,
+            destination: to,
+            weight,
+        })
     }
 
     fn outgoing_edges_of(&self, node_index: usize) -> Vec<usize> {
-        let index = self.nodes.capacity() * node_index;
+        assert_node(node_index, self.count);
+
+        let index = self.cell(node_index, 0);
 
         let mut out = Vec::new();
         for i in 0..self.count {
@@ -74,8 +239,15 @@ impl Graph<u64, u8> for AdjGraph {
         out
     }
 
+    /// the row is scanned front to back, so it is already in order
+    fn neighbors_sorted(&self, node_index: usize) -> Vec<usize> {
+        self.outgoing_edges_of(node_index)
+    }
+
     fn incoming_edges_of(&self, node_index: usize) -> Vec<usize> {
-        let index = self.nodes.capacity() * node_index;
+        assert_node(node_index, self.count);
+
+        let index = self.cell(node_index, 0);
 
         let mut out = Vec::new();
         for i in 0..self.count {
@@ -87,30 +259,49 @@ impl Graph<u64, u8> for AdjGraph {
         out
     }
 
+    /// doubles the matrices when they are full
     fn push_node(&mut self, value: u64) -> usize {
+        if self.nodes.len() == self.dim {
+            self.grow((self.dim * 2).max(DEFAULT_CAPACITY));
+        }
+
         self.count += 1;
         self.nodes.push(value);
         self.nodes.len() - 1
     }
 
-    fn has_edge(&self, from: usize, to: usize) -> bool {
-        // get proper word
-        let row = self.nodes.capacity() * from;
-        let column = to;
+    /// grows the matrices if the nodes do not fit
+    fn reserve_nodes(&mut self, additional: usize) {
+        self.grow(self.nodes.len() + additional);
+    }
 
-        self.edges[row + column] > 0
+    fn has_edge(&self, from: usize, to: usize) -> bool {
+        self.weight_of(from, to).is_some()
     }
 
-    fn set_node(&mut self, _node_index: usize, _value: u64) {
-        todo!()
+    fn set_node(&mut self, node_index: usize, value: u64) {
+        assert_node(node_index, self.count);
+        self.nodes[node_index] = value;
     }
 
-    fn get_node(&self, _node_index: usize) -> &u64 {
-        todo!()
+    fn get_node(&self, node_index: usize) -> &u64 {
+        assert_node(node_index, self.count);
+        &self.nodes[node_index]
     }
 
-    fn remove_node(&mut self, _node_index: usize) -> u64 {
-        todo!()
+    /// Removes the node and its edges the way `Vec::swap_remove` does, the
+    /// last node takes over `node_index` along with all of its edges. Same
+    /// as `HashGraph::remove_node`.
+    fn remove_node(&mut self, node_index: usize) -> u64 {
+        assert_node(node_index, self.count);
+        let dim = self.dim;
+        let last = self.count - 1;
+
+        swap_remove_cells(&mut self.edges, dim, node_index, last);
+        swap_remove_cells(&mut self.edges_transpose, dim, node_index, last);
+
+        self.count -= 1;
+        self.nodes.swap_remove(node_index)
     }
 
     #[inline]
@@ -118,12 +309,125 @@ impl Graph<u64, u8> for AdjGraph {
         self.count
     }
 
+    /// weight 0 removes the edge
     fn set_edge(&mut self, from_to: (usize, usize), weight: u8) -> bool {
-        self.set_edge_of_both(from_to.0, from_to.1, weight) > 0
+        self.replace_edge(from_to.0, from_to.1, weight).is_some()
+    }
+
+    /// zeroes both matrices in place
+    fn clear_edges(&mut self) {
+        self.edges.iter_mut().for_each(|weight| *weight = 0);
+        self.edges_transpose
+            .iter_mut()
+            .for_each(|weight| *weight = 0);
     }
 
-    fn set_count(&mut self, count: usize) {
-        self.count = count;
+    /// keeps the matrices and the node storage allocated, so `capacity` stays
+    /// the same
+    fn clear(&mut self) {
+        self.clear_edges();
+        self.nodes.clear();
+        self.count = 0;
+    }
+}
+
+/// Clears row and column `node` of a `dim` wide matrix, then moves row and
+/// column `last` into them so `last` ends up empty.
+fn swap_remove_cells(matrix: &mut [u8], dim: usize, node: usize, last: usize) {
+    for other in 0..=last {
+        matrix[dim * node + other] = 0;
+        matrix[dim * other + node] = 0;
+    }
+
+    if node == last {
+        return;
+    }
+
+    // the column pass picks up the self loop the row pass left at
+    // (node, last)
+    for other in 0..=last {
+        matrix[dim * node + other] = matrix[dim * last + other];
+    }
+    for other in 0..=last {
+        matrix[dim * other + node] = matrix[dim * other + last];
+    }
+
+    for other in 0..=last {
+        matrix[dim * last + other] = 0;
+        matrix[dim * other + last] = 0;
+    }
+}
+
+/// Serialized as `(node_count, nodes, weighted edges)` rather than the full
+/// matrix.
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use super::AdjGraph;
+    use crate::Graph;
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize)]
+    struct Repr<'a> {
+        node_count: usize,
+        nodes: &'a [u64],
+        edges: Vec<(usize, usize, u8)>,
+    }
+
+    #[derive(Deserialize)]
+    struct OwnedRepr {
+        node_count: usize,
+        nodes: Vec<u64>,
+        edges: Vec<(usize, usize, u8)>,
+    }
+
+    impl Serialize for AdjGraph {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let edges = self
+                .all_edge_pairs()
+                .into_iter()
+                .filter_map(|(from, to)| Some((from, to, self.weight_of(from, to)?)))
+                .collect();
+
+            Repr {
+                node_count: self.count,
+                nodes: &self.nodes,
+                edges,
+            }
+            .serialize(serializer)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for AdjGraph {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let repr = OwnedRepr::deserialize(deserializer)?;
+
+            if repr.nodes.len() != repr.node_count {
+                return Err(D::Error::custom(format!(
+                    "node_count is {} but there are {} nodes",
+                    repr.node_count,
+                    repr.nodes.len()
+                )));
+            }
+
+            let mut graph = AdjGraph::with_capacity(repr.node_count);
+            for value in repr.nodes {
+                graph.push_node(value);
+            }
+
+            for (from, to, weight) in repr.edges {
+                if from >= repr.node_count || to >= repr.node_count {
+                    return Err(D::Error::custom(format!(
+                        "edge ({}, {}) out of bounds for {} nodes",
+                        from, to, repr.node_count
+                    )));
+                }
+
+                graph.set_edge((from, to), weight);
+            }
+
+            Ok(graph)
+        }
     }
 }
 
@@ -160,7 +464,7 @@ mod tests {
         graph.add_edge(2, 0);
 
         assert!(graph.outgoing_edges_of(0).len() == 1);
-        assert!(graph.outgoing_edges_of(4).len() == 0);
+        assert!(graph.outgoing_edges_of(4).is_empty());
 
         graph.add_edge(10, 2);
         graph.add_edge(10, 3);
@@ -194,7 +498,7 @@ mod tests {
         graph.add_edge(2, 0);
 
         assert!(graph.outgoing_edges_of(500).len() == 1);
-        assert!(graph.outgoing_edges_of(4).len() == 0);
+        assert!(graph.outgoing_edges_of(4).is_empty());
 
         graph.add_edge(10, 2);
         graph.add_edge(10, 3);
@@ -228,7 +532,7 @@ mod tests {
         graph.add_edge(2, 0);
 
         assert!(graph.outgoing_edges_of(500).len() == 1);
-        assert!(graph.outgoing_edges_of(4).len() == 0);
+        assert!(graph.outgoing_edges_of(4).is_empty());
 
         graph.add_edge(10, 2);
         graph.add_edge(10, 3);
@@ -262,7 +566,7 @@ mod tests {
         graph.add_edge(2, 0);
 
         assert!(graph.incoming_edges_of(1).len() == 1);
-        assert!(graph.incoming_edges_of(4).len() == 0);
+        assert!(graph.incoming_edges_of(4).is_empty());
 
         graph.add_edge(2, 1);
         graph.add_edge(3, 1);
@@ -287,7 +591,7 @@ mod tests {
         graph.add_edge(2, 0);
 
         assert!(graph.incoming_edges_of(1).len() == 1);
-        assert!(graph.incoming_edges_of(4).len() == 0);
+        assert!(graph.incoming_edges_of(4).is_empty());
 
         graph.add_edge(2, 1);
         graph.add_edge(3, 1);
@@ -298,4 +602,231 @@ mod tests {
         assert!(graph.incoming_edges_of(1).len() == 6);
         assert_eq!(graph.incoming_edges_of(1), vec![0, 2, 3, 4, 5, 7]);
     }
+
+    #[test]
+    fn weight_test() {
+        let mut graph = AdjGraph::new();
+        for i in 0..4 {
+            graph.push_node(i);
+        }
+
+        assert!(!graph.set_edge((1, 2), 5));
+        assert_eq!(graph.weight_of(1, 2), Some(5));
+        assert!(graph.set_edge((1, 2), 3));
+        graph.set_edge((3, 2), 9);
+
+        let edge = graph.get_edge(1, 2).unwrap();
+        assert_eq!((edge.source, edge.destination, edge.weight), (1, 2, 3));
+        assert_eq!(graph.incoming_weights_of(2), vec![(1, 3), (3, 9)]);
+        assert!(graph.get_edge(2, 1).is_none());
+
+        // add_edge does not reset a weight
+        assert!(graph.add_edge(1, 2));
+        assert_eq!(graph.weight_of(1, 2), Some(3));
+
+        assert!(graph.remove_edge(1, 2));
+        assert!(!graph.remove_edge(1, 2));
+        assert_eq!(graph.weight_of(1, 2), None);
+        assert_eq!(graph.incoming_weights_of(2), vec![(3, 9)]);
+    }
+
+    #[test]
+    fn validate_test() {
+        let mut graph = AdjGraph::with_capacity(8);
+        for i in 0..5 {
+            graph.push_node(i);
+        }
+        graph.set_edge((0, 4), 3);
+        graph.add_edge(4, 4);
+        assert_eq!(graph.validate(), Ok(()));
+
+        // a weight changed in one matrix only
+        let cell = graph.cell(0, 4);
+        graph.edges[cell] = 5;
+        // an edge to node 6 in both, past the last node
+        let (cell, transposed) = (graph.cell(2, 6), graph.cell(6, 2));
+        graph.edges[cell] = 1;
+        graph.edges_transpose[transposed] = 1;
+
+        assert_eq!(
+            graph.validate(),
+            Err(vec![
+                IntegrityError::EdgeOutOfBounds {
+                    from: 2,
+                    to: 6,
+                    count: 5
+                },
+                IntegrityError::TransposeMismatch { from: 0, to: 4 },
+            ])
+        );
+
+        graph.nodes.pop();
+        assert_eq!(
+            graph.validate().unwrap_err()[0],
+            IntegrityError::NodeCountMismatch {
+                count: 5,
+                values: 4
+            }
+        );
+    }
+
+    #[test]
+    fn remove_node_weights_test() {
+        let mut graph = AdjGraph::new();
+        for i in 0..6 {
+            graph.push_node(i * 10);
+        }
+        graph.set_edge((5, 2), 7);
+        graph.set_edge((3, 5), 4);
+        graph.add_edge(5, 5);
+
+        // 5 moves into 1 and takes its weights along
+        graph.remove_node(1);
+        assert_eq!(graph.weight_of(1, 2), Some(7));
+        assert_eq!(graph.incoming_weights_of(1), vec![(1, 1), (3, 4)]);
+
+        graph.set_node(0, 7);
+        assert_eq!(*graph.get_node(0), 7);
+    }
+
+    #[test]
+    fn remove_last_node_test() {
+        let mut graph = AdjGraph::new();
+        for i in 0..4 {
+            graph.push_node(i * 10);
+        }
+        graph.add_edge(0, 3);
+        graph.add_edge(3, 1);
+        graph.add_edge(1, 2);
+
+        assert_eq!(graph.remove_node(3), 30);
+        assert_eq!(graph.node_count(), 3);
+        assert_eq!(graph.all_edge_pairs(), vec![(1, 2)]);
+
+        // the freed index comes back empty
+        assert_eq!(graph.push_node(40), 3);
+        assert!(graph.outgoing_edges_of(3).is_empty());
+        assert!(graph.incoming_edges_of(3).is_empty());
+    }
+
+    #[test]
+    fn remove_middle_node_test() {
+        let mut graph = AdjGraph::new();
+        for i in 0..4 {
+            graph.push_node(i * 10);
+        }
+        graph.add_edge(0, 1);
+        graph.add_edge(1, 3);
+        graph.add_edge(3, 2);
+        graph.add_edge(3, 3);
+        graph.add_edge(2, 3);
+
+        // 3 moves into 1
+        assert_eq!(graph.remove_node(1), 10);
+        assert_eq!(graph.node_count(), 3);
+        assert_eq!(*graph.get_node(1), 30);
+        assert_eq!(graph.all_edge_pairs(), vec![(1, 1), (1, 2), (2, 1)]);
+        assert_eq!(graph.incoming_edges_of(1), vec![1, 2]);
+        assert!(!graph.has_edge(0, 1));
+    }
+
+    #[test]
+    #[should_panic(expected = "node 4 out of bounds for 4 nodes")]
+    fn remove_node_bounds_test() {
+        let mut graph = AdjGraph::new();
+        for i in 0..4 {
+            graph.push_node(i);
+        }
+        graph.add_edge(3, 0);
+        graph.remove_node(4);
+    }
+
+    #[test]
+    fn grow_test() {
+        let mut graph = AdjGraph::with_capacity(16);
+        for i in 0..16 {
+            graph.push_node(i);
+        }
+
+        graph.set_edge((15, 0), 3);
+        graph.set_edge((0, 15), 4);
+        graph.add_edge(15, 15);
+        graph.set_edge((14, 15), 9);
+        assert_eq!(graph.capacity(), 16);
+
+        for i in 16..40 {
+            graph.push_node(i);
+        }
+        assert!(graph.capacity() >= 40);
+
+        assert_eq!(graph.weight_of(15, 0), Some(3));
+        assert_eq!(graph.weight_of(0, 15), Some(4));
+        assert_eq!(graph.weight_of(15, 15), Some(1));
+        assert_eq!(
+            graph.incoming_weights_of(15),
+            vec![(0, 4), (14, 9), (15, 1)]
+        );
+        assert_eq!(graph.all_edge_pairs().len(), 4);
+
+        graph.add_edge(39, 15);
+        graph.add_edge(15, 39);
+        assert_eq!(graph.outgoing_edges_of(15), vec![0, 15, 39]);
+        assert_eq!(graph.incoming_edges_of(15), vec![0, 14, 15, 39]);
+        assert!(graph.outgoing_edges_of(16).is_empty());
+    }
+
+    #[test]
+    fn eq_test() {
+        let mut a = AdjGraph::with_capacity(16);
+        let mut b = AdjGraph::with_capacity(40);
+
+        for i in 0..10 {
+            a.push_node(i);
+            b.push_node(i);
+        }
+
+        a.add_edge(0, 9);
+        a.set_edge((9, 0), 4);
+        b.add_edge(0, 9);
+        b.set_edge((9, 0), 4);
+
+        assert!(a == b);
+
+        b.set_edge((9, 0), 5);
+        assert!(a != b);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip_test() {
+        let mut graph = AdjGraph::with_capacity(32);
+
+        for i in 0..20 {
+            graph.push_node(i);
+        }
+
+        graph.add_edge(0, 19);
+        graph.set_edge((19, 3), 200);
+
+        let json = serde_json::to_string(&graph).unwrap();
+        let back: AdjGraph = serde_json::from_str(&json).unwrap();
+
+        assert!(back == graph);
+        assert!(back.has_edge(19, 3));
+    }
+
+    #[test]
+    fn collect_test() {
+        let graph: AdjGraph = (0..40).map(|i| (i, 39 - i)).collect();
+        assert_eq!(graph.node_count(), 40);
+        assert!(graph.capacity() >= 40);
+        assert_eq!(graph.weight_of(0, 39), Some(1));
+        assert_eq!(graph.incoming_edges_of(20), vec![19]);
+
+        let mut weighted: AdjGraph = vec![(0, 1, 3), (1, 0, 0)].into_iter().collect();
+        assert_eq!(weighted.all_edge_pairs(), vec![(0, 1)]);
+        weighted.extend(vec![(0, 1, 0), (2, 2, 9)]);
+        assert_eq!(weighted.all_edge_pairs(), vec![(2, 2)]);
+        assert_eq!(weighted.weight_of(2, 2), Some(9));
+    }
 }
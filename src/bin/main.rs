@@ -12,7 +12,7 @@ pub fn main() {
     graph.add_edge(2, 0);
 
     assert!(graph.outgoing_edges_of(0).len() == 1);
-    assert!(graph.outgoing_edges_of(4).len() == 0);
+    assert!(graph.outgoing_edges_of(4).is_empty());
 
     graph.add_edge(10, 2);
     graph.add_edge(10, 3);
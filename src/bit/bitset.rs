@@ -0,0 +1,176 @@
+use super::{get_bit, ones, set_bit, unset_bit, WORD_BITS};
+
+/// Fixed size set of node indices, one bit per node.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct NodeBitSet {
+    words: Vec<usize>,
+}
+
+impl NodeBitSet {
+    /// room for indices `0..size`
+    pub fn with_capacity(size: usize) -> NodeBitSet {
+        NodeBitSet {
+            words: vec![0; size / WORD_BITS + 1],
+        }
+    }
+
+    /// adds `index`, returns true if it was not set before
+    #[inline]
+    pub fn insert(&mut self, index: usize) -> bool {
+        let word = &mut self.words[index / WORD_BITS];
+        let was_set = get_bit(*word, index % WORD_BITS);
+        *word = set_bit(*word, index % WORD_BITS);
+
+        !was_set
+    }
+
+    #[inline]
+    pub fn contains(&self, index: usize) -> bool {
+        get_bit(self.words[index / WORD_BITS], index % WORD_BITS)
+    }
+
+    /// removes `index`, returns true if it was set
+    #[inline]
+    pub fn remove(&mut self, index: usize) -> bool {
+        let word = &mut self.words[index / WORD_BITS];
+        let was_set = get_bit(*word, index % WORD_BITS);
+        *word = unset_bit(*word, index % WORD_BITS);
+
+        was_set
+    }
+
+    /// removes every index, keeps the allocation
+    pub fn clear(&mut self) {
+        self.words.iter_mut().for_each(|word| *word = 0);
+    }
+
+    /// number of set indices
+    pub fn len(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|&word| word == 0)
+    }
+
+    /// adds every index in `other`, growing to fit it if needed
+    pub fn union_with(&mut self, other: &NodeBitSet) {
+        if self.words.len() < other.words.len() {
+            self.words.resize(other.words.len(), 0);
+        }
+        for (word, &theirs) in self.words.iter_mut().zip(&other.words) {
+            *word |= theirs;
+        }
+    }
+
+    /// keeps only the indices also in `other`
+    pub fn intersect_with(&mut self, other: &NodeBitSet) {
+        let shared = self.words.len().min(other.words.len());
+        for (word, &theirs) in self.words.iter_mut().zip(&other.words) {
+            *word &= theirs;
+        }
+        self.words[shared..].iter_mut().for_each(|word| *word = 0);
+    }
+
+    /// true if no index is in both sets
+    pub fn is_disjoint(&self, other: &NodeBitSet) -> bool {
+        self.words
+            .iter()
+            .zip(&other.words)
+            .all(|(&ours, &theirs)| ours & theirs == 0)
+    }
+
+    /// set indices in ascending order
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words
+            .iter()
+            .enumerate()
+            .flat_map(|(i, &word)| ones(word).map(move |offset| i * WORD_BITS + offset))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insert_remove_test() {
+        let mut set = NodeBitSet::with_capacity(130);
+        assert!(set.is_empty());
+
+        for &i in &[0, 1, 63, 64, 65, 127, 128, 130] {
+            assert!(!set.contains(i));
+            assert!(set.insert(i));
+            assert!(!set.insert(i));
+            assert!(set.contains(i));
+        }
+        assert!(!set.contains(2));
+        assert!(!set.contains(62));
+        assert!(!set.contains(66));
+        assert_eq!(set.len(), 8);
+
+        assert!(set.remove(64));
+        assert!(!set.remove(64));
+        assert!(!set.contains(64));
+        assert!(set.contains(63));
+        assert!(set.contains(65));
+        assert_eq!(set.len(), 7);
+
+        assert_eq!(
+            set.iter().collect::<Vec<_>>(),
+            vec![0, 1, 63, 65, 127, 128, 130]
+        );
+
+        set.clear();
+        assert!(set.is_empty());
+        assert_eq!(set.len(), 0);
+        assert_eq!(set.iter().next(), None);
+        assert!((0..=130).all(|i| !set.contains(i)));
+    }
+
+    fn of(size: usize, indices: &[usize]) -> NodeBitSet {
+        let mut set = NodeBitSet::with_capacity(size);
+        for &i in indices {
+            set.insert(i);
+        }
+        set
+    }
+
+    #[test]
+    fn set_ops_test() {
+        let a = of(66, &[1, 63, 64]);
+        let b = of(66, &[63, 65]);
+
+        let mut union = a.clone();
+        union.union_with(&b);
+        assert_eq!(union.iter().collect::<Vec<_>>(), vec![1, 63, 64, 65]);
+
+        let mut both = a.clone();
+        both.intersect_with(&b);
+        assert_eq!(both.iter().collect::<Vec<_>>(), vec![63]);
+
+        assert!(!a.is_disjoint(&b));
+        assert!(of(66, &[64]).is_disjoint(&b));
+        assert!(of(66, &[63]).is_disjoint(&of(66, &[64])));
+    }
+
+    #[test]
+    fn mismatched_sizes_test() {
+        let small = of(10, &[3]);
+        let big = of(200, &[3, 64, 199]);
+
+        let mut grown = small.clone();
+        grown.union_with(&big);
+        assert_eq!(grown.iter().collect::<Vec<_>>(), vec![3, 64, 199]);
+
+        let mut shrunk = big.clone();
+        shrunk.intersect_with(&small);
+        assert_eq!(shrunk.iter().collect::<Vec<_>>(), vec![3]);
+
+        assert!(!small.is_disjoint(&big));
+        assert!(of(10, &[4]).is_disjoint(&big));
+    }
+}
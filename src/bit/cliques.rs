@@ -0,0 +1,205 @@
+use super::{ones, set_bit, unset_bit, BitGraph, WORD_BITS};
+
+impl BitGraph {
+    /// Every maximal clique with Bron–Kerbosch and pivoting, lazily. The graph
+    /// is taken as undirected, two nodes are adjacent if an edge goes either
+    /// way, and self loops are ignored. Cliques come out sorted, isolated nodes
+    /// are cliques of one.
+    pub fn maximal_cliques(&self) -> impl Iterator<Item = Vec<usize>> {
+        if self.transpose_stale {
+            return self.refreshed().maximal_cliques();
+        }
+
+        let adjacency: Vec<Vec<usize>> = (0..self.count)
+            .map(|node| {
+                let mut row = self.aligned_row(&self.edges, node);
+                let incoming = self.aligned_row(&self.edges_transpose, node);
+                row.iter_mut()
+                    .zip(incoming)
+                    .for_each(|(word, other)| *word |= other);
+                row[node / WORD_BITS] = unset_bit(row[node / WORD_BITS], node % WORD_BITS);
+                row
+            })
+            .collect();
+
+        let mut cliques = MaximalCliques {
+            adjacency,
+            stack: Vec::new(),
+        };
+
+        if self.count > 0 {
+            let mut all = vec![0; self.aligned_row_len()];
+            for node in 0..self.count {
+                all[node / WORD_BITS] = set_bit(all[node / WORD_BITS], node % WORD_BITS);
+            }
+            let none = vec![0; self.aligned_row_len()];
+
+            cliques.push_frame(Vec::new(), all, none);
+        }
+
+        cliques
+    }
+}
+
+/// one level of the Bron–Kerbosch recursion
+struct Frame {
+    clique: Vec<usize>,
+    /// nodes that can still extend `clique`
+    candidates: Vec<usize>,
+    /// nodes that were already tried at this level
+    excluded: Vec<usize>,
+    /// candidates left to branch on, the ones not next to the pivot
+    branches: Vec<usize>,
+}
+
+struct MaximalCliques {
+    adjacency: Vec<Vec<usize>>,
+    stack: Vec<Frame>,
+}
+
+impl MaximalCliques {
+    fn push_frame(&mut self, clique: Vec<usize>, candidates: Vec<usize>, excluded: Vec<usize>) {
+        // the pivot with the most neighbours among the candidates leaves the
+        // fewest branches
+        let pivot = candidates
+            .iter()
+            .zip(&excluded)
+            .map(|(p, x)| p | x)
+            .enumerate()
+            .flat_map(|(i, word)| ones(word).map(move |offset| i * WORD_BITS + offset))
+            .max_by_key(|node| {
+                self.adjacency[*node]
+                    .iter()
+                    .zip(&candidates)
+                    .map(|(n, p)| (n & p).count_ones())
+                    .sum::<u32>()
+            });
+
+        let branches = candidates
+            .iter()
+            .enumerate()
+            .flat_map(|(i, word)| {
+                let word = match pivot {
+                    Some(pivot) => word & !self.adjacency[pivot][i],
+                    None => *word,
+                };
+                ones(word).map(move |offset| i * WORD_BITS + offset)
+            })
+            .collect();
+
+        self.stack.push(Frame {
+            clique,
+            candidates,
+            excluded,
+            branches,
+        });
+    }
+}
+
+impl Iterator for MaximalCliques {
+    type Item = Vec<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let frame = self.stack.last_mut()?;
+            let node = match frame.branches.pop() {
+                Some(node) => node,
+                None => {
+                    self.stack.pop();
+                    continue;
+                }
+            };
+
+            let neighbours = &self.adjacency[node];
+            let mut clique = frame.clique.clone();
+            clique.push(node);
+            let candidates: Vec<usize> = frame
+                .candidates
+                .iter()
+                .zip(neighbours)
+                .map(|(p, n)| p & n)
+                .collect();
+            let excluded: Vec<usize> = frame
+                .excluded
+                .iter()
+                .zip(neighbours)
+                .map(|(x, n)| x & n)
+                .collect();
+
+            let (word, offset) = (node / WORD_BITS, node % WORD_BITS);
+            frame.candidates[word] = unset_bit(frame.candidates[word], offset);
+            frame.excluded[word] = set_bit(frame.excluded[word], offset);
+
+            let no_candidates = candidates.iter().all(|word| *word == 0);
+            if no_candidates && excluded.iter().all(|word| *word == 0) {
+                clique.sort_unstable();
+                return Some(clique);
+            }
+
+            if !no_candidates {
+                self.push_frame(clique, candidates, excluded);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted_cliques(graph: &BitGraph) -> Vec<Vec<usize>> {
+        let mut cliques: Vec<_> = graph.maximal_cliques().collect();
+        cliques.sort();
+        cliques
+    }
+
+    #[test]
+    fn complete_test() {
+        let edges = (0..5).flat_map(|i| (0..5).map(move |j| (i, j)));
+        let graph = BitGraph::from_edges(5, edges).unwrap();
+
+        assert_eq!(sorted_cliques(&graph), vec![vec![0, 1, 2, 3, 4]]);
+    }
+
+    #[test]
+    fn overlapping_triangles_test() {
+        // triangles 0-1-2 and 1-2-3 share an edge, 3-4 hangs off and 5 is
+        // alone, edges only go one way
+        let edges = [(0, 1), (1, 2), (2, 0), (3, 1), (2, 3), (3, 4)];
+        let graph = BitGraph::from_edges(6, edges.iter().copied()).unwrap();
+
+        assert_eq!(
+            sorted_cliques(&graph),
+            vec![vec![0, 1, 2], vec![1, 2, 3], vec![3, 4], vec![5]]
+        );
+    }
+
+    #[test]
+    fn wide_test() {
+        // two cliques of 40 joined by one edge, spans several words
+        let mut edges = Vec::new();
+        for offset in &[0, 40] {
+            for i in 0..40 {
+                for j in i + 1..40 {
+                    edges.push((offset + i, offset + j));
+                }
+            }
+        }
+        edges.push((39, 40));
+        let graph = BitGraph::from_edges(80, edges).unwrap();
+
+        assert_eq!(
+            sorted_cliques(&graph),
+            vec![
+                (0..40).collect::<Vec<_>>(),
+                vec![39, 40],
+                (40..80).collect::<Vec<_>>()
+            ]
+        );
+    }
+
+    #[test]
+    fn empty_test() {
+        assert_eq!(BitGraph::new().maximal_cliques().count(), 0);
+    }
+}
@@ -0,0 +1,234 @@
+use super::{ones, BitGraph, WORD_BITS};
+use crate::Graph;
+
+/// What changed from one `BitGraph` to another, see `diff`. Edges are in
+/// ascending `(from, to)` order.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GraphDiff {
+    pub added_edges: Vec<(usize, usize)>,
+    /// also every edge of a node that is no longer there
+    pub removed_edges: Vec<(usize, usize)>,
+
+    pub old_node_count: usize,
+    pub new_node_count: usize,
+    /// values of the nodes past `old_node_count`, if the graph grew
+    pub added_nodes: Vec<u64>,
+    /// nodes present in both whose value changed, with the new value
+    pub changed_nodes: Vec<(usize, u64)>,
+}
+
+impl GraphDiff {
+    /// true if the two graphs were equal
+    pub fn is_empty(&self) -> bool {
+        self.added_edges.is_empty()
+            && self.removed_edges.is_empty()
+            && self.old_node_count == self.new_node_count
+            && self.changed_nodes.is_empty()
+    }
+
+    /// Applies the diff as a patch, turning a graph equal to the first one
+    /// given to `diff` into one equal to the second. Grows `graph` if the
+    /// new nodes do not fit, dropped nodes are cut off the end.
+    ///
+    /// Panics if `graph` does not have `old_node_count` nodes.
+    pub fn apply(&self, graph: &mut BitGraph) {
+        assert_eq!(
+            graph.count, self.old_node_count,
+            "diff is against {} nodes, graph has {}",
+            self.old_node_count, graph.count
+        );
+
+        if self.new_node_count > graph.nodes.capacity() {
+            graph.grow_to(self.new_node_count);
+        }
+        for &value in &self.added_nodes {
+            graph.push_node(value);
+        }
+        for &(node, value) in &self.changed_nodes {
+            graph.nodes[node] = value;
+        }
+
+        for &(from, to) in &self.removed_edges {
+            graph.remove_edge(from, to);
+        }
+        graph.add_edges(self.added_edges.iter().copied());
+
+        // their edges are gone with `removed_edges`, the rows are all zero
+        graph.nodes.truncate(self.new_node_count);
+        graph.count = self.new_node_count;
+    }
+}
+
+/// Edges and nodes that differ between `a` and `b`. Rows are compared a
+/// word at a time and only words that differ are looked into, so it stays
+/// cheap on large graphs with few changes. The graphs can have different
+/// sizes, nodes `b` has that `a` does not count as having no edges in `a`
+/// and the other way around.
+pub fn diff(a: &BitGraph, b: &BitGraph) -> GraphDiff {
+    let mut diff = GraphDiff {
+        old_node_count: a.count,
+        new_node_count: b.count,
+        added_nodes: b.nodes[a.count.min(b.count)..b.count].to_vec(),
+        changed_nodes: (0..a.count.min(b.count))
+            .filter(|&node| a.nodes[node] != b.nodes[node])
+            .map(|node| (node, b.nodes[node]))
+            .collect(),
+        ..GraphDiff::default()
+    };
+
+    if a.stride == b.stride && a.edges.len() == b.edges.len() {
+        // same layout, both matrices can be walked as they are
+        for (index, (&old, &new)) in a.edges.iter().zip(&b.edges).enumerate() {
+            if old != new {
+                let at = |offset| {
+                    let bit = index * WORD_BITS + offset;
+                    (bit / a.stride, bit % a.stride)
+                };
+                diff.added_edges.extend(ones(new & !old).map(at));
+                diff.removed_edges.extend(ones(old & !new).map(at));
+            }
+        }
+
+        return diff;
+    }
+
+    let len = a.count.max(b.count) / WORD_BITS + 1;
+    for node in 0..a.count.max(b.count) {
+        let old = padded_row(a, node, len);
+        let new = padded_row(b, node, len);
+
+        for (i, (&old, &new)) in old.iter().zip(&new).enumerate() {
+            if old != new {
+                let at = |offset| (node, i * WORD_BITS + offset);
+                diff.added_edges.extend(ones(new & !old).map(at));
+                diff.removed_edges.extend(ones(old & !new).map(at));
+            }
+        }
+    }
+
+    diff
+}
+
+/// word aligned row of `node` that is `len` words long, all zero if `graph`
+/// does not have the node
+fn padded_row(graph: &BitGraph, node: usize, len: usize) -> Vec<usize> {
+    let mut row = if node < graph.count {
+        graph.aligned_row(&graph.edges, node)
+    } else {
+        Vec::new()
+    };
+    row.resize(len, 0);
+    row
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// applying the diff of `a` and `b` to a copy of `a` gives `b`
+    fn assert_patches(a: &BitGraph, b: &BitGraph) {
+        let patch = diff(a, b);
+        let mut patched = a.clone();
+        patch.apply(&mut patched);
+
+        assert_eq!(&patched, b);
+        assert_eq!(patched.all_edge_pairs(), b.all_edge_pairs());
+        for node in 0..b.node_count() {
+            assert_eq!(patched.incoming_edges_of(node), b.incoming_edges_of(node));
+        }
+        assert!(diff(&patched, b).is_empty());
+    }
+
+    #[test]
+    fn identical_test() {
+        let graph = BitGraph::from_edges(70, vec![(0, 69), (69, 0), (5, 5), (64, 63)]).unwrap();
+
+        let same = diff(&graph, &graph.clone());
+        assert!(same.is_empty());
+        assert_eq!(
+            same,
+            GraphDiff {
+                old_node_count: 70,
+                new_node_count: 70,
+                ..GraphDiff::default()
+            }
+        );
+        assert!(diff(&BitGraph::new(), &BitGraph::new()).is_empty());
+
+        // a different layout does not make a difference
+        let mut aligned = BitGraph::with_capacity_aligned(70);
+        for _ in 0..70 {
+            aligned.push_node(0);
+        }
+        aligned.add_edges(graph.all_edge_pairs());
+        assert!(diff(&graph, &aligned).is_empty());
+    }
+
+    #[test]
+    fn same_size_test() {
+        let a = BitGraph::from_edges(70, vec![(0, 1), (3, 66), (69, 69), (40, 2)]).unwrap();
+        let b = BitGraph::from_edges(70, vec![(0, 1), (3, 67), (69, 68), (40, 2), (1, 0)]).unwrap();
+
+        let patch = diff(&a, &b);
+        assert_eq!(patch.added_edges, vec![(1, 0), (3, 67), (69, 68)]);
+        assert_eq!(patch.removed_edges, vec![(3, 66), (69, 69)]);
+        assert!(patch.added_nodes.is_empty());
+
+        assert_patches(&a, &b);
+        assert_patches(&b, &a);
+    }
+
+    #[test]
+    fn resized_test() {
+        let a = BitGraph::from_edges(5, vec![(0, 1), (1, 2), (4, 0)]).unwrap();
+        let b = BitGraph::from_nodes_and_edges(
+            (0..71).map(|node| if node == 2 { 7 } else { 0 }),
+            vec![(0, 1), (4, 0), (4, 70), (70, 2), (2, 2)],
+        )
+        .unwrap();
+
+        let grown = diff(&a, &b);
+        assert_eq!(grown.added_edges, vec![(2, 2), (4, 70), (70, 2)]);
+        assert_eq!(grown.removed_edges, vec![(1, 2)]);
+        assert_eq!((grown.old_node_count, grown.new_node_count), (5, 71));
+        assert_eq!(grown.added_nodes, vec![0; 66]);
+        assert_eq!(grown.changed_nodes, vec![(2, 7)]);
+        assert_patches(&a, &b);
+
+        // and back, edges of the dropped nodes go with them
+        let shrunk = diff(&b, &a);
+        assert_eq!(shrunk.removed_edges, vec![(2, 2), (4, 70), (70, 2)]);
+        assert!(shrunk.added_nodes.is_empty());
+        assert_patches(&b, &a);
+    }
+
+    #[test]
+    #[should_panic(expected = "diff is against 3 nodes, graph has 2")]
+    fn wrong_base_test() {
+        let a = BitGraph::from_edges(3, vec![(0, 1)]).unwrap();
+        let b = BitGraph::from_edges(3, vec![(1, 0)]).unwrap();
+        diff(&a, &b).apply(&mut BitGraph::from_edges(2, vec![]).unwrap());
+    }
+
+    #[test]
+    fn same_capacity_test() {
+        // both fit in the default capacity, so the matrices are compared
+        // as they are
+        let mut a = BitGraph::new();
+        let mut b = BitGraph::new();
+        for i in 0..6 {
+            a.push_node(i);
+            b.push_node(i);
+        }
+        b.push_node(6);
+        a.add_edges(vec![(0, 5), (5, 0), (3, 3)]);
+        b.add_edges(vec![(0, 5), (6, 1), (3, 4)]);
+
+        let patch = diff(&a, &b);
+        assert_eq!(patch.added_edges, vec![(3, 4), (6, 1)]);
+        assert_eq!(patch.removed_edges, vec![(3, 3), (5, 0)]);
+        assert_eq!(patch.added_nodes, vec![6]);
+        assert_patches(&a, &b);
+        assert_patches(&b, &a);
+    }
+}
@@ -0,0 +1,306 @@
+use super::BitGraph;
+use crate::Graph;
+use std::fmt;
+
+/// node counts past this take the 8 byte size header
+const SHORT_SIZE_LIMIT: usize = 258_047;
+const LONG_SIZE_LIMIT: usize = (1 << 36) - 1;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Graph6Error {
+    /// graph6 is undirected, `from -> to` has no edge back
+    Asymmetric { from: usize, to: usize },
+    /// graph6 can not hold a loop on `node`
+    SelfLoop { node: usize },
+    /// more nodes than the size header can hold
+    TooManyNodes(usize),
+    /// a byte outside of `?` to `~` at `position`
+    InvalidByte { position: usize, byte: u8 },
+    /// ran out of input before every edge was read
+    Truncated,
+    /// bytes left after every edge was read
+    TrailingData,
+}
+
+impl fmt::Display for Graph6Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Graph6Error::Asymmetric { from, to } => {
+                write!(f, "edge {} -> {} has no edge back", from, to)
+            }
+            Graph6Error::SelfLoop { node } => write!(f, "self loop on node {}", node),
+            Graph6Error::TooManyNodes(count) => write!(f, "{} nodes is too many", count),
+            Graph6Error::InvalidByte { position, byte } => {
+                write!(f, "invalid byte {:#x} at {}", byte, position)
+            }
+            Graph6Error::Truncated => write!(f, "input ends early"),
+            Graph6Error::TrailingData => write!(f, "data after the last edge"),
+        }
+    }
+}
+
+impl std::error::Error for Graph6Error {}
+
+impl BitGraph {
+    /// Encodes the graph in the undirected graph6 format used by nauty and
+    /// networkx, the upper triangle of the matrix column by column. Every
+    /// edge needs one back and there can be no self loops.
+    pub fn to_graph6(&self) -> Result<String, Graph6Error> {
+        let mut bits = Vec::with_capacity(self.count * self.count / 2);
+        for to in 0..self.count {
+            if self.has_edge(to, to) {
+                return Err(Graph6Error::SelfLoop { node: to });
+            }
+
+            for from in 0..to {
+                let edge = self.has_edge(from, to);
+                if edge != self.has_edge(to, from) {
+                    let (from, to) = if edge { (from, to) } else { (to, from) };
+                    return Err(Graph6Error::Asymmetric { from, to });
+                }
+                bits.push(edge);
+            }
+        }
+
+        let mut out = size_header(self.count)?;
+        push_bits(&mut out, &bits);
+        Ok(out)
+    }
+
+    /// Encodes the graph in digraph6, `&` followed by the whole matrix row
+    /// by row, so any edges and self loops can be written.
+    pub fn to_digraph6(&self) -> Result<String, Graph6Error> {
+        let bits: Vec<bool> = (0..self.count)
+            .flat_map(|from| (0..self.count).map(move |to| (from, to)))
+            .map(|(from, to)| self.has_edge(from, to))
+            .collect();
+
+        let mut out = String::from("&");
+        out.push_str(&size_header(self.count)?);
+        push_bits(&mut out, &bits);
+        Ok(out)
+    }
+
+    /// Reads a graph6 string, or a digraph6 one when it starts with `&`. The
+    /// optional `>>graph6<<` header and surrounding whitespace are skipped.
+    /// Undirected edges come out as an edge each way and node values are
+    /// their indices.
+    pub fn from_graph6(input: &str) -> Result<BitGraph, Graph6Error> {
+        let input = input.trim();
+        let input = input
+            .strip_prefix(">>graph6<<")
+            .or_else(|| input.strip_prefix(">>digraph6<<"))
+            .unwrap_or(input);
+
+        let (directed, body) = match input.strip_prefix('&') {
+            Some(body) => (true, body),
+            None => (false, input),
+        };
+        let offset = input.len() - body.len();
+
+        let values = body
+            .bytes()
+            .enumerate()
+            .map(|(position, byte)| match byte {
+                63..=126 => Ok(byte - 63),
+                _ => Err(Graph6Error::InvalidByte {
+                    position: position + offset,
+                    byte,
+                }),
+            })
+            .collect::<Result<Vec<u8>, _>>()?;
+
+        let (count, data) = read_size(&values)?;
+
+        // checked before building the pairs, a short header can claim far
+        // more nodes than the input has edge bits for
+        let pair_count = if directed {
+            count.checked_mul(count)
+        } else {
+            count.checked_mul(count.saturating_sub(1)).map(|n| n / 2)
+        };
+        let expected = pair_count.map(|n| n.div_ceil(6));
+        if expected != Some(data.len()) {
+            return Err(match expected {
+                Some(expected) if data.len() > expected => Graph6Error::TrailingData,
+                _ => Graph6Error::Truncated,
+            });
+        }
+
+        let pairs: Vec<(usize, usize)> = if directed {
+            (0..count)
+                .flat_map(|from| (0..count).map(move |to| (from, to)))
+                .collect()
+        } else {
+            (0..count)
+                .flat_map(|to| (0..to).map(move |from| (from, to)))
+                .collect()
+        };
+
+        let edges = pairs
+            .into_iter()
+            .enumerate()
+            .filter(|(i, _)| data[i / 6] & (1 << (5 - i % 6)) != 0)
+            .flat_map(|(_, (from, to))| {
+                let back = Some((to, from)).filter(|_| !directed);
+                Some((from, to)).into_iter().chain(back)
+            });
+
+        Ok(BitGraph::from_nodes_and_edges(0..count as u64, edges)
+            .expect("every pair is under the node count"))
+    }
+}
+
+/// `N(n)` from the format, one byte up to 62 nodes and 4 or 8 past that
+fn size_header(count: usize) -> Result<String, Graph6Error> {
+    let (prefix, groups) = match count {
+        0..=62 => ("", 1),
+        63..=SHORT_SIZE_LIMIT => ("~", 3),
+        _ if count <= LONG_SIZE_LIMIT => ("~~", 6),
+        _ => return Err(Graph6Error::TooManyNodes(count)),
+    };
+
+    let mut out = String::from(prefix);
+    for group in (0..groups).rev() {
+        out.push((((count >> (6 * group)) & 0x3f) as u8 + 63) as char);
+    }
+    Ok(out)
+}
+
+/// splits the size header off of the values, `?` already subtracted
+fn read_size(values: &[u8]) -> Result<(usize, &[u8]), Graph6Error> {
+    let groups = match values {
+        [63, 63, ..] => 6,
+        [63, ..] => 3,
+        _ => 1,
+    };
+    let start = if groups == 1 { 0 } else { groups / 3 };
+
+    let size = values
+        .get(start..start + groups)
+        .ok_or(Graph6Error::Truncated)?;
+    let count = size
+        .iter()
+        .fold(0, |count, value| (count << 6) | *value as usize);
+
+    Ok((count, &values[start + groups..]))
+}
+
+/// packs `bits` six to a byte, high bit first, padded with zeros
+fn push_bits(out: &mut String, bits: &[bool]) {
+    for chunk in bits.chunks(6) {
+        let value = chunk
+            .iter()
+            .enumerate()
+            .fold(0, |value, (i, bit)| value | ((*bit as u8) << (5 - i)));
+        out.push((value + 63) as char);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PETERSEN: &str = "IheA@GUAo";
+
+    #[test]
+    fn spec_example_test() {
+        // the examples from the format description
+        let graph = BitGraph::from_graph6("DQc").unwrap();
+        assert_eq!(graph.node_count(), 5);
+        assert_eq!(
+            graph.all_edge_pairs(),
+            vec![
+                (0, 2),
+                (0, 4),
+                (1, 3),
+                (2, 0),
+                (3, 1),
+                (3, 4),
+                (4, 0),
+                (4, 3)
+            ]
+        );
+        assert_eq!(graph.to_graph6().unwrap(), "DQc");
+
+        let graph = BitGraph::from_graph6("&DI?AO?").unwrap();
+        assert_eq!(graph.all_edge_pairs(), vec![(0, 2), (0, 4), (3, 1), (3, 4)]);
+        assert_eq!(graph.to_digraph6().unwrap(), "&DI?AO?");
+    }
+
+    #[test]
+    fn petersen_test() {
+        let graph = BitGraph::from_graph6(&format!(">>graph6<<{}\n", PETERSEN)).unwrap();
+
+        assert_eq!(graph.node_count(), 10);
+        assert_eq!(graph.all_edge_pairs().len(), 30);
+        assert!((0..10).all(|node| graph.outgoing_edges_of(node).len() == 3));
+        // no triangles in the petersen graph
+        assert_eq!(graph.triangle_count(), 0);
+        assert_eq!(graph.to_graph6().unwrap(), PETERSEN);
+
+        let complete = BitGraph::from_graph6("C~").unwrap();
+        assert_eq!(complete.all_edge_pairs().len(), 12);
+        assert_eq!(BitGraph::from_graph6("@").unwrap().node_count(), 1);
+        assert_eq!(BitGraph::from_graph6("?").unwrap().node_count(), 0);
+    }
+
+    #[test]
+    fn large_size_test() {
+        let edges = (0..99)
+            .map(|i| (i, i + 1))
+            .flat_map(|(a, b)| vec![(a, b), (b, a)]);
+        let graph = BitGraph::from_nodes_and_edges(0..100, edges).unwrap();
+
+        let encoded = graph.to_graph6().unwrap();
+        // 100 = 1 * 64 + 36
+        assert!(encoded.starts_with("~?@c"));
+        assert_eq!(encoded.len(), 4 + (100 * 99 / 2usize).div_ceil(6));
+        assert_eq!(BitGraph::from_graph6(&encoded).unwrap(), graph);
+
+        let encoded = graph.to_digraph6().unwrap();
+        assert!(encoded.starts_with("&~?@c"));
+        assert_eq!(BitGraph::from_graph6(&encoded).unwrap(), graph);
+
+        assert_eq!(size_header(258_048).unwrap(), "~~???~??");
+        assert_eq!(read_size(&[63, 63, 0, 0, 0, 63, 0, 0]).unwrap().0, 258_048);
+    }
+
+    #[test]
+    fn error_test() {
+        let mut graph = BitGraph::from_graph6("DQc").unwrap();
+        graph.add_edge(1, 2);
+        assert_eq!(
+            graph.to_graph6(),
+            Err(Graph6Error::Asymmetric { from: 1, to: 2 })
+        );
+        // fine as a digraph
+        let directed = graph.to_digraph6().unwrap();
+        assert_eq!(BitGraph::from_graph6(&directed).unwrap(), graph);
+
+        graph.add_edge(2, 1);
+        graph.add_edge(3, 3);
+        assert_eq!(graph.to_graph6(), Err(Graph6Error::SelfLoop { node: 3 }));
+
+        assert_eq!(BitGraph::from_graph6("DQ"), Err(Graph6Error::Truncated));
+        assert_eq!(
+            BitGraph::from_graph6("DQc?"),
+            Err(Graph6Error::TrailingData)
+        );
+        assert_eq!(BitGraph::from_graph6("~?"), Err(Graph6Error::Truncated));
+        // a header for 258047 nodes with no edge bits after it
+        assert_eq!(BitGraph::from_graph6("~}~~"), Err(Graph6Error::Truncated));
+        assert_eq!(BitGraph::from_graph6("&~}~~"), Err(Graph6Error::Truncated));
+        assert_eq!(
+            BitGraph::from_graph6("~~}~~~~~"),
+            Err(Graph6Error::Truncated)
+        );
+        assert_eq!(
+            BitGraph::from_graph6("&DQ c"),
+            Err(Graph6Error::InvalidByte {
+                position: 3,
+                byte: b' '
+            })
+        );
+    }
+}
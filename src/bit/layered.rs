@@ -0,0 +1,441 @@
+use super::{locate, set_bit, unset_bit, BitGraph, DEFAULT_CAPACITY};
+use crate::memory::{vec_bytes, MemoryBreakdown};
+use crate::{assert_node, EdgeMeta, Graph};
+
+/// A `BitGraph` with a weight of `BITS` bits on every edge, up to 8.
+///
+/// The edges themselves live in an ordinary `BitGraph`, the presence matrix,
+/// so neighbours, degrees and incoming edges cost what they do there and an
+/// edge can weigh 0. Bit `b` of each weight goes into plane `b`, a matrix of
+/// its own laid out like the presence matrix, so an edge costs `BITS` more
+/// bits rather than a table entry. Planes only have bits under edges that
+/// exist.
+#[derive(Clone, Debug)]
+pub struct LayeredBitGraph<const BITS: usize> {
+    presence: BitGraph,
+    planes: [Vec<usize>; BITS],
+}
+
+impl<const BITS: usize> Default for LayeredBitGraph<BITS> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const BITS: usize> LayeredBitGraph<BITS> {
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_CAPACITY)
+    }
+
+    pub fn with_capacity(size: usize) -> Self {
+        assert!(
+            (1..=8).contains(&BITS),
+            "LayeredBitGraph weights need 1 to 8 bits, not {}",
+            BITS
+        );
+
+        let presence = BitGraph::with_capacity(size);
+        let words = presence.edges.len();
+        LayeredBitGraph {
+            presence,
+            planes: std::array::from_fn(|_| vec![0; words]),
+        }
+    }
+
+    /// heaviest weight that fits, `2^BITS - 1`
+    pub fn max_weight() -> u8 {
+        ((1u16 << BITS) - 1) as u8
+    }
+
+    /// The edges without their weights, for anything written against
+    /// `BitGraph` itself.
+    pub fn presence(&self) -> &BitGraph {
+        &self.presence
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.presence.capacity()
+    }
+
+    /// the presence matrix and the planes under `edges`, the transpose under
+    /// `auxiliary`
+    pub fn memory_bytes(&self) -> MemoryBreakdown {
+        let presence = self.presence.memory_bytes();
+        MemoryBreakdown {
+            edges: presence.edges + self.planes.iter().map(vec_bytes).sum::<usize>(),
+            ..presence
+        }
+    }
+
+    fn read_weight(&self, from: usize, to: usize) -> u8 {
+        let (index, offset) = locate(self.presence.stride, from, to);
+        self.planes
+            .iter()
+            .enumerate()
+            .map(|(bit, plane)| (((plane[index] >> offset) & 1) as u8) << bit)
+            .sum()
+    }
+
+    fn write_weight(&mut self, from: usize, to: usize, weight: u8) {
+        let (index, offset) = locate(self.presence.stride, from, to);
+        for (bit, plane) in self.planes.iter_mut().enumerate() {
+            plane[index] = if (weight >> bit) & 1 != 0 {
+                set_bit(plane[index], offset)
+            } else {
+                unset_bit(plane[index], offset)
+            };
+        }
+    }
+
+    /// moves every edge and weight into matrices of dimension `dim`
+    fn grow_to(&mut self, dim: usize) {
+        let edges: Vec<EdgeMeta<u8>> = self.edges().collect();
+
+        self.presence.grow_to(dim);
+        let words = self.presence.edges.len();
+        self.planes = std::array::from_fn(|_| vec![0; words]);
+
+        for edge in edges {
+            self.write_weight(edge.source, edge.destination, edge.weight);
+        }
+    }
+}
+
+impl<const BITS: usize> Graph<u64, u8> for LayeredBitGraph<BITS> {
+    /// weight 1, an edge already there keeps its weight
+    fn add_edge(&mut self, from: usize, to: usize) -> bool {
+        if self.has_edge(from, to) {
+            return true;
+        }
+
+        self.set_edge((from, to), 1)
+    }
+
+    /// panics if `weight` does not fit in `BITS` bits
+    fn set_edge(&mut self, from_to: (usize, usize), weight: u8) -> bool {
+        assert!(
+            weight <= Self::max_weight(),
+            "weight {} does not fit in {} bits",
+            weight,
+            BITS
+        );
+
+        let (from, to) = from_to;
+        let existed = self.presence.add_edge(from, to);
+        self.write_weight(from, to, weight);
+        existed
+    }
+
+    fn remove_edge(&mut self, from: usize, to: usize) -> bool {
+        let existed = self.presence.remove_edge(from, to);
+        if existed {
+            self.write_weight(from, to, 0);
+        }
+        existed
+    }
+
+    fn has_edge(&self, from: usize, to: usize) -> bool {
+        self.presence.has_edge(from, to)
+    }
+
+    fn get_edge(&self, from: usize, to: usize) -> Option<EdgeMeta<u8>> {
+        if !self.presence.has_edge(from, to) {
+            return None;
+        }
+
+        Some(EdgeMeta {
+            source: from,
+            destination: to,
+            weight: self.read_weight(from, to),
+        })
+    }
+
+    fn outgoing_edges_of(&self, node_index: usize) -> Vec<usize> {
+        self.presence.outgoing_edges_of(node_index)
+    }
+
+    fn neighbors_sorted(&self, node_index: usize) -> Vec<usize> {
+        self.presence.neighbors_sorted(node_index)
+    }
+
+    fn incoming_edges_of(&self, node_index: usize) -> Vec<usize> {
+        self.presence.incoming_edges_of(node_index)
+    }
+
+    fn outgoing_edges_into(&self, node_index: usize, out: &mut Vec<usize>) {
+        self.presence.outgoing_edges_into(node_index, out)
+    }
+
+    fn incoming_edges_into(&self, node_index: usize, out: &mut Vec<usize>) {
+        self.presence.incoming_edges_into(node_index, out)
+    }
+
+    /// the edges of the presence matrix with their weights read off the
+    /// planes
+    fn edges(&self) -> Box<dyn Iterator<Item = EdgeMeta<u8>> + '_> {
+        Box::new(self.presence.edges().map(move |edge| EdgeMeta {
+            source: edge.source,
+            destination: edge.destination,
+            weight: self.read_weight(edge.source, edge.destination),
+        }))
+    }
+
+    fn out_degree(&self, node_index: usize) -> usize {
+        self.presence.out_degree(node_index)
+    }
+
+    fn in_degree(&self, node_index: usize) -> usize {
+        self.presence.in_degree(node_index)
+    }
+
+    fn self_loops(&self) -> Box<dyn Iterator<Item = usize> + '_> {
+        self.presence.self_loops()
+    }
+
+    /// zeroes the presence matrix and every plane in place
+    fn clear_edges(&mut self) {
+        self.presence.clear_edges();
+        for plane in &mut self.planes {
+            plane.iter_mut().for_each(|word| *word = 0);
+        }
+    }
+
+    /// keeps every matrix allocated, so `capacity` stays the same
+    fn clear(&mut self) {
+        self.clear_edges();
+        self.presence.clear();
+    }
+
+    /// doubles the matrices when they are full
+    fn push_node(&mut self, value: u64) -> usize {
+        let count = self.presence.count;
+        if count == self.capacity() {
+            self.grow_to((count * 2).max(DEFAULT_CAPACITY));
+        }
+
+        self.presence.push_node(value)
+    }
+
+    fn reserve_nodes(&mut self, additional: usize) {
+        let wanted = self.presence.count + additional;
+        if wanted > self.capacity() {
+            self.grow_to(wanted);
+        }
+    }
+
+    fn set_node(&mut self, node_index: usize, value: u64) {
+        self.presence.set_node(node_index, value);
+    }
+
+    fn get_node(&self, node_index: usize) -> &u64 {
+        self.presence.get_node(node_index)
+    }
+
+    /// Removes the node the way `Vec::swap_remove` does, like
+    /// `BitGraph::remove_node`, the last node moves to `node_index` with its
+    /// edges and their weights.
+    fn remove_node(&mut self, node_index: usize) -> u64 {
+        assert_node(node_index, self.presence.count);
+        self.clear_edges_of(node_index);
+
+        let last = self.presence.count - 1;
+        let mut moved = Vec::new();
+        if node_index != last {
+            let renamed = |node| if node == last { node_index } else { node };
+            for to in self.outgoing_edges_of(last) {
+                moved.push((node_index, renamed(to), self.read_weight(last, to)));
+            }
+            // a self loop came along with the outgoing edges
+            for from in self.incoming_edges_of(last) {
+                if from != last {
+                    moved.push((from, node_index, self.read_weight(from, last)));
+                }
+            }
+            self.clear_edges_of(last);
+        }
+
+        // neither node has edges left, so this only moves the value
+        let value = self.presence.remove_node(node_index);
+        for (from, to, weight) in moved {
+            self.set_edge((from, to), weight);
+        }
+
+        value
+    }
+
+    fn node_count(&self) -> usize {
+        self.presence.count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::a_star::AStar;
+    use crate::search::bellman_ford;
+    use crate::search::Pathfinder;
+
+    /// 100 nodes so rows start at different offsets inside their words
+    fn graph<const BITS: usize>() -> LayeredBitGraph<BITS> {
+        let mut graph = LayeredBitGraph::with_capacity(100);
+        for i in 0..100 {
+            graph.push_node(i);
+        }
+        graph
+    }
+
+    #[test]
+    fn weight_round_trip_test() {
+        let mut graph = graph::<3>();
+        let edges = [
+            (0, 63, 0),
+            (0, 64, 1),
+            (63, 64, 7),
+            (99, 0, 7),
+            (64, 63, 1),
+            (50, 50, 0),
+        ];
+        for &(from, to, weight) in &edges {
+            assert!(!graph.set_edge((from, to), weight));
+        }
+
+        for &(from, to, weight) in &edges {
+            assert!(graph.has_edge(from, to));
+            assert_eq!(graph.get_edge(from, to).unwrap().weight, weight);
+        }
+        assert_eq!(graph.get_edge(63, 0).map(|edge| edge.weight), None);
+        assert_eq!(graph.outgoing_edges_of(0), vec![63, 64]);
+        assert_eq!(graph.incoming_edges_of(64), vec![0, 63]);
+
+        let mut listed: Vec<(usize, usize, u8)> = graph
+            .edges()
+            .map(|edge| (edge.source, edge.destination, edge.weight))
+            .collect();
+        let mut expected = edges.to_vec();
+        listed.sort_unstable();
+        expected.sort_unstable();
+        assert_eq!(listed, expected);
+    }
+
+    #[test]
+    fn overwrite_test() {
+        let mut graph = graph::<3>();
+
+        graph.set_edge((10, 70), 7);
+        assert!(graph.set_edge((10, 70), 1));
+        assert_eq!(graph.get_edge(10, 70).unwrap().weight, 1);
+
+        // removing clears the planes, so the edge comes back as weight 1
+        graph.set_edge((10, 70), 6);
+        assert!(graph.remove_edge(10, 70));
+        assert!(!graph.remove_edge(10, 70));
+        assert!(!graph.add_edge(10, 70));
+        assert_eq!(graph.get_edge(10, 70).unwrap().weight, 1);
+
+        graph.set_edge((10, 70), 0);
+        assert!(graph.has_edge(10, 70));
+        assert_eq!(graph.get_edge(10, 70).unwrap().weight, 0);
+    }
+
+    #[test]
+    fn plane_count_test() {
+        assert_eq!(LayeredBitGraph::<1>::max_weight(), 1);
+        assert_eq!(LayeredBitGraph::<3>::max_weight(), 7);
+        assert_eq!(LayeredBitGraph::<8>::max_weight(), 255);
+
+        let mut wide = graph::<8>();
+        wide.set_edge((1, 2), 255);
+        wide.set_edge((2, 1), 128);
+        assert_eq!(wide.get_edge(1, 2).unwrap().weight, 255);
+        assert_eq!(wide.get_edge(2, 1).unwrap().weight, 128);
+
+        let bit = BitGraph::with_capacity(100).memory_bytes();
+        let layered = LayeredBitGraph::<3>::with_capacity(100).memory_bytes();
+        assert_eq!(layered.edges, 4 * bit.edges);
+        assert_eq!(layered.auxiliary, bit.auxiliary);
+    }
+
+    #[test]
+    #[should_panic(expected = "weight 8 does not fit in 3 bits")]
+    fn too_heavy_test() {
+        graph::<3>().set_edge((0, 1), 8);
+    }
+
+    #[test]
+    fn grow_test() {
+        let mut graph = LayeredBitGraph::<3>::new();
+        for i in 0..100 {
+            graph.push_node(i);
+            if i > 0 {
+                graph.set_edge((i as usize - 1, i as usize), (i % 8) as u8);
+            }
+        }
+
+        assert!(graph.capacity() >= 100);
+        for i in 1..100 {
+            assert_eq!(graph.get_edge(i - 1, i).unwrap().weight, (i % 8) as u8);
+        }
+        assert_eq!(graph.edges().count(), 99);
+    }
+
+    #[test]
+    fn remove_node_test() {
+        let mut graph = graph::<3>();
+        graph.set_edge((5, 99), 3);
+        graph.set_edge((99, 6), 5);
+        graph.set_edge((99, 99), 7);
+        graph.set_edge((7, 5), 2);
+
+        assert_eq!(graph.remove_node(5), 5);
+        assert_eq!(graph.node_count(), 99);
+        assert_eq!(*graph.get_node(5), 99);
+
+        // 99 now answers to 5, the edges of the old 5 are gone
+        assert_eq!(graph.get_edge(5, 6).unwrap().weight, 5);
+        assert_eq!(graph.get_edge(5, 5).unwrap().weight, 7);
+        assert!(graph.get_edge(7, 5).is_none());
+        assert_eq!(graph.edges().count(), 2);
+        assert_eq!(graph.presence().validate(), Ok(()));
+    }
+
+    #[test]
+    fn shortest_path_test() {
+        let mut graph = graph::<3>();
+
+        // the direct edge is heavy, the long way round weighs 1 + 0 + 2
+        graph.set_edge((0, 99), 7);
+        graph.set_edge((0, 63), 1);
+        graph.set_edge((63, 64), 0);
+        graph.set_edge((64, 99), 2);
+
+        let mut astar = AStar::new(&graph, 0, 99, |_| 0).weighted();
+        assert_eq!(astar.path_to(&graph, 99).unwrap(), vec![0, 63, 64, 99]);
+        assert_eq!(astar.cost_of(99), Some(3));
+
+        let mut astar = AStar::new(&graph, 0, 99, |_| 0);
+        assert_eq!(astar.path_to(&graph, 99).unwrap(), vec![0, 99]);
+    }
+
+    #[test]
+    fn dijkstra_test() {
+        let mut graph = graph::<3>();
+        for i in 0..400 {
+            let from = (i * 37) % 100;
+            let to = (i * 61 + 7) % 100;
+            graph.set_edge((from, to), (i % 8) as u8);
+        }
+
+        let (distances, _) = bellman_ford(&graph, 0).unwrap();
+        for (goal, distance) in distances.into_iter().enumerate() {
+            // A* with no heuristic is Dijkstra
+            let mut astar = AStar::new(&graph, 0, goal, |_| 0).weighted();
+            astar.path_to(&graph, goal);
+            assert_eq!(
+                astar.cost_of(goal).map(|cost| cost as i64),
+                distance,
+                "distance to {}",
+                goal
+            );
+        }
+    }
+}
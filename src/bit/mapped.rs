@@ -0,0 +1,306 @@
+//! Read only access to a snapshot file without loading it.
+//!
+//! The header is 28 bytes and every node value after it is 8, so the edge
+//! words start 4 bytes off an 8 byte boundary no matter what. Words are read
+//! out of the mapped bytes with `u64::from_le_bytes`, which has no alignment
+//! requirement, instead of casting the region to `&[u64]`.
+
+use super::snapshot::{file_word_count, read_header, HEADER_LEN};
+use super::BitGraph;
+use crate::assert_node;
+use memmap2::Mmap;
+use std::convert::TryInto;
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+/// A `BitGraph` snapshot mapped into memory. Queries read the file words
+/// directly, there is no transpose so incoming edges scan a column.
+///
+/// Only the read half of `Graph` is here, to change anything load it with
+/// `to_bit_graph` first.
+#[derive(Debug)]
+pub struct MappedBitGraph {
+    map: Mmap,
+    count: usize,
+    dimension: usize,
+    /// byte offset of the first edge word
+    words_start: usize,
+}
+
+impl BitGraph {
+    /// Maps a file written by `write_to`. The header is checked the same way
+    /// `read_from` checks it and a file too short for the sizes in its header
+    /// comes back as `UnexpectedEof`.
+    ///
+    /// The file must not be changed by anyone else while it is mapped.
+    pub fn map_readonly<P: AsRef<Path>>(path: P) -> io::Result<MappedBitGraph> {
+        let file = File::open(path)?;
+        // SAFETY: the map is never written through, and the caller promises
+        // nobody truncates or rewrites the file underneath it
+        let map = unsafe { Mmap::map(&file)? };
+
+        let header = read_header(&mut &map[..])?;
+        let words_start = HEADER_LEN + header.node_count * 8;
+        let expected = file_word_count(header.dimension)
+            .checked_mul(8)
+            .and_then(|words| words.checked_add(words_start));
+
+        match expected {
+            Some(expected) if expected <= map.len() => {}
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    format!(
+                        "snapshot is {} bytes, too short for {} nodes of dimension {}",
+                        map.len(),
+                        header.node_count,
+                        header.dimension
+                    ),
+                ))
+            }
+        }
+
+        Ok(MappedBitGraph {
+            map,
+            count: header.node_count,
+            dimension: header.dimension,
+            words_start,
+        })
+    }
+}
+
+impl MappedBitGraph {
+    pub fn node_count(&self) -> usize {
+        self.count
+    }
+
+    /// row stride of the stored matrix, the capacity of the graph written
+    pub fn dimension(&self) -> usize {
+        self.dimension
+    }
+
+    pub fn get_node(&self, node_index: usize) -> u64 {
+        assert_node(node_index, self.count);
+        self.read_u64(HEADER_LEN + node_index * 8)
+    }
+
+    pub fn node_values(&self) -> impl Iterator<Item = u64> + '_ {
+        (0..self.count).map(move |node| self.read_u64(HEADER_LEN + node * 8))
+    }
+
+    pub fn has_edge(&self, from: usize, to: usize) -> bool {
+        assert_node(from, self.count);
+        assert_node(to, self.count);
+        self.bit(from * self.dimension + to)
+    }
+
+    pub fn has_self_loop(&self, node_index: usize) -> bool {
+        self.has_edge(node_index, node_index)
+    }
+
+    /// outgoing neighbours in increasing order
+    pub fn neighbors(&self, node_index: usize) -> impl Iterator<Item = usize> + '_ {
+        assert_node(node_index, self.count);
+        let start = node_index * self.dimension;
+        let end = start + self.count;
+
+        (start / 64..end.div_ceil(64)).flat_map(move |index| {
+            let base = index * 64;
+            let mut word = self.word(index);
+            // drop bits belonging to the rows on either side
+            if base < start {
+                word &= u64::MAX << (start - base);
+            }
+            if end < base + 64 {
+                word &= !(u64::MAX << (end - base));
+            }
+
+            std::iter::from_fn(move || {
+                if word == 0 {
+                    return None;
+                }
+                let offset = word.trailing_zeros() as usize;
+                word &= word - 1;
+                Some(base + offset - start)
+            })
+        })
+    }
+
+    /// incoming neighbours in increasing order, one bit per node
+    pub fn incoming_neighbors(&self, node_index: usize) -> impl Iterator<Item = usize> + '_ {
+        assert_node(node_index, self.count);
+        (0..self.count).filter(move |&from| self.bit(from * self.dimension + node_index))
+    }
+
+    pub fn outgoing_edges_of(&self, node_index: usize) -> Vec<usize> {
+        self.neighbors(node_index).collect()
+    }
+
+    pub fn incoming_edges_of(&self, node_index: usize) -> Vec<usize> {
+        self.incoming_neighbors(node_index).collect()
+    }
+
+    pub fn out_degree(&self, node_index: usize) -> usize {
+        self.neighbors(node_index).count()
+    }
+
+    pub fn in_degree(&self, node_index: usize) -> usize {
+        self.incoming_neighbors(node_index).count()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        (0..self.count).map(|node| self.out_degree(node)).sum()
+    }
+
+    /// Reads the whole snapshot into an ordinary `BitGraph`.
+    pub fn to_bit_graph(&self) -> BitGraph {
+        BitGraph::read_from(&self.map[..]).expect("mapped snapshot was checked on open")
+    }
+
+    fn bit(&self, bit: usize) -> bool {
+        (self.word(bit / 64) >> (bit % 64)) & 1 != 0
+    }
+
+    fn word(&self, index: usize) -> u64 {
+        self.read_u64(self.words_start + index * 8)
+    }
+
+    fn read_u64(&self, offset: usize) -> u64 {
+        let bytes = self.map[offset..offset + 8].try_into().unwrap();
+        u64::from_le_bytes(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Graph;
+    use std::fs;
+    use std::path::PathBuf;
+
+    /// a file in the temp dir that is removed again when dropped
+    struct TempFile(PathBuf);
+
+    impl TempFile {
+        fn new(name: &str, bytes: &[u8]) -> TempFile {
+            let path = std::env::temp_dir().join(format!(
+                "bit-graph-{}-{}.bgrf",
+                name,
+                std::process::id()
+            ));
+            fs::write(&path, bytes).unwrap();
+            TempFile(path)
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.0);
+        }
+    }
+
+    fn snapshot(graph: &BitGraph) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        graph.write_to(&mut bytes).unwrap();
+        bytes
+    }
+
+    fn sample(mut graph: BitGraph, count: usize) -> BitGraph {
+        for i in 0..count {
+            graph.push_node(i as u64 * 7);
+        }
+        for i in 0..count {
+            let a = i.wrapping_mul(2654435761) % count;
+            let b = (i * 5 + 3) % count;
+            graph.add_edge(a, b);
+            graph.add_edge(i, (i + 64) % count);
+        }
+        graph.add_edge(0, count - 1);
+        graph.add_edge(count - 1, count - 1);
+        graph
+    }
+
+    fn assert_same(mapped: &MappedBitGraph, graph: &BitGraph) {
+        assert_eq!(mapped.node_count(), graph.node_count());
+        assert!(mapped.node_values().eq(graph.node_values().copied()));
+
+        for from in 0..graph.node_count() {
+            assert_eq!(mapped.get_node(from), *graph.get_node(from));
+            assert_eq!(mapped.outgoing_edges_of(from), graph.neighbors_sorted(from));
+
+            let mut incoming = graph.incoming_edges_of(from);
+            incoming.sort_unstable();
+            assert_eq!(mapped.incoming_edges_of(from), incoming);
+
+            assert_eq!(mapped.out_degree(from), graph.out_degree(from));
+            assert_eq!(mapped.in_degree(from), graph.in_degree(from));
+            assert_eq!(mapped.has_self_loop(from), graph.has_self_loop(from));
+            for to in 0..graph.node_count() {
+                assert_eq!(mapped.has_edge(from, to), graph.has_edge(from, to));
+            }
+        }
+
+        assert_eq!(mapped.edge_count(), graph.all_edge_pairs().len());
+    }
+
+    #[test]
+    fn mapped_test() {
+        // 131 nodes in a dimension of 200, rows straddle word boundaries
+        let graph = sample(BitGraph::with_capacity(200), 131);
+        let file = TempFile::new("mapped", &snapshot(&graph));
+
+        let mapped = BitGraph::map_readonly(&file.0).unwrap();
+        assert_eq!(mapped.dimension(), 200);
+        assert_same(&mapped, &graph);
+        assert!(mapped.to_bit_graph() == graph);
+    }
+
+    #[test]
+    fn mapped_aligned_test() {
+        // written packed, so it maps the same as an ordinary graph
+        let graph = sample(BitGraph::with_capacity_aligned(100), 70);
+        let file = TempFile::new("mapped-aligned", &snapshot(&graph));
+
+        let mapped = BitGraph::map_readonly(&file.0).unwrap();
+        assert_same(&mapped, &graph);
+    }
+
+    #[test]
+    fn mapped_empty_test() {
+        let file = TempFile::new("mapped-empty", &snapshot(&BitGraph::with_capacity(0)));
+
+        let mapped = BitGraph::map_readonly(&file.0).unwrap();
+        assert_eq!(mapped.node_count(), 0);
+        assert_eq!(mapped.edge_count(), 0);
+    }
+
+    #[test]
+    fn mapped_bad_file_test() {
+        let bytes = snapshot(&sample(BitGraph::with_capacity(80), 80));
+
+        let mut word_bits = bytes.clone();
+        word_bits[8] = 32;
+        let file = TempFile::new("mapped-word-bits", &word_bits);
+        let error = BitGraph::map_readonly(&file.0).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        let file = TempFile::new("mapped-short", &bytes[..bytes.len() - 1]);
+        let error = BitGraph::map_readonly(&file.0).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+
+        let file = TempFile::new("mapped-header", &bytes[..10]);
+        let error = BitGraph::map_readonly(&file.0).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn mapped_bounds_test() {
+        let file = TempFile::new(
+            "mapped-bounds",
+            &snapshot(&sample(BitGraph::with_capacity(10), 10)),
+        );
+        BitGraph::map_readonly(&file.0).unwrap().has_edge(0, 10);
+    }
+}
@@ -1,8 +1,40 @@
 const WORD_BYTES: usize = std::mem::size_of::<usize>();
 const WORD_BITS: usize = WORD_BYTES * 8;
 const DEFAULT_CAPACITY: usize = 16;
-
-use crate::{EdgeMeta, Graph};
+/// rows and columns past this are cut off in the `Debug` grid
+const DEBUG_GRID_LIMIT: usize = 64;
+
+mod bitset;
+mod cliques;
+mod diff;
+mod graph6;
+mod layered;
+#[cfg(feature = "mmap")]
+mod mapped;
+mod permute;
+#[cfg(feature = "petgraph")]
+mod petgraph_impl;
+mod reachability;
+mod rows;
+mod similarity;
+pub mod snapshot;
+mod sync;
+
+pub use bitset::NodeBitSet;
+pub use diff::{diff, GraphDiff};
+pub use graph6::Graph6Error;
+pub use layered::LayeredBitGraph;
+#[cfg(feature = "mmap")]
+pub use mapped::MappedBitGraph;
+pub use permute::PermutationError;
+pub use rows::RowWords;
+pub use sync::SyncBitGraph;
+
+use crate::integrity::{check_count, into_result, IntegrityError};
+use crate::memory::{vec_bytes, MemoryBreakdown};
+use crate::{assert_node, covering_count, EdgeMeta, Graph};
+use std::fmt;
+use std::iter::FromIterator;
 
 pub struct BitGraph {
     count: usize,
@@ -14,6 +46,93 @@ pub struct BitGraph {
     edges: Vec<usize>,
 
     edges_transpose: Vec<usize>,
+    /// Bits from the start of one row to the next in both matrices. The
+    /// dimension for the packed layout of `with_capacity`, rounded up to
+    /// whole words for `with_capacity_aligned`.
+    stride: usize,
+    row_aligned: bool,
+    /// Edge changes leave `edges_transpose` alone and mark it stale, see
+    /// `with_capacity_directed_only`. Never set for a plain `with_capacity`.
+    lazy_transpose: bool,
+    /// `edges_transpose` is behind `edges`, only ever set with
+    /// `lazy_transpose`
+    transpose_stale: bool,
+}
+
+impl Default for BitGraph {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// `Vec::clone` does not keep capacity, which is the row stride here, so the
+/// node storage has to be rebuilt at the right size
+impl Clone for BitGraph {
+    fn clone(&self) -> Self {
+        let mut graph = self.empty_copy();
+        graph.edges.copy_from_slice(&self.edges);
+        graph.edges_transpose.copy_from_slice(&self.edges_transpose);
+        graph.transpose_stale = self.transpose_stale;
+        graph
+    }
+}
+
+impl_node_access!(impl for BitGraph, u64);
+
+/// Adds every `(from, to)` pair, pushing nodes valued `0` until both ends are
+/// nodes. The pairs are collected first so the matrices are resized at most
+/// once, to at least double their old dimension.
+impl Extend<(usize, usize)> for BitGraph {
+    fn extend<I: IntoIterator<Item = (usize, usize)>>(&mut self, iter: I) {
+        let edges: Vec<(usize, usize)> = iter.into_iter().collect();
+        let needed = match edges
+            .iter()
+            .map(|&(from, to)| covering_count(from, to))
+            .max()
+        {
+            Some(needed) => needed,
+            None => return,
+        };
+
+        let dim = self.nodes.capacity();
+        if needed > dim {
+            self.grow_to(needed.max(dim * 2));
+        }
+        while self.count < needed {
+            self.push_node(0);
+        }
+
+        self.add_edges(edges);
+    }
+}
+
+/// sized for the largest index up front, nodes are valued `0`
+impl FromIterator<(usize, usize)> for BitGraph {
+    fn from_iter<I: IntoIterator<Item = (usize, usize)>>(iter: I) -> Self {
+        let edges: Vec<(usize, usize)> = iter.into_iter().collect();
+        let count = edges
+            .iter()
+            .map(|&(from, to)| covering_count(from, to))
+            .max()
+            .unwrap_or(0);
+
+        let mut graph = BitGraph::with_capacity(count.max(DEFAULT_CAPACITY));
+        graph.extend(edges);
+        graph
+    }
+}
+
+/// Two graphs are equal when they have the same node values and edges, the
+/// capacity they were made with does not matter. Since the row stride follows
+/// the capacity, rows are compared one at a time in word aligned form.
+impl PartialEq for BitGraph {
+    fn eq(&self, other: &Self) -> bool {
+        self.count == other.count
+            && self.nodes[..self.count] == other.nodes[..other.count]
+            && (0..self.count).all(|node| {
+                self.aligned_row(&self.edges, node) == other.aligned_row(&other.edges, node)
+            })
+    }
 }
 
 impl BitGraph {
@@ -22,23 +141,250 @@ impl BitGraph {
     }
 
     pub fn with_capacity(size: usize) -> BitGraph {
+        Self::with_stride(size, size, false)
+    }
+
+    /// Graph whose rows each start on a word boundary, padded up to a whole
+    /// number of words. Finding a bit then needs no carry from one word into
+    /// the next, and a row scan no masking at its start, for at most
+    /// `WORD_BITS - 1` extra bits per row in each matrix.
+    pub fn with_capacity_aligned(size: usize) -> BitGraph {
+        Self::with_stride(size, size.div_ceil(WORD_BITS) * WORD_BITS, true)
+    }
+
+    fn with_stride(size: usize, stride: usize, row_aligned: bool) -> BitGraph {
         BitGraph {
             count: 0,
 
             nodes: Vec::with_capacity(size),
-            edges: vec![0; (size * size) / WORD_BITS + 1],
-            edges_transpose: vec![0; (size * size) / WORD_BITS + 1],
+            edges: vec![0; (size * stride) / WORD_BITS + 1],
+            edges_transpose: vec![0; (size * stride) / WORD_BITS + 1],
+            stride,
+            row_aligned,
+            lazy_transpose: false,
+            transpose_stale: false,
+        }
+    }
+
+    /// `has_edge` without bounds checks on the nodes or the matrix, for hot
+    /// loops over indices that were checked once up front. Only debug builds
+    /// check the contract.
+    ///
+    /// # Safety
+    ///
+    /// `from` and `to` must both be below `capacity()`. Indices past
+    /// `node_count` but within the capacity are fine and read as no edge.
+    pub unsafe fn has_edge_unchecked(&self, from: usize, to: usize) -> bool {
+        debug_assert!(
+            from < self.nodes.capacity() && to < self.nodes.capacity(),
+            "edge ({}, {}) out of bounds for capacity {}",
+            from,
+            to,
+            self.nodes.capacity()
+        );
+
+        let (index, offset) = locate(self.stride, from, to);
+        get_bit(*self.edges.get_unchecked(index), offset)
+    }
+
+    /// Destinations of the edges out of `node_index` in ascending order,
+    /// lazily and without bounds checks on the matrix. Only debug builds
+    /// check the contract.
+    ///
+    /// # Safety
+    ///
+    /// `node_index` must be below `capacity()`.
+    pub unsafe fn outgoing_edges_unchecked(
+        &self,
+        node_index: usize,
+    ) -> impl Iterator<Item = usize> + '_ {
+        debug_assert!(
+            node_index < self.nodes.capacity(),
+            "node {} out of bounds for capacity {}",
+            node_index,
+            self.nodes.capacity()
+        );
+
+        let start = self.stride * node_index;
+        let end = start + self.nodes.capacity();
+
+        (start / WORD_BITS..end.div_ceil(WORD_BITS)).flat_map(move |index| {
+            let mut word = *self.edges.get_unchecked(index);
+            if index == start / WORD_BITS {
+                word &= mask_n_bits(start % WORD_BITS);
+            }
+            if index == end / WORD_BITS {
+                word &= !mask_n_bits(end % WORD_BITS);
+            }
+
+            ones(word).map(move |offset| index * WORD_BITS + offset - start)
+        })
+    }
+
+    /// true if made with `with_capacity_aligned`
+    pub fn is_row_aligned(&self) -> bool {
+        self.row_aligned
+    }
+
+    /// empty graph with room for `size` nodes in the same layout and
+    /// transpose mode as `self`
+    fn with_same_layout(&self, size: usize) -> BitGraph {
+        let mut graph = if self.row_aligned {
+            BitGraph::with_capacity_aligned(size)
+        } else {
+            BitGraph::with_capacity(size)
+        };
+        graph.lazy_transpose = self.lazy_transpose;
+        graph
+    }
+
+    /// Graph for mostly writing edges and rarely asking for incoming ones.
+    /// Adding or removing an edge only touches `edges`, the transpose is
+    /// left stale. While it is, `incoming_edges_of` and `in_degree` test one
+    /// bit per row instead of scanning a row of the transpose, and methods
+    /// that change both matrices at once rebuild it first.
+    /// `refresh_transpose` rebuilds it on demand.
+    pub fn with_capacity_directed_only(size: usize) -> BitGraph {
+        let mut graph = Self::with_capacity(size);
+        graph.lazy_transpose = true;
+        graph
+    }
+
+    /// true if made with `with_capacity_directed_only`
+    pub fn is_directed_only(&self) -> bool {
+        self.lazy_transpose
+    }
+
+    /// Rebuilds the transpose if edges changed since it was last up to
+    /// date, so incoming edges are a row scan again until the next change.
+    /// Does nothing for a graph made with `with_capacity`, its transpose is
+    /// never stale.
+    pub fn refresh_transpose(&mut self) {
+        if self.transpose_stale {
+            self.rebuild_transpose();
+        }
+    }
+
+    /// most nodes that fit, also the row length of both matrices
+    pub fn capacity(&self) -> usize {
+        self.nodes.capacity()
+    }
+
+    /// the matrix under `edges`, its transpose under `auxiliary`
+    pub fn memory_bytes(&self) -> MemoryBreakdown {
+        MemoryBreakdown {
+            nodes: vec_bytes(&self.nodes),
+            edges: vec_bytes(&self.edges),
+            auxiliary: vec_bytes(&self.edges_transpose),
+        }
+    }
+
+    /// Checks that every bit set in either matrix is between two nodes and
+    /// has its mirror image set in the other one. A transpose left stale by
+    /// `with_capacity_directed_only` is expected to be behind and is only
+    /// checked for bounds.
+    pub fn validate(&self) -> Result<(), Vec<IntegrityError>> {
+        let mut errors = Vec::new();
+        check_count(self.count, self.nodes.len(), &mut errors);
+
+        let stride = self.stride.max(1);
+        for (matrix, other, transposed) in [
+            (&self.edges, &self.edges_transpose, false),
+            (&self.edges_transpose, &self.edges, true),
+        ] {
+            for (index, &word) in matrix.iter().enumerate() {
+                for offset in ones(word) {
+                    let bit = index * WORD_BITS + offset;
+                    let (row, column) = (bit / stride, bit % stride);
+                    let (from, to) = if transposed {
+                        (column, row)
+                    } else {
+                        (row, column)
+                    };
+
+                    if row >= self.count || column >= self.count {
+                        errors.push(IntegrityError::EdgeOutOfBounds {
+                            from,
+                            to,
+                            count: self.count,
+                        });
+                        continue;
+                    }
+
+                    let (index, offset) = locate(self.stride, column, row);
+                    if !self.transpose_stale && !get_bit(other[index], offset) {
+                        errors.push(IntegrityError::TransposeMismatch { from, to });
+                    }
+                }
+            }
+        }
+
+        into_result(errors)
+    }
+
+    /// Builds a graph of `node_count` nodes valued `0` out of a list of
+    /// `(from, to)` pairs, the matrix is only allocated once.
+    ///
+    /// Returns the first pair with an endpoint `>= node_count` as the error.
+    pub fn from_edges<I>(node_count: usize, edges: I) -> Result<BitGraph, (usize, usize)>
+    where
+        I: IntoIterator<Item = (usize, usize)>,
+    {
+        Self::from_nodes_and_edges(std::iter::repeat_n(0, node_count), edges)
+    }
+
+    /// `nodes` with no edges between them, the matrix is sized to fit
+    pub(crate) fn from_nodes<N: IntoIterator<Item = u64>>(nodes: N) -> BitGraph {
+        let nodes: Vec<u64> = nodes.into_iter().collect();
+
+        let mut graph = BitGraph::with_capacity(nodes.len());
+        graph.nodes.extend_from_slice(&nodes);
+        graph.count = nodes.len();
+        graph
+    }
+
+    /// same as `from_edges` but with node values, `node_count` is the number
+    /// of values in `nodes`
+    pub fn from_nodes_and_edges<N, I>(nodes: N, edges: I) -> Result<BitGraph, (usize, usize)>
+    where
+        N: IntoIterator<Item = u64>,
+        I: IntoIterator<Item = (usize, usize)>,
+    {
+        let mut graph = Self::from_nodes(nodes);
+
+        for (from, to) in edges {
+            if from >= graph.count || to >= graph.count {
+                return Err((from, to));
+            }
+
+            graph.set_edge_of_both(from, to, set_bit);
         }
+
+        Ok(graph)
+    }
+
+    /// adds every `(from, to)` pair, returns how many of them were new edges
+    pub fn add_edges<I>(&mut self, edges: I) -> usize
+    where
+        I: IntoIterator<Item = (usize, usize)>,
+    {
+        edges
+            .into_iter()
+            .filter(|(from, to)| !self.set_edge_of_both(*from, *to, set_bit))
+            .count()
     }
 
     fn set_edge_of_both<F>(&mut self, from: usize, to: usize, fun: F) -> bool
     where
         F: Fn(usize, usize) -> usize,
     {
+        assert_node(from, self.count);
+        assert_node(to, self.count);
+
         // get proper word
-        let row = (self.nodes.capacity() * from) / WORD_BITS;
+        let row = (self.stride * from) / WORD_BITS;
         let mut column = to / WORD_BITS;
-        let mut offset = to % WORD_BITS + ((self.nodes.capacity() * from) % WORD_BITS);
+        let mut offset = to % WORD_BITS + ((self.stride * from) % WORD_BITS);
 
         if offset >= WORD_BITS {
             column += 1;
@@ -51,7 +397,11 @@ impl BitGraph {
 
         self.edges[row + column] = new_word;
 
-        self.set_edge_of_tranpose(to, from, fun);
+        if !self.lazy_transpose {
+            self.set_edge_of_tranpose(to, from, fun);
+        } else if new_word != word {
+            self.transpose_stale = true;
+        }
 
         get_bit(word, offset)
     }
@@ -61,9 +411,9 @@ impl BitGraph {
         F: FnOnce(usize, usize) -> usize,
     {
         // get proper word
-        let row = (self.nodes.capacity() * from) / WORD_BITS;
+        let row = (self.stride * from) / WORD_BITS;
         let mut column = to / WORD_BITS;
-        let mut offset = to % WORD_BITS + ((self.nodes.capacity() * from) % WORD_BITS);
+        let mut offset = to % WORD_BITS + ((self.stride * from) % WORD_BITS);
 
         if offset >= WORD_BITS {
             column += 1;
@@ -76,6 +426,506 @@ impl BitGraph {
 
         self.edges_transpose[row + column] = new_word;
     }
+
+    /// number of words needed to hold one word aligned row of `count` bits
+    #[inline]
+    fn aligned_row_len(&self) -> usize {
+        self.count / WORD_BITS + 1
+    }
+
+    /// copies the row of `node_index` out of `matrix` so that bit `i` of the
+    /// result is column `i`, columns at or past `count` are masked off
+    fn aligned_row(&self, matrix: &[usize], node_index: usize) -> Vec<usize> {
+        let start = self.stride * node_index;
+        let len = self.aligned_row_len();
+
+        let mut out = Vec::with_capacity(len);
+        for i in 0..len {
+            let index = start / WORD_BITS + i;
+            let offset = start % WORD_BITS;
+
+            let mut word = matrix.get(index).copied().unwrap_or(0) >> offset;
+            if offset != 0 {
+                word |= matrix.get(index + 1).copied().unwrap_or(0) << (WORD_BITS - offset);
+            }
+
+            out.push(word);
+        }
+
+        // drop anything past the last live column, this also covers bits
+        // that spilled over from the next row
+        out[len - 1] &= !mask_n_bits(self.count % WORD_BITS);
+
+        out
+    }
+
+    /// ORs a word aligned row, as made by `aligned_row`, back into the row of
+    /// `node_index` in `matrix`
+    fn or_aligned_row(&self, matrix: &mut [usize], node_index: usize, row: &[usize]) {
+        let start = self.stride * node_index;
+        let offset = start % WORD_BITS;
+
+        for (i, word) in row.iter().enumerate() {
+            if *word == 0 {
+                continue;
+            }
+
+            let index = start / WORD_BITS + i;
+            matrix[index] |= word << offset;
+            if offset != 0 {
+                let spill = word >> (WORD_BITS - offset);
+                if spill != 0 {
+                    matrix[index + 1] |= spill;
+                }
+            }
+        }
+    }
+
+    /// Writes the set columns of the row of `node_index` in `matrix` into
+    /// `out`, which is cleared first.
+    fn row_into(&self, matrix: &[usize], node_index: usize, out: &mut Vec<usize>) {
+        assert_node(node_index, self.count);
+
+        /*
+         * Implementation notes:
+         *  To calculate the destination node from the ctz correctly, we need
+         *  the to consider the following.
+         *
+         *  First, the start_offset tells how many bits into the
+         *  first row of the word should be discarded. Bits before the offset
+         *  should not be scanned. The start_offset additionally should be
+         *  subtracted from the calculated position for words after the start
+         *  word.
+         *
+         *  Second, the end_offset tells how many bits in the last word are
+         *  applicable to the current row. Bits after the end offset should
+         *  not be scanned.
+         *
+         *  Third, the number of words n already checked in the row should
+         *  add n*WORD_BITS to the destination node index.
+         */
+
+        let start = (self.stride * node_index) / WORD_BITS;
+        let start_offset = (self.stride * node_index) % WORD_BITS;
+        let end = (self.stride * (node_index + 1)) / WORD_BITS;
+        let end_offset = (self.stride * (node_index + 1)) % WORD_BITS;
+
+        let mut index = start;
+
+        /*
+         * The first word in the row will always need to mask the first
+         * start_offset bits. Additionally if the row is one word wide,
+         * the last end_offset bits must be masked as well
+         */
+        let mut word = matrix[index]
+            & (mask_n_bits(start_offset) & (!mask_n_bits(end_offset) | bool_to_mask(index != end)));
+
+        out.clear();
+        loop {
+            // If the word is empty, check for completion and get next word
+            if word == 0x0 {
+                if index == end {
+                    break;
+                }
+                index += 1;
+                // Get the next word, and if it is the last word, mask out
+                // any bit larger than end_offset
+                word = matrix[index] & (!mask_n_bits(end_offset) | bool_to_mask(index != end));
+            // If the word is not empty run ctz
+            } else {
+                // Get the total trailing zeroes in the word
+                // Finds the position of the next edge
+                let trailing_zeroes: usize = word.trailing_zeros() as usize;
+                // Compute and push the destination node index
+                // Address implementation notes in computation
+                out.push(trailing_zeroes + WORD_BITS * (index - start) - start_offset);
+                // clear the lowest set bit of the word
+                word = clear_lowest_set_bit(word);
+            }
+        }
+    }
+
+    /// copy with an up to date transpose, for `&self` methods that need
+    /// whole rows of it
+    fn refreshed(&self) -> BitGraph {
+        let mut graph = self.clone();
+        graph.refresh_transpose();
+        graph
+    }
+
+    /// the column of `node_index` read a bit per row, for a stale transpose
+    fn column_into(&self, node_index: usize, out: &mut Vec<usize>) {
+        assert_node(node_index, self.count);
+
+        out.clear();
+        out.extend((0..self.count).filter(|from| self.has_edge(*from, node_index)));
+    }
+
+    /// rebuilds `edges_transpose` from scratch out of `edges`
+    fn rebuild_transpose(&mut self) {
+        self.edges_transpose.iter_mut().for_each(|word| *word = 0);
+
+        for from in 0..self.count {
+            for to in self.outgoing_edges_of(from) {
+                self.set_edge_of_tranpose(to, from, set_bit);
+            }
+        }
+
+        self.transpose_stale = false;
+    }
+
+    /// moves nodes and edges into matrices of dimension `dim`, which has to
+    /// fit every node
+    fn grow_to(&mut self, dim: usize) {
+        let mut graph = self.with_same_layout(dim);
+        graph.nodes.extend_from_slice(&self.nodes);
+        graph.count = self.count;
+        graph.add_edges(self.edges().map(|edge| edge.key_pair()));
+
+        *self = graph;
+    }
+
+    /// copy in the packed layout of `with_capacity` at the same dimension
+    fn packed(&self) -> BitGraph {
+        let mut graph = BitGraph::with_capacity(self.nodes.capacity());
+        graph.nodes.extend_from_slice(&self.nodes);
+        graph.count = self.count;
+        graph.add_edges(self.edges().map(|edge| edge.key_pair()));
+        graph
+    }
+
+    /// empty graph with the same dimension and node values as `self`
+    fn empty_copy(&self) -> BitGraph {
+        let mut graph = self.with_same_layout(self.nodes.capacity());
+        graph.nodes.extend_from_slice(&self.nodes);
+        graph.count = self.count;
+        graph
+    }
+
+    /// Returns the complement of the graph, every pair `(i, j)` with `i != j`
+    /// among the first `node_count` nodes has an edge in the result exactly
+    /// when it has none in `self`. The result never has self loops.
+    pub fn complement(&self) -> BitGraph {
+        let mut graph = self.empty_copy();
+        let mut edges = std::mem::take(&mut graph.edges);
+
+        for node in 0..self.count {
+            let mut row = self.aligned_row(&self.edges, node);
+            row.iter_mut().for_each(|word| *word = !*word);
+
+            let len = row.len();
+            row[len - 1] &= !mask_n_bits(self.count % WORD_BITS);
+            row[node / WORD_BITS] = unset_bit(row[node / WORD_BITS], node % WORD_BITS);
+
+            graph.or_aligned_row(&mut edges, node, &row);
+        }
+
+        graph.edges = edges;
+        graph.rebuild_transpose();
+        graph
+    }
+
+    /// applies `fun` word by word to both matrices of `self` and `other`
+    fn combine_with<F>(&mut self, other: &BitGraph, fun: F)
+    where
+        F: Fn(usize, usize) -> usize,
+    {
+        assert_eq!(
+            self.nodes.capacity(),
+            other.nodes.capacity(),
+            "BitGraph dimensions differ, {} vs {}",
+            self.nodes.capacity(),
+            other.nodes.capacity()
+        );
+        assert_eq!(
+            self.stride, other.stride,
+            "BitGraph layouts differ, rows {} vs {} bits apart",
+            self.stride, other.stride
+        );
+
+        self.refresh_transpose();
+
+        for (word, other_word) in self.edges.iter_mut().zip(&other.edges) {
+            *word = fun(*word, *other_word);
+        }
+
+        if other.transpose_stale {
+            self.rebuild_transpose();
+            return;
+        }
+
+        for (word, other_word) in self.edges_transpose.iter_mut().zip(&other.edges_transpose) {
+            *word = fun(*word, *other_word);
+        }
+    }
+
+    /// Adds every edge of `other` to `self`. If `other` has more nodes their
+    /// values are copied over.
+    ///
+    /// Panics if the two graphs were not made with the same capacity and
+    /// layout.
+    pub fn union_with(&mut self, other: &BitGraph) {
+        self.combine_with(other, |a, b| a | b);
+
+        if other.count > self.count {
+            self.nodes.extend_from_slice(&other.nodes[self.count..]);
+            self.count = other.count;
+        }
+    }
+
+    /// Keeps only the edges that are in both `self` and `other`.
+    ///
+    /// Panics if the two graphs were not made with the same capacity and
+    /// layout.
+    pub fn intersect_with(&mut self, other: &BitGraph) {
+        self.combine_with(other, |a, b| a & b);
+    }
+
+    /// Removes every edge of `other` from `self`.
+    ///
+    /// Panics if the two graphs were not made with the same capacity and
+    /// layout.
+    pub fn difference_with(&mut self, other: &BitGraph) {
+        self.combine_with(other, |a, b| a & !b);
+    }
+
+    /// non mutating version of `union_with`
+    pub fn union(&self, other: &BitGraph) -> BitGraph {
+        let mut graph = self.clone();
+        graph.union_with(other);
+        graph
+    }
+
+    /// non mutating version of `intersect_with`
+    pub fn intersection(&self, other: &BitGraph) -> BitGraph {
+        let mut graph = self.clone();
+        graph.intersect_with(other);
+        graph
+    }
+
+    /// non mutating version of `difference_with`
+    pub fn difference(&self, other: &BitGraph) -> BitGraph {
+        let mut graph = self.clone();
+        graph.difference_with(other);
+        graph
+    }
+
+    /// Pulls the subgraph induced by `nodes` out into a new graph sized to the
+    /// subset. Returns the new graph and a map from new index to old index,
+    /// new indices follow the order of `nodes`, duplicates are dropped.
+    pub fn induced_subgraph(&self, nodes: &[usize]) -> (BitGraph, Vec<usize>) {
+        let mut new_of_old = vec![usize::MAX; self.count];
+        let mut old_of_new = Vec::with_capacity(nodes.len());
+
+        for &old in nodes {
+            if new_of_old[old] == usize::MAX {
+                new_of_old[old] = old_of_new.len();
+                old_of_new.push(old);
+            }
+        }
+
+        let mut graph = BitGraph::with_capacity(old_of_new.len());
+        for &old in &old_of_new {
+            graph.push_node(self.nodes[old]);
+        }
+
+        for (new, &old) in old_of_new.iter().enumerate() {
+            for to in self.outgoing_edges_of(old) {
+                if new_of_old[to] != usize::MAX {
+                    graph.add_edge(new, new_of_old[to]);
+                }
+            }
+        }
+
+        (graph, old_of_new)
+    }
+
+    /// Dumps the raw words backing each live row of `edges` in hex, with the
+    /// word range and the start/end bit offsets of the row. Words are shared
+    /// between neighbouring rows so the same word can show up twice.
+    pub fn dump_words(&self) -> String {
+        let width = WORD_BITS / 4 + 2;
+        let mut out = String::new();
+
+        for node in 0..self.count {
+            let start = (self.stride * node) / WORD_BITS;
+            let start_offset = (self.stride * node) % WORD_BITS;
+            let end = (self.stride * (node + 1)) / WORD_BITS;
+            let end_offset = (self.stride * (node + 1)) % WORD_BITS;
+
+            out.push_str(&format!(
+                "row {}: words {}..={} start_offset {} end_offset {} |",
+                node, start, end, start_offset, end_offset
+            ));
+            for word in &self.edges[start..=end] {
+                out.push_str(&format!(" {:#0width$x}", word, width = width));
+            }
+            out.push('\n');
+        }
+
+        out
+    }
+
+    /// Counts directed 3-cycles `u -> v -> w -> u`, each cycle is counted once
+    /// regardless of which node it starts at. For a graph with edges in both
+    /// directions every undirected triangle is two directed 3-cycles, so K5
+    /// with all 20 edges has 20. Self loops are ignored.
+    pub fn triangle_count(&self) -> usize {
+        if self.transpose_stale {
+            return self.refreshed().triangle_count();
+        }
+
+        let outgoing: Vec<Vec<usize>> = (0..self.count)
+            .map(|node| self.aligned_row(&self.edges, node))
+            .collect();
+
+        let mut total = 0;
+        for u in 0..self.count {
+            let incoming = self.aligned_row(&self.edges_transpose, u);
+
+            for v in self.outgoing_edges_of(u) {
+                if v == u {
+                    continue;
+                }
+
+                // every w where v -> w and w -> u closes the cycle
+                let mut closing = 0;
+                for (i, (out_word, in_word)) in outgoing[v].iter().zip(&incoming).enumerate() {
+                    let mut word = out_word & in_word;
+                    // u and v can only show up here through self loops
+                    if u / WORD_BITS == i {
+                        word = unset_bit(word, u % WORD_BITS);
+                    }
+                    if v / WORD_BITS == i {
+                        word = unset_bit(word, v % WORD_BITS);
+                    }
+                    closing += word.count_ones() as usize;
+                }
+
+                total += closing;
+            }
+        }
+
+        // each cycle was found once from each of its three edges
+        total / 3
+    }
+
+    /// Moves every edge of `merge` over to `keep`, `merge` keeps its index
+    /// and value but is left without edges. Edges between the two and a self
+    /// loop on `merge` become a self loop on `keep` when `keep_self_loops`,
+    /// otherwise they are dropped. Returns how many edges `keep` gained.
+    ///
+    /// The rows of `merge` are ORed into those of `keep` a word at a time,
+    /// only the mirrored bits of the other matrix are set one by one.
+    pub fn merge_nodes(&mut self, keep: usize, merge: usize, keep_self_loops: bool) -> usize {
+        assert_node(keep, self.count);
+        assert_node(merge, self.count);
+        assert!(keep != merge, "can not merge node {} into itself", keep);
+        self.refresh_transpose();
+
+        let mut outgoing = self.aligned_row(&self.edges, merge);
+        let mut incoming = self.aligned_row(&self.edges_transpose, merge);
+
+        // anything between `keep` and `merge` turns into a self loop
+        let mut self_loop = false;
+        for row in [&mut outgoing, &mut incoming] {
+            for node in [keep, merge] {
+                let (index, offset) = (node / WORD_BITS, node % WORD_BITS);
+                self_loop |= get_bit(row[index], offset);
+                row[index] = unset_bit(row[index], offset);
+            }
+        }
+
+        let gained = |row: &[usize], kept: &[usize]| -> usize {
+            row.iter()
+                .zip(kept)
+                .map(|(word, kept)| (word & !kept).count_ones() as usize)
+                .sum()
+        };
+        let mut redirected = gained(&outgoing, &self.aligned_row(&self.edges, keep))
+            + gained(&incoming, &self.aligned_row(&self.edges_transpose, keep));
+
+        self.clear_edges_of(merge);
+
+        let mut edges = std::mem::take(&mut self.edges);
+        self.or_aligned_row(&mut edges, keep, &outgoing);
+        self.edges = edges;
+
+        let mut edges_transpose = std::mem::take(&mut self.edges_transpose);
+        self.or_aligned_row(&mut edges_transpose, keep, &incoming);
+        self.edges_transpose = edges_transpose;
+
+        for to in row_ones(&outgoing) {
+            self.set_edge_of_tranpose(to, keep, set_bit);
+        }
+        for from in row_ones(&incoming) {
+            let (index, offset) = locate(self.stride, from, keep);
+            self.edges[index] = set_bit(self.edges[index], offset);
+        }
+
+        if self_loop && keep_self_loops && !self.add_edge(keep, keep) {
+            redirected += 1;
+        }
+
+        redirected
+    }
+}
+
+/// columns set in a word aligned row, lowest first
+fn row_ones(row: &[usize]) -> impl Iterator<Item = usize> + '_ {
+    row.iter()
+        .enumerate()
+        .flat_map(|(index, word)| ones(*word).map(move |offset| index * WORD_BITS + offset))
+}
+
+/// word index and bit offset of `(from, to)` in a matrix with rows `stride`
+/// bits apart
+#[inline(always)]
+fn locate(stride: usize, from: usize, to: usize) -> (usize, usize) {
+    let bit = stride * from + to;
+    (bit / WORD_BITS, bit % WORD_BITS)
+}
+
+/// zeroes every bit of the row of `node_index` in a matrix with rows `stride`
+/// bits apart, padding included
+fn clear_row(matrix: &mut [usize], stride: usize, node_index: usize) {
+    let start = (stride * node_index) / WORD_BITS;
+    let start_offset = (stride * node_index) % WORD_BITS;
+    let end = (stride * (node_index + 1)) / WORD_BITS;
+    let end_offset = (stride * (node_index + 1)) % WORD_BITS;
+
+    if start == end {
+        matrix[start] &= !(mask_n_bits(start_offset) & !mask_n_bits(end_offset));
+        return;
+    }
+
+    matrix[start] &= !mask_n_bits(start_offset);
+    matrix[start + 1..end].iter_mut().for_each(|word| *word = 0);
+    matrix[end] &= mask_n_bits(end_offset);
+}
+
+/// number of set bits in the row of `node_index` in a matrix with rows
+/// `stride` bits apart
+fn count_row(matrix: &[usize], stride: usize, node_index: usize) -> usize {
+    let start = (stride * node_index) / WORD_BITS;
+    let start_offset = (stride * node_index) % WORD_BITS;
+    let end = (stride * (node_index + 1)) / WORD_BITS;
+    let end_offset = (stride * (node_index + 1)) % WORD_BITS;
+
+    let ones = if start == end {
+        (matrix[start] & mask_n_bits(start_offset) & !mask_n_bits(end_offset)).count_ones()
+    } else {
+        let first = (matrix[start] & mask_n_bits(start_offset)).count_ones();
+        let middle: u32 = matrix[start + 1..end]
+            .iter()
+            .map(|word| word.count_ones())
+            .sum();
+        let last = (matrix[end] & !mask_n_bits(end_offset)).count_ones();
+
+        first + middle + last
+    };
+
+    ones as usize
 }
 
 /// makes a mask for a single bit of a given offset
@@ -85,11 +935,7 @@ pub fn single_bit_mask(offset: usize) -> usize {
 
 #[inline(always)]
 fn get_bit(n: usize, k: usize) -> bool {
-    if (n >> k) & 1 == 0 {
-        false
-    } else {
-        true
-    }
+    (n >> k) & 1 != 0
 }
 
 #[inline(always)]
@@ -109,7 +955,7 @@ pub fn toggle_bit(n: usize, k: usize) -> usize {
 
 #[inline(always)]
 pub fn mask_n_bits(n: usize) -> usize {
-    std::usize::MAX << n
+    usize::MAX << n
 }
 
 #[inline(always)]
@@ -122,6 +968,48 @@ pub fn clear_lowest_set_bit(w: usize) -> usize {
     w & (w - 1)
 }
 
+/// offsets of the set bits of `word`, lowest first
+fn ones(mut word: usize) -> impl Iterator<Item = usize> {
+    std::iter::from_fn(move || {
+        if word == 0 {
+            return None;
+        }
+
+        let offset = word.trailing_zeros() as usize;
+        word = clear_lowest_set_bit(word);
+        Some(offset)
+    })
+}
+
+/// prints the size of the graph followed by the adjacency matrix as a grid of
+/// `0`/`1`, past `DEBUG_GRID_LIMIT` rows and columns are cut off with `...`
+impl fmt::Debug for BitGraph {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "BitGraph {{ node_count: {}, dimension: {} }}",
+            self.count,
+            self.nodes.capacity()
+        )?;
+
+        let shown = self.count.min(DEBUG_GRID_LIMIT);
+        for from in 0..shown {
+            for to in 0..shown {
+                f.write_str(if self.has_edge(from, to) { "1" } else { "0" })?;
+            }
+            if shown < self.count {
+                f.write_str("...")?;
+            }
+            writeln!(f)?;
+        }
+        if shown < self.count {
+            writeln!(f, "...")?;
+        }
+
+        Ok(())
+    }
+}
+
 impl Graph<u64, bool> for BitGraph {
     fn add_edge(&mut self, from: usize, to: usize) -> bool {
         self.set_edge_of_both(from, to, set_bit)
@@ -132,9 +1020,17 @@ impl Graph<u64, bool> for BitGraph {
     }
 
     fn has_edge(&self, from: usize, to: usize) -> bool {
-        let row = (self.nodes.capacity() * from) / WORD_BITS;
+        assert_node(from, self.count);
+        assert_node(to, self.count);
+
+        if self.row_aligned {
+            let word = self.edges[from * (self.stride / WORD_BITS) + to / WORD_BITS];
+            return get_bit(word, to % WORD_BITS);
+        }
+
+        let row = (self.stride * from) / WORD_BITS;
         let mut column = to / WORD_BITS;
-        let mut offset = to % WORD_BITS + ((self.nodes.capacity() * from) % WORD_BITS);
+        let mut offset = to % WORD_BITS + ((self.stride * from) % WORD_BITS);
 
         if offset >= WORD_BITS {
             column += 1;
@@ -147,150 +1043,288 @@ impl Graph<u64, bool> for BitGraph {
     }
 
     fn outgoing_edges_of(&self, node_index: usize) -> Vec<usize> {
-        /*
-         * Implementation notes:
-         *  To calculate the destination node from the ctz correctly, we need
-         *  the to consider the following.
-         *
-         *  First, the start_offset tells how many bits into the
-         *  first row of the word should be discarded. Bits before the offset
-         *  should not be scanned. The start_offset additionally should be
-         *  subtracted from the calculated position for words after the start
-         *  word.
-         *
-         *  Second, the end_offset tells how many bits in the last word are
-         *  applicable to the current row. Bits after the end offset should
-         *  not be scanned.
-         *
-         *  Third, the number of words n already checked in the row should
-         *  add n*WORD_BITS to the destination node index.
-         */
+        let mut out = Vec::new();
+        self.row_into(&self.edges, node_index, &mut out);
+        out
+    }
 
-        let start = (self.nodes.capacity() * node_index) / WORD_BITS;
-        let start_offset = (self.nodes.capacity() * node_index) % WORD_BITS;
-        let end = (self.nodes.capacity() * (node_index + 1)) / WORD_BITS;
-        let end_offset = (self.nodes.capacity() * (node_index + 1)) % WORD_BITS;
+    /// the ctz scan of a row already comes out in ascending order
+    fn neighbors_sorted(&self, node_index: usize) -> Vec<usize> {
+        self.outgoing_edges_of(node_index)
+    }
 
-        let mut index = start;
+    fn incoming_edges_of(&self, node_index: usize) -> Vec<usize> {
+        let mut out = Vec::new();
+        self.incoming_edges_into(node_index, &mut out);
+        out
+    }
 
-        /*
-         * The first word in the row will always need to mask the first
-         * start_offset bits. Additionally if the row is one word wide,
-         * the last end_offset bits must be masked as well
-         */
-        let mut word = self.edges[index]
-            & (mask_n_bits(start_offset) & (!mask_n_bits(end_offset) | bool_to_mask(index != end)));
+    /// scans the row straight into `out`
+    fn outgoing_edges_into(&self, node_index: usize, out: &mut Vec<usize>) {
+        self.row_into(&self.edges, node_index, out);
+    }
 
-        let mut out = Vec::new();
-        loop {
-            // If the word is empty, check for completion and get next word
-            if word == 0x0 {
-                if index == end {
-                    break;
-                }
-                index = index + 1;
-                // Get the next word, and if it is the last word, mask out
-                // any bit larger than end_offset
-                word = self.edges[index] & (!mask_n_bits(end_offset) | bool_to_mask(index != end));
-            // If the word is not empty run ctz
-            } else {
-                // Get the total trailing zeroes in the word
-                // Finds the position of the next edge
-                let trailing_zeroes: usize = word.trailing_zeros() as usize;
-                // Compute and push the destination node index
-                // Address implementation notes in computation
-                out.push(trailing_zeroes + WORD_BITS * (index - start) - start_offset);
-                // clear the lowest set bit of the word
-                word = clear_lowest_set_bit(word);
-            }
+    /// scans the row of the transpose straight into `out`, or the column of
+    /// `edges` if the transpose is stale
+    fn incoming_edges_into(&self, node_index: usize, out: &mut Vec<usize>) {
+        if self.transpose_stale {
+            self.column_into(node_index, out);
+        } else {
+            self.row_into(&self.edges_transpose, node_index, out);
         }
-        out
     }
 
-    fn incoming_edges_of(&self, node_index: usize) -> Vec<usize> {
-        /*
-         * Implementation notes:
-         *  To calculate the destination node from the ctz correctly, we need
-         *  the to consider the following.
-         *
-         *  First, the start_offset tells how many bits into the
-         *  first row of the word should be discarded. Bits before the offset
-         *  should not be scanned. The start_offset additionally should be
-         *  subtracted from the calculated position for words after the start
-         *  word.
-         *
-         *  Second, the end_offset tells how many bits in the last word are
-         *  applicable to the current row. Bits after the end offset should
-         *  not be scanned.
-         *
-         *  Third, the number of words n already checked in the row should
-         *  add n*WORD_BITS to the destination node index.
-         */
+    /// Finds the first word where the matrix and its transpose differ, the
+    /// lowest differing bit in it is the answer. It is either an edge of the
+    /// matrix missing from the transpose or the other way round.
+    fn find_asymmetry(&self) -> Option<(usize, usize)> {
+        if self.transpose_stale {
+            return self
+                .edges()
+                .map(|edge| edge.key_pair())
+                .find(|(from, to)| !self.has_edge(*to, *from));
+        }
 
-        let start = (self.nodes.capacity() * node_index) / WORD_BITS;
-        let start_offset = (self.nodes.capacity() * node_index) % WORD_BITS;
-        let end = (self.nodes.capacity() * (node_index + 1)) / WORD_BITS;
-        let end_offset = (self.nodes.capacity() * (node_index + 1)) % WORD_BITS;
+        let stride = self.stride;
+        let (index, word) = self
+            .edges
+            .iter()
+            .zip(&self.edges_transpose)
+            .enumerate()
+            .find(|(_, (word, transposed))| word != transposed)?;
 
-        let mut index = start;
+        let offset = (word.0 ^ word.1).trailing_zeros() as usize;
+        let bit = index * WORD_BITS + offset;
+        let (row, column) = (bit / stride, bit % stride);
 
-        /*
-         * The first word in the row will always need to mask the first
-         * start_offset bits. Additionally if the row is one word wide,
-         * the last end_offset bits must be masked as well
-         */
-        let mut word = self.edges_transpose[index]
-            & (mask_n_bits(start_offset) & (!mask_n_bits(end_offset) | bool_to_mask(index != end)));
+        if get_bit(*word.0, offset) {
+            Some((row, column))
+        } else {
+            Some((column, row))
+        }
+    }
 
-        let mut out = Vec::new();
-        loop {
-            // If the word is empty, check for completion and get next word
-            if word == 0x0 {
-                if index == end {
-                    break;
+    /// the matrix compared with its transpose, word by word
+    fn is_symmetric(&self) -> bool {
+        if self.transpose_stale {
+            return self.find_asymmetry().is_none();
+        }
+
+        self.edges == self.edges_transpose
+    }
+
+    /// ORs the matrix and its transpose into both
+    fn symmetrize(&mut self) {
+        self.refresh_transpose();
+        for (word, transposed) in self.edges.iter_mut().zip(&mut self.edges_transpose) {
+            *word |= *transposed;
+            *transposed = *word;
+        }
+    }
+
+    /// Asks `pred` about the set bits a word at a time and clears the
+    /// rejected ones from the word in one go, the transpose is fixed up bit
+    /// by bit for just those.
+    fn retain_edges(&mut self, mut pred: impl FnMut(usize, usize, &bool) -> bool) {
+        self.refresh_transpose();
+        let stride = self.stride;
+        let words = (stride * self.count)
+            .div_ceil(WORD_BITS)
+            .min(self.edges.len());
+
+        for index in 0..words {
+            let mut drop = 0;
+            for offset in ones(self.edges[index]) {
+                let bit = index * WORD_BITS + offset;
+                let (from, to) = (bit / stride, bit % stride);
+                if from < self.count && to < self.count && !pred(from, to, &true) {
+                    drop = set_bit(drop, offset);
+
+                    let (index, offset) = locate(stride, to, from);
+                    self.edges_transpose[index] = unset_bit(self.edges_transpose[index], offset);
                 }
-                index = index + 1;
-                // Get the next word, and if it is the last word, mask out
-                // any bit larger than end_offset
-                word = self.edges_transpose[index]
-                    & (!mask_n_bits(end_offset) | bool_to_mask(index != end));
-            // If the word is not empty run ctz
+            }
+
+            self.edges[index] &= !drop;
+        }
+    }
+
+    /// Rows of dropped nodes are cleared, every other row is ANDed with a
+    /// mask of the kept columns, in both matrices.
+    fn retain_nodes(&mut self, mut pred: impl FnMut(usize, &u64) -> bool) {
+        self.refresh_transpose();
+        let stride = self.stride;
+
+        let mut kept = vec![0; self.aligned_row_len()];
+        let mut dropped = Vec::new();
+        for node in 0..self.count {
+            if pred(node, &self.nodes[node]) {
+                kept[node / WORD_BITS] = set_bit(kept[node / WORD_BITS], node % WORD_BITS);
             } else {
-                // Get the total trailing zeroes in the word
-                // Finds the position of the next edge
-                let trailing_zeroes: usize = word.trailing_zeros() as usize;
-                // Compute and push the destination node index
-                // Address implementation notes in computation
-                out.push(trailing_zeroes + WORD_BITS * (index - start) - start_offset);
-                // clear the lowest set bit of the word
-                word = clear_lowest_set_bit(word);
+                dropped.push(node);
+            }
+        }
+        if dropped.is_empty() {
+            return;
+        }
+
+        for matrix in [&mut self.edges, &mut self.edges_transpose] {
+            for node in &dropped {
+                clear_row(matrix, stride, *node);
+            }
+        }
+
+        let mut edges = std::mem::take(&mut self.edges);
+        let mut edges_transpose = std::mem::take(&mut self.edges_transpose);
+        for matrix in [&mut edges, &mut edges_transpose] {
+            for node in row_ones(&kept) {
+                let row = self.aligned_row(matrix, node);
+                if row.iter().zip(&kept).all(|(word, kept)| word & !kept == 0) {
+                    continue;
+                }
+
+                let masked: Vec<usize> = row
+                    .iter()
+                    .zip(&kept)
+                    .map(|(word, kept)| word & kept)
+                    .collect();
+                clear_row(matrix, stride, node);
+                self.or_aligned_row(matrix, node, &masked);
             }
         }
-        out
+        self.edges = edges;
+        self.edges_transpose = edges_transpose;
+    }
+
+    /// popcount of the row of `node_index`
+    fn out_degree(&self, node_index: usize) -> usize {
+        assert_node(node_index, self.count);
+        count_row(&self.edges, self.stride, node_index)
+    }
+
+    /// popcount of the row of `node_index` in the transpose, or a bit per
+    /// row if it is stale
+    fn in_degree(&self, node_index: usize) -> usize {
+        assert_node(node_index, self.count);
+        if self.transpose_stale {
+            return (0..self.count)
+                .filter(|from| self.has_edge(*from, node_index))
+                .count();
+        }
+
+        count_row(&self.edges_transpose, self.stride, node_index)
+    }
+
+    /// tests the diagonal bit of every node
+    fn self_loops(&self) -> Box<dyn Iterator<Item = usize> + '_> {
+        let stride = self.stride;
+        Box::new((0..self.count).filter(move |node| {
+            let (index, offset) = locate(stride, *node, *node);
+            get_bit(self.edges[index], offset)
+        }))
+    }
+
+    /// Walks the set bits of the matrix words in order, so edges come out
+    /// sorted by source then destination without a `Vec` per node.
+    fn edges(&self) -> Box<dyn Iterator<Item = EdgeMeta<bool>> + '_> {
+        let stride = self.stride;
+        let count = self.count;
+        let words = (stride * count).div_ceil(WORD_BITS).min(self.edges.len());
+
+        Box::new(
+            self.edges[..words]
+                .iter()
+                .enumerate()
+                .flat_map(move |(index, word)| {
+                    ones(*word).map(move |offset| {
+                        let bit = index * WORD_BITS + offset;
+                        EdgeMeta {
+                            source: bit / stride,
+                            destination: bit % stride,
+                            weight: true,
+                        }
+                    })
+                })
+                .filter(move |edge| edge.source < count && edge.destination < count),
+        )
     }
 
+    /// doubles the matrices when they are full
     fn push_node(&mut self, value: u64) -> usize {
+        if self.count == self.nodes.capacity() {
+            self.grow_to((self.count * 2).max(DEFAULT_CAPACITY));
+        }
+
         self.count += 1;
         self.nodes.push(value);
         self.nodes.len() - 1
     }
 
-    fn set_node(&mut self, _node_index: usize, _value: u64) {
-        todo!()
+    /// grows the matrices once up front if `count` does not fit
+    fn resize_nodes(&mut self, count: usize, fill: u64) {
+        if count > self.nodes.capacity() {
+            self.grow_to(count);
+        }
+
+        while self.count > count {
+            self.remove_node(self.count - 1);
+        }
+        while self.count < count {
+            self.push_node(fill);
+        }
+    }
+
+    /// rebuilds the matrices at the new size if the nodes do not fit
+    fn reserve_nodes(&mut self, additional: usize) {
+        if self.count + additional > self.nodes.capacity() {
+            self.grow_to(self.count + additional);
+        }
+    }
+
+    fn set_node(&mut self, node_index: usize, value: u64) {
+        assert_node(node_index, self.count);
+        self.nodes[node_index] = value;
     }
 
-    fn get_node(&self, _node_index: usize) -> &u64 {
-        todo!()
+    fn get_node(&self, node_index: usize) -> &u64 {
+        assert_node(node_index, self.count);
+        &self.nodes[node_index]
     }
 
-    fn remove_node(&mut self, _node_index: usize) -> u64 {
-        todo!()
+    /// Removes the node and its edges the way `Vec::swap_remove` does, the
+    /// last node takes over `node_index` along with all of its edges. Same
+    /// as `HashGraph::remove_node`.
+    fn remove_node(&mut self, node_index: usize) -> u64 {
+        assert_node(node_index, self.count);
+        self.clear_edges_of(node_index);
+
+        let last = self.count - 1;
+        if node_index != last {
+            let outgoing = self.outgoing_edges_of(last);
+            let incoming = self.incoming_edges_of(last);
+            self.clear_edges_of(last);
+
+            let moved = |node| if node == last { node_index } else { node };
+            for to in outgoing {
+                self.add_edge(node_index, moved(to));
+            }
+            // a self loop came along with the outgoing edges
+            for from in incoming.into_iter().filter(|&from| from != last) {
+                self.add_edge(from, node_index);
+            }
+        }
+
+        self.count -= 1;
+        self.nodes.swap_remove(node_index)
     }
 
     fn get_edge(&self, from: usize, to: usize) -> Option<EdgeMeta<bool>> {
-        let row = (self.nodes.capacity() * from) / WORD_BITS;
+        assert_node(from, self.count);
+        assert_node(to, self.count);
+
+        let row = (self.stride * from) / WORD_BITS;
         let mut column = to / WORD_BITS;
-        let mut offset = to % WORD_BITS + ((self.nodes.capacity() * from) % WORD_BITS);
+        let mut offset = to % WORD_BITS + ((self.stride * from) % WORD_BITS);
 
         if offset >= WORD_BITS {
             column += 1;
@@ -315,8 +1349,38 @@ impl Graph<u64, bool> for BitGraph {
         self.count
     }
 
-    fn set_count(&mut self, count: usize) {
-        self.count = count;
+    fn clear_edges_of(&mut self, node_index: usize) {
+        self.refresh_transpose();
+        let stride = self.stride;
+
+        // the row in each matrix says which columns of the other need clearing
+        for to in self.outgoing_edges_of(node_index) {
+            let (index, offset) = locate(stride, to, node_index);
+            self.edges_transpose[index] = unset_bit(self.edges_transpose[index], offset);
+        }
+
+        for from in self.incoming_edges_of(node_index) {
+            let (index, offset) = locate(stride, from, node_index);
+            self.edges[index] = unset_bit(self.edges[index], offset);
+        }
+
+        clear_row(&mut self.edges, stride, node_index);
+        clear_row(&mut self.edges_transpose, stride, node_index);
+    }
+
+    /// zeroes both matrices in place
+    fn clear_edges(&mut self) {
+        self.edges.iter_mut().for_each(|word| *word = 0);
+        self.edges_transpose.iter_mut().for_each(|word| *word = 0);
+        self.transpose_stale = false;
+    }
+
+    /// keeps the matrices and the node storage allocated, so `capacity` stays
+    /// the same
+    fn clear(&mut self) {
+        self.clear_edges();
+        self.nodes.clear();
+        self.count = 0;
     }
 
     fn set_edge(&mut self, from_to: (usize, usize), weight: bool) -> bool {
@@ -328,6 +1392,64 @@ impl Graph<u64, bool> for BitGraph {
     }
 }
 
+/// Serialized as `(node_count, nodes, edge pairs)` instead of the raw words,
+/// the words are mostly zero for sparse graphs and their layout depends on
+/// the capacity and word size of the machine that wrote them.
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use super::BitGraph;
+    use crate::Graph;
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize)]
+    struct Repr<'a> {
+        node_count: usize,
+        nodes: &'a [u64],
+        edges: Vec<(usize, usize)>,
+    }
+
+    #[derive(Deserialize)]
+    struct OwnedRepr {
+        node_count: usize,
+        nodes: Vec<u64>,
+        edges: Vec<(usize, usize)>,
+    }
+
+    impl Serialize for BitGraph {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            Repr {
+                node_count: self.count,
+                nodes: &self.nodes[..self.count],
+                edges: self.all_edge_pairs(),
+            }
+            .serialize(serializer)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for BitGraph {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let repr = OwnedRepr::deserialize(deserializer)?;
+
+            if repr.nodes.len() != repr.node_count {
+                return Err(D::Error::custom(format!(
+                    "node_count is {} but there are {} nodes",
+                    repr.node_count,
+                    repr.nodes.len()
+                )));
+            }
+
+            let node_count = repr.node_count;
+            BitGraph::from_nodes_and_edges(repr.nodes, repr.edges).map_err(|(from, to)| {
+                D::Error::custom(format!(
+                    "edge ({}, {}) out of bounds for {} nodes",
+                    from, to, node_count
+                ))
+            })
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -360,7 +1482,7 @@ mod tests {
 
         graph.add_edge(2, 0);
         assert!(graph.outgoing_edges_of(0).len() == 1);
-        assert!(graph.outgoing_edges_of(4).len() == 0);
+        assert!(graph.outgoing_edges_of(4).is_empty());
 
         graph.add_edge(10, 2);
         graph.add_edge(10, 3);
@@ -394,7 +1516,7 @@ mod tests {
         graph.add_edge(2, 0);
 
         assert!(graph.outgoing_edges_of(500).len() == 1);
-        assert!(graph.outgoing_edges_of(4).len() == 0);
+        assert!(graph.outgoing_edges_of(4).is_empty());
 
         graph.add_edge(10, 2);
         graph.add_edge(10, 3);
@@ -428,7 +1550,7 @@ mod tests {
         graph.add_edge(2, 0);
 
         assert!(graph.outgoing_edges_of(500).len() == 1);
-        assert!(graph.outgoing_edges_of(4).len() == 0);
+        assert!(graph.outgoing_edges_of(4).is_empty());
 
         graph.add_edge(10, 2);
         graph.add_edge(10, 3);
@@ -465,7 +1587,7 @@ mod tests {
         graph.add_edge(2, 0);
 
         assert!(graph.incoming_edges_of(1).len() == 1);
-        assert!(graph.incoming_edges_of(4).len() == 0);
+        assert!(graph.incoming_edges_of(4).is_empty());
 
         graph.add_edge(2, 1);
         graph.add_edge(3, 1);
@@ -490,7 +1612,7 @@ mod tests {
         graph.add_edge(2, 0);
 
         assert!(graph.incoming_edges_of(1).len() == 1);
-        assert!(graph.incoming_edges_of(4).len() == 0);
+        assert!(graph.incoming_edges_of(4).is_empty());
 
         graph.add_edge(2, 1);
         graph.add_edge(3, 1);
@@ -515,7 +1637,7 @@ mod tests {
         graph.add_edge(2, 0);
 
         assert!(graph.incoming_edges_of(1).len() == 1);
-        assert!(graph.incoming_edges_of(4).len() == 0);
+        assert!(graph.incoming_edges_of(4).is_empty());
 
         graph.add_edge(2, 1);
         graph.add_edge(3, 1);
@@ -529,4 +1651,865 @@ mod tests {
         println!("{:#?}", graph.all_edge_pairs());
         assert!(graph.all_edge_pairs().len() == 7);
     }
+
+    #[test]
+    fn triangle_count_test() {
+        let mut graph = BitGraph::with_capacity(5);
+
+        for i in 0..5 {
+            graph.push_node(i);
+        }
+
+        for i in 0..5 {
+            for j in 0..5 {
+                if i != j {
+                    graph.add_edge(i, j);
+                }
+            }
+        }
+
+        // 10 undirected triangles, each one is two directed 3-cycles
+        assert_eq!(graph.triangle_count(), 20);
+
+        // self loops should not change anything
+        graph.add_edge(2, 2);
+        assert_eq!(graph.triangle_count(), 20);
+    }
+
+    #[test]
+    fn directed_triangle_count_test() {
+        let mut graph = BitGraph::with_capacity(100);
+
+        for i in 0..100 {
+            graph.push_node(i);
+        }
+
+        graph.add_edge(3, 70);
+        graph.add_edge(70, 99);
+        graph.add_edge(99, 3);
+        assert_eq!(graph.triangle_count(), 1);
+
+        // closed triple that is not a cycle
+        graph.add_edge(10, 11);
+        graph.add_edge(11, 12);
+        graph.add_edge(10, 12);
+        assert_eq!(graph.triangle_count(), 1);
+    }
+
+    #[test]
+    fn bipartite_triangle_count_test() {
+        let mut graph = BitGraph::with_capacity(70);
+
+        for i in 0..70 {
+            graph.push_node(i);
+        }
+
+        for i in 0..35 {
+            for j in 35..70 {
+                graph.add_edge(i, j);
+                graph.add_edge(j, i);
+            }
+        }
+
+        assert_eq!(graph.triangle_count(), 0);
+    }
+
+    #[test]
+    fn complement_test() {
+        let mut graph = BitGraph::with_capacity(100);
+
+        for i in 0..70 {
+            graph.push_node(i);
+        }
+
+        graph.add_edge(0, 1);
+        graph.add_edge(1, 0);
+        graph.add_edge(5, 5);
+        graph.add_edge(10, 65);
+        graph.add_edge(69, 3);
+        graph.add_edge(40, 41);
+
+        let complement = graph.complement();
+
+        for i in 0..70 {
+            assert!(!complement.has_edge(i, i));
+        }
+
+        assert!(!complement.has_edge(0, 1));
+        assert!(!complement.has_edge(10, 65));
+        assert!(complement.has_edge(65, 10));
+        assert_eq!(complement.outgoing_edges_of(10).len(), 68);
+        assert_eq!(complement.incoming_edges_of(3).len(), 68);
+        // nodes past node_count are never connected
+        assert!(complement.outgoing_edges_of(69).iter().all(|to| *to < 70));
+
+        let mut twice = complement.complement();
+        // the self loop is lost going through the complement
+        graph.remove_edge(5, 5);
+        assert_eq!(twice.all_edge_pairs(), graph.all_edge_pairs());
+
+        twice.remove_edge(40, 41);
+        assert_ne!(twice.all_edge_pairs(), graph.all_edge_pairs());
+    }
+
+    fn overlapping_pair() -> (BitGraph, BitGraph) {
+        let mut a = BitGraph::with_capacity(80);
+        let mut b = BitGraph::with_capacity(80);
+
+        for i in 0..80 {
+            a.push_node(i);
+            b.push_node(i);
+        }
+
+        a.add_edge(0, 1);
+        a.add_edge(1, 2);
+        a.add_edge(70, 3);
+        a.add_edge(79, 79);
+
+        b.add_edge(1, 2);
+        b.add_edge(70, 3);
+        b.add_edge(4, 65);
+
+        (a, b)
+    }
+
+    #[test]
+    fn union_test() {
+        let (a, b) = overlapping_pair();
+
+        let union = a.union(&b);
+        assert_eq!(
+            union.all_edge_pairs(),
+            vec![(0, 1), (1, 2), (4, 65), (70, 3), (79, 79)]
+        );
+        assert_eq!(union.incoming_edges_of(65), vec![4]);
+
+        // inputs are untouched
+        assert_eq!(a.all_edge_pairs().len(), 4);
+        assert_eq!(b.all_edge_pairs().len(), 3);
+    }
+
+    #[test]
+    fn intersection_test() {
+        let (mut a, b) = overlapping_pair();
+
+        a.intersect_with(&b);
+        assert_eq!(a.all_edge_pairs(), vec![(1, 2), (70, 3)]);
+        assert_eq!(a.incoming_edges_of(3), vec![70]);
+        assert!(a.incoming_edges_of(1).is_empty());
+    }
+
+    #[test]
+    fn difference_test() {
+        let (a, b) = overlapping_pair();
+
+        let difference = a.difference(&b);
+        assert_eq!(difference.all_edge_pairs(), vec![(0, 1), (79, 79)]);
+        assert!(difference.incoming_edges_of(3).is_empty());
+
+        let difference = b.difference(&a);
+        assert_eq!(difference.all_edge_pairs(), vec![(4, 65)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "dimensions differ")]
+    fn mismatched_union_test() {
+        let mut a = BitGraph::with_capacity(16);
+        let b = BitGraph::with_capacity(32);
+
+        a.union_with(&b);
+    }
+
+    #[test]
+    fn induced_subgraph_test() {
+        let mut graph = BitGraph::with_capacity(9);
+
+        for i in 0..9 {
+            graph.push_node(i * 10);
+        }
+
+        // three cycles 0-1-2, 3-4-5 and 6-7-8, chained together
+        graph.add_edge(0, 1);
+        graph.add_edge(1, 2);
+        graph.add_edge(2, 0);
+        graph.add_edge(3, 4);
+        graph.add_edge(4, 5);
+        graph.add_edge(5, 3);
+        graph.add_edge(6, 7);
+        graph.add_edge(7, 8);
+        graph.add_edge(8, 6);
+        graph.add_edge(2, 3);
+        graph.add_edge(5, 6);
+        graph.add_edge(4, 4);
+
+        let (sub, old_of_new) = graph.induced_subgraph(&[5, 3, 4, 3]);
+
+        assert_eq!(old_of_new, vec![5, 3, 4]);
+        assert_eq!(sub.node_count(), 3);
+        assert_eq!(sub.nodes.capacity(), 3);
+        assert_eq!(sub.nodes, vec![50, 30, 40]);
+
+        // 3 -> 4, 4 -> 5, 5 -> 3 and the 4 -> 4 loop, nothing leaving the set
+        assert_eq!(sub.all_edge_pairs(), vec![(0, 1), (1, 2), (2, 0), (2, 2)]);
+        assert_eq!(sub.incoming_edges_of(0), vec![2]);
+    }
+
+    #[test]
+    fn clear_edges_of_test() {
+        let mut graph = BitGraph::with_capacity(100);
+
+        for i in 0..100 {
+            graph.push_node(i);
+        }
+
+        graph.add_edge(50, 0);
+        graph.add_edge(50, 63);
+        graph.add_edge(50, 64);
+        graph.add_edge(50, 50);
+        graph.add_edge(50, 99);
+        graph.add_edge(1, 50);
+        graph.add_edge(98, 50);
+        graph.add_edge(49, 51);
+        graph.add_edge(51, 49);
+        graph.add_edge(49, 0);
+        graph.add_edge(51, 99);
+
+        graph.clear_edges_of(50);
+
+        assert!(graph.outgoing_edges_of(50).is_empty());
+        assert!(graph.incoming_edges_of(50).is_empty());
+        assert!(graph.incoming_edges_of(64).is_empty());
+        assert!(graph.outgoing_edges_of(98).is_empty());
+        assert_eq!(graph.incoming_edges_of(0), vec![49]);
+        assert_eq!(graph.incoming_edges_of(99), vec![51]);
+        assert_eq!(
+            graph.all_edge_pairs(),
+            vec![(49, 0), (49, 51), (51, 49), (51, 99)]
+        );
+    }
+
+    #[test]
+    fn from_edges_test() {
+        // small lcg so the edge list is the same every run
+        let mut state: usize = 12345;
+        let mut next = move || {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (state >> 33) % 1000
+        };
+
+        let pairs: Vec<(usize, usize)> = (0..10_000).map(|_| (next(), next())).collect();
+        let graph = BitGraph::from_edges(1000, pairs.iter().copied()).unwrap();
+
+        assert_eq!(graph.node_count(), 1000);
+        for &(from, to) in pairs.iter().step_by(97) {
+            assert!(graph.has_edge(from, to));
+        }
+
+        let mut unique = pairs.clone();
+        unique.sort_unstable();
+        unique.dedup();
+        assert_eq!(graph.all_edge_pairs(), unique);
+
+        for node in [0, 1, 63, 64, 500, 999] {
+            let out = unique.iter().filter(|(from, _)| *from == node).count();
+            let incoming = unique.iter().filter(|(_, to)| *to == node).count();
+            assert_eq!(graph.outgoing_edges_of(node).len(), out);
+            assert_eq!(graph.incoming_edges_of(node).len(), incoming);
+        }
+    }
+
+    #[test]
+    fn from_edges_out_of_bounds_test() {
+        let result = BitGraph::from_edges(4, vec![(0, 1), (3, 4), (5, 0)]);
+        assert_eq!(result.err(), Some((3, 4)));
+
+        let graph = BitGraph::from_nodes_and_edges(vec![7, 8, 9], vec![(2, 0)]).unwrap();
+        assert_eq!(graph.node_count(), 3);
+        assert_eq!(graph.nodes, vec![7, 8, 9]);
+        assert!(graph.has_edge(2, 0));
+    }
+
+    #[test]
+    fn add_edges_test() {
+        let mut graph = BitGraph::new();
+
+        for i in 0..16 {
+            graph.push_node(i);
+        }
+
+        graph.add_edge(1, 2);
+        assert_eq!(graph.add_edges(vec![(0, 1), (1, 2), (15, 3), (0, 1)]), 2);
+        assert_eq!(graph.all_edge_pairs(), vec![(0, 1), (1, 2), (15, 3)]);
+        assert_eq!(graph.incoming_edges_of(3), vec![15]);
+    }
+
+    #[test]
+    fn edge_return_test() {
+        // true means the edge was there before, in every layout
+        let layouts = vec![
+            BitGraph::with_capacity(70),
+            BitGraph::with_capacity_aligned(70),
+            BitGraph::with_capacity_directed_only(70),
+        ];
+        for mut graph in layouts {
+            for i in 0..70 {
+                graph.push_node(i);
+            }
+
+            for &(from, to) in &[(0, 1), (63, 64), (69, 69), (64, 0)] {
+                assert!(!graph.add_edge(from, to));
+                assert!(graph.add_edge(from, to));
+                assert!(graph.set_edge((from, to), true));
+                assert!(graph.remove_edge(from, to));
+                assert!(!graph.remove_edge(from, to));
+                assert!(!graph.set_edge((from, to), false));
+                assert!(!graph.set_edge((from, to), true));
+                assert!(graph.has_edge(from, to));
+            }
+        }
+    }
+
+    #[test]
+    fn validate_test() {
+        let mut graph = BitGraph::with_capacity(70);
+        for i in 0..60 {
+            graph.push_node(i);
+        }
+        graph.add_edges(vec![(0, 1), (59, 0), (30, 30), (2, 58)]);
+        assert_eq!(graph.validate(), Ok(()));
+
+        let flip = |matrix: &mut Vec<usize>, stride, from, to| {
+            let (index, offset) = locate(stride, from, to);
+            matrix[index] = toggle_bit(matrix[index], offset);
+        };
+        let stride = graph.stride;
+        // a bit missing from the transpose and one only in it
+        flip(&mut graph.edges_transpose, stride, 1, 0);
+        flip(&mut graph.edges_transpose, stride, 3, 2);
+        // an edge to column 65, past the last node, in both matrices
+        flip(&mut graph.edges, stride, 4, 65);
+        flip(&mut graph.edges_transpose, stride, 65, 4);
+        // a count out of step with the stored values
+        graph.count = 61;
+
+        assert_eq!(
+            graph.validate(),
+            Err(vec![
+                IntegrityError::NodeCountMismatch {
+                    count: 61,
+                    values: 60
+                },
+                IntegrityError::EdgeOutOfBounds {
+                    from: 4,
+                    to: 65,
+                    count: 61
+                },
+                IntegrityError::TransposeMismatch { from: 0, to: 1 },
+                IntegrityError::TransposeMismatch { from: 2, to: 3 },
+            ])
+        );
+
+        // padding past the capacity of an aligned row
+        let mut aligned = BitGraph::with_capacity_aligned(70);
+        for i in 0..70 {
+            aligned.push_node(i);
+        }
+        aligned.add_edges(vec![(69, 69), (0, 64)]);
+        assert_eq!(aligned.validate(), Ok(()));
+        let stride = aligned.stride;
+        flip(&mut aligned.edges, stride, 0, 100);
+        assert_eq!(
+            aligned.validate(),
+            Err(vec![IntegrityError::EdgeOutOfBounds {
+                from: 0,
+                to: 100,
+                count: 70
+            }])
+        );
+
+        // a stale transpose is allowed to be behind
+        let mut lazy = BitGraph::with_capacity_directed_only(16);
+        for i in 0..4 {
+            lazy.push_node(i);
+        }
+        lazy.add_edges(vec![(0, 1), (3, 2)]);
+        assert_eq!(lazy.validate(), Ok(()));
+        lazy.refresh_transpose();
+        let stride = lazy.stride;
+        flip(&mut lazy.edges_transpose, stride, 2, 3);
+        assert_eq!(
+            lazy.validate(),
+            Err(vec![IntegrityError::TransposeMismatch { from: 3, to: 2 }])
+        );
+    }
+
+    #[test]
+    fn debug_test() {
+        let mut graph = BitGraph::with_capacity(6);
+
+        for i in 0..4 {
+            graph.push_node(i);
+        }
+
+        graph.add_edge(0, 1);
+        graph.add_edge(1, 2);
+        graph.add_edge(3, 0);
+        graph.add_edge(3, 3);
+
+        assert_eq!(
+            format!("{:?}", graph),
+            "BitGraph { node_count: 4, dimension: 6 }\n\
+             0100\n\
+             0010\n\
+             0000\n\
+             1001\n"
+        );
+
+        let dump = graph.dump_words();
+        assert_eq!(dump.lines().count(), 4);
+        assert!(dump.starts_with("row 0: words 0..=0 start_offset 0 end_offset 6 |"));
+        assert!(dump.contains("row 3: words 0..=0 start_offset 18 end_offset 24 |"));
+    }
+
+    #[test]
+    fn debug_truncated_test() {
+        let mut graph = BitGraph::with_capacity(70);
+
+        for i in 0..70 {
+            graph.push_node(i);
+        }
+
+        graph.add_edge(69, 0);
+
+        let debug = format!("{:?}", graph);
+        let lines: Vec<&str> = debug.lines().collect();
+        assert_eq!(lines.len(), 66);
+        assert_eq!(lines[1].len(), 67);
+        assert!(lines[1].ends_with("..."));
+        assert_eq!(lines[65], "...");
+    }
+
+    #[test]
+    fn eq_test() {
+        let mut a = BitGraph::with_capacity(16);
+        let mut b = BitGraph::with_capacity(100);
+
+        for i in 0..10 {
+            a.push_node(i);
+            b.push_node(i);
+        }
+
+        a.add_edge(0, 9);
+        a.add_edge(9, 0);
+        a.add_edge(5, 5);
+        b.add_edge(0, 9);
+        b.add_edge(9, 0);
+        b.add_edge(5, 5);
+
+        assert!(a == b);
+        assert!(a == a.clone());
+
+        b.add_edge(3, 4);
+        assert!(a != b);
+        b.remove_edge(3, 4);
+        assert!(a == b);
+
+        b.push_node(10);
+        assert!(a != b);
+        a.push_node(11);
+        assert!(a != b);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip_test() {
+        let mut graph = BitGraph::with_capacity(100);
+
+        for i in 0..70 {
+            graph.push_node(i * 2);
+        }
+
+        graph.add_edge(0, 69);
+        graph.add_edge(69, 0);
+        graph.add_edge(64, 63);
+        graph.add_edge(5, 5);
+
+        let json = serde_json::to_string(&graph).unwrap();
+        let back: BitGraph = serde_json::from_str(&json).unwrap();
+
+        // sized to node_count, so the row stride differs from the original
+        assert_eq!(back.nodes.capacity(), 70);
+        assert!(back == graph);
+        assert_eq!(back.incoming_edges_of(63), vec![64]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_layout_independent_test() {
+        // nothing about words or capacity in the format
+        let json = r#"{"node_count":3,"nodes":[7,8,9],"edges":[[0,2],[2,1]]}"#;
+        let graph: BitGraph = serde_json::from_str(json).unwrap();
+
+        assert_eq!(graph.all_edge_pairs(), vec![(0, 2), (2, 1)]);
+        assert_eq!(*graph.get_node(1), 8);
+
+        let bad = r#"{"node_count":3,"nodes":[7,8,9],"edges":[[0,3]]}"#;
+        assert!(serde_json::from_str::<BitGraph>(bad).is_err());
+        let bad = r#"{"node_count":4,"nodes":[7,8,9],"edges":[]}"#;
+        assert!(serde_json::from_str::<BitGraph>(bad).is_err());
+    }
+
+    #[test]
+    fn collect_test() {
+        let edges = vec![(0, 1), (1, 2), (2, 0), (4, 4)];
+        let graph: BitGraph = edges.clone().into_iter().collect();
+        assert_eq!(graph.node_count(), 5);
+        assert_eq!(graph.all_edge_pairs(), vec![(0, 1), (1, 2), (2, 0), (4, 4)]);
+        assert_eq!(*graph.get_node(3), 0);
+
+        let filtered: BitGraph = (0..200)
+            .map(|i| (i, (i * 7) % 200))
+            .filter(|(from, to)| from != to)
+            .collect();
+        assert_eq!(filtered.node_count(), 200);
+        assert_eq!(filtered.incoming_edges_of(7), vec![1]);
+        assert!(!filtered.has_edge(0, 0));
+    }
+
+    #[test]
+    fn extend_test() {
+        let mut graph = BitGraph::with_capacity(4);
+        graph.push_node(9);
+        graph.add_edge(0, 0);
+
+        // past the capacity, the matrices are rebuilt once
+        graph.extend(vec![(0, 3), (3, 10), (10, 0)]);
+        assert_eq!(graph.node_count(), 11);
+        assert_eq!(graph.nodes.capacity(), 11);
+        assert_eq!(*graph.get_node(0), 9);
+        assert_eq!(
+            graph.all_edge_pairs(),
+            vec![(0, 0), (0, 3), (3, 10), (10, 0)]
+        );
+        assert_eq!(graph.incoming_edges_of(0), vec![0, 10]);
+
+        graph.extend((0..11).map(|i| (i, 5)));
+        assert_eq!(graph.nodes.capacity(), 11);
+        assert_eq!(graph.incoming_edges_of(5).len(), 11);
+
+        // a small step past the end still doubles
+        graph.extend(std::iter::once((11, 11)));
+        assert_eq!(graph.nodes.capacity(), 22);
+        assert!(graph.has_edge(11, 11) && graph.has_edge(3, 5));
+
+        graph.extend(Vec::new());
+        assert_eq!(graph.node_count(), 12);
+    }
+
+    #[test]
+    #[should_panic(expected = "node index usize::MAX can never be a node")]
+    fn extend_overflow_test() {
+        let mut graph = BitGraph::new();
+        graph.extend(vec![(usize::MAX, 0)]);
+    }
+
+    /// hubs 3 and 65, far enough apart that their rows start mid word
+    fn hubs() -> BitGraph {
+        let mut graph = BitGraph::from_edges(
+            70,
+            vec![
+                (3, 10),
+                (3, 20),
+                (3, 30),
+                (40, 3),
+                (65, 20),
+                (65, 50),
+                (65, 69),
+                (1, 65),
+                (40, 65),
+                (66, 65),
+            ],
+        )
+        .unwrap();
+        graph.add_edges(vec![(3, 65), (65, 3), (65, 65)]);
+        graph
+    }
+
+    #[test]
+    fn merge_nodes_test() {
+        let mut graph = hubs();
+        assert_eq!(graph.merge_nodes(3, 65, false), 4);
+
+        assert_eq!(graph.outgoing_edges_of(3), vec![10, 20, 30, 50, 69]);
+        assert_eq!(graph.incoming_edges_of(3), vec![1, 40, 66]);
+        assert!(graph.outgoing_edges_of(65).is_empty());
+        assert!(graph.incoming_edges_of(65).is_empty());
+        assert_eq!(graph.incoming_edges_of(50), vec![3]);
+        assert_eq!(graph.outgoing_edges_of(66), vec![3]);
+        assert_eq!(graph.all_edge_pairs().len(), 8);
+        assert_eq!(graph.node_count(), 70);
+
+        let mut graph = hubs();
+        assert_eq!(graph.merge_nodes(3, 65, true), 5);
+        assert!(graph.has_edge(3, 3));
+        assert_eq!(graph.incoming_edges_of(3), vec![1, 3, 40, 66]);
+
+        // the other way round, the self loop 65 already had stays
+        let mut graph = hubs();
+        assert_eq!(graph.merge_nodes(65, 3, false), 2);
+        assert_eq!(graph.outgoing_edges_of(65), vec![10, 20, 30, 50, 65, 69]);
+        assert_eq!(graph.incoming_edges_of(65), vec![1, 40, 65, 66]);
+    }
+
+    /// the same edges in a plain graph and a directed only one
+    fn lazy_pair() -> (BitGraph, BitGraph) {
+        let mut eager = BitGraph::with_capacity(100);
+        let mut lazy = BitGraph::with_capacity_directed_only(100);
+        for graph in [&mut eager, &mut lazy] {
+            for i in 0..100 {
+                graph.push_node(i);
+            }
+            for i in 0..100 {
+                graph.add_edge(i, (i * 7 + 1) % 100);
+                graph.add_edge(i, (i + 1) % 100);
+                graph.add_edge((i * 3) % 100, i);
+            }
+            graph.remove_edge(3, 4);
+            graph.add_edge(50, 50);
+        }
+
+        (eager, lazy)
+    }
+
+    fn assert_same_incoming(eager: &BitGraph, lazy: &BitGraph) {
+        for node in 0..eager.count {
+            assert_eq!(lazy.incoming_edges_of(node), eager.incoming_edges_of(node));
+            assert_eq!(lazy.in_degree(node), eager.in_degree(node));
+        }
+    }
+
+    #[test]
+    fn directed_only_test() {
+        let (mut eager, mut lazy) = lazy_pair();
+        assert!(lazy.is_directed_only() && !eager.is_directed_only());
+        assert!(lazy.transpose_stale);
+        assert!(lazy.edges_transpose.iter().all(|word| *word == 0));
+
+        // read off the columns while stale
+        assert_eq!(lazy, eager);
+        assert_same_incoming(&eager, &lazy);
+        assert_eq!(lazy.triangle_count(), eager.triangle_count());
+        assert_eq!(lazy.find_asymmetry(), eager.find_asymmetry());
+        assert_eq!(
+            lazy.maximal_cliques().count(),
+            eager.maximal_cliques().count()
+        );
+
+        lazy.refresh_transpose();
+        assert!(!lazy.transpose_stale);
+        assert_eq!(lazy.edges_transpose, eager.edges_transpose);
+        assert_same_incoming(&eager, &lazy);
+
+        // re-adding an edge that is there changes nothing
+        lazy.add_edge(50, 50);
+        assert!(!lazy.transpose_stale);
+        lazy.add_edge(60, 62);
+        eager.add_edge(60, 62);
+        assert!(lazy.transpose_stale);
+
+        // methods that work on both matrices bring it up to date first
+        lazy.clear_edges_of(62);
+        eager.clear_edges_of(62);
+        assert!(!lazy.transpose_stale);
+        assert_same_incoming(&eager, &lazy);
+
+        lazy.add_edge(0, 99);
+        eager.add_edge(0, 99);
+        assert_eq!(lazy.merge_nodes(1, 2, true), eager.merge_nodes(1, 2, true));
+        assert_same_incoming(&eager, &lazy);
+
+        let (mut other_eager, mut other_lazy) = lazy_pair();
+        other_lazy.add_edge(10, 90);
+        other_eager.add_edge(10, 90);
+        lazy.union_with(&other_lazy);
+        eager.union_with(&other_eager);
+        assert_eq!(lazy, eager);
+        assert_same_incoming(&eager, &lazy);
+
+        lazy.remove_edge(10, 90);
+        eager.remove_edge(10, 90);
+        lazy.symmetrize();
+        eager.symmetrize();
+        assert!(lazy.is_symmetric());
+        assert_eq!(lazy, eager);
+
+        // growing keeps the mode
+        lazy.extend(vec![(150, 0)]);
+        assert!(lazy.is_directed_only());
+        assert!(lazy.transpose_stale);
+        assert_eq!(lazy.incoming_edges_of(0).last(), Some(&150));
+        assert!(lazy.clone().transpose_stale);
+
+        lazy.clear_edges();
+        assert!(!lazy.transpose_stale);
+        assert!(lazy.incoming_edges_of(0).is_empty());
+    }
+
+    /// the same edges in both layouts, rows straddle words in the packed one
+    fn layout_pair(size: usize) -> (BitGraph, BitGraph) {
+        let mut packed = BitGraph::with_capacity(size);
+        let mut aligned = BitGraph::with_capacity_aligned(size);
+        for graph in [&mut packed, &mut aligned] {
+            for i in 0..size as u64 {
+                graph.push_node(i);
+            }
+            for i in 0..size {
+                graph.add_edge(i, (i * 7 + 1) % size);
+                graph.add_edge(i, size - 1 - i);
+                graph.add_edge((i * 3) % size, i);
+            }
+            graph.remove_edge(0, 1 % size);
+        }
+
+        (packed, aligned)
+    }
+
+    fn assert_same_graph(packed: &BitGraph, aligned: &BitGraph) {
+        assert_eq!(packed, aligned);
+        assert_eq!(packed.all_edge_pairs(), aligned.all_edge_pairs());
+        for node in 0..packed.count {
+            assert_eq!(
+                packed.outgoing_edges_of(node),
+                aligned.outgoing_edges_of(node)
+            );
+            assert_eq!(
+                packed.incoming_edges_of(node),
+                aligned.incoming_edges_of(node)
+            );
+            assert_eq!(packed.out_degree(node), aligned.out_degree(node));
+            assert_eq!(packed.in_degree(node), aligned.in_degree(node));
+        }
+        assert_eq!(
+            packed.self_loops().collect::<Vec<_>>(),
+            aligned.self_loops().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn aligned_layout_test() {
+        for size in [1, 5, 63, 64, 65, 100, 130] {
+            let (mut packed, mut aligned) = layout_pair(size);
+            assert!(aligned.is_row_aligned() && !packed.is_row_aligned());
+            assert_eq!(aligned.stride % WORD_BITS, 0);
+            assert_same_graph(&packed, &aligned);
+
+            assert_eq!(packed.triangle_count(), aligned.triangle_count());
+            assert_eq!(packed.is_symmetric(), aligned.is_symmetric());
+            assert_eq!(packed.find_asymmetry(), aligned.find_asymmetry());
+            assert_eq!(packed.complement(), aligned.complement());
+            assert!(aligned.complement().is_row_aligned());
+            assert_eq!(
+                packed.maximal_cliques().collect::<Vec<_>>(),
+                aligned.maximal_cliques().collect::<Vec<_>>()
+            );
+
+            let last = size - 1;
+            packed.clear_edges_of(last / 2);
+            aligned.clear_edges_of(last / 2);
+            assert_same_graph(&packed, &aligned);
+
+            if size > 1 {
+                assert_eq!(
+                    packed.merge_nodes(0, last, true),
+                    aligned.merge_nodes(0, last, true)
+                );
+                assert_same_graph(&packed, &aligned);
+            }
+
+            packed.retain_edges(|from, to, _| (from + to) % 3 != 0);
+            aligned.retain_edges(|from, to, _| (from + to) % 3 != 0);
+            packed.retain_nodes(|node, _| node % 5 != 1);
+            aligned.retain_nodes(|node, _| node % 5 != 1);
+            assert_same_graph(&packed, &aligned);
+
+            let mut union = layout_pair(size).1;
+            union.union_with(&aligned);
+            assert_eq!(union, layout_pair(size).0.union(&packed));
+
+            packed.symmetrize();
+            aligned.symmetrize();
+            assert!(aligned.is_symmetric());
+            assert_same_graph(&packed, &aligned);
+
+            // growing keeps the layout
+            aligned.extend(vec![(size + 10, 0)]);
+            packed.extend(vec![(size + 10, 0)]);
+            assert!(aligned.is_row_aligned());
+            assert_same_graph(&packed, &aligned);
+
+            // snapshots are always packed
+            let mut bytes = Vec::new();
+            aligned.write_to(&mut bytes).unwrap();
+            let back = BitGraph::read_from(&bytes[..]).unwrap();
+            assert!(!back.is_row_aligned());
+            assert_eq!(back, aligned);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "layouts differ")]
+    fn mismatched_layout_union_test() {
+        let (mut packed, aligned) = layout_pair(100);
+        packed.union_with(&aligned);
+    }
+
+    #[test]
+    fn unchecked_test() {
+        for size in [1, 63, 64, 65, 130] {
+            let (packed, aligned) = layout_pair(size);
+            for graph in [&packed, &aligned] {
+                for from in 0..size {
+                    let out: Vec<usize> = unsafe { graph.outgoing_edges_unchecked(from) }.collect();
+                    assert_eq!(out, graph.outgoing_edges_of(from));
+
+                    for to in 0..size {
+                        let unchecked = unsafe { graph.has_edge_unchecked(from, to) };
+                        assert_eq!(unchecked, graph.has_edge(from, to));
+                    }
+                }
+            }
+        }
+
+        // room past the last node reads as empty
+        let mut graph = BitGraph::with_capacity(100);
+        graph.push_node(0);
+        graph.add_edge(0, 0);
+        unsafe {
+            assert!(graph.has_edge_unchecked(0, 0));
+            assert!(!graph.has_edge_unchecked(99, 99));
+            assert_eq!(
+                graph.outgoing_edges_unchecked(0).collect::<Vec<_>>(),
+                vec![0]
+            );
+            assert_eq!(graph.outgoing_edges_unchecked(99).count(), 0);
+        }
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "out of bounds for capacity 16")]
+    fn unchecked_edge_debug_test() {
+        let graph = BitGraph::with_capacity(16);
+        unsafe { graph.has_edge_unchecked(3, 16) };
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "node 16 out of bounds for capacity 16")]
+    fn unchecked_row_debug_test() {
+        let graph = BitGraph::with_capacity(16);
+        let _ = unsafe { graph.outgoing_edges_unchecked(16) };
+    }
 }
@@ -0,0 +1,178 @@
+use super::BitGraph;
+use crate::Graph;
+use std::fmt;
+
+/// Why a slice handed to `BitGraph::permute` is not a permutation of the
+/// node indices.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PermutationError {
+    /// one entry is needed per node
+    WrongLength { expected: usize, found: usize },
+    /// `perm[index]` is `value`, past the last node
+    OutOfRange { index: usize, value: usize },
+    /// `value` shows up more than once, so some other index is missing
+    Repeated { value: usize },
+}
+
+impl fmt::Display for PermutationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PermutationError::WrongLength { expected, found } => {
+                write!(f, "expected {} entries, found {}", expected, found)
+            }
+            PermutationError::OutOfRange { index, value } => {
+                write!(f, "entry {} is {}, past the last node", index, value)
+            }
+            PermutationError::Repeated { value } => write!(f, "{} shows up more than once", value),
+        }
+    }
+}
+
+impl std::error::Error for PermutationError {}
+
+impl BitGraph {
+    /// Copy with the nodes renumbered, old node `i` becomes node `perm[i]`
+    /// and takes its value and edges along. `perm` has to hold every index
+    /// below `node_count` exactly once. The copy keeps the layout and
+    /// transpose mode of `self`.
+    pub fn permute(&self, perm: &[usize]) -> Result<BitGraph, PermutationError> {
+        check_permutation(perm, self.count)?;
+
+        let mut graph = self.with_same_layout(self.nodes.capacity());
+        graph.resize_nodes(self.count, 0);
+        for (old, &new) in perm.iter().enumerate() {
+            graph.nodes[new] = self.nodes[old];
+        }
+
+        let mut row = Vec::new();
+        for (old, &new) in perm.iter().enumerate() {
+            self.row_into(&self.edges, old, &mut row);
+            for &to in &row {
+                graph.add_edge(new, perm[to]);
+            }
+        }
+
+        Ok(graph)
+    }
+}
+
+fn check_permutation(perm: &[usize], count: usize) -> Result<(), PermutationError> {
+    if perm.len() != count {
+        return Err(PermutationError::WrongLength {
+            expected: count,
+            found: perm.len(),
+        });
+    }
+
+    let mut seen = vec![false; count];
+    for (index, &value) in perm.iter().enumerate() {
+        if value >= count {
+            return Err(PermutationError::OutOfRange { index, value });
+        }
+        if seen[value] {
+            return Err(PermutationError::Repeated { value });
+        }
+        seen[value] = true;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inverse(perm: &[usize]) -> Vec<usize> {
+        let mut inverse = vec![0; perm.len()];
+        for (old, &new) in perm.iter().enumerate() {
+            inverse[new] = old;
+        }
+        inverse
+    }
+
+    fn sample() -> BitGraph {
+        let mut graph = BitGraph::from_nodes_and_edges(
+            (0..70).map(|value| value * 10),
+            vec![(0, 1), (1, 2), (2, 0), (3, 3), (5, 64), (64, 69), (69, 5)],
+        )
+        .unwrap();
+        graph.add_edge(63, 65);
+        graph
+    }
+
+    #[test]
+    fn hand_written_test() {
+        let graph = BitGraph::from_nodes_and_edges(
+            vec![10, 20, 30, 40],
+            vec![(0, 1), (1, 2), (3, 3), (2, 0)],
+        )
+        .unwrap();
+
+        let permuted = graph.permute(&[2, 0, 3, 1]).unwrap();
+
+        assert_eq!(permuted.node_count(), 4);
+        assert_eq!(
+            (0..4)
+                .map(|node| *permuted.get_node(node))
+                .collect::<Vec<_>>(),
+            vec![20, 40, 10, 30]
+        );
+        let mut edges = permuted.all_edge_pairs();
+        edges.sort_unstable();
+        assert_eq!(edges, vec![(0, 3), (1, 1), (2, 0), (3, 2)]);
+        assert_eq!(permuted.incoming_edges_of(3), vec![0]);
+        assert_eq!(permuted.validate(), Ok(()));
+    }
+
+    #[test]
+    fn round_trip_test() {
+        let graph = sample();
+        // 17 and 70 are coprime, so this hits every index and spreads
+        // neighbours across words
+        let perm: Vec<usize> = (0..70).map(|i| (i * 17 + 3) % 70).collect();
+
+        let permuted = graph.permute(&perm).unwrap();
+        assert_ne!(permuted, graph);
+        assert_eq!(
+            permuted.all_edge_pairs().len(),
+            graph.all_edge_pairs().len()
+        );
+        for (from, to) in graph.all_edge_pairs() {
+            assert!(permuted.has_edge(perm[from], perm[to]));
+        }
+
+        assert_eq!(permuted.permute(&inverse(&perm)).unwrap(), graph);
+
+        let aligned = {
+            let mut aligned = BitGraph::with_capacity_aligned(70);
+            aligned.resize_nodes(70, 0);
+            aligned.extend(graph.all_edge_pairs());
+            aligned
+        };
+        let permuted = aligned.permute(&perm).unwrap();
+        assert!(permuted.is_row_aligned());
+        assert_eq!(permuted.permute(&inverse(&perm)).unwrap(), aligned);
+    }
+
+    #[test]
+    fn invalid_test() {
+        let graph = BitGraph::from_edges(3, vec![(0, 1)]).unwrap();
+
+        assert_eq!(
+            graph.permute(&[0, 1]),
+            Err(PermutationError::WrongLength {
+                expected: 3,
+                found: 2
+            })
+        );
+        assert_eq!(
+            graph.permute(&[0, 3, 1]),
+            Err(PermutationError::OutOfRange { index: 1, value: 3 })
+        );
+        assert_eq!(
+            graph.permute(&[2, 0, 2]),
+            Err(PermutationError::Repeated { value: 2 })
+        );
+        assert_eq!(graph.permute(&[0, 1, 2]), Ok(graph.clone()));
+    }
+}
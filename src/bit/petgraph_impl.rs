@@ -0,0 +1,209 @@
+//! Conversions to and from `petgraph::Graph` and the petgraph visit traits,
+//! so petgraph's algorithms can walk a `BitGraph` in place. Node ids are the
+//! indices and edges have no weight.
+
+use super::{BitGraph, NodeBitSet};
+use crate::Graph;
+use petgraph::graph::{DiGraph, IndexType};
+use petgraph::visit::{
+    Data, GraphBase, GraphProp, IntoEdgeReferences, IntoEdges, IntoNeighbors,
+    IntoNeighborsDirected, IntoNodeIdentifiers, NodeCount, NodeIndexable, VisitMap, Visitable,
+};
+use petgraph::{Directed, Direction};
+
+impl From<&BitGraph> for DiGraph<u64, ()> {
+    fn from(graph: &BitGraph) -> Self {
+        let mut out = DiGraph::with_capacity(graph.count, 0);
+        let indices: Vec<_> = graph.nodes[..graph.count]
+            .iter()
+            .map(|value| out.add_node(*value))
+            .collect();
+
+        for (from, to) in graph.all_edge_pairs() {
+            out.add_edge(indices[from], indices[to], ());
+        }
+
+        out
+    }
+}
+
+impl BitGraph {
+    /// Copies the nodes and edges of a petgraph graph, parallel edges become
+    /// one and edge weights are dropped.
+    pub fn from_petgraph<E, Ix: IndexType>(graph: &DiGraph<u64, E, Ix>) -> BitGraph {
+        let edges = graph
+            .raw_edges()
+            .iter()
+            .map(|edge| (edge.source().index(), edge.target().index()));
+
+        BitGraph::from_nodes_and_edges(graph.raw_nodes().iter().map(|node| node.weight), edges)
+            .expect("petgraph edge endpoints are under its node count")
+    }
+}
+
+impl VisitMap<usize> for NodeBitSet {
+    fn visit(&mut self, node: usize) -> bool {
+        self.insert(node)
+    }
+
+    fn is_visited(&self, node: &usize) -> bool {
+        self.contains(*node)
+    }
+}
+
+impl GraphBase for BitGraph {
+    type EdgeId = (usize, usize);
+    type NodeId = usize;
+}
+
+impl Data for BitGraph {
+    type NodeWeight = u64;
+    type EdgeWeight = ();
+}
+
+impl GraphProp for BitGraph {
+    type EdgeType = Directed;
+}
+
+impl NodeCount for BitGraph {
+    fn node_count(&self) -> usize {
+        self.count
+    }
+}
+
+impl NodeIndexable for BitGraph {
+    fn node_bound(&self) -> usize {
+        self.count
+    }
+
+    fn to_index(&self, node: usize) -> usize {
+        node
+    }
+
+    fn from_index(&self, index: usize) -> usize {
+        index
+    }
+}
+
+impl Visitable for BitGraph {
+    type Map = NodeBitSet;
+
+    fn visit_map(&self) -> NodeBitSet {
+        NodeBitSet::with_capacity(self.count)
+    }
+
+    fn reset_map(&self, map: &mut NodeBitSet) {
+        *map = NodeBitSet::with_capacity(self.count);
+    }
+}
+
+impl IntoNeighbors for &BitGraph {
+    type Neighbors = std::vec::IntoIter<usize>;
+
+    fn neighbors(self, node: usize) -> Self::Neighbors {
+        self.outgoing_edges_of(node).into_iter()
+    }
+}
+
+impl IntoNeighborsDirected for &BitGraph {
+    type NeighborsDirected = std::vec::IntoIter<usize>;
+
+    fn neighbors_directed(self, node: usize, direction: Direction) -> Self::NeighborsDirected {
+        match direction {
+            Direction::Outgoing => self.outgoing_edges_of(node),
+            Direction::Incoming => self.incoming_edges_of(node),
+        }
+        .into_iter()
+    }
+}
+
+impl IntoNodeIdentifiers for &BitGraph {
+    type NodeIdentifiers = std::ops::Range<usize>;
+
+    fn node_identifiers(self) -> Self::NodeIdentifiers {
+        0..self.count
+    }
+}
+
+impl<'a> IntoEdgeReferences for &'a BitGraph {
+    type EdgeRef = (usize, usize, &'a ());
+    type EdgeReferences = std::vec::IntoIter<(usize, usize, &'a ())>;
+
+    fn edge_references(self) -> Self::EdgeReferences {
+        self.all_edge_pairs()
+            .into_iter()
+            .map(|(from, to)| (from, to, &()))
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+}
+
+impl<'a> IntoEdges for &'a BitGraph {
+    type Edges = std::vec::IntoIter<(usize, usize, &'a ())>;
+
+    fn edges(self, node: usize) -> Self::Edges {
+        self.outgoing_edges_of(node)
+            .into_iter()
+            .map(|to| (node, to, &()))
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::{grid_graph, index_of};
+    use crate::search::bfs::BFS;
+    use petgraph::algo::{dijkstra, toposort};
+
+    #[test]
+    fn conversion_test() {
+        let mut graph = BitGraph::with_capacity(8);
+        for value in [10, 20, 30, 40] {
+            graph.push_node(value);
+        }
+        graph.add_edges(vec![(0, 1), (1, 2), (2, 0), (3, 3)]);
+
+        let converted = DiGraph::<u64, ()>::from(&graph);
+        assert_eq!(converted.node_count(), 4);
+        assert_eq!(converted.edge_count(), 4);
+        assert_eq!(converted.raw_nodes()[2].weight, 30);
+        assert!(converted.find_edge(2.into(), 0.into()).is_some());
+        assert!(converted.find_edge(0.into(), 2.into()).is_none());
+
+        assert_eq!(BitGraph::from_petgraph(&converted), graph);
+    }
+
+    #[test]
+    fn dijkstra_test() {
+        let mut graph = grid_graph(9, 7, false);
+        // a wall with a gap at the top, the wall cells are cut off
+        for y in 1..7 {
+            graph.clear_edges_of(index_of(4, y, 9));
+        }
+
+        let costs = dijkstra(&graph, 0, None, |_| 1usize);
+        let distances = BFS::new(&graph, 0).distances(&graph);
+
+        for (node, distance) in distances.into_iter().enumerate() {
+            assert_eq!(costs.get(&node).copied(), distance, "node {}", node);
+        }
+        assert_eq!(costs[&index_of(8, 6, 9)], 14);
+        assert!(!costs.contains_key(&index_of(4, 3, 9)));
+    }
+
+    #[test]
+    fn toposort_test() {
+        let graph = BitGraph::from_edges(5, vec![(3, 1), (1, 0), (4, 2), (2, 0)]).unwrap();
+        let order = toposort(&graph, None).unwrap();
+
+        let position = |node| order.iter().position(|n| *n == node).unwrap();
+        for (from, to) in graph.all_edge_pairs() {
+            assert!(position(from) < position(to));
+        }
+
+        let cyclic = BitGraph::from_edges(2, vec![(0, 1), (1, 0)]).unwrap();
+        assert!(toposort(&cyclic, None).is_err());
+    }
+}
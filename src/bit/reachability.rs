@@ -0,0 +1,293 @@
+use super::{ones, BitGraph, WORD_BITS};
+use crate::search::topo::{topological_sort, CycleError};
+use crate::Graph;
+
+impl BitGraph {
+    /// Every pair `(u, v)` such that a path of at least one edge leads from
+    /// `u` to `v`, so nodes on a cycle reach themselves. Warshall's algorithm
+    /// a whole row at a time, with the `rayon` feature the rows are updated
+    /// in parallel for every intermediate node.
+    pub fn transitive_closure(&self) -> BitGraph {
+        let mut reach = self.aligned_rows();
+
+        #[cfg(feature = "rayon")]
+        par_warshall(&mut reach);
+        #[cfg(not(feature = "rayon"))]
+        warshall(&mut reach);
+
+        self.with_rows(&reach)
+    }
+
+    /// Every pair `(u, w)` such that `u -> v` in `self` and `v -> w` in
+    /// `other` for some `v`, the boolean product of the two matrices. Nodes
+    /// are taken from `self`. With the `rayon` feature the destination rows
+    /// are worked out in parallel.
+    ///
+    /// Panics if the two graphs have a different number of nodes.
+    pub fn compose(&self, other: &BitGraph) -> BitGraph {
+        assert_eq!(
+            self.count, other.count,
+            "compose needs the same node count, {} vs {}",
+            self.count, other.count
+        );
+
+        let sources = self.aligned_rows();
+        let targets = other.aligned_rows();
+
+        #[cfg(feature = "rayon")]
+        let rows = par_compose_rows(&sources, &targets);
+        #[cfg(not(feature = "rayon"))]
+        let rows = compose_rows(&sources, &targets);
+
+        self.with_rows(&rows)
+    }
+
+    /// Smallest graph with the same reachability as this DAG, every edge
+    /// `(u, v)` with another path from `u` to `v` is dropped. Nodes are
+    /// handled in reverse topological order so each one can clear the
+    /// descendants of its direct successors out of its own row.
+    pub fn transitive_reduction(&self) -> Result<BitGraph, CycleError> {
+        let order = topological_sort(self)?;
+
+        // strict descendants of every node
+        let mut reach = vec![Vec::new(); self.count];
+        let mut reduced = vec![Vec::new(); self.count];
+
+        for &node in order.iter().rev() {
+            let direct = self.aligned_row(&self.edges, node);
+            let mut below = vec![0; direct.len()];
+
+            for next in self.outgoing_edges_of(node) {
+                below
+                    .iter_mut()
+                    .zip(&reach[next])
+                    .for_each(|(word, other): (&mut usize, &usize)| *word |= other);
+            }
+
+            reduced[node] = direct
+                .iter()
+                .zip(&below)
+                .map(|(direct, below)| direct & !below)
+                .collect();
+            reach[node] = direct
+                .iter()
+                .zip(&below)
+                .map(|(direct, below)| direct | below)
+                .collect();
+        }
+
+        Ok(self.with_rows(&reduced))
+    }
+
+    /// word aligned copy of every live row
+    fn aligned_rows(&self) -> Vec<Vec<usize>> {
+        (0..self.count)
+            .map(|node| self.aligned_row(&self.edges, node))
+            .collect()
+    }
+
+    /// same nodes and dimension as `self` with word aligned `rows` as edges
+    fn with_rows(&self, rows: &[Vec<usize>]) -> BitGraph {
+        let mut graph = self.empty_copy();
+        let mut edges = std::mem::take(&mut graph.edges);
+
+        for (node, row) in rows.iter().enumerate() {
+            graph.or_aligned_row(&mut edges, node, row);
+        }
+
+        graph.edges = edges;
+        graph.rebuild_transpose();
+        graph
+    }
+}
+
+/// Warshall over word aligned rows, in place
+#[cfg_attr(feature = "rayon", allow(dead_code))]
+fn warshall(reach: &mut [Vec<usize>]) {
+    for through in 0..reach.len() {
+        let through_row = reach[through].clone();
+        reach
+            .iter_mut()
+            .for_each(|row| or_through(row, through, &through_row));
+    }
+}
+
+/// `warshall` with the rows of each step handed out to the rayon pool, a
+/// step only reads the row of `through` so the rows are independent
+#[cfg(feature = "rayon")]
+fn par_warshall(reach: &mut [Vec<usize>]) {
+    use rayon::prelude::*;
+
+    for through in 0..reach.len() {
+        let through_row = reach[through].clone();
+        reach
+            .par_iter_mut()
+            .for_each(|row| or_through(row, through, &through_row));
+    }
+}
+
+/// ORs `through_row` into `row` if `row` reaches `through`
+#[inline]
+fn or_through(row: &mut [usize], through: usize, through_row: &[usize]) {
+    if super::get_bit(row[through / WORD_BITS], through % WORD_BITS) {
+        row.iter_mut()
+            .zip(through_row)
+            .for_each(|(word, other)| *word |= other);
+    }
+}
+
+/// one row of the product, the OR of the `targets` rows of every node set
+/// in `source`
+fn compose_row(source: &[usize], targets: &[Vec<usize>]) -> Vec<usize> {
+    let mut row = vec![0; source.len()];
+    for (i, &word) in source.iter().enumerate() {
+        for offset in ones(word) {
+            row.iter_mut()
+                .zip(&targets[i * WORD_BITS + offset])
+                .for_each(|(word, other)| *word |= other);
+        }
+    }
+
+    row
+}
+
+#[cfg_attr(feature = "rayon", allow(dead_code))]
+fn compose_rows(sources: &[Vec<usize>], targets: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    sources
+        .iter()
+        .map(|source| compose_row(source, targets))
+        .collect()
+}
+
+#[cfg(feature = "rayon")]
+fn par_compose_rows(sources: &[Vec<usize>], targets: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    use rayon::prelude::*;
+
+    sources
+        .par_iter()
+        .map(|source| compose_row(source, targets))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diamond_test() {
+        // 0 -> 1 -> 3, 0 -> 2 -> 3 and the shortcut 0 -> 3
+        let edges = [(0, 1), (0, 2), (1, 3), (2, 3), (0, 3), (3, 4)];
+        let graph = BitGraph::from_edges(6, edges.iter().copied()).unwrap();
+
+        let reduced = graph.transitive_reduction().unwrap();
+        assert_eq!(
+            reduced.all_edge_pairs(),
+            vec![(0, 1), (0, 2), (1, 3), (2, 3), (3, 4)]
+        );
+        assert_eq!(reduced.incoming_edges_of(3), vec![1, 2]);
+        assert_eq!(reduced.transitive_closure(), graph.transitive_closure());
+    }
+
+    #[test]
+    fn closure_test() {
+        let graph = BitGraph::from_edges(4, vec![(0, 1), (1, 2), (2, 1)]).unwrap();
+        let closure = graph.transitive_closure();
+
+        assert_eq!(
+            closure.all_edge_pairs(),
+            vec![(0, 1), (0, 2), (1, 1), (1, 2), (2, 1), (2, 2)]
+        );
+        assert_eq!(closure.incoming_edges_of(2), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn cycle_test() {
+        let graph = BitGraph::from_edges(3, vec![(0, 1), (1, 2), (2, 0)]).unwrap();
+        assert!(graph.transitive_reduction().is_err());
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn random_dag_test() {
+        use crate::generate::gnp;
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        let mut rng = StdRng::seed_from_u64(17);
+        for &(n, p) in &[(10, 0.3), (40, 0.2), (90, 0.1)] {
+            // keep the edges that go forward so it is a DAG
+            let random = gnp(n, p, &mut rng);
+            let edges = random
+                .all_edge_pairs()
+                .into_iter()
+                .filter(|(from, to)| from < to);
+            let graph = BitGraph::from_edges(n, edges).unwrap();
+
+            let reduced = graph.transitive_reduction().unwrap();
+            assert_eq!(reduced.transitive_closure(), graph.transitive_closure());
+            assert!(reduced
+                .all_edge_pairs()
+                .iter()
+                .all(|(from, to)| graph.has_edge(*from, *to)));
+        }
+    }
+
+    #[test]
+    fn compose_test() {
+        let first = BitGraph::from_edges(5, vec![(0, 1), (0, 2), (3, 4), (4, 4)]).unwrap();
+        let second = BitGraph::from_edges(5, vec![(1, 3), (2, 3), (2, 0), (4, 1)]).unwrap();
+
+        let composed = first.compose(&second);
+        assert_eq!(
+            composed.all_edge_pairs(),
+            vec![(0, 0), (0, 3), (3, 1), (4, 1)]
+        );
+        assert_eq!(composed.incoming_edges_of(1), vec![3, 4]);
+
+        // composing with itself is paths of exactly two edges
+        let chain = BitGraph::from_edges(4, vec![(0, 1), (1, 2), (2, 3)]).unwrap();
+        assert_eq!(chain.compose(&chain).all_edge_pairs(), vec![(0, 2), (1, 3)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "compose needs the same node count, 3 vs 4")]
+    fn compose_mismatch_test() {
+        let small = BitGraph::from_edges(3, vec![(0, 1)]).unwrap();
+        let big = BitGraph::from_edges(4, vec![(0, 1)]).unwrap();
+        small.compose(&big);
+    }
+
+    /// about one pair in seven, spread without any pattern a row would line
+    /// up with
+    #[cfg(feature = "rayon")]
+    fn scattered(count: usize, seed: usize) -> BitGraph {
+        let edges = (0..count).flat_map(move |from| {
+            (0..count)
+                .filter(move |to| (from * 31 + to * 17 + seed).wrapping_mul(2654435761) % 7 == 3)
+                .map(move |to| (from, to))
+        });
+        BitGraph::from_edges(count, edges).unwrap()
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn parallel_matches_serial_test() {
+        for &count in &[1, 5, 63, 64, 65, 130] {
+            let graph = scattered(count, 3);
+            let other = scattered(count, 11);
+
+            let mut serial = graph.aligned_rows();
+            warshall(&mut serial);
+            let mut parallel = graph.aligned_rows();
+            par_warshall(&mut parallel);
+            assert_eq!(serial, parallel);
+            assert_eq!(graph.transitive_closure(), graph.with_rows(&serial));
+
+            let sources = graph.aligned_rows();
+            let targets = other.aligned_rows();
+            let serial = compose_rows(&sources, &targets);
+            assert_eq!(serial, par_compose_rows(&sources, &targets));
+            assert_eq!(graph.compose(&other), graph.with_rows(&serial));
+        }
+    }
+}
@@ -0,0 +1,233 @@
+use super::{clear_row, ones, set_bit, unset_bit, BitGraph, WORD_BITS};
+use crate::assert_node;
+
+/// The row of one node a word at a time, see `BitGraph::row_words`.
+#[derive(Clone, Debug)]
+pub struct RowWords<'a> {
+    matrix: &'a [usize],
+    /// bit the row starts at
+    start: usize,
+    count: usize,
+    index: usize,
+    len: usize,
+}
+
+impl<'a> RowWords<'a> {
+    fn new(matrix: &'a [usize], start: usize, count: usize) -> RowWords<'a> {
+        RowWords {
+            matrix,
+            start,
+            count,
+            index: 0,
+            len: count.div_ceil(WORD_BITS),
+        }
+    }
+
+    /// bits of word `i` that are columns of live nodes
+    fn valid_mask(&self, i: usize) -> usize {
+        let end = (i + 1) * WORD_BITS;
+        if end <= self.count {
+            usize::MAX
+        } else {
+            usize::MAX >> (end - self.count)
+        }
+    }
+}
+
+impl Iterator for RowWords<'_> {
+    type Item = (usize, usize);
+
+    fn next(&mut self) -> Option<(usize, usize)> {
+        if self.index == self.len {
+            return None;
+        }
+
+        let i = self.index;
+        self.index += 1;
+
+        let index = self.start / WORD_BITS + i;
+        let offset = self.start % WORD_BITS;
+        let mut word = self.matrix[index] >> offset;
+        if offset != 0 {
+            word |= self.matrix.get(index + 1).copied().unwrap_or(0) << (WORD_BITS - offset);
+        }
+
+        let mask = self.valid_mask(i);
+        Some((word & mask, mask))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let left = self.len - self.index;
+        (left, Some(left))
+    }
+}
+
+impl ExactSizeIterator for RowWords<'_> {}
+
+impl BitGraph {
+    /// Outgoing edges of `node_index` as `(word, valid_mask)` pairs, bit `i`
+    /// of the `n`th word is the edge to node `n * usize::BITS + i` whatever
+    /// the row's offset in the matrix. `valid_mask` covers the columns of
+    /// live nodes, only the last word has it short of all ones and the
+    /// words are already masked with it.
+    pub fn row_words(&self, node_index: usize) -> RowWords<'_> {
+        assert_node(node_index, self.count);
+        RowWords::new(&self.edges, self.stride * node_index, self.count)
+    }
+
+    /// The words of the row of `node_index` as stored, `None` unless made
+    /// with `with_capacity_aligned`. Bit `i` of the `n`th word is the edge
+    /// to node `n * usize::BITS + i`, words past the last node are zero.
+    pub fn row_slice(&self, node_index: usize) -> Option<&[usize]> {
+        assert_node(node_index, self.count);
+        if !self.row_aligned {
+            return None;
+        }
+
+        let start = self.stride * node_index / WORD_BITS;
+        Some(&self.edges[start..start + self.stride / WORD_BITS])
+    }
+
+    /// Replaces every word of the row of `node_index` with `f(word)`, in the
+    /// order `row_words` yields them. Bits past the last node are dropped.
+    /// The transpose is updated bit by bit for whatever changed, or marked
+    /// stale for a graph made with `with_capacity_directed_only`.
+    pub fn row_apply(&mut self, node_index: usize, mut f: impl FnMut(usize) -> usize) {
+        let (old, new): (Vec<usize>, Vec<usize>) = self
+            .row_words(node_index)
+            .map(|(word, mask)| (word, f(word) & mask))
+            .unzip();
+        if old == new {
+            return;
+        }
+
+        clear_row(&mut self.edges, self.stride, node_index);
+        let mut edges = std::mem::take(&mut self.edges);
+        self.or_aligned_row(&mut edges, node_index, &new);
+        self.edges = edges;
+
+        if self.lazy_transpose {
+            self.transpose_stale = true;
+            return;
+        }
+
+        for (i, (old, new)) in old.into_iter().zip(new).enumerate() {
+            for offset in ones(old ^ new) {
+                let to = i * WORD_BITS + offset;
+                let fun = if new & (1 << offset) != 0 {
+                    set_bit
+                } else {
+                    unset_bit
+                };
+                self.set_edge_of_tranpose(to, node_index, fun);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Graph;
+
+    /// 100 nodes, rows straddle words in the packed layout
+    fn sample(mut graph: BitGraph) -> BitGraph {
+        for i in 0..100 {
+            graph.push_node(i);
+        }
+        for i in 0..100 {
+            graph.add_edge(i, (i * 7 + 3) % 100);
+            graph.add_edge(i, (i * 13) % 100);
+        }
+        graph.extend(vec![(3, 0), (3, 63), (3, 64), (3, 99), (70, 64), (70, 65)]);
+        graph
+    }
+
+    fn columns(graph: &BitGraph, node: usize) -> Vec<usize> {
+        graph
+            .row_words(node)
+            .enumerate()
+            .flat_map(|(i, (word, _))| ones(word).map(move |offset| i * WORD_BITS + offset))
+            .collect()
+    }
+
+    #[test]
+    fn row_words_test() {
+        for graph in &[
+            sample(BitGraph::with_capacity(130)),
+            sample(BitGraph::with_capacity_aligned(130)),
+        ] {
+            for node in 0..100 {
+                assert_eq!(columns(graph, node), graph.outgoing_edges_of(node));
+            }
+
+            let len = 100usize.div_ceil(WORD_BITS);
+            let masks: Vec<usize> = graph.row_words(3).map(|(_, mask)| mask).collect();
+            assert_eq!(graph.row_words(3).len(), len);
+            assert!(masks[..len - 1].iter().all(|&mask| mask == usize::MAX));
+            assert_eq!(masks[len - 1], usize::MAX >> (len * WORD_BITS - 100));
+        }
+    }
+
+    #[test]
+    fn row_slice_test() {
+        let aligned = sample(BitGraph::with_capacity_aligned(130));
+        let words: Vec<usize> = aligned.row_words(3).map(|(word, _)| word).collect();
+        let slice = aligned.row_slice(3).unwrap();
+
+        assert_eq!(&slice[..words.len()], &words[..]);
+        assert!(slice[words.len()..].iter().all(|&word| word == 0));
+        assert_eq!(sample(BitGraph::with_capacity(130)).row_slice(3), None);
+    }
+
+    /// ORs the row of `b` into the row of `a` both ways and compares
+    fn assert_or_rows(graph: BitGraph, a: usize, b: usize) {
+        let mut by_words = graph.clone();
+        let other: Vec<usize> = by_words.row_words(b).map(|(word, _)| word).collect();
+        let mut other = other.into_iter();
+        by_words.row_apply(a, |word| word | other.next().unwrap());
+
+        let mut by_edges = graph;
+        for to in by_edges.outgoing_edges_of(b) {
+            by_edges.add_edge(a, to);
+        }
+
+        assert!(by_words == by_edges);
+        assert_eq!(by_words.outgoing_edges_of(a), by_edges.outgoing_edges_of(a));
+        by_words.refresh_transpose();
+        by_edges.refresh_transpose();
+        for node in 0..by_edges.node_count() {
+            assert_eq!(
+                by_words.incoming_edges_of(node),
+                by_edges.incoming_edges_of(node)
+            );
+        }
+        assert_eq!(by_words.validate(), Ok(()));
+    }
+
+    #[test]
+    fn row_apply_or_test() {
+        for &(a, b) in &[(3, 70), (70, 3), (99, 0), (1, 1)] {
+            assert_or_rows(sample(BitGraph::with_capacity(130)), a, b);
+            assert_or_rows(sample(BitGraph::with_capacity_aligned(130)), a, b);
+            assert_or_rows(sample(BitGraph::with_capacity_directed_only(130)), a, b);
+        }
+    }
+
+    #[test]
+    fn row_apply_clear_test() {
+        let mut graph = sample(BitGraph::with_capacity(130));
+        // all ones past the last node must not leak into the next row
+        graph.row_apply(3, |_| usize::MAX);
+        assert_eq!(graph.out_degree(3), 100);
+        assert_eq!(
+            graph.outgoing_edges_of(4),
+            sample(BitGraph::new()).outgoing_edges_of(4)
+        );
+
+        graph.row_apply(3, |_| 0);
+        assert_eq!(graph.out_degree(3), 0);
+        assert!(!graph.incoming_edges_of(64).contains(&3));
+        assert_eq!(graph.validate(), Ok(()));
+    }
+}
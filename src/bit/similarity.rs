@@ -0,0 +1,231 @@
+use super::BitGraph;
+use crate::assert_node;
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+
+impl BitGraph {
+    /// Number of nodes both `a` and `b` have an edge to.
+    pub fn common_neighbor_count(&self, a: usize, b: usize) -> usize {
+        self.overlap(a, b).shared
+    }
+
+    /// `|N(a) ∩ N(b)| / |N(a) ∪ N(b)|` over outgoing neighbours, 0 when
+    /// neither node has any.
+    pub fn jaccard(&self, a: usize, b: usize) -> f64 {
+        self.overlap(a, b).jaccard().ratio()
+    }
+
+    /// `|N(a) ∩ N(b)| / min(|N(a)|, |N(b)|)` over outgoing neighbours, 0 when
+    /// either node has none.
+    pub fn overlap_coefficient(&self, a: usize, b: usize) -> f64 {
+        let overlap = self.overlap(a, b);
+        let smaller = overlap.a_len.min(overlap.b_len);
+        if smaller == 0 {
+            return 0.0;
+        }
+
+        overlap.shared as f64 / smaller as f64
+    }
+
+    /// The `k` nodes other than `a` with the highest `jaccard` against it,
+    /// best first, ties going to the lower index. Nodes sharing no neighbour
+    /// with `a` are left out, so fewer than `k` can come back.
+    pub fn top_k_similar(&self, a: usize, k: usize) -> Vec<(usize, f64)> {
+        assert_node(a, self.count);
+        let row = self.aligned_row(&self.edges, a);
+
+        // min heap of the best so far, the root is the first to go
+        let mut best: BinaryHeap<Reverse<Scored>> = BinaryHeap::with_capacity(k + 1);
+        for node in (0..self.count).filter(|&node| node != a) {
+            let overlap = Overlap::of(&row, &self.aligned_row(&self.edges, node));
+            if overlap.shared == 0 {
+                continue;
+            }
+
+            best.push(Reverse(overlap.jaccard().with_node(node)));
+            if best.len() > k {
+                best.pop();
+            }
+        }
+
+        let mut best: Vec<Scored> = best.into_iter().map(|Reverse(scored)| scored).collect();
+        best.sort_by(|x, y| y.cmp(x));
+        best.into_iter()
+            .map(|scored| (scored.node, scored.ratio()))
+            .collect()
+    }
+
+    fn overlap(&self, a: usize, b: usize) -> Overlap {
+        assert_node(a, self.count);
+        assert_node(b, self.count);
+
+        Overlap::of(
+            &self.aligned_row(&self.edges, a),
+            &self.aligned_row(&self.edges, b),
+        )
+    }
+}
+
+/// popcounts of two word aligned rows
+struct Overlap {
+    shared: usize,
+    a_len: usize,
+    b_len: usize,
+}
+
+impl Overlap {
+    fn of(a: &[usize], b: &[usize]) -> Overlap {
+        let count = |row: &[usize]| row.iter().map(|word| word.count_ones() as usize).sum();
+
+        Overlap {
+            shared: a
+                .iter()
+                .zip(b)
+                .map(|(x, y)| (x & y).count_ones() as usize)
+                .sum(),
+            a_len: count(a),
+            b_len: count(b),
+        }
+    }
+
+    fn jaccard(&self) -> Scored {
+        Scored {
+            shared: self.shared,
+            total: self.a_len + self.b_len - self.shared,
+            node: 0,
+        }
+    }
+}
+
+/// A similarity kept as a fraction so the heap compares exactly, higher
+/// ratios and then lower node indices come first.
+#[derive(PartialEq, Eq)]
+struct Scored {
+    shared: usize,
+    total: usize,
+    node: usize,
+}
+
+impl Scored {
+    fn with_node(self, node: usize) -> Scored {
+        Scored { node, ..self }
+    }
+
+    fn ratio(&self) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+
+        self.shared as f64 / self.total as f64
+    }
+}
+
+impl Ord for Scored {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.shared * other.total)
+            .cmp(&(other.shared * self.total))
+            .then(other.node.cmp(&self.node))
+    }
+}
+
+impl PartialOrd for Scored {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Graph;
+
+    /// 100 nodes so rows start at different offsets inside their words
+    fn graph(edges: &[(usize, usize)]) -> BitGraph {
+        let mut graph = BitGraph::with_capacity(100);
+        for _ in 0..100 {
+            graph.push_node(0);
+        }
+        graph.extend(edges.iter().copied());
+        graph
+    }
+
+    #[test]
+    fn full_overlap_test() {
+        let graph = graph(&[(1, 5), (1, 63), (1, 64), (70, 5), (70, 63), (70, 64)]);
+
+        assert_eq!(graph.common_neighbor_count(1, 70), 3);
+        assert_eq!(graph.jaccard(1, 70), 1.0);
+        assert_eq!(graph.overlap_coefficient(1, 70), 1.0);
+        assert_eq!(graph.jaccard(1, 1), 1.0);
+    }
+
+    #[test]
+    fn partial_overlap_test() {
+        // N(3) = {0, 63, 64, 99}, N(65) = {63, 64, 65}
+        let graph = graph(&[
+            (3, 0),
+            (3, 63),
+            (3, 64),
+            (3, 99),
+            (65, 63),
+            (65, 64),
+            (65, 65),
+        ]);
+
+        assert_eq!(graph.common_neighbor_count(3, 65), 2);
+        assert_eq!(graph.common_neighbor_count(65, 3), 2);
+        assert_eq!(graph.jaccard(3, 65), 2.0 / 5.0);
+        assert_eq!(graph.jaccard(65, 3), 2.0 / 5.0);
+        assert_eq!(graph.overlap_coefficient(3, 65), 2.0 / 3.0);
+    }
+
+    #[test]
+    fn disjoint_test() {
+        let graph = graph(&[(2, 10), (2, 63), (64, 64), (64, 99)]);
+
+        assert_eq!(graph.common_neighbor_count(2, 64), 0);
+        assert_eq!(graph.jaccard(2, 64), 0.0);
+        assert_eq!(graph.overlap_coefficient(2, 64), 0.0);
+
+        // no neighbours at all
+        assert_eq!(graph.jaccard(5, 6), 0.0);
+        assert_eq!(graph.overlap_coefficient(2, 6), 0.0);
+    }
+
+    #[test]
+    fn top_k_similar_test() {
+        // N(0) = {10, 11, 12, 13}
+        let graph = graph(&[
+            (0, 10),
+            (0, 11),
+            (0, 12),
+            (0, 13),
+            // same set, 1.0
+            (64, 10),
+            (64, 11),
+            (64, 12),
+            (64, 13),
+            // half of it, 2 / 4
+            (63, 10),
+            (63, 11),
+            (65, 12),
+            (65, 13),
+            // one of it plus another, 1 / 5
+            (99, 10),
+            (99, 50),
+            // nothing in common
+            (7, 50),
+        ]);
+
+        assert_eq!(
+            graph.top_k_similar(0, 3),
+            vec![(64, 1.0), (63, 0.5), (65, 0.5)]
+        );
+        assert_eq!(
+            graph.top_k_similar(0, 10),
+            vec![(64, 1.0), (63, 0.5), (65, 0.5), (99, 0.2)]
+        );
+        assert_eq!(graph.top_k_similar(0, 0), vec![]);
+        assert_eq!(graph.top_k_similar(7, 2), vec![(99, 0.5)]);
+    }
+}
@@ -0,0 +1,242 @@
+//! Binary snapshot format for `BitGraph`.
+//!
+//! Everything is little endian:
+//!
+//! | field        | size                |
+//! |--------------|---------------------|
+//! | magic        | 4 bytes, `BGRF`     |
+//! | version      | u32                 |
+//! | word bits    | u32, always 64      |
+//! | node_count   | u64                 |
+//! | dimension    | u64                 |
+//! | nodes        | node_count × u64    |
+//! | edge words   | dim² / 64 + 1 × u64 |
+//!
+//! The edge words are the bits of `edges` laid out as one long bit string,
+//! row after row with a stride of `dimension`. They are always written as u64
+//! words, so a file from a 64 bit machine can be read where `usize` is 32 bits.
+//! The transpose is not stored and gets rebuilt on load. A graph made with
+//! `with_capacity_aligned` is written in the packed layout all the same and
+//! loads as a packed graph.
+//!
+//! With the `mmap` feature `BitGraph::map_readonly` queries a snapshot file in
+//! place instead of reading it.
+
+use super::{BitGraph, WORD_BITS};
+use std::convert::TryFrom;
+use std::io::{self, Read, Write};
+
+pub(crate) const MAGIC: [u8; 4] = *b"BGRF";
+pub(crate) const VERSION: u32 = 1;
+pub(crate) const FILE_WORD_BITS: u32 = 64;
+/// bytes before the node values start
+#[cfg(any(test, feature = "mmap"))]
+pub(crate) const HEADER_LEN: usize = 4 + 4 + 4 + 8 + 8;
+
+/// usize words packed into each u64 word of the file
+const WORDS_PER_FILE_WORD: usize = 64 / WORD_BITS;
+
+/// header fields after they have been checked
+pub(crate) struct Header {
+    pub node_count: usize,
+    pub dimension: usize,
+}
+
+/// number of u64 edge words stored for a given dimension
+pub(crate) fn file_word_count(dimension: usize) -> usize {
+    dimension * dimension / 64 + 1
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_u64<R: Read>(reader: &mut R) -> io::Result<u64> {
+    let mut bytes = [0; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+/// reads and validates everything up to the node values
+pub(crate) fn read_header<R: Read>(reader: &mut R) -> io::Result<Header> {
+    let mut magic = [0; 4];
+    reader.read_exact(&mut magic)?;
+    if magic != MAGIC {
+        return Err(invalid(format!("bad magic bytes {:?}", magic)));
+    }
+
+    let version = read_u32(reader)?;
+    if version != VERSION {
+        return Err(invalid(format!("unsupported snapshot version {}", version)));
+    }
+
+    let word_bits = read_u32(reader)?;
+    if word_bits != FILE_WORD_BITS {
+        return Err(invalid(format!("unsupported word size {}", word_bits)));
+    }
+
+    let too_big = |value: u64| invalid(format!("{} does not fit in usize", value));
+    let node_count = read_u64(reader)?;
+    let node_count = usize::try_from(node_count).map_err(|_| too_big(node_count))?;
+    let dimension = read_u64(reader)?;
+    let dimension = usize::try_from(dimension).map_err(|_| too_big(dimension))?;
+
+    if node_count > dimension {
+        return Err(invalid(format!(
+            "node_count {} is larger than dimension {}",
+            node_count, dimension
+        )));
+    }
+    if dimension.checked_mul(dimension).is_none() {
+        return Err(invalid(format!("dimension {} is too large", dimension)));
+    }
+
+    Ok(Header {
+        node_count,
+        dimension,
+    })
+}
+
+impl BitGraph {
+    /// Writes the graph in the binary snapshot format described in
+    /// `bit::snapshot`.
+    pub fn write_to<Wtr: Write>(&self, mut writer: Wtr) -> io::Result<()> {
+        let dimension = self.nodes.capacity();
+        if self.stride != dimension {
+            return self.packed().write_to(writer);
+        }
+
+        writer.write_all(&MAGIC)?;
+        writer.write_all(&VERSION.to_le_bytes())?;
+        writer.write_all(&FILE_WORD_BITS.to_le_bytes())?;
+        writer.write_all(&(self.count as u64).to_le_bytes())?;
+        writer.write_all(&(dimension as u64).to_le_bytes())?;
+
+        for value in &self.nodes[..self.count] {
+            writer.write_all(&value.to_le_bytes())?;
+        }
+
+        for i in 0..file_word_count(dimension) {
+            let mut word = 0u64;
+            for j in 0..WORDS_PER_FILE_WORD {
+                let part = self.edges.get(i * WORDS_PER_FILE_WORD + j).copied();
+                word |= (part.unwrap_or(0) as u64) << (j * WORD_BITS);
+            }
+            writer.write_all(&word.to_le_bytes())?;
+        }
+
+        writer.flush()
+    }
+
+    /// Reads a graph written by `write_to`. Bad headers come back as
+    /// `InvalidData` and short input as `UnexpectedEof`.
+    pub fn read_from<R: Read>(mut reader: R) -> io::Result<BitGraph> {
+        let header = read_header(&mut reader)?;
+
+        let mut graph = BitGraph::with_capacity(header.dimension);
+        for _ in 0..header.node_count {
+            graph.nodes.push(read_u64(&mut reader)?);
+        }
+        graph.count = header.node_count;
+
+        let len = graph.edges.len();
+        for i in 0..file_word_count(header.dimension) {
+            let word = read_u64(&mut reader)?;
+            for j in 0..WORDS_PER_FILE_WORD {
+                let index = i * WORDS_PER_FILE_WORD + j;
+                if index < len {
+                    graph.edges[index] = (word >> (j * WORD_BITS)) as usize;
+                }
+            }
+        }
+
+        graph.rebuild_transpose();
+        Ok(graph)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Graph;
+
+    fn sample() -> BitGraph {
+        let mut graph = BitGraph::with_capacity(130);
+
+        for i in 0..100 {
+            graph.push_node(i * 3);
+        }
+
+        graph.add_edge(0, 99);
+        graph.add_edge(99, 0);
+        graph.add_edge(64, 63);
+        graph.add_edge(63, 64);
+        graph.add_edge(50, 50);
+        graph.add_edge(1, 2);
+
+        graph
+    }
+
+    #[test]
+    fn round_trip_test() {
+        let graph = sample();
+
+        let mut bytes = Vec::new();
+        graph.write_to(&mut bytes).unwrap();
+        assert_eq!(bytes.len(), HEADER_LEN + 100 * 8 + file_word_count(130) * 8);
+
+        let back = BitGraph::read_from(&bytes[..]).unwrap();
+        assert!(back == graph);
+        assert_eq!(back.nodes.capacity(), 130);
+        assert_eq!(back.incoming_edges_of(64), vec![63]);
+        assert_eq!(back.incoming_edges_of(0), vec![99]);
+    }
+
+    #[test]
+    fn empty_round_trip_test() {
+        let graph = BitGraph::with_capacity(0);
+
+        let mut bytes = Vec::new();
+        graph.write_to(&mut bytes).unwrap();
+
+        let back = BitGraph::read_from(&bytes[..]).unwrap();
+        assert_eq!(back.node_count(), 0);
+    }
+
+    #[test]
+    fn bad_magic_test() {
+        let mut bytes = Vec::new();
+        sample().write_to(&mut bytes).unwrap();
+        bytes[0] = b'X';
+
+        let error = BitGraph::read_from(&bytes[..]).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn bad_header_test() {
+        let mut bytes = Vec::new();
+        sample().write_to(&mut bytes).unwrap();
+
+        let mut version = bytes.clone();
+        version[4] = 9;
+        let error = BitGraph::read_from(&version[..]).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        // node_count larger than the dimension
+        let mut count = bytes.clone();
+        count[12..20].copy_from_slice(&1000u64.to_le_bytes());
+        let error = BitGraph::read_from(&count[..]).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        bytes.truncate(bytes.len() - 3);
+        let error = BitGraph::read_from(&bytes[..]).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+    }
+}
@@ -0,0 +1,238 @@
+use super::{get_bit, locate, single_bit_mask, BitGraph, WORD_BITS};
+use crate::assert_node;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// `BitGraph` under construction from several threads at once. Edge words
+/// are atomic, so `add_edge` only needs `&self` and is a `fetch_or` on each
+/// matrix, no lock. Nodes are still pushed through `&mut self`, and the
+/// capacity is fixed at creation since the matrices can not be resized
+/// while shared. `freeze` turns it into a plain `BitGraph` for querying.
+pub struct SyncBitGraph {
+    nodes: Vec<u64>,
+    /// packed like `BitGraph::with_capacity`, rows `capacity` bits apart
+    edges: Vec<AtomicUsize>,
+    edges_transpose: Vec<AtomicUsize>,
+}
+
+impl SyncBitGraph {
+    pub fn with_capacity(size: usize) -> SyncBitGraph {
+        SyncBitGraph {
+            nodes: Vec::with_capacity(size),
+            edges: atomic_words((size * size) / WORD_BITS + 1),
+            edges_transpose: atomic_words((size * size) / WORD_BITS + 1),
+        }
+    }
+
+    /// `node_count` nodes valued `0` and room for no more
+    pub fn with_nodes(node_count: usize) -> SyncBitGraph {
+        let mut graph = Self::with_capacity(node_count);
+        graph.nodes.resize(node_count, 0);
+        graph
+    }
+
+    /// Panics if the graph is already at capacity.
+    pub fn push_node(&mut self, value: u64) -> usize {
+        assert!(
+            self.nodes.len() < self.nodes.capacity(),
+            "SyncBitGraph is full, capacity {}",
+            self.nodes.capacity()
+        );
+
+        self.nodes.push(value);
+        self.nodes.len() - 1
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// most nodes that fit, fixed at creation
+    pub fn capacity(&self) -> usize {
+        self.nodes.capacity()
+    }
+
+    /// Adds the edge from any thread, returns true if it was already there
+    /// like `BitGraph::add_edge`. When several threads add the same edge
+    /// exactly one of them gets false.
+    pub fn add_edge(&self, from: usize, to: usize) -> bool {
+        assert_node(from, self.nodes.len());
+        assert_node(to, self.nodes.len());
+
+        let (index, offset) = locate(self.capacity(), from, to);
+        let word = self.edges[index].fetch_or(single_bit_mask(offset), Ordering::Relaxed);
+
+        let (t_index, t_offset) = locate(self.capacity(), to, from);
+        self.edges_transpose[t_index].fetch_or(single_bit_mask(t_offset), Ordering::Relaxed);
+
+        get_bit(word, offset)
+    }
+
+    /// Sees edges added by other threads, though not necessarily the ones
+    /// they are adding right now.
+    pub fn has_edge(&self, from: usize, to: usize) -> bool {
+        assert_node(from, self.nodes.len());
+        assert_node(to, self.nodes.len());
+
+        let (index, offset) = locate(self.capacity(), from, to);
+        get_bit(self.edges[index].load(Ordering::Relaxed), offset)
+    }
+
+    /// The finished graph, in the packed layout with the same capacity.
+    /// Taking `self` means every thread that held a reference is done, so
+    /// all of their edges are in.
+    pub fn freeze(self) -> BitGraph {
+        // nothing is allocated here, every buffer is moved in below
+        let mut graph = BitGraph::with_capacity(0);
+        graph.stride = self.capacity();
+        graph.count = self.nodes.len();
+        graph.nodes = self.nodes;
+        graph.edges = self
+            .edges
+            .into_iter()
+            .map(AtomicUsize::into_inner)
+            .collect();
+        graph.edges_transpose = self
+            .edges_transpose
+            .into_iter()
+            .map(AtomicUsize::into_inner)
+            .collect();
+
+        graph
+    }
+}
+
+fn atomic_words(len: usize) -> Vec<AtomicUsize> {
+    (0..len).map(|_| AtomicUsize::new(0)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Graph;
+    use std::thread;
+
+    const THREADS: usize = 8;
+
+    /// about a quarter of all pairs, self loops included
+    fn pairs(count: usize) -> Vec<(usize, usize)> {
+        (0..count)
+            .flat_map(|from| (0..count).map(move |to| (from, to)))
+            .filter(|(from, to)| (from * 5 + to * 3) % 4 == 1)
+            .collect()
+    }
+
+    #[test]
+    fn disjoint_test() {
+        // 70 straddles words, the other two do not divide rows evenly
+        for &count in &[70, 129, 200] {
+            let pairs = pairs(count);
+            let graph = SyncBitGraph::with_nodes(count);
+
+            // thread t takes every THREADS-th pair starting at t
+            let new = thread::scope(|scope| {
+                let handles: Vec<_> = (0..THREADS)
+                    .map(|t| {
+                        let (graph, pairs) = (&graph, &pairs);
+                        scope.spawn(move || {
+                            pairs
+                                .iter()
+                                .skip(t)
+                                .step_by(THREADS)
+                                .filter(|(from, to)| !graph.add_edge(*from, *to))
+                                .count()
+                        })
+                    })
+                    .collect();
+                handles
+                    .into_iter()
+                    .map(|handle| handle.join().unwrap())
+                    .sum::<usize>()
+            });
+            assert_eq!(new, pairs.len());
+            assert!(pairs.iter().all(|(from, to)| graph.has_edge(*from, *to)));
+
+            let frozen = graph.freeze();
+            let serial = BitGraph::from_edges(count, pairs.iter().copied()).unwrap();
+            assert_eq!(frozen, serial);
+            assert_eq!(frozen.capacity(), count);
+            for node in 0..count {
+                assert_eq!(
+                    frozen.incoming_edges_of(node),
+                    serial.incoming_edges_of(node)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn overlapping_test() {
+        let count = 150;
+        let pairs = pairs(count);
+
+        let mut graph = SyncBitGraph::with_capacity(count + 10);
+        for i in 0..count {
+            graph.push_node(i as u64);
+        }
+
+        // every thread adds the whole list, starting at a different place
+        let new = thread::scope(|scope| {
+            let handles: Vec<_> = (0..THREADS)
+                .map(|t| {
+                    let (graph, pairs) = (&graph, &pairs);
+                    scope.spawn(move || {
+                        let start = t * pairs.len() / THREADS;
+                        pairs[start..]
+                            .iter()
+                            .chain(&pairs[..start])
+                            .filter(|(from, to)| !graph.add_edge(*from, *to))
+                            .count()
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .sum::<usize>()
+        });
+        // exactly one thread saw each edge as new
+        assert_eq!(new, pairs.len());
+
+        let frozen = graph.freeze();
+        let mut serial = BitGraph::with_capacity(count + 10);
+        for i in 0..count {
+            serial.push_node(i as u64);
+        }
+        serial.add_edges(pairs.iter().copied());
+
+        assert_eq!(frozen, serial);
+        assert_eq!(frozen.capacity(), count + 10);
+        assert_eq!(frozen[count - 1], (count - 1) as u64);
+        for node in 0..count {
+            assert_eq!(
+                frozen.incoming_edges_of(node),
+                serial.incoming_edges_of(node)
+            );
+        }
+
+        // the frozen graph is an ordinary one
+        let mut frozen = frozen;
+        frozen.push_node(0);
+        frozen.add_edge(count, 0);
+        assert_eq!(frozen.incoming_edges_of(0).last(), Some(&count));
+        assert!(frozen.has_edge(count, 0));
+    }
+
+    #[test]
+    #[should_panic(expected = "SyncBitGraph is full, capacity 2")]
+    fn full_test() {
+        let mut graph = SyncBitGraph::with_nodes(2);
+        graph.push_node(0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn out_of_range_test() {
+        let graph = SyncBitGraph::with_capacity(4);
+        graph.add_edge(0, 0);
+    }
+}
@@ -0,0 +1,251 @@
+use crate::baseline::AdjGraph;
+use crate::bit::BitGraph;
+use crate::hash::HashGraph;
+use crate::list::ListGraph;
+use crate::{Graph, GraphError};
+use std::convert::TryFrom;
+
+/// Collects nodes and edges, then builds any of the graphs in one go with
+/// storage sized up front. Endpoints are only checked by the `build_`
+/// methods, which list every bad edge at once.
+///
+/// Edges added without a weight get `1`, `BitGraph` ignores weights.
+#[derive(Clone, Debug)]
+pub struct GraphBuilder<W = usize> {
+    nodes: Vec<u64>,
+    edges: Vec<(usize, usize, Option<W>)>,
+}
+
+impl<W> Default for GraphBuilder<W> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<W> GraphBuilder<W> {
+    pub fn new() -> Self {
+        Self {
+            nodes: Vec::new(),
+            edges: Vec::new(),
+        }
+    }
+
+    /// `count` nodes, each valued with its own index
+    pub fn nodes(mut self, count: usize) -> Self {
+        self.nodes = (0..count as u64).collect();
+        self
+    }
+
+    /// one node per value, in order
+    pub fn node_values<I: IntoIterator<Item = u64>>(mut self, values: I) -> Self {
+        self.nodes = values.into_iter().collect();
+        self
+    }
+
+    pub fn edge(self, from: usize, to: usize) -> Self {
+        self.edge_opt(from, to, None)
+    }
+
+    pub fn weighted_edge(self, from: usize, to: usize, weight: W) -> Self {
+        self.edge_opt(from, to, Some(weight))
+    }
+
+    /// an edge in each direction, one for a self loop
+    pub fn undirected_edge(self, from: usize, to: usize) -> Self {
+        let builder = self.edge(from, to);
+        if from == to {
+            builder
+        } else {
+            builder.edge(to, from)
+        }
+    }
+
+    /// shared by `edge` and `weighted_edge`
+    fn edge_opt(mut self, from: usize, to: usize, weight: Option<W>) -> Self {
+        self.edges.push((from, to, weight));
+        self
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn build_bit(self) -> Result<BitGraph, GraphError> {
+        self.validate()?;
+
+        let mut graph = BitGraph::with_capacity(self.nodes.len());
+        for value in self.nodes {
+            graph.push_node(value);
+        }
+        graph.add_edges(self.edges.into_iter().map(|(from, to, _)| (from, to)));
+
+        Ok(graph)
+    }
+
+    /// `Ok` unless an edge has an end past the last node
+    fn validate(&self) -> Result<(), GraphError> {
+        let count = self.nodes.len();
+        let edges: Vec<(usize, usize)> = self
+            .edges
+            .iter()
+            .map(|(from, to, _)| (*from, *to))
+            .filter(|(from, to)| *from >= count || *to >= count)
+            .collect();
+
+        if edges.is_empty() {
+            Ok(())
+        } else {
+            Err(GraphError::InvalidEdges { edges, count })
+        }
+    }
+
+    /// every node and edge pushed into `graph`, which has to be empty
+    fn fill<G: Graph<u64, W>>(self, mut graph: G) -> Result<G, GraphError>
+    where
+        W: From<u8>,
+    {
+        self.validate()?;
+
+        for value in self.nodes {
+            graph.push_node(value);
+        }
+        for (from, to, weight) in self.edges {
+            graph.set_edge((from, to), weight.unwrap_or_else(|| W::from(1)));
+        }
+
+        Ok(graph)
+    }
+}
+
+impl<W: Clone + From<u8>> GraphBuilder<W> {
+    pub fn build_hash(self) -> Result<HashGraph<u64, W>, GraphError> {
+        let size = self.nodes.len().max(self.edges.len());
+        self.fill(HashGraph::with_default_weight(size, W::from(1)))
+    }
+
+    pub fn build_list(self) -> Result<ListGraph<u64, W>, GraphError> {
+        let size = self.nodes.len();
+        self.fill(ListGraph::with_default_weight(size, W::from(1)))
+    }
+}
+
+impl<W> GraphBuilder<W>
+where
+    u8: TryFrom<W>,
+{
+    /// Weights too big for a `u8` become `u8::MAX`, and a weight of 0 is
+    /// no edge at all in an `AdjGraph`.
+    pub fn build_adj(self) -> Result<AdjGraph, GraphError> {
+        self.validate()?;
+
+        let mut graph = AdjGraph::with_capacity(self.nodes.len());
+        for value in self.nodes {
+            graph.push_node(value);
+        }
+        for (from, to, weight) in self.edges {
+            let weight = weight.map_or(1, |weight| u8::try_from(weight).unwrap_or(u8::MAX));
+            graph.set_edge((from, to), weight);
+        }
+
+        Ok(graph)
+    }
+}
+
+/// Shorthand for a `GraphBuilder` with `usize` weights, `a -> b` is an edge
+/// and `a -> b(w)` one with weight `w`. Finish it with one of the `build_`
+/// methods, e.g. `graph! { nodes: 3, edges: [0 -> 1, 1 -> 2(5)] }`.
+#[macro_export]
+macro_rules! graph {
+    { nodes: $count:expr $(, edges: [$($edges:tt)*])? $(,)? } => {
+        $crate::graph!(@edges <$crate::GraphBuilder>::new().nodes($count); $($($edges)*)?)
+    };
+    // the edges are taken one at a time, weighted ones first
+    (@edges $builder:expr; $from:tt -> $to:tt ($weight:expr) $(, $($rest:tt)*)?) => {
+        $crate::graph!(@edges $builder.weighted_edge($from, $to, $weight); $($($rest)*)?)
+    };
+    (@edges $builder:expr; $from:tt -> $to:tt $(, $($rest:tt)*)?) => {
+        $crate::graph!(@edges $builder.edge($from, $to); $($($rest)*)?)
+    };
+    (@edges $builder:expr;) => {
+        $builder
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_test() {
+        let builder = GraphBuilder::new()
+            .node_values(vec![10, 20, 30, 40])
+            .edge(0, 1)
+            .weighted_edge(1, 2, 7)
+            .undirected_edge(2, 3)
+            .undirected_edge(3, 3);
+
+        let bit = builder.clone().build_bit().unwrap();
+        let hash = builder.clone().build_hash().unwrap();
+        let adj = builder.clone().build_adj().unwrap();
+        let list = builder.build_list().unwrap();
+
+        let edges = vec![(0, 1), (1, 2), (2, 3), (3, 2), (3, 3)];
+        assert_eq!(bit.all_edge_pairs(), edges);
+        assert_eq!(hash.all_edge_pairs(), edges);
+        assert_eq!(adj.all_edge_pairs(), edges);
+        assert_eq!(list.all_edge_pairs(), edges);
+
+        assert_eq!(*bit.get_node(2), 30);
+        assert_eq!(hash.get_edge(1, 2).unwrap().weight, 7);
+        assert_eq!(hash.get_edge(0, 1).unwrap().weight, 1);
+        assert_eq!(adj.weight_of(1, 2), Some(7));
+        assert_eq!(list.get_edge(3, 2).unwrap().weight, 1);
+    }
+
+    #[test]
+    fn invalid_edges_test() {
+        let error = GraphBuilder::<usize>::new()
+            .nodes(3)
+            .edge(0, 3)
+            .edge(1, 2)
+            .edge(7, 0)
+            .build_bit()
+            .unwrap_err();
+
+        assert_eq!(
+            error,
+            GraphError::InvalidEdges {
+                edges: vec![(0, 3), (7, 0)],
+                count: 3
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "edges [(0, 3), (7, 0)] out of bounds for 3 nodes"
+        );
+    }
+
+    #[test]
+    fn macro_test() {
+        let graph = graph! { nodes: 5, edges: [0 -> 1, 1 -> 2(3), 4 -> 4,] }
+            .build_hash()
+            .unwrap();
+
+        assert_eq!(graph.node_count(), 5);
+        assert_eq!(*graph.get_node(4), 4);
+        assert_eq!(graph.all_edge_pairs(), vec![(0, 1), (1, 2), (4, 4)]);
+        assert_eq!(graph.get_edge(1, 2).unwrap().weight, 3);
+
+        let weight = 2;
+        let last = graph! { nodes: 2, edges: [0 -> 1(weight + 3)] }
+            .build_hash()
+            .unwrap();
+        assert_eq!(last.get_edge(0, 1).unwrap().weight, 5);
+
+        let empty = graph! { nodes: 3 }.build_adj().unwrap();
+        assert!(empty.all_edge_pairs().is_empty());
+
+        let bad = graph! { nodes: 2, edges: [0 -> 2] }.build_bit();
+        assert!(bad.is_err());
+    }
+}
@@ -0,0 +1,207 @@
+use crate::Graph;
+use std::fmt::Display;
+
+/// knobs for `to_dot_with`
+#[derive(Clone, Copy, Debug)]
+pub struct DotOptions {
+    /// emit a `digraph` with `->` edges, otherwise a `graph` with `--` edges
+    /// where a pair connected both ways is written once
+    pub directed: bool,
+    /// label nodes with `get_node`, turn off for implementations that do not
+    /// store node values and fall back to indices
+    pub labels: bool,
+    /// label edges with the weight from `get_edge`
+    pub weights: bool,
+    /// also write nodes that have no edges at all
+    pub include_isolated: bool,
+}
+
+impl Default for DotOptions {
+    fn default() -> Self {
+        Self {
+            directed: true,
+            labels: true,
+            weights: false,
+            include_isolated: false,
+        }
+    }
+}
+
+/// Writes the graph in Graphviz DOT format with node labels from `get_node`,
+/// nodes without edges are skipped.
+pub fn to_dot<T: Display, W: Display>(graph: &dyn Graph<T, W>, directed: bool) -> String {
+    to_dot_with(
+        graph,
+        &DotOptions {
+            directed,
+            ..DotOptions::default()
+        },
+    )
+}
+
+/// `to_dot` with every option exposed
+pub fn to_dot_with<T: Display, W: Display>(
+    graph: &dyn Graph<T, W>,
+    options: &DotOptions,
+) -> String {
+    let (keyword, arrow) = if options.directed {
+        ("digraph", "->")
+    } else {
+        ("graph", "--")
+    };
+
+    let mut out = format!("{} {{\n", keyword);
+
+    for node in 0..graph.node_count() {
+        if !options.include_isolated
+            && graph.outgoing_edges_of(node).is_empty()
+            && graph.incoming_edges_of(node).is_empty()
+        {
+            continue;
+        }
+
+        if options.labels {
+            out.push_str(&format!(
+                "    {} [label=\"{}\"];\n",
+                node,
+                escape(&graph.get_node(node).to_string())
+            ));
+        } else {
+            out.push_str(&format!("    {};\n", node));
+        }
+    }
+
+    for (from, to) in graph.all_edge_pairs() {
+        // the reverse pair gets written from the smaller index
+        if !options.directed && from > to && graph.has_edge(to, from) {
+            continue;
+        }
+
+        out.push_str(&format!("    {} {} {}", from, arrow, to));
+        if options.weights {
+            if let Some(edge) = graph.get_edge(from, to) {
+                out.push_str(&format!(
+                    " [label=\"{}\"]",
+                    escape(&edge.weight.to_string())
+                ));
+            }
+        }
+        out.push_str(";\n");
+    }
+
+    out.push_str("}\n");
+    out
+}
+
+/// escapes a string for use inside a quoted DOT id
+fn escape(label: &str) -> String {
+    let mut out = String::with_capacity(label.len());
+    for c in label.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::baseline::AdjGraph;
+    use crate::bit::BitGraph;
+    use crate::hash::HashGraph;
+
+    #[test]
+    fn bit_graph_test() {
+        let mut graph = BitGraph::new();
+
+        for i in 0..6 {
+            graph.push_node(i * 10);
+        }
+
+        graph.add_edge(0, 1);
+        graph.add_edge(1, 0);
+        graph.add_edge(1, 2);
+        graph.add_edge(4, 4);
+
+        let dot = to_dot(&graph, true);
+        assert!(dot.starts_with("digraph {\n"));
+        assert!(dot.ends_with("}\n"));
+        assert!(dot.contains("    0 [label=\"0\"];\n"));
+        assert!(dot.contains("    2 [label=\"20\"];\n"));
+        assert!(dot.contains("    0 -> 1;\n"));
+        assert!(dot.contains("    1 -> 0;\n"));
+        assert!(dot.contains("    1 -> 2;\n"));
+        assert!(dot.contains("    4 -> 4;\n"));
+        // 3 and 5 have no edges
+        assert!(!dot.contains("    3 "));
+        assert!(!dot.contains("    5 "));
+
+        let dot = to_dot(&graph, false);
+        assert!(dot.starts_with("graph {\n"));
+        assert!(dot.contains("    0 -- 1;\n"));
+        assert!(!dot.contains("    1 -- 0;\n"));
+        assert!(dot.contains("    1 -- 2;\n"));
+
+        let dot = to_dot_with(
+            &graph,
+            &DotOptions {
+                include_isolated: true,
+                ..DotOptions::default()
+            },
+        );
+        assert!(dot.contains("    3 [label=\"30\"];\n"));
+        assert!(dot.contains("    5 [label=\"50\"];\n"));
+    }
+
+    #[test]
+    fn hash_graph_weights_test() {
+        let mut graph = HashGraph::new();
+
+        for i in 0..4 {
+            graph.push_node(i);
+        }
+
+        graph.add_edge(0, 1);
+        graph.set_edge((2, 3), 7);
+
+        let dot = to_dot_with(
+            &graph,
+            &DotOptions {
+                weights: true,
+                ..DotOptions::default()
+            },
+        );
+        assert!(dot.contains("    0 -> 1 [label=\"1\"];\n"));
+        assert!(dot.contains("    2 -> 3 [label=\"7\"];\n"));
+        assert!(dot.contains("    3 [label=\"3\"];\n"));
+    }
+
+    #[test]
+    fn index_labels_test() {
+        let mut graph = AdjGraph::new();
+
+        for i in 0..4 {
+            graph.push_node(i);
+        }
+
+        graph.add_edge(3, 1);
+
+        let dot = to_dot_with(
+            &graph,
+            &DotOptions {
+                labels: false,
+                ..DotOptions::default()
+            },
+        );
+        assert_eq!(dot, "digraph {\n    1;\n    3;\n    3 -> 1;\n}\n");
+    }
+
+    #[test]
+    fn escape_test() {
+        assert_eq!(escape("a \"b\" \\ c\nd"), "a \\\"b\\\" \\\\ c\\nd");
+    }
+}
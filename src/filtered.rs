@@ -0,0 +1,240 @@
+use crate::{EdgeMeta, Graph};
+
+/// predicate that keeps every edge, for `FilteredGraph::keep_nodes`
+pub type AllEdges = fn(usize, usize) -> bool;
+/// predicate that keeps every node, for `FilteredGraph::keep_edges`
+pub type AllNodes = fn(usize) -> bool;
+
+/// Read only view of a graph that hides the nodes `node_filter` returns
+/// `false` for, along with their edges, and the edges `edge_filter` returns
+/// `false` for. Indices and node values are the same as in the wrapped
+/// graph, a hidden node is only cut off from the rest.
+///
+/// Every mutating `Graph` method panics.
+pub struct FilteredGraph<'a, G: ?Sized, NF, EF> {
+    graph: &'a G,
+    node_filter: NF,
+    edge_filter: EF,
+}
+
+impl<'a, G: ?Sized, NF, EF> FilteredGraph<'a, G, NF, EF>
+where
+    NF: Fn(usize) -> bool,
+    EF: Fn(usize, usize) -> bool,
+{
+    pub fn new(graph: &'a G, node_filter: NF, edge_filter: EF) -> Self {
+        Self {
+            graph,
+            node_filter,
+            edge_filter,
+        }
+    }
+
+    pub fn inner(&self) -> &'a G {
+        self.graph
+    }
+
+    /// true unless `node_index` is hidden
+    pub fn is_visible(&self, node_index: usize) -> bool {
+        (self.node_filter)(node_index)
+    }
+
+    fn keeps(&self, from: usize, to: usize) -> bool {
+        (self.node_filter)(from) && (self.node_filter)(to) && (self.edge_filter)(from, to)
+    }
+}
+
+impl<'a, G: ?Sized, NF: Fn(usize) -> bool> FilteredGraph<'a, G, NF, AllEdges> {
+    /// hides nodes only
+    pub fn keep_nodes(graph: &'a G, node_filter: NF) -> Self {
+        Self::new(graph, node_filter, |_, _| true)
+    }
+}
+
+impl<'a, G: ?Sized, EF: Fn(usize, usize) -> bool> FilteredGraph<'a, G, AllNodes, EF> {
+    /// hides edges only
+    pub fn keep_edges(graph: &'a G, edge_filter: EF) -> Self {
+        Self::new(graph, |_| true, edge_filter)
+    }
+}
+
+#[track_caller]
+fn read_only() -> ! {
+    panic!("a FilteredGraph can not be changed, change the graph it wraps")
+}
+
+impl<'a, T, W, G, NF, EF> Graph<T, W> for FilteredGraph<'a, G, NF, EF>
+where
+    G: Graph<T, W> + ?Sized,
+    W: 'a,
+    NF: Fn(usize) -> bool,
+    EF: Fn(usize, usize) -> bool,
+{
+    fn add_edge(&mut self, _from: usize, _to: usize) -> bool {
+        read_only()
+    }
+
+    fn set_edge(&mut self, _from_to: (usize, usize), _weight: W) -> bool {
+        read_only()
+    }
+
+    fn remove_edge(&mut self, _from: usize, _to: usize) -> bool {
+        read_only()
+    }
+
+    fn clear_edges_of(&mut self, _node_index: usize) {
+        read_only()
+    }
+
+    fn clear_edges(&mut self) {
+        read_only()
+    }
+
+    fn clear(&mut self) {
+        read_only()
+    }
+
+    fn has_edge(&self, from: usize, to: usize) -> bool {
+        self.keeps(from, to) && self.graph.has_edge(from, to)
+    }
+
+    fn get_edge(&self, from: usize, to: usize) -> Option<EdgeMeta<W>> {
+        if self.keeps(from, to) {
+            self.graph.get_edge(from, to)
+        } else {
+            None
+        }
+    }
+
+    /// empty for a hidden node
+    fn outgoing_edges_of(&self, node_index: usize) -> Vec<usize> {
+        let mut out = self.graph.outgoing_edges_of(node_index);
+        out.retain(|to| self.keeps(node_index, *to));
+        out
+    }
+
+    fn neighbors_sorted(&self, node_index: usize) -> Vec<usize> {
+        let mut out = self.graph.neighbors_sorted(node_index);
+        out.retain(|to| self.keeps(node_index, *to));
+        out
+    }
+
+    /// empty for a hidden node
+    fn incoming_edges_of(&self, node_index: usize) -> Vec<usize> {
+        let mut out = self.graph.incoming_edges_of(node_index);
+        out.retain(|from| self.keeps(*from, node_index));
+        out
+    }
+
+    fn edges(&self) -> Box<dyn Iterator<Item = EdgeMeta<W>> + '_> {
+        Box::new(
+            self.graph
+                .edges()
+                .filter(move |edge| self.keeps(edge.source, edge.destination)),
+        )
+    }
+
+    fn push_node(&mut self, _value: T) -> usize {
+        read_only()
+    }
+
+    fn set_node(&mut self, _node_index: usize, _value: T) {
+        read_only()
+    }
+
+    /// hidden nodes still have their value
+    fn get_node(&self, node_index: usize) -> &T {
+        self.graph.get_node(node_index)
+    }
+
+    fn remove_node(&mut self, _node_index: usize) -> T {
+        read_only()
+    }
+
+    /// same as the wrapped graph, hidden nodes included
+    fn node_count(&self) -> usize {
+        self.graph.node_count()
+    }
+
+    fn resize_nodes(&mut self, _count: usize, _fill: T)
+    where
+        T: Clone,
+    {
+        read_only()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::{grid_graph, index_of};
+    use crate::hash::HashGraph;
+    use crate::search::a_star::AStar;
+    use crate::search::bfs::BFS;
+    use crate::search::dfs::DFS;
+    use crate::search::Pathfinder;
+
+    #[test]
+    fn masked_bfs_test() {
+        let grid = grid_graph(5, 5, false);
+        let blocked = [index_of(1, 0, 5), index_of(1, 1, 5), index_of(1, 2, 5)];
+        let view = FilteredGraph::keep_nodes(&grid, |node| !blocked.contains(&node));
+
+        let goal = index_of(2, 0, 5);
+        let path = BFS::new(&grid, 0).path_to(&grid, goal).unwrap();
+        assert_eq!(path.len(), 3);
+
+        // down and around the wall
+        let graph: &dyn Graph<u64, bool> = &view;
+        let path = BFS::new(graph, 0).path_to(graph, goal).unwrap();
+        assert_eq!(path.len(), 9);
+        assert!(path.iter().all(|node| view.is_visible(*node)));
+        assert!(path.windows(2).all(|pair| view.has_edge(pair[0], pair[1])));
+
+        let mut dfs = DFS::new(graph, 0);
+        assert!(dfs.path_to(graph, goal).is_some());
+
+        let mut astar = AStar::new(graph, 0, goal, |_| 0);
+        assert_eq!(astar.path_to(graph, goal).unwrap().len(), 9);
+
+        // a hidden node is cut off but keeps its index and value
+        assert!(BFS::new(graph, 0).path_to(graph, blocked[0]).is_none());
+        assert!(view.outgoing_edges_of(blocked[1]).is_empty());
+        assert_eq!(view.node_count(), 25);
+        assert_eq!(*view.get_node(blocked[2]), *grid.get_node(blocked[2]));
+    }
+
+    #[test]
+    fn masked_edges_test() {
+        let mut graph = HashGraph::new();
+        for i in 0..4 {
+            graph.push_node(i);
+        }
+        graph.set_edge((0, 1), 5);
+        graph.set_edge((1, 3), 5);
+        graph.set_edge((0, 2), 1);
+        graph.set_edge((2, 3), 1);
+
+        // pretend the cheap route is closed
+        let view = FilteredGraph::keep_edges(&graph, |from, to| (from, to) != (2, 3));
+        assert!(!view.has_edge(2, 3));
+        assert!(view.get_edge(2, 3).is_none());
+        assert_eq!(view.incoming_edges_of(3), vec![1]);
+        assert_eq!(view.all_edge_pairs().len(), 3);
+
+        let path = BFS::new(&view, 0).path_to(&view, 3).unwrap();
+        assert_eq!(path, vec![0, 1, 3]);
+
+        let both = FilteredGraph::new(&graph, |node| node != 1, |from, _| from != 2);
+        assert!(BFS::new(&both, 0).path_to(&both, 3).is_none());
+        assert_eq!(both.all_edge_pairs(), vec![(0, 2)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "can not be changed")]
+    fn read_only_test() {
+        let graph = HashGraph::new();
+        let mut view = FilteredGraph::keep_nodes(&graph, |_| true);
+        view.push_node(1);
+    }
+}
@@ -0,0 +1,150 @@
+use crate::bit::BitGraph;
+use crate::Graph;
+use rand::Rng;
+
+/// `n` nodes valued by their index and no edges
+fn empty(n: usize) -> BitGraph {
+    BitGraph::from_nodes(0..n as u64)
+}
+
+/// Erdős–Rényi `G(n, p)`, every directed pair `(i, j)` with `i != j` gets an
+/// edge independently with probability `p`.
+///
+/// Panics if `p` is not in `[0, 1]`.
+pub fn gnp<R: Rng>(n: usize, p: f64, rng: &mut R) -> BitGraph {
+    assert!((0.0..=1.0).contains(&p), "probability {} not in [0, 1]", p);
+
+    let mut graph = empty(n);
+    for from in 0..n {
+        for to in 0..n {
+            if from != to && rng.gen_bool(p) {
+                graph.add_edge(from, to);
+            }
+        }
+    }
+
+    graph
+}
+
+/// Erdős–Rényi `G(n, m)`, exactly `m` distinct directed edges without self
+/// loops chosen uniformly. When more than half of the possible edges are asked
+/// for the missing ones are sampled instead, so rejection never has to find
+/// the last few free pairs of a nearly full graph.
+///
+/// Panics if `m > n * (n - 1)`.
+pub fn gnm<R: Rng>(n: usize, m: usize, rng: &mut R) -> BitGraph {
+    let possible = n * n.saturating_sub(1);
+    assert!(m <= possible, "{} edges do not fit in {} nodes", m, n);
+
+    if m > possible / 2 {
+        return gnm(n, possible - m, rng).complement();
+    }
+
+    let mut graph = empty(n);
+    let mut added = 0;
+    while added < m {
+        let from = rng.gen_range(0..n);
+        let to = rng.gen_range(0..n);
+
+        if from != to && !graph.has_edge(from, to) {
+            graph.add_edge(from, to);
+            added += 1;
+        }
+    }
+
+    graph
+}
+
+/// Barabási–Albert style preferential attachment. Nodes are added one at a
+/// time and each links to `m_per_node` distinct earlier nodes, or all of them
+/// while there are fewer, picked with probability proportional to their in
+/// degree plus one so fresh nodes can still be chosen.
+pub fn preferential<R: Rng>(n: usize, m_per_node: usize, rng: &mut R) -> BitGraph {
+    let mut graph = empty(n);
+
+    // every node appears in_degree + 1 times
+    let mut weighted: Vec<usize> = Vec::with_capacity(n * (m_per_node + 1));
+    let mut targets = Vec::with_capacity(m_per_node);
+
+    for node in 0..n {
+        targets.clear();
+        while targets.len() < m_per_node.min(node) {
+            let target = weighted[rng.gen_range(0..weighted.len())];
+            if !targets.contains(&target) {
+                targets.push(target);
+            }
+        }
+
+        for &target in &targets {
+            graph.add_edge(node, target);
+            weighted.push(target);
+        }
+        weighted.push(node);
+    }
+
+    graph
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn gnp_test() {
+        let a = gnp(50, 0.2, &mut StdRng::seed_from_u64(7));
+        let b = gnp(50, 0.2, &mut StdRng::seed_from_u64(7));
+        assert_eq!(a, b);
+        assert!((0..50).all(|i| !a.has_edge(i, i)));
+
+        let empty = gnp(10, 0.0, &mut StdRng::seed_from_u64(7));
+        assert!(empty.all_edge_pairs().is_empty());
+
+        let full = gnp(10, 1.0, &mut StdRng::seed_from_u64(7));
+        assert_eq!(full.all_edge_pairs().len(), 90);
+    }
+
+    #[test]
+    fn gnm_test() {
+        let a = gnm(30, 100, &mut StdRng::seed_from_u64(11));
+        let b = gnm(30, 100, &mut StdRng::seed_from_u64(11));
+        assert_eq!(a, b);
+        assert_eq!(a.all_edge_pairs().len(), 100);
+        assert!((0..30).all(|i| !a.has_edge(i, i)));
+    }
+
+    #[test]
+    fn gnm_dense_test() {
+        let mut rng = StdRng::seed_from_u64(3);
+
+        for &m in &[0, 1, 45, 88, 89, 90] {
+            let graph = gnm(10, m, &mut rng);
+            assert_eq!(graph.all_edge_pairs().len(), m);
+            assert!((0..10).all(|i| !graph.has_edge(i, i)));
+        }
+
+        assert_eq!(gnm(1, 0, &mut rng).node_count(), 1);
+        assert_eq!(gnm(0, 0, &mut rng).node_count(), 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn gnm_too_many_test() {
+        gnm(4, 13, &mut StdRng::seed_from_u64(0));
+    }
+
+    #[test]
+    fn preferential_test() {
+        let a = preferential(100, 3, &mut StdRng::seed_from_u64(5));
+        let b = preferential(100, 3, &mut StdRng::seed_from_u64(5));
+        assert_eq!(a, b);
+
+        // 0 + 1 + 2 edges for the first three nodes then 3 each
+        assert_eq!(a.all_edge_pairs().len(), 3 + 97 * 3);
+        for node in 0..100 {
+            assert_eq!(a.outgoing_edges_of(node).len(), node.min(3));
+            assert!(a.outgoing_edges_of(node).iter().all(|&to| to < node));
+        }
+    }
+}
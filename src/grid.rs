@@ -0,0 +1,149 @@
+use crate::bit::BitGraph;
+use crate::Graph;
+
+/// Index of the cell at `(x, y)`, row major so it matches the convention the
+/// manhattan heuristic in `search::a_star` uses with `dim == width`.
+#[inline]
+pub fn index_of(x: usize, y: usize, width: usize) -> usize {
+    y * width + x
+}
+
+/// `(x, y)` of the cell at `idx`
+#[inline]
+pub fn coords(idx: usize, width: usize) -> (usize, usize) {
+    (idx % width, idx / width)
+}
+
+/// Builds a `width * height` grid where every cell is connected both ways to
+/// its 4 neighbours, or 8 with `diagonal`. Node values are the cell indices.
+pub fn grid_graph(width: usize, height: usize, diagonal: bool) -> BitGraph {
+    build(width, height, diagonal, &[])
+}
+
+/// 4 neighbour grid where cells in `blocked` get no edges at all, so they can
+/// never be entered or left.
+pub fn with_obstacles(width: usize, height: usize, blocked: &[usize]) -> BitGraph {
+    build(width, height, false, blocked)
+}
+
+fn build(width: usize, height: usize, diagonal: bool, blocked: &[usize]) -> BitGraph {
+    let count = width * height;
+
+    let mut is_blocked = vec![false; count];
+    for &idx in blocked {
+        is_blocked[idx] = true;
+    }
+
+    let mut graph = BitGraph::with_capacity(count);
+    for idx in 0..count {
+        graph.push_node(idx as u64);
+    }
+
+    for idx in 0..count {
+        if is_blocked[idx] {
+            continue;
+        }
+
+        let (x, y) = coords(idx, width);
+        for (dx, dy) in neighbourhood(diagonal) {
+            let nx = x as isize + dx;
+            let ny = y as isize + dy;
+            if nx < 0 || ny < 0 || nx >= width as isize || ny >= height as isize {
+                continue;
+            }
+
+            let neighbour = index_of(nx as usize, ny as usize, width);
+            if !is_blocked[neighbour] {
+                graph.add_edge(idx, neighbour);
+            }
+        }
+    }
+
+    graph
+}
+
+/// offsets to the neighbouring cells
+fn neighbourhood(diagonal: bool) -> &'static [(isize, isize)] {
+    const ORTHOGONAL: [(isize, isize); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+    const ALL: [(isize, isize); 8] = [
+        (1, 0),
+        (-1, 0),
+        (0, 1),
+        (0, -1),
+        (1, 1),
+        (1, -1),
+        (-1, 1),
+        (-1, -1),
+    ];
+
+    if diagonal {
+        &ALL
+    } else {
+        &ORTHOGONAL
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::a_star::AStarMH;
+    use crate::search::Pathfinder;
+
+    #[test]
+    fn coords_test() {
+        assert_eq!(coords(0, 10), (0, 0));
+        assert_eq!(coords(13, 10), (3, 1));
+        assert_eq!(index_of(3, 1, 10), 13);
+        assert_eq!(coords(index_of(7, 4, 9), 9), (7, 4));
+    }
+
+    #[test]
+    fn grid_test() {
+        let graph = grid_graph(4, 3, false);
+
+        assert_eq!(graph.node_count(), 12);
+        // corners have 2 neighbours, edges 3 and the middle 4
+        assert_eq!(graph.outgoing_edges_of(0), vec![1, 4]);
+        assert_eq!(graph.outgoing_edges_of(1), vec![0, 2, 5]);
+        assert_eq!(graph.outgoing_edges_of(5), vec![1, 4, 6, 9]);
+        assert_eq!(graph.outgoing_edges_of(11), vec![7, 10]);
+        // no wrapping from the end of one row to the start of the next
+        assert!(!graph.has_edge(3, 4));
+        assert_eq!(graph.all_edge_pairs().len(), 2 * (3 * 3 + 4 * 2));
+
+        let graph = grid_graph(4, 3, true);
+        assert_eq!(graph.outgoing_edges_of(0), vec![1, 4, 5]);
+        assert_eq!(graph.outgoing_edges_of(5), vec![0, 1, 2, 4, 6, 8, 9, 10]);
+        assert!(!graph.has_edge(3, 4));
+    }
+
+    #[test]
+    fn obstacles_test() {
+        let graph = with_obstacles(3, 3, &[4]);
+
+        assert!(graph.outgoing_edges_of(4).is_empty());
+        assert!(graph.incoming_edges_of(4).is_empty());
+        assert_eq!(graph.outgoing_edges_of(1), vec![0, 2]);
+        assert_eq!(graph.outgoing_edges_of(0), vec![1, 3]);
+    }
+
+    #[test]
+    fn a_star_wall_test() {
+        // wall down x = 5 with a gap at the bottom row
+        let blocked: Vec<usize> = (0..9).map(|y| index_of(5, y, 10)).collect();
+        let graph = with_obstacles(10, 10, &blocked);
+
+        let goal = index_of(9, 0, 10);
+        let mut astar = AStarMH::new(&graph, 0, goal, 10);
+        let path = astar.path_to(&graph, goal).unwrap();
+
+        // down 9, across 9 and back up 9
+        assert_eq!(path.len(), 28);
+        assert_eq!(path[0], 0);
+        assert_eq!(path[27], goal);
+        assert!(path.contains(&index_of(5, 9, 10)));
+        for pair in path.windows(2) {
+            assert!(graph.has_edge(pair[0], pair[1]));
+        }
+    }
+}
@@ -0,0 +1,174 @@
+use super::{
+    pair_hash, HashGraph, PairHashTable, PairHasher, DEFAULT_CAPACITY, GROW_FACTOR, MAX_LOAD,
+};
+
+/// Sets up a `HashGraph` with a custom edge table, from `HashGraph::builder`.
+pub struct HashGraphBuilder<W = usize> {
+    capacity: usize,
+    default_weight: W,
+
+    hasher: PairHasher,
+    max_load: f32,
+    grow_factor: usize,
+}
+
+impl HashGraph {
+    /// builder starting from the same settings as `HashGraph::new`
+    pub fn builder() -> HashGraphBuilder {
+        HashGraphBuilder {
+            capacity: DEFAULT_CAPACITY,
+            default_weight: 1,
+
+            hasher: pair_hash,
+            max_load: MAX_LOAD,
+            grow_factor: GROW_FACTOR,
+        }
+    }
+}
+
+impl<W: Clone> HashGraphBuilder<W> {
+    /// room for this many nodes and edges before anything grows
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    /// hash for the edge table, `pair_hash` by default
+    pub fn hasher(mut self, hasher: PairHasher) -> Self {
+        self.hasher = hasher;
+        self
+    }
+
+    /// see `PairHashTable::set_load_limits`
+    pub fn max_load(mut self, max_load: f32) -> Self {
+        self.max_load = max_load;
+        self
+    }
+
+    /// see `PairHashTable::set_load_limits`
+    pub fn grow_factor(mut self, grow_factor: usize) -> Self {
+        self.grow_factor = grow_factor;
+        self
+    }
+
+    /// the weight `add_edge` gives, which also picks the weight type
+    pub fn default_weight<V: Clone>(self, default_weight: V) -> HashGraphBuilder<V> {
+        HashGraphBuilder {
+            capacity: self.capacity,
+            default_weight,
+
+            hasher: self.hasher,
+            max_load: self.max_load,
+            grow_factor: self.grow_factor,
+        }
+    }
+
+    /// Panics if the load limits are out of range, see
+    /// `PairHashTable::set_load_limits`.
+    pub fn build(self) -> HashGraph<u64, W> {
+        let mut edges = PairHashTable::with_hasher(self.capacity, self.hasher);
+        edges.set_load_limits(self.max_load, self.grow_factor);
+
+        let mut graph = HashGraph::with_default_weight(self.capacity, self.default_weight);
+        graph.edges = edges;
+        graph
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Graph;
+
+    /// every key lands in the same slot, so every lookup is a full scan
+    fn same_slot(_: (usize, usize)) -> usize {
+        7
+    }
+
+    #[test]
+    fn identity_hasher_test() {
+        let mut graph = HashGraph::builder()
+            .capacity(4)
+            .hasher(|(from, to)| from * 1000 + to)
+            .build();
+        for i in 0..40 {
+            graph.push_node(i);
+        }
+
+        for from in 0..40 {
+            for to in (0..40).step_by(from % 5 + 1) {
+                graph.set_edge((from, to), from + to);
+            }
+        }
+        for from in (0..40).step_by(3) {
+            graph.remove_edge(from, 0);
+        }
+
+        for from in 0..40 {
+            for to in 0..40 {
+                let expected = to % (from % 5 + 1) == 0 && !(to == 0 && from % 3 == 0);
+                assert_eq!(graph.has_edge(from, to), expected, "{} -> {}", from, to);
+            }
+        }
+        assert_eq!(graph.get_edge(39, 35).unwrap().weight, 74);
+    }
+
+    #[test]
+    fn pathological_hasher_test() {
+        let mut table = PairHashTable::with_hasher(8, same_slot);
+        for i in 0..100 {
+            assert_eq!(table.insert((i, i), i), None);
+        }
+        for i in (0..100).step_by(2) {
+            assert!(table.remove((i, i)).is_some());
+        }
+
+        assert_eq!(table.len(), 50);
+        assert!((0..100).all(|i| table.get((i, i)).is_some() == (i % 2 == 1)));
+        assert_eq!(table.insert((3, 3), 0), Some(3));
+        // one cluster, so an insert can walk past every other key
+        assert!(table.max_probe_length() >= 50);
+    }
+
+    #[test]
+    fn settings_test() {
+        let mut graph = HashGraph::builder()
+            .capacity(10)
+            .max_load(0.5)
+            .grow_factor(4)
+            .default_weight(2.5f64)
+            .build();
+
+        let table = graph.edge_table();
+        assert_eq!(table.capacity(), 10);
+        assert_eq!(table.max_load(), 0.5);
+        assert_eq!(table.grow_factor(), 4);
+        assert_eq!(table.load_factor(), 0.0);
+
+        for i in 0..6 {
+            graph.push_node(i);
+        }
+        for i in 0..5 {
+            graph.add_edge(i, i + 1);
+        }
+
+        // the sixth edge is past half full, so 10 slots become 40
+        let table = graph.edge_table();
+        assert_eq!(table.len(), 5);
+        assert_eq!(table.capacity(), 10);
+        graph.add_edge(5, 0);
+        assert_eq!(graph.edge_table().capacity(), 40);
+        assert_eq!(graph.edge_table().load_factor(), 6.0 / 40.0);
+        assert_eq!(graph.get_edge(5, 0).unwrap().weight, 2.5);
+
+        let defaults = HashGraph::builder().build();
+        assert_eq!(defaults.edge_table().capacity(), DEFAULT_CAPACITY);
+        assert_eq!(defaults.edge_table().max_load(), MAX_LOAD);
+    }
+
+    #[test]
+    #[should_panic(expected = "max_load")]
+    fn bad_load_test() {
+        HashGraph::builder().max_load(1.5).build();
+    }
+}
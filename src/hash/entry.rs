@@ -0,0 +1,249 @@
+use super::{Entry, HashGraph, HashIndex};
+use crate::assert_node;
+
+/// A view into one `(from, to)` slot of a `HashGraph`, from `edge_entry`.
+pub enum EdgeEntry<'a, T, W, Ix = usize> {
+    Occupied(OccupiedEdge<'a, T, W, Ix>),
+    Vacant(VacantEdge<'a, T, W, Ix>),
+}
+
+/// an edge that is in the graph
+pub struct OccupiedEdge<'a, T, W, Ix = usize> {
+    graph: &'a mut HashGraph<T, W, Ix>,
+    /// slot of the edge in the table
+    index: usize,
+}
+
+/// an edge that is not in the graph yet
+pub struct VacantEdge<'a, T, W, Ix = usize> {
+    graph: &'a mut HashGraph<T, W, Ix>,
+    key: (usize, usize),
+}
+
+impl<T, W, Ix: HashIndex> HashGraph<T, W, Ix> {
+    /// The edge from `from` to `to` for reading, changing or adding it in
+    /// place with a single lookup. Panics if either end is not a node.
+    pub fn edge_entry(&mut self, from: usize, to: usize) -> EdgeEntry<'_, T, W, Ix> {
+        assert_node(from, self.count);
+        assert_node(to, self.count);
+        match self.edges.index_of((from, to)) {
+            Some(index) => EdgeEntry::Occupied(OccupiedEdge { graph: self, index }),
+            None => EdgeEntry::Vacant(VacantEdge {
+                graph: self,
+                key: (from, to),
+            }),
+        }
+    }
+
+    /// Runs `f` on the weight of the edge from `from` to `to`, returns
+    /// `false` without calling it if there is no such edge.
+    pub fn update_edge<F: FnOnce(&mut W)>(&mut self, from: usize, to: usize, f: F) -> bool {
+        match self.edge_entry(from, to) {
+            EdgeEntry::Occupied(mut edge) => {
+                f(edge.get_mut());
+                true
+            }
+            EdgeEntry::Vacant(_) => false,
+        }
+    }
+
+    /// weight of the edge from `from` to `to` without cloning it
+    pub fn weight_of(&self, from: usize, to: usize) -> Option<&W> {
+        self.edges.get((from, to)).map(|edge| edge.weight)
+    }
+
+    /// the weight of the edge from `from` to `to` to change in place
+    pub fn weight_of_mut(&mut self, from: usize, to: usize) -> Option<&mut W> {
+        match self.edge_entry(from, to) {
+            EdgeEntry::Occupied(edge) => Some(edge.into_mut()),
+            EdgeEntry::Vacant(_) => None,
+        }
+    }
+}
+
+impl<'a, T, W, Ix: HashIndex> EdgeEntry<'a, T, W, Ix> {
+    /// `(from, to)` of the edge
+    pub fn key(&self) -> (usize, usize) {
+        match self {
+            EdgeEntry::Occupied(edge) => edge.key(),
+            EdgeEntry::Vacant(edge) => edge.key,
+        }
+    }
+
+    /// runs `f` on the weight if the edge is there
+    pub fn and_modify<F: FnOnce(&mut W)>(mut self, f: F) -> Self {
+        if let EdgeEntry::Occupied(edge) = &mut self {
+            f(edge.get_mut());
+        }
+
+        self
+    }
+
+    /// adds the edge with `weight` if it is not there
+    pub fn or_insert(self, weight: W) -> &'a mut W {
+        self.or_insert_with(|| weight)
+    }
+
+    pub fn or_insert_with<F: FnOnce() -> W>(self, f: F) -> &'a mut W {
+        match self {
+            EdgeEntry::Occupied(edge) => edge.into_mut(),
+            EdgeEntry::Vacant(edge) => edge.insert(f()),
+        }
+    }
+}
+
+impl<'a, T, W: Clone, Ix: HashIndex> EdgeEntry<'a, T, W, Ix> {
+    /// adds the edge with the graph's default weight if it is not there
+    pub fn or_default_weight(self) -> &'a mut W {
+        match self {
+            EdgeEntry::Occupied(edge) => edge.into_mut(),
+            EdgeEntry::Vacant(edge) => {
+                let weight = edge.graph.default_weight.clone();
+                edge.insert(weight)
+            }
+        }
+    }
+}
+
+impl<'a, T, W, Ix: HashIndex> OccupiedEdge<'a, T, W, Ix> {
+    pub fn key(&self) -> (usize, usize) {
+        self.slot().key_pair()
+    }
+
+    pub fn get(&self) -> &W {
+        &self.slot().weight
+    }
+
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.slot_mut().weight
+    }
+
+    /// the weight borrowed for as long as the graph was
+    pub fn into_mut(self) -> &'a mut W {
+        let entry = self.graph.edges.table[self.index].live_mut();
+        // the slot was live when the entry was made and nothing else could
+        // touch the table since
+        &mut entry.expect("occupied slot").weight
+    }
+
+    /// sets the weight, returns the old one
+    pub fn insert(&mut self, weight: W) -> W {
+        std::mem::replace(self.get_mut(), weight)
+    }
+
+    /// takes the edge out of the graph, returns its weight
+    pub fn remove(self) -> W {
+        let (from, to) = self.key();
+        self.graph.take_edge(from, to).expect("occupied edge")
+    }
+
+    fn slot(&self) -> &Entry<W, Ix> {
+        let entry = self.graph.edges.table[self.index].live();
+        entry.expect("occupied slot")
+    }
+
+    fn slot_mut(&mut self) -> &mut Entry<W, Ix> {
+        let entry = self.graph.edges.table[self.index].live_mut();
+        entry.expect("occupied slot")
+    }
+}
+
+impl<'a, T, W, Ix: HashIndex> VacantEdge<'a, T, W, Ix> {
+    pub fn key(&self) -> (usize, usize) {
+        self.key
+    }
+
+    /// adds the edge with `weight`
+    pub fn insert(self, weight: W) -> &'a mut W {
+        let key = self.key;
+        self.graph.insert_edge(key, weight);
+
+        // insert_edge may have grown the table, so look the slot up again
+        let index = self.graph.edges.index_of(key).expect("edge just added");
+        let entry = self.graph.edges.table[index].live_mut();
+        &mut entry.expect("edge just added").weight
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Graph;
+
+    fn graph() -> HashGraph {
+        let mut graph = HashGraph::with_capacity(8);
+        for i in 0..8 {
+            graph.push_node(i);
+        }
+        graph
+    }
+
+    #[test]
+    fn increment_test() {
+        let mut graph = graph();
+        graph.add_edge(2, 3);
+
+        for _ in 0..3 {
+            graph.edge_entry(2, 3).and_modify(|weight| *weight += 1);
+        }
+        assert_eq!(graph.get_edge(2, 3).unwrap().weight, 4);
+
+        assert!(graph.update_edge(2, 3, |weight| *weight *= 10));
+        assert!(!graph.update_edge(3, 2, |weight| *weight *= 10));
+        assert_eq!(graph.get_edge(2, 3).unwrap().weight, 40);
+        assert!(!graph.has_edge(3, 2));
+    }
+
+    #[test]
+    fn weight_of_test() {
+        let mut graph = graph();
+        graph.set_edge((1, 6), 5);
+
+        assert_eq!(graph.weight_of(1, 6), Some(&5));
+        assert_eq!(graph.weight_of(6, 1), None);
+
+        *graph.weight_of_mut(1, 6).unwrap() += 2;
+        assert_eq!(graph.get_edge(1, 6).unwrap().weight, 7);
+        assert!(graph.weight_of_mut(6, 1).is_none());
+        assert!(!graph.has_edge(6, 1));
+    }
+
+    #[test]
+    fn or_insert_test() {
+        let mut graph = graph();
+
+        // counting edges as they stream in
+        for &(from, to) in &[(0, 1), (1, 2), (0, 1), (0, 1), (7, 7)] {
+            *graph.edge_entry(from, to).or_insert(0) += 1;
+        }
+
+        assert_eq!(graph.get_edge(0, 1).unwrap().weight, 3);
+        assert_eq!(graph.get_edge(1, 2).unwrap().weight, 1);
+        assert_eq!(graph.outgoing_edges_of(0), vec![1]);
+        assert_eq!(graph.incoming_edges_of(7), vec![7]);
+
+        assert_eq!(*graph.edge_entry(4, 5).or_default_weight(), 1);
+        assert_eq!(graph.edge_entry(4, 5).key(), (4, 5));
+        assert!(graph.has_edge(4, 5));
+    }
+
+    #[test]
+    fn occupied_test() {
+        let mut graph = graph();
+        graph.set_edge((5, 6), 9);
+
+        match graph.edge_entry(5, 6) {
+            EdgeEntry::Occupied(mut edge) => {
+                assert_eq!(*edge.get(), 9);
+                assert_eq!(edge.insert(11), 9);
+                assert_eq!(edge.key(), (5, 6));
+                assert_eq!(edge.remove(), 11);
+            }
+            EdgeEntry::Vacant(_) => panic!("edge is there"),
+        }
+
+        assert!(!graph.has_edge(5, 6));
+        assert!(graph.outgoing_edges_of(5).is_empty());
+        assert!(matches!(graph.edge_entry(5, 6), EdgeEntry::Vacant(_)));
+    }
+}
@@ -1,29 +1,146 @@
-use crate::{EdgeMeta, Graph};
+use crate::integrity::{check_count, into_result, IntegrityError};
+use crate::memory::{nested_vec_bytes, vec_bytes, MemoryBreakdown};
+use crate::{assert_node, cover_nodes, EdgeMeta, Graph, GraphError};
+use std::convert::TryFrom;
+use std::iter::FromIterator;
 use std::num::Wrapping;
 
-const DEFAULT_CAPACITY: usize = 256;
+mod builder;
+mod entry;
+
+pub use builder::HashGraphBuilder;
+pub use entry::{EdgeEntry, OccupiedEdge, VacantEdge};
+
+pub(crate) const DEFAULT_CAPACITY: usize = 256;
 const MAX_LOAD: f32 = 0.75;
 const GROW_FACTOR: usize = 2;
 const PRIME_OF_MATHS: Wrapping<usize> = Wrapping(97);
 
-pub struct PairHashTable {
+/// Integer type a `PairHashTable` keeps node indices in. `usize` is the
+/// default, `u32` takes 8 bytes off every slot on 64 bit targets in return
+/// for a limit on the indices it can hold.
+pub trait HashIndex: Copy + Eq {
+    /// largest index that fits
+    const MAX: usize;
+
+    /// `None` if `index` is past `MAX`
+    fn from_index(index: usize) -> Option<Self>;
+
+    fn index(self) -> usize;
+}
+
+macro_rules! hash_index {
+    ($($ty:ty),*) => {$(
+        impl HashIndex for $ty {
+            const MAX: usize = <$ty>::MAX as usize;
+
+            #[inline(always)]
+            fn from_index(index: usize) -> Option<Self> {
+                <$ty>::try_from(index).ok()
+            }
+
+            #[inline(always)]
+            fn index(self) -> usize {
+                self as usize
+            }
+        }
+    )*};
+}
+
+hash_index!(usize, u32, u16);
+
+/// `PairHashTable` with `u32` keys
+pub type CompactPairHashTable<W = usize> = PairHashTable<W, u32>;
+
+/// Open addressing table from `(source, destination)` to an edge of weight
+/// `W`, linear probing with tombstones for deletes. Both ends of the key are
+/// stored as `Ix`, inserting an index past `Ix::MAX` panics.
+pub struct PairHashTable<W = usize, Ix = usize> {
+    /// live entries
     count: usize,
+    /// Deleted entries still taking up a slot. They are part of probe chains
+    /// until the table is rebuilt, so they count towards the load.
+    tombstone_count: usize,
+    max_probe_length: usize,
+
+    hasher: PairHasher,
+    /// live entries and tombstones over slots allowed before a rebuild
+    max_load: f32,
+    grow_factor: usize,
+
+    table: Vec<Slot<W, Ix>>,
+}
+
+enum Slot<W, Ix> {
+    Empty,
+    /// a deleted entry, which keeps probe chains going past it
+    Tombstone,
+    Live(Entry<W, Ix>),
+}
+
+impl<W, Ix> Slot<W, Ix> {
+    fn live(&self) -> Option<&Entry<W, Ix>> {
+        match self {
+            Slot::Live(entry) => Some(entry),
+            _ => None,
+        }
+    }
+
+    fn live_mut(&mut self) -> Option<&mut Entry<W, Ix>> {
+        match self {
+            Slot::Live(entry) => Some(entry),
+            _ => None,
+        }
+    }
+}
+
+struct Entry<W, Ix> {
+    source: Ix,
+    destination: Ix,
+    weight: W,
+}
+
+impl<W, Ix: HashIndex> Entry<W, Ix> {
+    #[inline]
+    fn key_pair(&self) -> IndexPair {
+        (self.source.index(), self.destination.index())
+    }
 
-    table: Vec<Option<Entry>>,
+    fn meta(&self) -> EdgeMeta<&W> {
+        EdgeMeta {
+            source: self.source.index(),
+            destination: self.destination.index(),
+            weight: &self.weight,
+        }
+    }
 }
 
-struct Entry {
-    edge_meta: EdgeMeta<usize>,
-    is_deleted: bool,
+/// `index` as an `Ix`, panics if it does not fit
+fn to_ix<Ix: HashIndex>(index: usize) -> Ix {
+    Ix::from_index(index).unwrap_or_else(|| {
+        panic!(
+            "node index {} does not fit the edge table, at most {}",
+            index,
+            Ix::MAX
+        )
+    })
 }
 
 /// Source Index, Destination Index
-type IndexPair = (usize, usize);
+pub type IndexPair = (usize, usize);
+
+/// hashes a key, the table takes the result modulo its capacity
+pub type PairHasher = fn(IndexPair) -> usize;
+
+/// the default `PairHasher`, mixes both indices with `hash_usize`
+pub fn pair_hash(key: IndexPair) -> usize {
+    (PRIME_OF_MATHS * hash_usize(key.0) + PRIME_OF_MATHS + hash_usize(key.1)).0
+}
 
 // http://web.archive.org/web/20071223173210/http://www.concentric.net/~Ttwang/tech/inthash.htm
 // 64 bit shift/mix
 #[inline(always)]
-fn hash_usize(input: usize) -> Wrapping<usize> {
+pub(crate) fn hash_usize(input: usize) -> Wrapping<usize> {
     let mut key = Wrapping(input);
     key = (!key) + (key << 21); // key = (key << 21) - key - 1;
     key = key ^ (key >> 24);
@@ -34,158 +151,391 @@ fn hash_usize(input: usize) -> Wrapping<usize> {
     key + (key << 31)
 }
 
-impl PairHashTable {
+impl<W> Default for PairHashTable<W> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<W> PairHashTable<W> {
     pub fn new() -> Self {
         Self::with_capacity(DEFAULT_CAPACITY)
     }
 
     pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_hasher(capacity, pair_hash)
+    }
+
+    /// Table hashing keys with `hasher` instead of `pair_hash`. The capacity
+    /// is at least one slot.
+    pub fn with_hasher(capacity: usize, hasher: PairHasher) -> Self {
+        Self::with_index_type(capacity, hasher)
+    }
+}
+
+impl<W, Ix: HashIndex> PairHashTable<W, Ix> {
+    /// `with_hasher` for a table keyed by `Ix` instead of `usize`
+    pub fn with_index_type(capacity: usize, hasher: PairHasher) -> Self {
         Self {
             count: 0,
-            table: std::iter::repeat_with(|| None).take(capacity).collect(),
+            tombstone_count: 0,
+            max_probe_length: 0,
+
+            hasher,
+            max_load: MAX_LOAD,
+            grow_factor: GROW_FACTOR,
+
+            table: std::iter::repeat_with(|| Slot::Empty)
+                .take(capacity.max(1))
+                .collect(),
         }
     }
 
-    fn index_of(&self, key: IndexPair) -> usize {
-        let mut index = self.index_calc(key);
+    /// Sets how full the table can get before it is rebuilt, `max_load` is
+    /// the fraction of slots taken by live entries and tombstones and has to
+    /// be in `(0, 1]`. `grow_factor` is how many times bigger it gets when
+    /// tombstones are not what filled it, at least 2.
+    pub fn set_load_limits(&mut self, max_load: f32, grow_factor: usize) {
+        assert!(
+            max_load > 0.0 && max_load <= 1.0,
+            "max_load must be in (0, 1], got {}",
+            max_load
+        );
+        assert!(grow_factor >= 2, "grow_factor must be at least 2");
+
+        self.max_load = max_load;
+        self.grow_factor = grow_factor;
+    }
 
-        loop {
-            match &self.table[index] {
-                Some(entry) => {
-                    if !entry.is_deleted && entry.edge_meta.key_pair() == key {
-                        return index;
-                    }
+    /// number of slots
+    pub fn capacity(&self) -> usize {
+        self.table.len()
+    }
 
-                    index = (index + 1) % self.table.capacity();
-                }
-                None => return index,
+    /// live entries over slots
+    pub fn load_factor(&self) -> f32 {
+        self.count as f32 / self.table.len() as f32
+    }
+
+    pub fn max_load(&self) -> f32 {
+        self.max_load
+    }
+
+    pub fn grow_factor(&self) -> usize {
+        self.grow_factor
+    }
+
+    /// live entries
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// slots of the probe sequence for `key`, at most once around the table
+    fn probe(&self, key: IndexPair) -> impl Iterator<Item = usize> {
+        let start = self.index_calc(key);
+        let len = self.table.len();
+        (0..len).map(move |step| (start + step) % len)
+    }
+
+    /// slot holding `key`, `None` if it is not in the table
+    fn index_of(&self, key: IndexPair) -> Option<usize> {
+        for index in self.probe(key) {
+            match &self.table[index] {
+                Slot::Live(entry) if entry.key_pair() == key => return Some(index),
+                Slot::Live(_) | Slot::Tombstone => {}
+                Slot::Empty => return None,
             }
         }
+
+        // went all the way around a table with no empty slot
+        None
     }
 
-    fn index_of_insertion(&self, key: IndexPair) -> usize {
-        let mut index = self.index_calc(key);
+    /// Slot holding `key`, or else the first tombstone or empty slot on its
+    /// probe sequence. `None` only when every slot holds another live key.
+    fn index_of_insertion(&self, key: IndexPair) -> Option<usize> {
         let mut tombstone = None;
 
-        loop {
+        for index in self.probe(key) {
             match &self.table[index] {
-                Some(entry) => {
-                    if !entry.is_deleted && entry.edge_meta.key_pair() == key {
-                        return index;
+                Slot::Live(entry) => {
+                    if entry.key_pair() == key {
+                        return Some(index);
                     }
-
-                    // if found a tombstone, keep track of index
-                    if entry.is_deleted && tombstone.is_none() {
-                        tombstone = Some(index);
-                    }
-
-                    index = (index + 1) % self.table.capacity();
                 }
-                None => {
-                    if let Some(tombstone) = tombstone {
-                        return tombstone;
-                    } else {
-                        return index;
+                // if found a tombstone, keep track of index
+                Slot::Tombstone => {
+                    if tombstone.is_none() {
+                        tombstone = Some(index);
                     }
                 }
+                Slot::Empty => return Some(tombstone.unwrap_or(index)),
             }
         }
+
+        tombstone
     }
 
-    fn insert(&mut self, key: IndexPair, weight: usize) -> bool {
-        if self.count + 1 > (self.table.capacity() as f32 * MAX_LOAD) as usize {
-            let new_capacity = self.table.capacity() * GROW_FACTOR;
+    /// longest probe sequence any insert since the last rebuild has taken
+    pub fn max_probe_length(&self) -> usize {
+        self.max_probe_length
+    }
+
+    /// Sets the weight of `key`, returns the weight it had before if it was
+    /// already in the table.
+    pub(crate) fn insert(&mut self, key: IndexPair, weight: W) -> Option<W> {
+        let max_load = (self.table.len() as f32 * self.max_load) as usize;
+        if self.count + self.tombstone_count + 1 > max_load {
+            // clearing out tombstones is enough if they are what fills it
+            let new_capacity = if self.count + 1 > max_load / self.grow_factor {
+                self.table.len() * self.grow_factor
+            } else {
+                self.table.len()
+            };
             self.resize(new_capacity);
         }
 
-        let index = self.index_of_insertion(key);
-
-        let had_edge = if let Some(_) = self.table[index] {
-            true
-        } else {
-            false
+        let index = match self.index_of_insertion(key) {
+            Some(index) => index,
+            None => {
+                // only reachable if the load check above was bypassed
+                let new_capacity = self.table.len() * self.grow_factor;
+                self.resize(new_capacity);
+                return self.insert(key, weight);
+            }
         };
 
-        if !had_edge {
-            self.count += 1;
-        }
-
-        self.table[index] = Some(Entry {
-            is_deleted: false,
+        let probe_len = (index + self.table.len() - self.index_calc(key)) % self.table.len() + 1;
+        self.max_probe_length = self.max_probe_length.max(probe_len);
+        debug_assert!(probe_len <= self.table.len());
 
-            edge_meta: EdgeMeta {
-                source: key.0,
-                destination: key.1,
-                weight,
-            },
-        });
+        let entry = Entry {
+            source: to_ix(key.0),
+            destination: to_ix(key.1),
+            weight,
+        };
 
-        had_edge
+        match std::mem::replace(&mut self.table[index], Slot::Live(entry)) {
+            Slot::Live(old) => Some(old.weight),
+            Slot::Tombstone => {
+                self.tombstone_count -= 1;
+                self.count += 1;
+                None
+            }
+            Slot::Empty => {
+                self.count += 1;
+                None
+            }
+        }
     }
 
-    fn delete(&mut self, key: IndexPair) -> bool {
+    /// Tombstones `key`, returns its weight if it was in the table.
+    pub(crate) fn remove(&mut self, key: IndexPair) -> Option<W> {
         if self.count == 0 {
-            return false;
+            return None;
         }
 
-        let index = self.index_of_insertion(key);
-
-        let index = if let Some(entry) = &self.table[index] {
-            if entry.is_deleted {
-                None
-            } else {
-                Some(index)
-            }
-        } else {
-            None
+        let index = self.index_of(key)?;
+        let weight = match std::mem::replace(&mut self.table[index], Slot::Tombstone) {
+            Slot::Live(entry) => entry.weight,
+            _ => unreachable!("index_of only finds live slots"),
         };
+        self.count -= 1;
+        self.tombstone_count += 1;
 
-        if let Some(index) = index {
-            let mut entry = self.table[index].take().unwrap();
-            entry.is_deleted = true;
-            self.table[index] = Some(entry);
-            true
-        } else {
-            false
+        // past half the slots the probe chains are mostly tombstones
+        if self.tombstone_count > self.table.len() / 2 {
+            self.resize(self.table.len());
         }
+
+        Some(weight)
+    }
+
+    pub(crate) fn get(&self, key: IndexPair) -> Option<EdgeMeta<&W>> {
+        self.index_of(key)
+            .and_then(|index| self.table[index].live())
+            .map(Entry::meta)
     }
 
-    fn get(&self, key: IndexPair) -> Option<&EdgeMeta<usize>> {
-        if let Some(entry) = &self.table[self.index_of(key)] {
-            Some(&entry.edge_meta)
-        } else {
-            None
+    /// Tombstones every entry `keep` returns `false` for in one pass over the
+    /// table, rebuilding afterwards if tombstones took over half of it.
+    pub(crate) fn retain<F: FnMut(EdgeMeta<&W>) -> bool>(&mut self, mut keep: F) {
+        let mut removed = 0;
+        for slot in self.table.iter_mut() {
+            if slot.live().is_some_and(|entry| !keep(entry.meta())) {
+                *slot = Slot::Tombstone;
+                removed += 1;
+            }
+        }
+
+        self.count -= removed;
+        self.tombstone_count += removed;
+        if self.tombstone_count > self.table.len() / 2 {
+            self.resize(self.table.len());
         }
     }
 
+    /// heap bytes of the slots, empty ones and tombstones included
+    pub fn memory_bytes(&self) -> usize {
+        vec_bytes(&self.table)
+    }
+
+    /// empties every slot in place, the capacity stays the same
+    pub(crate) fn clear(&mut self) {
+        self.table.iter_mut().for_each(|slot| *slot = Slot::Empty);
+        self.count = 0;
+        self.tombstone_count = 0;
+        self.max_probe_length = 0;
+    }
+
+    /// every live entry in table order, empty slots and tombstones skipped
+    pub fn iter(&self) -> impl Iterator<Item = EdgeMeta<&W>> {
+        self.table.iter().filter_map(Slot::live).map(Entry::meta)
+    }
+
     fn resize(&mut self, capacity: usize) {
-        let mut new_table = Self::with_capacity(capacity);
+        let mut new_table = Self::with_index_type(capacity, self.hasher);
+        new_table.set_load_limits(self.max_load, self.grow_factor);
 
-        for i in 0..self.table.capacity() {
-            if let Some(entry) = &self.table[i] {
-                if !entry.is_deleted {
-                    new_table.insert(entry.edge_meta.key_pair(), entry.edge_meta.weight);
-                }
+        let old_table = std::mem::take(&mut self.table);
+        for slot in old_table {
+            if let Slot::Live(entry) = slot {
+                new_table.insert(entry.key_pair(), entry.weight);
             }
         }
 
         self.count = new_table.count;
+        self.tombstone_count = 0;
+        self.max_probe_length = new_table.max_probe_length;
         self.table = new_table.table;
     }
 
+    /// Rebuilds the table at the smallest power of two capacity that fits
+    /// the live entries under the load limit, dropping every tombstone.
+    pub fn shrink_to_fit(&mut self) {
+        let needed = ((self.count + 1) as f32 / self.max_load).ceil() as usize;
+        self.resize(needed.next_power_of_two());
+    }
+
     /// helper
     #[inline(always)]
     fn index_calc(&self, key: IndexPair) -> usize {
-        (PRIME_OF_MATHS * hash_usize(key.0) + PRIME_OF_MATHS + hash_usize(key.1)).0
-            % self.table.capacity()
+        (self.hasher)(key) % self.table.len()
     }
 }
 
-pub struct HashGraph {
+/// Graph over a `PairHashTable` of edges, with node values of type `T` and
+/// weights of type `W`. Plain `HashGraph` is `u64` nodes and `usize` weights,
+/// other weights are picked with `with_default_weight`. `Ix` is what the
+/// table keys are stored as, see `with_index_type`.
+///
+/// The node type comes first to match `Graph<T, W>`, which breaks code from
+/// when the weight was the only parameter: `HashGraph<W>` still compiles but
+/// now picks the node type. `WeightedHashGraph<W>` keeps the old meaning.
+pub struct HashGraph<T = u64, W = usize, Ix = usize> {
     count: usize,
-    nodes: Vec<u64>,
+    nodes: Vec<T>,
 
-    edges: PairHashTable,
+    /// weight store, the lists below are only for looking up neighbours
+    edges: PairHashTable<W, Ix>,
+    /// weight given to edges made with `add_edge`
+    default_weight: W,
+
+    /// destinations of every node's edges, kept sorted
+    out_adj: Vec<Vec<usize>>,
+    /// sources of every node's incoming edges, kept sorted
+    in_adj: Vec<Vec<usize>>,
+}
+
+/// `HashGraph` with `u64` nodes and `W` weights, what `HashGraph<W>` was
+/// before node values were generic
+pub type WeightedHashGraph<W, Ix = usize> = HashGraph<u64, W, Ix>;
+
+impl Default for HashGraph {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl_node_access!(impl<T, W, Ix> for HashGraph<T, W, Ix>, T);
+
+/// Adds every `(from, to)` pair with the default weight, pushing
+/// `T::default()` nodes until both ends are nodes.
+impl<T: Default, W: Clone, Ix: HashIndex> Extend<(usize, usize)> for HashGraph<T, W, Ix> {
+    fn extend<I: IntoIterator<Item = (usize, usize)>>(&mut self, iter: I) {
+        for (from, to) in iter {
+            cover_nodes(self, from, to);
+            self.add_edge(from, to);
+        }
+    }
+}
+
+/// same as the unweighted `extend`, an existing edge gets the new weight
+impl<T: Default, W: Clone, Ix: HashIndex> Extend<(usize, usize, W)> for HashGraph<T, W, Ix> {
+    fn extend<I: IntoIterator<Item = (usize, usize, W)>>(&mut self, iter: I) {
+        for (from, to, weight) in iter {
+            cover_nodes(self, from, to);
+            self.set_edge((from, to), weight);
+        }
+    }
+}
+
+/// `add_edge` on the result gives weight 1, like `with_capacity`
+impl<T, W, Ix> FromIterator<(usize, usize)> for HashGraph<T, W, Ix>
+where
+    T: Default,
+    W: Clone + From<u8>,
+    Ix: HashIndex,
+{
+    fn from_iter<I: IntoIterator<Item = (usize, usize)>>(iter: I) -> Self {
+        let iter = iter.into_iter();
+        let size = iter.size_hint().0.max(DEFAULT_CAPACITY);
+
+        let mut graph = HashGraph::with_index_type(size, W::from(1));
+        graph.extend(iter);
+        graph
+    }
+}
+
+/// `add_edge` on the result gives `W::default()`, every collected edge
+/// brings its own weight
+impl<T, W, Ix> FromIterator<(usize, usize, W)> for HashGraph<T, W, Ix>
+where
+    T: Default,
+    W: Clone + Default,
+    Ix: HashIndex,
+{
+    fn from_iter<I: IntoIterator<Item = (usize, usize, W)>>(iter: I) -> Self {
+        let iter = iter.into_iter();
+        let size = iter.size_hint().0.max(DEFAULT_CAPACITY);
+
+        let mut graph = HashGraph::with_index_type(size, W::default());
+        graph.extend(iter);
+        graph
+    }
+}
+
+/// equal when node values and weighted edge sets match, table layout and
+/// tombstones are ignored
+impl<T: PartialEq, W: PartialEq, Ix: HashIndex> PartialEq for HashGraph<T, W, Ix> {
+    fn eq(&self, other: &Self) -> bool {
+        let contains_all = |a: &Self, b: &Self| {
+            a.edges.iter().all(|edge| {
+                b.edges
+                    .get(edge.key_pair())
+                    .is_some_and(|other_edge| other_edge.weight == edge.weight)
+            })
+        };
+
+        self.count == other.count
+            && self.nodes == other.nodes
+            && contains_all(self, other)
+            && contains_all(other, self)
+    }
 }
 
 impl HashGraph {
@@ -193,99 +543,524 @@ impl HashGraph {
         Self::with_capacity(DEFAULT_CAPACITY)
     }
 
+    /// `add_edge` gives weight 1
     pub fn with_capacity(size: usize) -> Self {
+        Self::with_default_weight(size, 1)
+    }
+}
+
+impl<T, W> HashGraph<T, W> {
+    /// empty graph with room for `size` nodes and edges, `add_edge` gives
+    /// every edge `default_weight`
+    pub fn with_default_weight(size: usize, default_weight: W) -> Self {
+        Self::with_index_type(size, default_weight)
+    }
+}
+
+/// `HashGraph` with `u32` table keys, for graphs that stay under 2³² nodes.
+/// `push_node` panics on the node that would get index 2³², `try_push_node`
+/// returns an error instead.
+pub type CompactHashGraph<T = u64, W = usize> = HashGraph<T, W, u32>;
+
+impl<T, W, Ix: HashIndex> HashGraph<T, W, Ix> {
+    /// `with_default_weight` with the edge table keyed by `Ix`. A smaller
+    /// `Ix` makes every slot of the table smaller, pushing a node or adding
+    /// an edge to an index past `Ix::MAX` panics.
+    pub fn with_index_type(size: usize, default_weight: W) -> Self {
         Self {
             count: 0,
 
             nodes: Vec::with_capacity(size),
-            edges: PairHashTable::with_capacity(size),
+            edges: PairHashTable::with_index_type(size, pair_hash),
+            default_weight,
+
+            out_adj: Vec::with_capacity(size),
+            in_adj: Vec::with_capacity(size),
+        }
+    }
+
+    /// `push_node` that returns `IndexTooLarge` rather than panicking once
+    /// the new node's index would be past `Ix::MAX`
+    pub fn try_push_node(&mut self, value: T) -> Result<usize, GraphError> {
+        let index = self.nodes.len();
+        if index > Ix::MAX {
+            return Err(GraphError::IndexTooLarge {
+                index,
+                max: Ix::MAX,
+            });
+        }
+
+        self.count += 1;
+        self.nodes.push(value);
+        if self.out_adj.len() < self.nodes.len() {
+            self.out_adj.push(Vec::new());
+            self.in_adj.push(Vec::new());
+        }
+        Ok(index)
+    }
+
+    /// slots in the edge table, unlike the node counting `capacity` of the
+    /// other graphs
+    pub fn edge_capacity(&self) -> usize {
+        self.edges.capacity()
+    }
+
+    /// the edge table under `edges`, the neighbour lists under `auxiliary`
+    pub fn memory_bytes(&self) -> MemoryBreakdown {
+        MemoryBreakdown {
+            nodes: vec_bytes(&self.nodes),
+            edges: self.edges.memory_bytes(),
+            auxiliary: nested_vec_bytes(&self.out_adj) + nested_vec_bytes(&self.in_adj),
         }
     }
+
+    /// Checks that every edge in the table is between two nodes and is in
+    /// the neighbour lists of both ends, and that the lists hold nothing the
+    /// table does not.
+    pub fn validate(&self) -> Result<(), Vec<IntegrityError>> {
+        let mut errors = Vec::new();
+        check_count(self.count, self.nodes.len(), &mut errors);
+
+        let listed = |lists: &Vec<Vec<usize>>, node: usize, other: usize| {
+            lists
+                .get(node)
+                .is_some_and(|list| list.binary_search(&other).is_ok())
+        };
+
+        for (from, to) in self.edges.iter().map(|edge| edge.key_pair()) {
+            if from >= self.count || to >= self.count {
+                errors.push(IntegrityError::EdgeOutOfBounds {
+                    from,
+                    to,
+                    count: self.count,
+                });
+            }
+            if !listed(&self.out_adj, from, to) || !listed(&self.in_adj, to, from) {
+                errors.push(IntegrityError::AdjacencyMismatch { from, to });
+            }
+        }
+
+        for (from, list) in self.out_adj.iter().enumerate() {
+            for &to in list {
+                if self.edges.get((from, to)).is_none() {
+                    errors.push(IntegrityError::AdjacencyMismatch { from, to });
+                }
+            }
+        }
+        for (to, list) in self.in_adj.iter().enumerate() {
+            for &from in list {
+                if self.edges.get((from, to)).is_none() {
+                    errors.push(IntegrityError::AdjacencyMismatch { from, to });
+                }
+            }
+        }
+
+        into_result(errors)
+    }
+
+    /// Gives back the memory left over from removed edges, the edge table is
+    /// rebuilt to fit and the neighbour lists trimmed.
+    pub fn compact(&mut self) {
+        self.edges.shrink_to_fit();
+
+        for list in self.out_adj.iter_mut().chain(self.in_adj.iter_mut()) {
+            list.shrink_to_fit();
+        }
+    }
+
+    /// number of edges, kept by the table as edges come and go
+    pub fn edge_count(&self) -> usize {
+        self.edges.len()
+    }
+
+    /// number of edges going out of `node_index`
+    pub fn out_degree(&self, node_index: usize) -> usize {
+        self.out_adj.get(node_index).map_or(0, Vec::len)
+    }
+
+    /// number of edges coming in to `node_index`
+    pub fn in_degree(&self, node_index: usize) -> usize {
+        self.in_adj.get(node_index).map_or(0, Vec::len)
+    }
+
+    /// the table holding the edges, for its diagnostics
+    pub fn edge_table(&self) -> &PairHashTable<W, Ix> {
+        &self.edges
+    }
+
+    /// adds `from -> to` to the neighbour lists
+    fn link(&mut self, from: usize, to: usize) {
+        insert_sorted(&mut self.out_adj[from], to);
+        insert_sorted(&mut self.in_adj[to], from);
+    }
+
+    /// every edge between live nodes with its weight, in no particular order
+    pub fn weighted_edges(&self) -> impl Iterator<Item = EdgeMeta<&W>> {
+        self.edges
+            .iter()
+            .filter(move |edge| edge.source < self.count && edge.destination < self.count)
+    }
+
+    /// value of `node_index`, `None` past the last node
+    pub fn node(&self, node_index: usize) -> Option<&T> {
+        self.nodes.get(node_index)
+    }
+
+    /// Moves every edge of `from` over to `to`, which must have none. Only
+    /// the keys change, weights come along.
+    fn relabel(&mut self, from: usize, to: usize) {
+        let moved = |node| if node == from { to } else { node };
+
+        for next in self.out_adj[from].clone() {
+            if let Some(weight) = self.take_edge(from, next) {
+                self.insert_edge((to, moved(next)), weight);
+            }
+        }
+
+        for prev in self.in_adj[from].clone() {
+            if let Some(weight) = self.take_edge(prev, from) {
+                self.insert_edge((prev, to), weight);
+            }
+        }
+    }
+
+    /// `set_edge` that gives back the weight the edge had before
+    pub(crate) fn insert_edge(&mut self, from_to: (usize, usize), weight: W) -> Option<W> {
+        let old = self.edges.insert(from_to, weight);
+        if old.is_none() {
+            self.link(from_to.0, from_to.1);
+        }
+
+        old
+    }
+
+    /// `remove_edge` that gives back the weight the edge had
+    pub(crate) fn take_edge(&mut self, from: usize, to: usize) -> Option<W> {
+        let weight = self.edges.remove((from, to));
+        if weight.is_some() {
+            self.unlink(from, to);
+        }
+
+        weight
+    }
+
+    fn unlink(&mut self, from: usize, to: usize) {
+        remove_sorted(&mut self.out_adj[from], to);
+        remove_sorted(&mut self.in_adj[to], from);
+    }
 }
 
-impl Graph<u64, usize> for HashGraph {
+impl<T, W: Clone, Ix: HashIndex> HashGraph<T, W, Ix> {
+    /// Moves every edge of `merge` over to `keep` by rewriting its keys,
+    /// `merge` keeps its index and value but is left without edges. Where
+    /// `keep` already has the edge its weight wins. Edges between the two and
+    /// a self loop on `merge` become a self loop on `keep` when
+    /// `keep_self_loops`, otherwise they are dropped. Returns how many edges
+    /// `keep` gained.
+    pub fn merge_nodes(&mut self, keep: usize, merge: usize, keep_self_loops: bool) -> usize {
+        assert_node(keep, self.count);
+        assert_node(merge, self.count);
+        assert!(keep != merge, "can not merge node {} into itself", keep);
+
+        // a self loop on `merge` gives its weight first, then the edges
+        // between the two
+        let self_loop = [(merge, merge), (merge, keep), (keep, merge)]
+            .iter()
+            .find_map(|&(from, to)| self.get_edge(from, to))
+            .map(|edge| edge.weight);
+
+        let mut redirected = 0;
+        let mut redirect = |graph: &mut Self, key: IndexPair, new_key: IndexPair| {
+            let edge = match graph.get_edge(key.0, key.1) {
+                Some(edge) => edge,
+                None => return,
+            };
+            graph.remove_edge(key.0, key.1);
+
+            if new_key.0 != new_key.1 && !graph.has_edge(new_key.0, new_key.1) {
+                graph.set_edge(new_key, edge.weight);
+                redirected += 1;
+            }
+        };
+
+        let moved = |node| if node == merge { keep } else { node };
+        for next in self.out_adj[merge].clone() {
+            redirect(self, (merge, next), (keep, moved(next)));
+        }
+        for prev in self.in_adj[merge].clone() {
+            redirect(self, (prev, merge), (moved(prev), keep));
+        }
+
+        if let Some(weight) = self_loop.filter(|_| keep_self_loops) {
+            if !self.has_edge(keep, keep) {
+                self.set_edge((keep, keep), weight);
+                redirected += 1;
+            }
+        }
+
+        redirected
+    }
+}
+
+fn insert_sorted(list: &mut Vec<usize>, value: usize) {
+    if let Err(index) = list.binary_search(&value) {
+        list.insert(index, value);
+    }
+}
+
+fn remove_sorted(list: &mut Vec<usize>, value: usize) {
+    if let Ok(index) = list.binary_search(&value) {
+        list.remove(index);
+    }
+}
+
+impl<T, W: Clone, Ix: HashIndex> Graph<T, W> for HashGraph<T, W, Ix> {
     fn add_edge(&mut self, from: usize, to: usize) -> bool {
-        self.edges.insert((from, to), 1)
+        if self.has_edge(from, to) {
+            return true;
+        }
+
+        self.set_edge((from, to), self.default_weight.clone())
     }
 
     fn remove_edge(&mut self, from: usize, to: usize) -> bool {
-        self.edges.delete((from, to))
+        self.take_edge(from, to).is_some()
     }
 
     fn has_edge(&self, from: usize, to: usize) -> bool {
+        assert_node(from, self.count);
+        assert_node(to, self.count);
         self.edges.get((from, to)).is_some()
     }
 
-    fn get_edge(&self, from: usize, to: usize) -> Option<EdgeMeta<usize>> {
-        if let Some(edge) = self.edges.get((from, to)) {
-            Some(*edge)
-        } else {
-            None
-        }
+    fn get_edge(&self, from: usize, to: usize) -> Option<EdgeMeta<W>> {
+        assert_node(from, self.count);
+        assert_node(to, self.count);
+        self.edges.get((from, to)).map(|edge| edge.cloned())
     }
 
+    /// in ascending order
     fn outgoing_edges_of(&self, node_index: usize) -> Vec<usize> {
-        let mut out = Vec::new();
+        assert_node(node_index, self.count);
+        self.out_adj[node_index].clone()
+    }
 
-        for i in 0..self.count {
-            if self.edges.get((node_index, i)).is_some() {
-                out.push(i);
-            }
-        }
+    /// the adjacency lists are kept sorted
+    fn neighbors_sorted(&self, node_index: usize) -> Vec<usize> {
+        self.outgoing_edges_of(node_index)
+    }
+
+    /// in ascending order
+    fn incoming_edges_of(&self, node_index: usize) -> Vec<usize> {
+        assert_node(node_index, self.count);
+        self.in_adj[node_index].clone()
+    }
+
+    fn outgoing_edges_into(&self, node_index: usize, out: &mut Vec<usize>) {
+        assert_node(node_index, self.count);
+        out.clear();
+        out.extend_from_slice(&self.out_adj[node_index]);
+    }
+
+    fn incoming_edges_into(&self, node_index: usize, out: &mut Vec<usize>) {
+        assert_node(node_index, self.count);
+        out.clear();
+        out.extend_from_slice(&self.in_adj[node_index]);
+    }
+
+    /// read straight off the table instead of a neighbour query per node, in
+    /// table order
+    fn edges(&self) -> Box<dyn Iterator<Item = EdgeMeta<W>> + '_> {
+        Box::new(self.weighted_edges().map(|edge| edge.cloned()))
+    }
+
+    /// sorted to match the other graphs
+    fn all_edge_pairs(&self) -> Vec<(usize, usize)> {
+        let mut out: Vec<(usize, usize)> = self.edges().map(|edge| edge.key_pair()).collect();
 
+        out.sort_unstable();
         out
     }
 
-    fn incoming_edges_of(&self, node_index: usize) -> Vec<usize> {
-        let mut out = Vec::new();
+    fn out_degree(&self, node_index: usize) -> usize {
+        HashGraph::out_degree(self, node_index)
+    }
+
+    fn in_degree(&self, node_index: usize) -> usize {
+        HashGraph::in_degree(self, node_index)
+    }
+
+    /// empties the table and the neighbour lists in place, nothing is freed
+    fn clear_edges(&mut self) {
+        self.edges.clear();
+        self.out_adj.iter_mut().for_each(Vec::clear);
+        self.in_adj.iter_mut().for_each(Vec::clear);
+    }
+
+    /// keeps the table at its capacity, along with the node storage and the
+    /// emptied neighbour lists for the nodes pushed next
+    fn clear(&mut self) {
+        self.clear_edges();
+        self.nodes.clear();
+        self.count = 0;
+    }
 
-        for i in 0..self.count {
-            if self.edges.get((i, node_index)).is_some() {
-                out.push(i);
+    /// one pass over the table, tombstoning the edges `pred` rejects, only
+    /// those are then taken out of the neighbour lists
+    fn retain_edges(&mut self, mut pred: impl FnMut(usize, usize, &W) -> bool) {
+        let mut doomed = Vec::new();
+        self.edges.retain(|edge| {
+            let keep = pred(edge.source, edge.destination, edge.weight);
+            if !keep {
+                doomed.push(edge.key_pair());
             }
+            keep
+        });
+
+        for (from, to) in doomed {
+            self.unlink(from, to);
         }
+    }
 
-        out
+    /// Panics once there are more nodes than `Ix` can index, see
+    /// `try_push_node`.
+    fn push_node(&mut self, value: T) -> usize {
+        self.try_push_node(value)
+            .unwrap_or_else(|error| panic!("{}", error))
     }
 
-    fn push_node(&mut self, value: u64) -> usize {
-        self.count += 1;
-        self.nodes.push(value);
-        self.nodes.len() - 1
+    fn reserve_nodes(&mut self, additional: usize) {
+        self.nodes.reserve(additional);
+        self.out_adj.reserve(additional);
+        self.in_adj.reserve(additional);
     }
 
-    fn set_node(&mut self, _node_index: usize, _value: u64) {
-        todo!();
+    /// Panics if `node_index` is not a node.
+    fn set_node(&mut self, node_index: usize, value: T) {
+        assert_node(node_index, self.count);
+        self.nodes[node_index] = value;
     }
 
-    fn get_node(&self, node_index: usize) -> &u64 {
-        // @FIXME
+    /// Panics if `node_index` is not a node, see `node` for a checked lookup.
+    fn get_node(&self, node_index: usize) -> &T {
+        assert_node(node_index, self.count);
         &self.nodes[node_index]
     }
 
-    fn remove_node(&mut self, node_index: usize) -> u64 {
-        for i in 0..self.count {
-            self.remove_edge(i, node_index);
-            self.remove_edge(node_index, i);
-        }
+    /// Removes the node and its edges the way `Vec::swap_remove` does, the
+    /// last node takes over `node_index` along with all of its edges and
+    /// every index past the new last node is gone.
+    fn remove_node(&mut self, node_index: usize) -> T {
+        assert_node(node_index, self.count);
+        self.clear_edges_of(node_index);
 
-        let val = self.nodes[node_index];
-
-        self.nodes[node_index] = 0;
+        let last = self.count - 1;
+        if node_index != last {
+            self.relabel(last, node_index);
+        }
 
-        val
+        self.count -= 1;
+        self.nodes.swap_remove(node_index)
     }
 
     fn node_count(&self) -> usize {
         self.count
     }
 
-    fn set_count(&mut self, count: usize) {
-        self.count = count;
+    /// Panics if either end is not a node.
+    fn set_edge(&mut self, from_to: (usize, usize), weight: W) -> bool {
+        assert_node(from_to.0, self.count);
+        assert_node(from_to.1, self.count);
+        self.insert_edge(from_to, weight).is_some()
+    }
+}
+
+/// Serialized as `(node_count, nodes, default weight, weighted edges)`, the
+/// table itself is not written out since it is mostly empty slots and
+/// tombstones. Graphs written before the default weight was saved read back
+/// with a default weight of 1, what `add_edge` always used then.
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use super::{HashGraph, HashIndex};
+    use crate::Graph;
+    use serde::de::{Error, IntoDeserializer};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize)]
+    struct Repr<'a, T, W> {
+        node_count: usize,
+        nodes: &'a [T],
+        default_weight: &'a W,
+        edges: Vec<(usize, usize, &'a W)>,
+    }
+
+    #[derive(Deserialize)]
+    struct OwnedRepr<T, W> {
+        node_count: usize,
+        nodes: Vec<T>,
+        /// missing from graphs written before it was saved, serde reads
+        /// that as `None`
+        default_weight: Option<W>,
+        edges: Vec<(usize, usize, W)>,
+    }
+
+    impl<T: Serialize, W: Serialize, Ix: HashIndex> Serialize for HashGraph<T, W, Ix> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            Repr {
+                node_count: self.count,
+                nodes: &self.nodes,
+                default_weight: &self.default_weight,
+                edges: self
+                    .edges
+                    .iter()
+                    .map(|edge| (edge.source, edge.destination, edge.weight))
+                    .collect(),
+            }
+            .serialize(serializer)
+        }
     }
 
-    fn set_edge(&mut self, from_to: (usize, usize), weight: usize) -> bool {
-        self.edges.insert(from_to, weight)
+    impl<'de, T, W, Ix> Deserialize<'de> for HashGraph<T, W, Ix>
+    where
+        T: Deserialize<'de>,
+        W: Deserialize<'de> + Clone,
+        Ix: HashIndex,
+    {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let repr = OwnedRepr::<T, W>::deserialize(deserializer)?;
+
+            if repr.nodes.len() != repr.node_count {
+                return Err(D::Error::custom(format!(
+                    "node_count is {} but there are {} nodes",
+                    repr.node_count,
+                    repr.nodes.len()
+                )));
+            }
+
+            let default_weight = match repr.default_weight {
+                Some(weight) => weight,
+                None => W::deserialize(IntoDeserializer::<D::Error>::into_deserializer(1u8))?,
+            };
+
+            let mut graph =
+                HashGraph::with_index_type(repr.node_count.max(repr.edges.len()), default_weight);
+            for value in repr.nodes {
+                graph.push_node(value);
+            }
+
+            for (from, to, weight) in repr.edges {
+                if from >= repr.node_count || to >= repr.node_count {
+                    return Err(D::Error::custom(format!(
+                        "edge ({}, {}) out of bounds for {} nodes",
+                        from, to, repr.node_count
+                    )));
+                }
+
+                graph.set_edge((from, to), weight);
+            }
+
+            Ok(graph)
+        }
     }
 }
 
@@ -305,10 +1080,153 @@ mod test_hashtable {
 
         assert_eq!(table.count, 5);
 
-        assert!(table.insert((1, 0), 1));
+        assert_eq!(table.insert((1, 0), 3), Some(1));
+        assert_eq!(table.insert((1, 0), 1), Some(3));
+        assert_eq!(table.count, 5);
         assert!(table.get((9, 10)).is_some());
         assert!(table.get((9, 3)).is_none());
     }
+
+    #[test]
+    fn churn_test() {
+        let mut table = PairHashTable::with_capacity(64);
+
+        for round in 0..5 {
+            for i in 0..10_000 {
+                assert_eq!(table.insert((i, round), i), None);
+            }
+            assert_eq!(table.count, 10_000);
+
+            for i in 0..10_000 {
+                assert_eq!(table.get((i, round)).map(|edge| *edge.weight), Some(i));
+                assert!(table.remove((i, round)).is_some());
+            }
+            assert_eq!(table.count, 0);
+            assert!(table.get((5, round)).is_none());
+        }
+
+        // sized for the 10k live keys, not for every key ever inserted
+        assert!(table.table.len() <= 32_768, "{}", table.table.len());
+        assert!(
+            table.count + table.tombstone_count <= (table.table.len() as f32 * MAX_LOAD) as usize
+        );
+    }
+
+    #[test]
+    fn full_table_test() {
+        let entry = |source, destination, is_deleted| {
+            if is_deleted {
+                Slot::Tombstone
+            } else {
+                Slot::Live(Entry {
+                    source,
+                    destination,
+                    weight: source,
+                })
+            }
+        };
+
+        // every slot live, which the load factor never allows on its own
+        let mut table = PairHashTable::with_capacity(8);
+        for (i, slot) in table.table.iter_mut().enumerate() {
+            *slot = entry(i, 0, false);
+        }
+        table.count = 8;
+
+        assert!(table.get((100, 100)).is_none());
+        assert!(table.remove((100, 100)).is_none());
+        assert_eq!(*table.get((3, 0)).unwrap().weight, 3);
+        assert_eq!(table.insert((100, 100), 1), None);
+        assert_eq!(table.table.len(), 16);
+        assert_eq!(*table.get((100, 100)).unwrap().weight, 1);
+
+        // and every slot a tombstone
+        let mut table = PairHashTable::with_capacity(8);
+        for (i, slot) in table.table.iter_mut().enumerate() {
+            *slot = entry(i, 0, true);
+        }
+        table.tombstone_count = 8;
+
+        assert!(table.get((3, 0)).is_none());
+        assert!(table.remove((3, 0)).is_none());
+        assert_eq!(table.insert((3, 0), 9), None);
+        assert_eq!(*table.get((3, 0)).unwrap().weight, 9);
+        assert_eq!(table.tombstone_count, 0);
+        assert!(table.max_probe_length() <= table.table.len());
+    }
+
+    #[test]
+    fn tombstone_reuse_test() {
+        let mut table = PairHashTable::with_capacity(16);
+        table.insert((1, 1), 5);
+        assert_eq!(table.remove((1, 1)), Some(5));
+        assert!(table.remove((1, 1)).is_none());
+        assert_eq!((table.count, table.tombstone_count), (0, 1));
+
+        assert_eq!(table.insert((1, 1), 6), None);
+        assert_eq!((table.count, table.tombstone_count), (1, 0));
+        assert_eq!(*table.get((1, 1)).unwrap().weight, 6);
+    }
+
+    #[test]
+    fn iter_test() {
+        // small table so the keys collide and probe past each other
+        let mut table = PairHashTable::with_capacity(8);
+        let mut live = Vec::new();
+        for from in 0..6 {
+            for to in 0..4 {
+                table.insert((from, to), from * to);
+                live.push((from, to, from * to));
+            }
+        }
+
+        for key in [(0, 0), (3, 2), (5, 3), (1, 1), (3, 2)] {
+            table.remove(key);
+        }
+        live.retain(|&(from, to, _)| ![(0, 0), (3, 2), (5, 3), (1, 1)].contains(&(from, to)));
+        // a tombstoned slot reused
+        table.insert((3, 2), 99);
+        live.push((3, 2, 99));
+
+        let mut found: Vec<_> = table
+            .iter()
+            .map(|edge| (edge.source, edge.destination, *edge.weight))
+            .collect();
+        found.sort_unstable();
+        live.sort_unstable();
+        assert_eq!(found, live);
+        assert!(PairHashTable::<usize>::new().iter().next().is_none());
+    }
+
+    #[test]
+    fn u32_boundary_test() {
+        let max = u32::MAX as usize;
+        let mut table = CompactPairHashTable::with_index_type(8, pair_hash);
+
+        for &key in &[(max, 0), (0, max), (max, max), (max - 1, max)] {
+            assert_eq!(table.insert(key, key.0 ^ key.1), None);
+        }
+        assert_eq!(
+            table.get((max, 0)).map(|edge| edge.key_pair()),
+            Some((max, 0))
+        );
+        assert_eq!(*table.get((max - 1, max)).unwrap().weight, 1);
+        assert_eq!(*table.get((max, max)).unwrap().weight, 0);
+        assert!(table.get((max, 1)).is_none());
+        // lookups past the limit just miss
+        assert!(table.get((max + 1, 0)).is_none());
+
+        let mut keys: Vec<_> = table.iter().map(|edge| edge.key_pair()).collect();
+        keys.sort_unstable();
+        assert_eq!(keys, vec![(0, max), (max - 1, max), (max, 0), (max, max)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "node index 4294967296 does not fit the edge table")]
+    fn past_u32_test() {
+        let mut table = CompactPairHashTable::with_index_type(8, pair_hash);
+        table.insert((0, u32::MAX as usize + 1), 1);
+    }
 }
 
 #[cfg(test)]
@@ -344,7 +1262,7 @@ mod test_hashgraph {
         graph.add_edge(2, 0);
 
         assert!(graph.outgoing_edges_of(0).len() == 1);
-        assert!(graph.outgoing_edges_of(4).len() == 0);
+        assert!(graph.outgoing_edges_of(4).is_empty());
 
         graph.add_edge(10, 2);
         graph.add_edge(10, 3);
@@ -378,7 +1296,7 @@ mod test_hashgraph {
         graph.add_edge(2, 0);
 
         assert!(graph.outgoing_edges_of(500).len() == 1);
-        assert!(graph.outgoing_edges_of(4).len() == 0);
+        assert!(graph.outgoing_edges_of(4).is_empty());
 
         graph.add_edge(10, 2);
         graph.add_edge(10, 3);
@@ -412,7 +1330,7 @@ mod test_hashgraph {
         graph.add_edge(2, 0);
 
         assert!(graph.outgoing_edges_of(500).len() == 1);
-        assert!(graph.outgoing_edges_of(4).len() == 0);
+        assert!(graph.outgoing_edges_of(4).is_empty());
 
         graph.add_edge(10, 2);
         graph.add_edge(10, 3);
@@ -446,7 +1364,7 @@ mod test_hashgraph {
         graph.add_edge(2, 0);
 
         assert!(graph.incoming_edges_of(1).len() == 1);
-        assert!(graph.incoming_edges_of(4).len() == 0);
+        assert!(graph.incoming_edges_of(4).is_empty());
 
         graph.add_edge(2, 1);
         graph.add_edge(3, 1);
@@ -471,7 +1389,7 @@ mod test_hashgraph {
         graph.add_edge(2, 0);
 
         assert!(graph.incoming_edges_of(1).len() == 1);
-        assert!(graph.incoming_edges_of(4).len() == 0);
+        assert!(graph.incoming_edges_of(4).is_empty());
 
         graph.add_edge(2, 1);
         graph.add_edge(3, 1);
@@ -482,4 +1400,545 @@ mod test_hashgraph {
         assert!(graph.incoming_edges_of(1).len() == 6);
         assert_eq!(graph.incoming_edges_of(1), vec![0, 2, 3, 4, 5, 7]);
     }
+
+    #[test]
+    fn clear_edges_of_test() {
+        let mut graph = HashGraph::new();
+
+        for i in 0..16 {
+            graph.push_node(i);
+        }
+
+        graph.add_edge(5, 1);
+        graph.add_edge(5, 5);
+        graph.add_edge(2, 5);
+        graph.add_edge(2, 3);
+
+        graph.clear_edges_of(5);
+
+        assert!(graph.outgoing_edges_of(5).is_empty());
+        assert!(graph.incoming_edges_of(5).is_empty());
+        assert_eq!(graph.all_edge_pairs(), vec![(2, 3)]);
+    }
+
+    #[test]
+    fn eq_test() {
+        let mut a = HashGraph::with_capacity(16);
+        let mut b = HashGraph::with_capacity(512);
+
+        for i in 0..10 {
+            a.push_node(i);
+            b.push_node(i);
+        }
+
+        a.add_edge(0, 9);
+        a.set_edge((9, 0), 4);
+        b.set_edge((9, 0), 4);
+        b.add_edge(0, 9);
+        b.add_edge(3, 3);
+        b.remove_edge(3, 3);
+
+        assert!(a == b);
+
+        b.set_edge((9, 0), 5);
+        assert!(a != b);
+        b.set_edge((9, 0), 4);
+        b.add_edge(1, 2);
+        assert!(a != b);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip_test() {
+        let mut graph = HashGraph::new();
+
+        for i in 0..16 {
+            graph.push_node(i);
+        }
+
+        graph.add_edge(0, 1);
+        graph.set_edge((3, 2), 17);
+        graph.add_edge(4, 4);
+        graph.remove_edge(4, 4);
+
+        let json = serde_json::to_string(&graph).unwrap();
+        // tombstones are not written out
+        assert!(!json.contains("[4,4"));
+
+        let back: HashGraph = serde_json::from_str(&json).unwrap();
+        assert!(back == graph);
+        assert_eq!(back.get_edge(3, 2).unwrap().weight, 17);
+
+        let mut graph = HashGraph::with_default_weight(4, 0.5f32);
+        graph.push_node(0);
+        graph.add_edge(0, 0);
+        let json = serde_json::to_string(&graph).unwrap();
+        let mut back: WeightedHashGraph<f32> = serde_json::from_str(&json).unwrap();
+        back.push_node(1);
+        back.add_edge(0, 1);
+        assert_eq!(back.get_edge(0, 1).unwrap().weight, 0.5);
+
+        // written before the default weight was part of the format
+        let old = r#"{"node_count":2,"nodes":[5,6],"edges":[[0,1,4]]}"#;
+        let mut back: HashGraph = serde_json::from_str(old).unwrap();
+        assert_eq!(back.get_edge(0, 1).unwrap().weight, 4);
+        back.add_edge(1, 0);
+        assert_eq!(back.get_edge(1, 0).unwrap().weight, 1);
+        let back: HashGraph<u64, f32> = serde_json::from_str(old).unwrap();
+        assert_eq!(back.get_edge(0, 1).unwrap().weight, 4.0);
+    }
+
+    #[test]
+    fn edges_test() {
+        let mut graph = HashGraph::with_capacity(4);
+        for i in 0..10 {
+            graph.push_node(i);
+        }
+
+        graph.set_edge((9, 1), 3);
+        graph.add_edge(0, 4);
+        graph.set_edge((4, 4), 7);
+        graph.add_edge(2, 8);
+        graph.remove_edge(0, 4);
+
+        assert_eq!(graph.all_edge_pairs(), vec![(2, 8), (4, 4), (9, 1)]);
+
+        let mut weighted: Vec<_> = graph
+            .weighted_edges()
+            .map(|edge| (edge.source, edge.destination, *edge.weight))
+            .collect();
+        weighted.sort_unstable();
+        assert_eq!(weighted, vec![(2, 8, 1), (4, 4, 7), (9, 1, 3)]);
+    }
+
+    #[test]
+    fn float_weight_test() {
+        let mut graph = HashGraph::with_default_weight(16, 1.0f32);
+        for i in 0..4 {
+            graph.push_node(i);
+        }
+
+        graph.add_edge(0, 1);
+        graph.set_edge((1, 2), 0.25);
+        graph.set_edge((2, 3), f32::INFINITY);
+
+        assert_eq!(graph.get_edge(0, 1).unwrap().weight, 1.0);
+        assert_eq!(graph.get_edge(1, 2).unwrap().weight, 0.25);
+        assert!(graph.get_edge(2, 3).unwrap().weight.is_infinite());
+        assert!(graph.set_edge((1, 2), 0.5));
+        assert_eq!(graph.get_edge(1, 2).unwrap().weight, 0.5);
+        assert_eq!(graph.outgoing_edges_of(1), vec![2]);
+    }
+
+    #[test]
+    fn struct_weight_test() {
+        #[derive(Clone, Debug, PartialEq)]
+        struct Road {
+            name: String,
+            lanes: u8,
+        }
+
+        let unnamed = Road {
+            name: String::new(),
+            lanes: 1,
+        };
+        let mut graph = HashGraph::with_default_weight(4, unnamed.clone());
+        for i in 0..20 {
+            graph.push_node(i);
+        }
+
+        for i in 0..19 {
+            graph.add_edge(i, i + 1);
+        }
+        let main = Road {
+            name: "main".to_string(),
+            lanes: 4,
+        };
+        graph.set_edge((18, 19), main.clone());
+
+        // the table grew a few times since, the weights move with it
+        assert_eq!(graph.get_edge(18, 19).unwrap().weight, main);
+        assert_eq!(graph.get_edge(3, 4).unwrap().weight, unnamed);
+
+        // and come along when the last node is moved into a hole
+        graph.remove_node(4);
+        assert_eq!(graph.get_edge(18, 4).unwrap().weight, main);
+        assert!(!graph.has_edge(3, 4));
+    }
+
+    #[test]
+    fn degree_test() {
+        use std::collections::BTreeSet;
+
+        let mut graph = HashGraph::with_capacity(8);
+        for i in 0..12 {
+            graph.push_node(i);
+        }
+
+        // a model to check the counters against
+        let mut truth = BTreeSet::new();
+        let check = |graph: &HashGraph, truth: &BTreeSet<(usize, usize)>| {
+            assert_eq!(graph.edge_count(), truth.len());
+            for node in 0..12 {
+                let out = truth.iter().filter(|(from, _)| *from == node).count();
+                let into = truth.iter().filter(|(_, to)| *to == node).count();
+                assert_eq!(graph.out_degree(node), out);
+                assert_eq!(graph.in_degree(node), into);
+            }
+        };
+
+        for step in 0..300 {
+            let from = (step * 7) % 12;
+            let to = (step * 5 + step / 12) % 12;
+            match step % 4 {
+                0 | 1 => {
+                    assert_eq!(graph.add_edge(from, to), !truth.insert((from, to)));
+                }
+                2 => {
+                    assert_eq!(graph.remove_edge(from, to), truth.remove(&(from, to)));
+                }
+                _ => {
+                    // a new weight on an existing edge is not a new edge
+                    assert_eq!(graph.set_edge((from, to), step), !truth.insert((from, to)));
+                }
+            }
+            check(&graph, &truth);
+        }
+
+        graph.update_edge(0, 0, |weight| *weight += 1);
+        graph.clear_edges_of(3);
+        truth.retain(|(from, to)| *from != 3 && *to != 3);
+        check(&graph, &truth);
+        assert_eq!(graph.out_degree(100), 0);
+    }
+
+    #[test]
+    fn compact_test() {
+        let mut graph = HashGraph::with_capacity(16);
+        for i in 0..100 {
+            graph.push_node(i);
+        }
+
+        for from in 0..100 {
+            for to in 0..100 {
+                graph.set_edge((from, to), from * to);
+            }
+        }
+        let full_capacity = graph.edge_table().capacity();
+
+        // keep every edge out of the first node
+        for from in 1..100 {
+            for to in 0..100 {
+                assert!(graph.remove_edge(from, to));
+            }
+        }
+        graph.compact();
+
+        assert!(graph.edge_table().capacity() < full_capacity / 32);
+        assert!(graph.edge_table().capacity().is_power_of_two());
+        assert_eq!(graph.edge_table().len(), 100);
+        for to in 0..100 {
+            assert_eq!(graph.get_edge(0, to).unwrap().weight, 0);
+            assert!(!graph.has_edge(1, to));
+        }
+        assert_eq!(graph.outgoing_edges_of(0).len(), 100);
+        assert_eq!(graph.incoming_edges_of(5), vec![0]);
+
+        graph.add_edge(99, 99);
+        assert!(graph.has_edge(99, 99));
+    }
+
+    #[test]
+    fn validate_test() {
+        let mut graph = HashGraph::with_capacity(16);
+        for i in 0..5 {
+            graph.push_node(i);
+        }
+        graph.add_edge(0, 1);
+        graph.add_edge(1, 2);
+        graph.set_edge((4, 4), 7);
+        assert_eq!(graph.validate(), Ok(()));
+
+        // in the table but missing from one list
+        remove_sorted(&mut graph.out_adj[0], 1);
+        // in a list but not the table
+        insert_sorted(&mut graph.in_adj[3], 2);
+        // in the table only, and to a node that is not there
+        graph.edges.insert((2, 9), 1);
+
+        assert_eq!(
+            graph.validate(),
+            Err(vec![
+                IntegrityError::EdgeOutOfBounds {
+                    from: 2,
+                    to: 9,
+                    count: 5
+                },
+                IntegrityError::AdjacencyMismatch { from: 0, to: 1 },
+                IntegrityError::AdjacencyMismatch { from: 2, to: 3 },
+                IntegrityError::AdjacencyMismatch { from: 2, to: 9 },
+            ])
+        );
+
+        graph.count = 6;
+        assert_eq!(
+            graph.validate().unwrap_err()[0],
+            IntegrityError::NodeCountMismatch {
+                count: 6,
+                values: 5
+            }
+        );
+    }
+
+    #[test]
+    fn remove_node_weights_test() {
+        let mut graph = HashGraph::new();
+        for i in 0..6 {
+            graph.push_node(i * 10);
+        }
+        graph.set_edge((5, 2), 7);
+        graph.set_edge((3, 5), 4);
+
+        // 5 moves into 1 and takes its weights along
+        graph.remove_node(1);
+        assert_eq!(graph.get_edge(1, 2).unwrap().weight, 7);
+        assert_eq!(graph.get_edge(3, 1).unwrap().weight, 4);
+        assert_eq!(graph.node(5), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "node 7 out of bounds for 4 nodes")]
+    fn remove_node_bounds_test() {
+        let mut graph = HashGraph::new();
+        for i in 0..4 {
+            graph.push_node(i);
+        }
+        graph.add_edge(3, 0);
+        graph.remove_node(7);
+    }
+
+    #[test]
+    #[should_panic(expected = "node 5 out of bounds for 3 nodes")]
+    fn add_edge_bounds_test() {
+        let mut graph = HashGraph::new();
+        for i in 0..3 {
+            graph.push_node(i);
+        }
+        graph.add_edge(0, 5);
+    }
+
+    #[test]
+    #[should_panic(expected = "node 3 out of bounds for 3 nodes")]
+    fn edge_entry_bounds_test() {
+        let mut graph: HashGraph<u64> = HashGraph::new();
+        for i in 0..3 {
+            graph.push_node(i);
+        }
+        graph.edge_entry(3, 0).or_default_weight();
+    }
+
+    #[test]
+    fn string_node_test() {
+        let mut graph: HashGraph<String> = HashGraph::with_default_weight(8, 1);
+        for name in &["a", "b", "c", "d"] {
+            graph.push_node(name.to_string());
+        }
+
+        graph.add_edge(0, 1);
+        graph.add_edge(1, 2);
+        graph.set_node(2, "see".to_string());
+        graph.set_edge((2, 3), 5);
+        graph.remove_edge(0, 1);
+        graph.add_edge(3, 0);
+        assert_eq!(graph.get_node(2), "see");
+
+        // d moves into b's place
+        assert_eq!(graph.remove_node(1), "b");
+        assert_eq!(graph.get_node(0), "a");
+        assert_eq!(graph.get_node(1), "d");
+        assert_eq!(graph.get_node(2), "see");
+        assert_eq!(graph.node(3), None);
+        assert_eq!(graph.all_edge_pairs(), vec![(1, 0), (2, 1)]);
+
+        graph.set_node(1, "dee".to_string());
+        graph.add_edge(0, 2);
+        assert_eq!(graph.get_edge(2, 1).unwrap().weight, 5);
+        assert_eq!(graph.get_node(1), "dee");
+        assert_eq!(graph.node(0).map(String::as_str), Some("a"));
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn set_node_bounds_test() {
+        let mut graph = HashGraph::new();
+        graph.push_node(0);
+        graph.set_node(1, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn get_node_bounds_test() {
+        let graph = HashGraph::new();
+        graph.get_node(0);
+    }
+
+    #[test]
+    fn readd_edge_test() {
+        let mut graph = HashGraph::new();
+        for i in 0..3 {
+            graph.push_node(i);
+        }
+
+        graph.add_edge(0, 1);
+        graph.remove_edge(0, 1);
+        assert!(!graph.add_edge(0, 1));
+
+        assert_eq!(graph.outgoing_edges_of(0), vec![1]);
+        assert_eq!(graph.incoming_edges_of(1), vec![0]);
+    }
+
+    #[test]
+    fn collect_test() {
+        let graph: HashGraph = vec![(0, 1), (1, 2), (3, 3)].into_iter().collect();
+        assert_eq!(graph.node_count(), 4);
+        assert_eq!(graph.get_edge(1, 2).unwrap().weight, 1);
+
+        let mut weighted: HashGraph<String, i64> = (0..50)
+            .map(|i| (i, (i + 1) % 50, -(i as i64)))
+            .filter(|(from, _, _)| from % 2 == 0)
+            .collect();
+        assert_eq!(weighted.node_count(), 50);
+        assert_eq!(weighted.get_node(7), "");
+        assert_eq!(weighted.get_edge(48, 49).unwrap().weight, -48);
+        assert!(!weighted.has_edge(1, 2));
+
+        weighted.extend(vec![(48, 49, 5), (60, 0, 2)]);
+        weighted.extend(vec![(1, 2)]);
+        assert_eq!(weighted.node_count(), 61);
+        assert_eq!(weighted.get_edge(48, 49).unwrap().weight, 5);
+        assert_eq!(weighted.get_edge(60, 0).unwrap().weight, 2);
+        assert_eq!(weighted.get_edge(1, 2).unwrap().weight, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "node index usize::MAX can never be a node")]
+    fn extend_overflow_test() {
+        let mut graph = HashGraph::new();
+        graph.extend(vec![(0, usize::MAX)]);
+    }
+
+    #[test]
+    fn merge_nodes_test() {
+        let mut graph = HashGraph::with_capacity(16);
+        for i in 0..8 {
+            graph.push_node(i);
+        }
+        for &(from, to, weight) in &[
+            (0, 2, 1),
+            (0, 3, 1),
+            (5, 0, 1),
+            (1, 3, 7),
+            (1, 4, 7),
+            (6, 1, 7),
+            (5, 1, 7),
+            (0, 1, 9),
+            (1, 1, 8),
+        ] {
+            graph.set_edge((from, to), weight);
+        }
+
+        assert_eq!(graph.merge_nodes(0, 1, true), 3);
+        assert_eq!(graph.outgoing_edges_of(0), vec![0, 2, 3, 4]);
+        assert_eq!(graph.incoming_edges_of(0), vec![0, 5, 6]);
+        assert_eq!(graph.out_degree(1) + graph.in_degree(1), 0);
+
+        // existing edges keep their weight, moved ones bring theirs along
+        assert_eq!(graph.get_edge(0, 3).unwrap().weight, 1);
+        assert_eq!(graph.get_edge(0, 4).unwrap().weight, 7);
+        assert_eq!(graph.get_edge(6, 0).unwrap().weight, 7);
+        assert_eq!(graph.get_edge(0, 0).unwrap().weight, 8);
+        assert_eq!(graph.edge_count(), 6);
+
+        assert_eq!(graph.merge_nodes(2, 0, false), 4);
+        assert_eq!(graph.outgoing_edges_of(2), vec![3, 4]);
+        assert_eq!(graph.incoming_edges_of(2), vec![5, 6]);
+        assert!(!graph.has_edge(2, 2));
+    }
+
+    #[test]
+    fn compact_memory_test() {
+        let wide = HashGraph::with_capacity(1024);
+        let compact = CompactHashGraph::<u64, usize>::with_index_type(1024, 1);
+        assert_eq!(wide.edge_capacity(), compact.edge_capacity());
+
+        // two indices of 8 bytes each down to 4
+        let wide_slot = wide.memory_bytes().edges / wide.edge_capacity();
+        let compact_slot = compact.memory_bytes().edges / compact.edge_capacity();
+        assert_eq!(wide_slot - compact_slot, 2 * (8 - 4));
+        assert!(compact.memory_bytes().total() < wide.memory_bytes().total());
+    }
+
+    #[test]
+    fn compact_round_trip_test() {
+        let node_count = 2000;
+        let mut compact = CompactHashGraph::with_index_type(64, 1);
+        let mut wide = HashGraph::with_capacity(64);
+        for i in 0..node_count {
+            compact.push_node(i as u64);
+            wide.push_node(i as u64);
+        }
+
+        let mut seed = 0x2545_f491_4f6c_dd1d_u64;
+        let mut next = |bound: usize| {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            seed as usize % bound
+        };
+
+        for step in 0..20_000 {
+            let (from, to) = (next(node_count), next(node_count));
+            if step % 5 == 4 {
+                assert_eq!(compact.remove_edge(from, to), wide.remove_edge(from, to));
+            } else {
+                let weight = next(1000);
+                assert_eq!(
+                    compact.set_edge((from, to), weight),
+                    wide.set_edge((from, to), weight)
+                );
+            }
+        }
+
+        assert_eq!(compact.edge_count(), wide.edge_count());
+        assert_eq!(compact.all_edge_pairs(), wide.all_edge_pairs());
+        for (from, to) in wide.all_edge_pairs() {
+            assert_eq!(
+                compact.get_edge(from, to).map(|edge| edge.weight),
+                wide.get_edge(from, to).map(|edge| edge.weight)
+            );
+        }
+        assert_eq!(compact.validate(), Ok(()));
+    }
+
+    #[test]
+    #[should_panic(expected = "node 65536 does not fit the edge table, at most 65535")]
+    fn node_limit_test() {
+        let limit = u16::MAX as usize + 1;
+        let mut graph = HashGraph::<u64, usize, u16>::with_index_type(8, 1);
+        for i in 0..limit {
+            graph.push_node(i as u64);
+        }
+        graph.add_edge(limit - 1, 0);
+        assert!(graph.has_edge(limit - 1, 0));
+
+        assert_eq!(
+            graph.try_push_node(0),
+            Err(GraphError::IndexTooLarge {
+                index: limit,
+                max: u16::MAX as usize
+            })
+        );
+        assert_eq!(graph.node_count(), limit);
+        graph.push_node(0);
+    }
 }
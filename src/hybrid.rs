@@ -0,0 +1,420 @@
+use crate::bit::BitGraph;
+use crate::integrity::IntegrityError;
+use crate::list::ListGraph;
+use crate::memory::MemoryBreakdown;
+use crate::{cover_nodes, EdgeMeta, Graph};
+use std::iter::FromIterator;
+
+/// edges per node pair past which a `HybridGraph` turns into a `BitGraph`,
+/// about where a bit per pair gets cheaper than a list entry per edge
+pub const DEFAULT_DENSITY: f64 = 1.0 / 64.0;
+
+const DEFAULT_CAPACITY: usize = 16;
+
+enum Repr {
+    Sparse(ListGraph<u64, bool>),
+    Dense(BitGraph),
+}
+
+/// Unweighted graph that starts out as a `ListGraph` and moves itself over
+/// to a `BitGraph` once `edge_count / node_count²` goes past the density
+/// threshold. It never goes back on its own, `sparsify` does that.
+pub struct HybridGraph {
+    repr: Repr,
+    edge_count: usize,
+    threshold: f64,
+
+    /// nodes the `BitGraph` has room for, it is rebuilt bigger when full
+    dense_capacity: usize,
+}
+
+impl Default for HybridGraph {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Adds every `(from, to)` pair, pushing nodes valued `0` until both ends are
+/// nodes. Switches to a `BitGraph` on the way if the edges get dense enough.
+impl Extend<(usize, usize)> for HybridGraph {
+    fn extend<I: IntoIterator<Item = (usize, usize)>>(&mut self, iter: I) {
+        for (from, to) in iter {
+            cover_nodes(self, from, to);
+            self.add_edge(from, to);
+        }
+    }
+}
+
+impl FromIterator<(usize, usize)> for HybridGraph {
+    fn from_iter<I: IntoIterator<Item = (usize, usize)>>(iter: I) -> Self {
+        let mut graph = HybridGraph::new();
+        graph.extend(iter);
+        graph
+    }
+}
+
+impl HybridGraph {
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_CAPACITY)
+    }
+
+    pub fn with_capacity(size: usize) -> Self {
+        Self::with_threshold(size, DEFAULT_DENSITY)
+    }
+
+    /// Switches to a `BitGraph` once more than `threshold` of all node pairs
+    /// have an edge, so `threshold` above 1 never switches.
+    pub fn with_threshold(size: usize, threshold: f64) -> Self {
+        Self {
+            repr: Repr::Sparse(ListGraph::with_default_weight(size, true)),
+            edge_count: 0,
+            threshold,
+
+            dense_capacity: 0,
+        }
+    }
+
+    /// whatever the graph in use reports
+    pub fn memory_bytes(&self) -> MemoryBreakdown {
+        match &self.repr {
+            Repr::Sparse(graph) => graph.memory_bytes(),
+            Repr::Dense(graph) => graph.memory_bytes(),
+        }
+    }
+
+    /// whatever the graph in use finds
+    pub fn validate(&self) -> Result<(), Vec<IntegrityError>> {
+        match &self.repr {
+            Repr::Sparse(graph) => graph.validate(),
+            Repr::Dense(graph) => graph.validate(),
+        }
+    }
+
+    /// true once the edges live in a `BitGraph`
+    pub fn is_dense(&self) -> bool {
+        matches!(self.repr, Repr::Dense(_))
+    }
+
+    pub fn threshold(&self) -> f64 {
+        self.threshold
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.edge_count
+    }
+
+    /// fraction of all node pairs, self loops included, that have an edge
+    pub fn density(&self) -> f64 {
+        let count = self.node_count() as f64;
+        if count == 0.0 {
+            0.0
+        } else {
+            self.edge_count as f64 / (count * count)
+        }
+    }
+
+    /// Moves the edges into a `BitGraph`, does nothing if they already are.
+    pub fn densify(&mut self) {
+        if let Repr::Sparse(graph) = &self.repr {
+            let capacity = (graph.node_count() * 2).max(DEFAULT_CAPACITY);
+            self.rebuild_dense(capacity);
+        }
+    }
+
+    /// Moves the edges into a `ListGraph`, does nothing if they already are.
+    pub fn sparsify(&mut self) {
+        if let Repr::Dense(graph) = &self.repr {
+            let count = graph.node_count();
+
+            let mut sparse = ListGraph::with_default_weight(count, true);
+            for node in 0..count {
+                sparse.push_node(*graph.get_node(node));
+            }
+            for (from, to) in graph.all_edge_pairs() {
+                sparse.add_edge(from, to);
+            }
+
+            self.repr = Repr::Sparse(sparse);
+            self.dense_capacity = 0;
+        }
+    }
+
+    fn active(&self) -> &dyn Graph<u64, bool> {
+        match &self.repr {
+            Repr::Sparse(graph) => graph,
+            Repr::Dense(graph) => graph,
+        }
+    }
+
+    fn active_mut(&mut self) -> &mut dyn Graph<u64, bool> {
+        match &mut self.repr {
+            Repr::Sparse(graph) => graph,
+            Repr::Dense(graph) => graph,
+        }
+    }
+
+    /// copies everything into a `BitGraph` with room for `capacity` nodes
+    fn rebuild_dense(&mut self, capacity: usize) {
+        let source = self.active();
+        let count = source.node_count();
+
+        let mut dense = BitGraph::with_capacity(capacity);
+        for node in 0..count {
+            dense.push_node(*source.get_node(node));
+        }
+        dense.add_edges(source.all_edge_pairs());
+
+        self.repr = Repr::Dense(dense);
+        self.dense_capacity = capacity;
+    }
+
+    fn densify_if_needed(&mut self) {
+        if !self.is_dense() && self.density() > self.threshold {
+            self.densify();
+        }
+    }
+}
+
+impl Graph<u64, bool> for HybridGraph {
+    fn add_edge(&mut self, from: usize, to: usize) -> bool {
+        let had_edge = self.active_mut().add_edge(from, to);
+        if !had_edge {
+            self.edge_count += 1;
+            self.densify_if_needed();
+        }
+
+        had_edge
+    }
+
+    /// `false` removes the edge
+    fn set_edge(&mut self, from_to: (usize, usize), weight: bool) -> bool {
+        if weight {
+            self.add_edge(from_to.0, from_to.1)
+        } else {
+            self.remove_edge(from_to.0, from_to.1)
+        }
+    }
+
+    fn remove_edge(&mut self, from: usize, to: usize) -> bool {
+        let had_edge = self.active_mut().remove_edge(from, to);
+        if had_edge {
+            self.edge_count -= 1;
+        }
+
+        had_edge
+    }
+
+    fn clear_edges_of(&mut self, node_index: usize) {
+        let graph = self.active_mut();
+        let removed = graph.outgoing_edges_of(node_index).len()
+            + graph
+                .incoming_edges_of(node_index)
+                .into_iter()
+                .filter(|from| *from != node_index)
+                .count();
+
+        graph.clear_edges_of(node_index);
+        self.edge_count -= removed;
+    }
+
+    fn clear_edges(&mut self) {
+        self.active_mut().clear_edges();
+        self.edge_count = 0;
+    }
+
+    /// stays dense if it was, keeping the `BitGraph` allocated
+    fn clear(&mut self) {
+        self.active_mut().clear();
+        self.edge_count = 0;
+    }
+
+    fn has_edge(&self, from: usize, to: usize) -> bool {
+        self.active().has_edge(from, to)
+    }
+
+    fn get_edge(&self, from: usize, to: usize) -> Option<EdgeMeta<bool>> {
+        self.active().get_edge(from, to)
+    }
+
+    fn outgoing_edges_of(&self, node_index: usize) -> Vec<usize> {
+        self.active().outgoing_edges_of(node_index)
+    }
+
+    fn neighbors_sorted(&self, node_index: usize) -> Vec<usize> {
+        self.active().neighbors_sorted(node_index)
+    }
+
+    fn incoming_edges_of(&self, node_index: usize) -> Vec<usize> {
+        self.active().incoming_edges_of(node_index)
+    }
+
+    fn outgoing_edges_into(&self, node_index: usize, out: &mut Vec<usize>) {
+        self.active().outgoing_edges_into(node_index, out);
+    }
+
+    fn incoming_edges_into(&self, node_index: usize, out: &mut Vec<usize>) {
+        self.active().incoming_edges_into(node_index, out);
+    }
+
+    fn edges(&self) -> Box<dyn Iterator<Item = EdgeMeta<bool>> + '_> {
+        self.active().edges()
+    }
+
+    fn all_edge_pairs(&self) -> Vec<(usize, usize)> {
+        self.active().all_edge_pairs()
+    }
+
+    /// a full `BitGraph` is copied into one twice the size first
+    fn push_node(&mut self, value: u64) -> usize {
+        if self.is_dense() && self.node_count() == self.dense_capacity {
+            self.rebuild_dense(self.dense_capacity * 2);
+        }
+
+        self.active_mut().push_node(value)
+    }
+
+    fn set_node(&mut self, node_index: usize, value: u64) {
+        self.active_mut().set_node(node_index, value);
+    }
+
+    fn get_node(&self, node_index: usize) -> &u64 {
+        self.active().get_node(node_index)
+    }
+
+    /// Swap removes like `ListGraph::remove_node`, in whichever form the
+    /// graph is in
+    fn remove_node(&mut self, node_index: usize) -> u64 {
+        self.clear_edges_of(node_index);
+        self.active_mut().remove_node(node_index)
+    }
+
+    fn node_count(&self) -> usize {
+        self.active().node_count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn switch_test() {
+        let mut graph = HybridGraph::with_threshold(32, 0.05);
+        for i in 0..32 {
+            graph.push_node(i * 3);
+        }
+
+        // 51 of 1024 pairs is just under 5%
+        let edges: Vec<(usize, usize)> = (0..51)
+            .map(|i| (i % 32, (i % 32 + i / 32 + 1) % 32))
+            .collect();
+        for &(from, to) in &edges {
+            assert!(!graph.add_edge(from, to));
+        }
+        assert_eq!(graph.edge_count(), 51);
+        assert!(!graph.is_dense());
+
+        let before = graph.all_edge_pairs();
+        assert!(!graph.add_edge(31, 31));
+        assert!(graph.is_dense());
+
+        let mut expected = before;
+        expected.push((31, 31));
+        expected.sort_unstable();
+        assert_eq!(graph.all_edge_pairs(), expected);
+        assert_eq!(*graph.get_node(5), 15);
+        assert!(graph.add_edge(31, 31));
+        assert_eq!(graph.edge_count(), 52);
+    }
+
+    #[test]
+    fn explicit_switch_test() {
+        let mut graph = HybridGraph::new();
+        for i in 0..10 {
+            graph.push_node(i);
+        }
+        graph.add_edge(0, 9);
+        graph.add_edge(9, 0);
+        assert!(graph.is_dense());
+
+        graph.sparsify();
+        assert!(!graph.is_dense());
+        assert_eq!(graph.all_edge_pairs(), vec![(0, 9), (9, 0)]);
+        assert_eq!(graph.incoming_edges_of(0), vec![9]);
+
+        graph.densify();
+        assert!(graph.is_dense());
+        assert_eq!(graph.outgoing_edges_of(9), vec![0]);
+        assert_eq!(graph.edge_count(), 2);
+    }
+
+    #[test]
+    fn dense_growth_test() {
+        let mut graph = HybridGraph::with_threshold(4, 0.0);
+        for i in 0..4 {
+            graph.push_node(i);
+        }
+        graph.add_edge(3, 0);
+        assert!(graph.is_dense());
+
+        // past the capacity the BitGraph was built with
+        for i in 4..100 {
+            graph.push_node(i as u64);
+            graph.add_edge(i, i - 1);
+        }
+
+        assert_eq!(graph.node_count(), 100);
+        assert_eq!(graph.edge_count(), 97);
+        assert!(graph.has_edge(3, 0));
+        assert!(graph.has_edge(99, 98));
+        assert_eq!(graph.incoming_edges_of(50), vec![51]);
+        assert_eq!(*graph.get_node(77), 77);
+    }
+
+    #[test]
+    fn remove_test() {
+        let mut graph = HybridGraph::with_threshold(4, 0.0);
+        for i in 0..4 {
+            graph.push_node(i * 10);
+        }
+        graph.add_edge(0, 1);
+        graph.add_edge(1, 1);
+        graph.add_edge(3, 2);
+        graph.add_edge(1, 3);
+
+        graph.clear_edges_of(2);
+        assert_eq!(graph.edge_count(), 3);
+
+        // 3 moves into 1
+        assert_eq!(graph.remove_node(1), 10);
+        assert!(graph.is_dense());
+        assert_eq!(graph.node_count(), 3);
+        assert_eq!(*graph.get_node(1), 30);
+        assert!(graph.all_edge_pairs().is_empty());
+        assert_eq!(graph.edge_count(), 0);
+
+        graph.set_node(2, 5);
+        assert_eq!(*graph.get_node(2), 5);
+    }
+
+    #[test]
+    fn collect_test() {
+        // the first few edges are among few nodes, so dense right away
+        let graph: HybridGraph = (0..100).map(|i| (i, (i + 1) % 100)).collect();
+        assert_eq!(graph.node_count(), 100);
+        assert_eq!(graph.edge_count(), 100);
+        assert!(graph.is_dense());
+
+        let mut graph = HybridGraph::with_threshold(100, 0.02);
+        for _ in 0..100 {
+            graph.push_node(0);
+        }
+        graph.extend((0..100).map(|i| (i, (i + 1) % 100)));
+        assert!(!graph.is_dense());
+
+        graph.extend((0..100).flat_map(|i| vec![(i, (i + 2) % 100), (i, i)]));
+        assert!(graph.is_dense());
+        assert_eq!(graph.edge_count(), 300);
+        assert_eq!(graph.outgoing_edges_of(99), vec![0, 1, 99]);
+    }
+}
@@ -0,0 +1,151 @@
+use crate::{EdgeMeta, Graph};
+use std::fmt;
+
+/// A node's position in a graph, kept apart from weights and counts so the
+/// two can not be mixed up. Converts to and from `usize` both ways.
+#[repr(transparent)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NodeIndex(pub usize);
+
+impl NodeIndex {
+    #[inline]
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+impl From<usize> for NodeIndex {
+    #[inline]
+    fn from(index: usize) -> Self {
+        NodeIndex(index)
+    }
+}
+
+impl From<NodeIndex> for usize {
+    #[inline]
+    fn from(index: NodeIndex) -> Self {
+        index.0
+    }
+}
+
+impl fmt::Display for NodeIndex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+fn typed(indices: Vec<usize>) -> Vec<NodeIndex> {
+    indices.into_iter().map(NodeIndex).collect()
+}
+
+/// `NodeIndex` versions of the `Graph` methods, implemented for every graph.
+/// Each one does the same as the `usize` method it is named after.
+pub trait TypedGraph<T, W>: Graph<T, W> {
+    fn add_edge_ix(&mut self, from: NodeIndex, to: NodeIndex) -> bool {
+        self.add_edge(from.0, to.0)
+    }
+
+    fn set_edge_ix(&mut self, from: NodeIndex, to: NodeIndex, weight: W) -> bool {
+        self.set_edge((from.0, to.0), weight)
+    }
+
+    fn remove_edge_ix(&mut self, from: NodeIndex, to: NodeIndex) -> bool {
+        self.remove_edge(from.0, to.0)
+    }
+
+    fn has_edge_ix(&self, from: NodeIndex, to: NodeIndex) -> bool {
+        self.has_edge(from.0, to.0)
+    }
+
+    fn get_edge_ix(&self, from: NodeIndex, to: NodeIndex) -> Option<EdgeMeta<W>> {
+        self.get_edge(from.0, to.0)
+    }
+
+    fn outgoing_edges_of_ix(&self, node: NodeIndex) -> Vec<NodeIndex> {
+        typed(self.outgoing_edges_of(node.0))
+    }
+
+    fn incoming_edges_of_ix(&self, node: NodeIndex) -> Vec<NodeIndex> {
+        typed(self.incoming_edges_of(node.0))
+    }
+
+    fn push_node_ix(&mut self, value: T) -> NodeIndex {
+        NodeIndex(self.push_node(value))
+    }
+
+    fn set_node_ix(&mut self, node: NodeIndex, value: T) {
+        self.set_node(node.0, value)
+    }
+
+    fn get_node_ix(&self, node: NodeIndex) -> &T {
+        self.get_node(node.0)
+    }
+
+    fn remove_node_ix(&mut self, node: NodeIndex) -> T {
+        self.remove_node(node.0)
+    }
+
+    /// every node index in order
+    fn node_indices(&self) -> Box<dyn Iterator<Item = NodeIndex>> {
+        Box::new((0..self.node_count()).map(NodeIndex))
+    }
+}
+
+impl<T, W, G: Graph<T, W> + ?Sized> TypedGraph<T, W> for G {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bit::BitGraph;
+    use crate::hash::HashGraph;
+    use crate::search::bfs::BFS;
+    use crate::search::Pathfinder;
+
+    #[test]
+    fn typed_test() {
+        let mut graph = HashGraph::new();
+        let a = graph.push_node_ix(10);
+        let b = graph.push_node_ix(20);
+        let c = graph.push_node_ix(30);
+
+        assert!(!graph.add_edge_ix(a, b));
+        graph.set_edge_ix(b, c, 7);
+        assert!(graph.has_edge_ix(a, b));
+        assert_eq!(graph.get_edge_ix(b, c).unwrap().weight, 7);
+        assert_eq!(graph.outgoing_edges_of_ix(b), vec![c]);
+        assert_eq!(graph.incoming_edges_of_ix(b), vec![a]);
+        assert_eq!(*graph.get_node_ix(c), 30);
+        assert_eq!(graph.node_indices().collect::<Vec<_>>(), vec![a, b, c]);
+
+        // the usize api still works on the same graph
+        assert!(graph.has_edge(0, 1));
+        assert!(graph.remove_edge(a.into(), b.index()));
+        assert!(!graph.has_edge_ix(a, b));
+        assert_eq!(graph.remove_node_ix(NodeIndex::from(0)), 10);
+    }
+
+    #[test]
+    fn typed_path_test() {
+        let mut graph = BitGraph::new();
+        for i in 0..6 {
+            graph.push_node(i);
+        }
+        graph.add_edges(vec![(0, 1), (1, 2), (2, 5), (0, 3)]);
+
+        let dyn_graph: &dyn Graph<u64, bool> = &graph;
+        let mut bfs = BFS::new(dyn_graph, 0);
+        let path = bfs.path_to_ix(dyn_graph, NodeIndex(5)).unwrap();
+        assert_eq!(
+            path,
+            vec![NodeIndex(0), NodeIndex(1), NodeIndex(2), NodeIndex(5)]
+        );
+        assert_eq!(
+            Pathfinder::<u64, bool>::from_index_of_ix(&bfs, NodeIndex(5)),
+            NodeIndex(2)
+        );
+        assert_eq!(
+            BFS::new(&graph, 0).path_to(&graph, 5),
+            Some(path.into_iter().map(usize::from).collect())
+        );
+    }
+}
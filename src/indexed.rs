@@ -0,0 +1,326 @@
+use crate::{EdgeMeta, Graph};
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Wraps a graph with a map from node value to index for constant time
+/// `index_of`, kept up to date through the node methods of `Graph`. Values
+/// do not have to be unique.
+pub struct IndexedGraph<G, T> {
+    graph: G,
+    /// every index holding each value, ascending
+    lookup: HashMap<T, Vec<usize>>,
+}
+
+impl<G, T: Hash + Eq + Clone> IndexedGraph<G, T> {
+    /// indexes every node already in `graph`
+    pub fn new<W>(graph: G) -> Self
+    where
+        G: Graph<T, W>,
+    {
+        let mut indexed = Self {
+            graph,
+            lookup: HashMap::new(),
+        };
+        indexed.reindex();
+        indexed
+    }
+
+    pub fn inner(&self) -> &G {
+        &self.graph
+    }
+
+    pub fn into_inner(self) -> G {
+        self.graph
+    }
+
+    /// lowest index of a node valued `value`
+    pub fn index_of(&self, value: &T) -> Option<usize> {
+        self.lookup.get(value)?.first().copied()
+    }
+
+    /// every index of a node valued `value`, ascending
+    pub fn indices_of(&self, value: &T) -> &[usize] {
+        self.lookup.get(value).map_or(&[], Vec::as_slice)
+    }
+
+    /// rebuilds the map from scratch
+    fn reindex<W>(&mut self)
+    where
+        G: Graph<T, W>,
+    {
+        self.lookup.clear();
+        for node in 0..self.graph.node_count() {
+            let value = self.graph.get_node(node).clone();
+            self.lookup.entry(value).or_default().push(node);
+        }
+    }
+
+    fn insert(&mut self, value: T, node: usize) {
+        let list = self.lookup.entry(value).or_default();
+        if let Err(position) = list.binary_search(&node) {
+            list.insert(position, node);
+        }
+    }
+
+    fn remove(&mut self, value: &T, node: usize) {
+        if let Some(list) = self.lookup.get_mut(value) {
+            if let Ok(position) = list.binary_search(&node) {
+                list.remove(position);
+            }
+            if list.is_empty() {
+                self.lookup.remove(value);
+            }
+        }
+    }
+}
+
+impl<T, W, G> Graph<T, W> for IndexedGraph<G, T>
+where
+    T: Hash + Eq + Clone,
+    G: Graph<T, W>,
+{
+    fn add_edge(&mut self, from: usize, to: usize) -> bool {
+        self.graph.add_edge(from, to)
+    }
+
+    fn set_edge(&mut self, from_to: (usize, usize), weight: W) -> bool {
+        self.graph.set_edge(from_to, weight)
+    }
+
+    fn remove_edge(&mut self, from: usize, to: usize) -> bool {
+        self.graph.remove_edge(from, to)
+    }
+
+    fn clear_edges_of(&mut self, node_index: usize) {
+        self.graph.clear_edges_of(node_index);
+    }
+
+    fn clear_edges(&mut self) {
+        self.graph.clear_edges();
+    }
+
+    fn clear(&mut self) {
+        self.graph.clear();
+        self.lookup.clear();
+    }
+
+    fn has_edge(&self, from: usize, to: usize) -> bool {
+        self.graph.has_edge(from, to)
+    }
+
+    fn get_edge(&self, from: usize, to: usize) -> Option<EdgeMeta<W>> {
+        self.graph.get_edge(from, to)
+    }
+
+    fn outgoing_edges_of(&self, node_index: usize) -> Vec<usize> {
+        self.graph.outgoing_edges_of(node_index)
+    }
+
+    fn neighbors_sorted(&self, node_index: usize) -> Vec<usize> {
+        self.graph.neighbors_sorted(node_index)
+    }
+
+    fn incoming_edges_of(&self, node_index: usize) -> Vec<usize> {
+        self.graph.incoming_edges_of(node_index)
+    }
+
+    fn outgoing_edges_into(&self, node_index: usize, out: &mut Vec<usize>) {
+        self.graph.outgoing_edges_into(node_index, out);
+    }
+
+    fn incoming_edges_into(&self, node_index: usize, out: &mut Vec<usize>) {
+        self.graph.incoming_edges_into(node_index, out);
+    }
+
+    fn edges(&self) -> Box<dyn Iterator<Item = EdgeMeta<W>> + '_> {
+        self.graph.edges()
+    }
+
+    fn all_edge_pairs(&self) -> Vec<(usize, usize)> {
+        self.graph.all_edge_pairs()
+    }
+
+    fn push_node(&mut self, value: T) -> usize {
+        let node = self.graph.push_node(value.clone());
+        self.insert(value, node);
+        node
+    }
+
+    fn set_node(&mut self, node_index: usize, value: T) {
+        let old = self.graph.get_node(node_index).clone();
+        self.graph.set_node(node_index, value.clone());
+
+        self.remove(&old, node_index);
+        self.insert(value, node_index);
+    }
+
+    fn get_node(&self, node_index: usize) -> &T {
+        self.graph.get_node(node_index)
+    }
+
+    /// Follows whatever the wrapped graph does with the other indices. A
+    /// swap remove costs two map updates, anything else rebuilds the map.
+    fn remove_node(&mut self, node_index: usize) -> T {
+        let last = self.graph.node_count() - 1;
+        let value = self.graph.remove_node(node_index);
+        self.remove(&value, node_index);
+
+        if node_index == last && self.graph.node_count() == last {
+            return value;
+        }
+
+        let swapped = self.graph.node_count() == last
+            && self
+                .indices_of(self.graph.get_node(node_index))
+                .contains(&last);
+
+        if swapped {
+            let moved = self.graph.get_node(node_index).clone();
+            self.remove(&moved, last);
+            self.insert(moved, node_index);
+        } else {
+            self.reindex();
+        }
+
+        value
+    }
+
+    fn node_count(&self) -> usize {
+        self.graph.node_count()
+    }
+
+    fn resize_nodes(&mut self, count: usize, fill: T) {
+        self.graph.resize_nodes(count, fill);
+        self.reindex();
+    }
+
+    fn reserve_nodes(&mut self, additional: usize) {
+        self.graph.reserve_nodes(additional);
+    }
+
+    fn find_node_by_value(&self, value: &T) -> Option<usize> {
+        self.index_of(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::HashGraph;
+    use crate::list::ListGraph;
+
+    fn names() -> Vec<String> {
+        ["ant", "bee", "cat", "dog", "eel"]
+            .iter()
+            .map(|name| name.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn find_node_test() {
+        let mut graph: HashGraph<String> = HashGraph::with_default_weight(8, 1);
+        for name in names() {
+            graph.push_node(name);
+        }
+
+        assert_eq!(graph.find_node(|name| name.starts_with('c')), Some(2));
+        assert_eq!(graph.find_node_by_value(&"eel".to_string()), Some(4));
+        assert_eq!(graph.find_node_by_value(&"fox".to_string()), None);
+
+        // eel moves into bee's place
+        graph.remove_node(1);
+        assert_eq!(graph.find_node_by_value(&"eel".to_string()), Some(1));
+        assert_eq!(graph.find_node(|name| name == "bee"), None);
+
+        let dyn_graph: &dyn Graph<String, usize> = &graph;
+        assert_eq!(dyn_graph.find_node_by_value(&"dog".to_string()), Some(3));
+        assert_eq!(dyn_graph.node_values().count(), 4);
+    }
+
+    #[test]
+    fn indexed_test() {
+        let mut graph = IndexedGraph::new(ListGraph::with_default_weight(8, 1));
+        for name in names() {
+            graph.push_node(name);
+        }
+        graph.add_edge(4, 0);
+
+        let id = |name: &str| name.to_string();
+        assert_eq!(graph.index_of(&id("cat")), Some(2));
+
+        // eel moves into bee's place and takes its edge along
+        assert_eq!(graph.remove_node(1), "bee");
+        assert_eq!(graph.index_of(&id("bee")), None);
+        assert_eq!(graph.index_of(&id("eel")), Some(1));
+        assert!(graph.has_edge(1, 0));
+
+        graph.set_node(2, id("ant"));
+        assert_eq!(graph.indices_of(&id("ant")), &[0, 2]);
+        assert_eq!(graph.index_of(&id("cat")), None);
+
+        // the last node goes without moving anything
+        graph.remove_node(3);
+        assert_eq!(graph.index_of(&id("dog")), None);
+        assert_eq!(graph.find_node_by_value(&id("eel")), Some(1));
+
+        graph.push_node(id("fox"));
+        assert_eq!(graph.index_of(&id("fox")), Some(3));
+        for node in 0..graph.node_count() {
+            assert!(graph.indices_of(graph.get_node(node)).contains(&node));
+        }
+    }
+
+    /// removes by shifting every later node down, the way `Vec::remove` does
+    struct Shifting(Vec<u64>);
+
+    impl Graph<u64, ()> for Shifting {
+        fn add_edge(&mut self, _: usize, _: usize) -> bool {
+            false
+        }
+        fn set_edge(&mut self, _: (usize, usize), _: ()) -> bool {
+            false
+        }
+        fn remove_edge(&mut self, _: usize, _: usize) -> bool {
+            false
+        }
+        fn has_edge(&self, _: usize, _: usize) -> bool {
+            false
+        }
+        fn get_edge(&self, _: usize, _: usize) -> Option<EdgeMeta<()>> {
+            None
+        }
+        fn outgoing_edges_of(&self, _: usize) -> Vec<usize> {
+            Vec::new()
+        }
+        fn incoming_edges_of(&self, _: usize) -> Vec<usize> {
+            Vec::new()
+        }
+        fn push_node(&mut self, value: u64) -> usize {
+            self.0.push(value);
+            self.0.len() - 1
+        }
+        fn set_node(&mut self, node_index: usize, value: u64) {
+            self.0[node_index] = value;
+        }
+        fn get_node(&self, node_index: usize) -> &u64 {
+            &self.0[node_index]
+        }
+        fn remove_node(&mut self, node_index: usize) -> u64 {
+            self.0.remove(node_index)
+        }
+        fn node_count(&self) -> usize {
+            self.0.len()
+        }
+    }
+
+    #[test]
+    fn shifting_removal_test() {
+        let mut graph = IndexedGraph::new(Shifting(vec![10, 20, 30, 40]));
+        assert_eq!(graph.index_of(&40), Some(3));
+
+        graph.remove_node(1);
+        assert_eq!(graph.index_of(&20), None);
+        assert_eq!(graph.index_of(&30), Some(1));
+        assert_eq!(graph.index_of(&40), Some(2));
+    }
+}
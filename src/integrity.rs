@@ -0,0 +1,108 @@
+use std::fmt;
+
+/// One inconsistency between the parts of a graph, found by the `validate`
+/// methods. Every graph keeps its edges in more than one place, a matrix
+/// and its transpose or a table and neighbour lists, and those have to agree.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum IntegrityError {
+    /// the graph counts `count` nodes but holds `values` node values
+    NodeCountMismatch { count: usize, values: usize },
+    /// an edge, or a bit set in a matrix, with an end that is not one of the
+    /// `count` nodes
+    EdgeOutOfBounds {
+        from: usize,
+        to: usize,
+        count: usize,
+    },
+    /// the edge is in the matrix but not in its transpose, the other way
+    /// around, or has a different weight in each
+    TransposeMismatch { from: usize, to: usize },
+    /// the edge store and the neighbour lists disagree on the edge
+    AdjacencyMismatch { from: usize, to: usize },
+}
+
+impl fmt::Display for IntegrityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IntegrityError::NodeCountMismatch { count, values } => {
+                write!(f, "{} nodes counted but {} values stored", count, values)
+            }
+            IntegrityError::EdgeOutOfBounds { from, to, count } => {
+                write!(
+                    f,
+                    "edge ({}, {}) out of bounds for {} nodes",
+                    from, to, count
+                )
+            }
+            IntegrityError::TransposeMismatch { from, to } => {
+                write!(f, "edge ({}, {}) differs from its transpose", from, to)
+            }
+            IntegrityError::AdjacencyMismatch { from, to } => {
+                write!(
+                    f,
+                    "edge ({}, {}) differs from the neighbour lists",
+                    from, to
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for IntegrityError {}
+
+/// `Ok` for no errors, otherwise all of them sorted and without repeats
+pub(crate) fn into_result(mut errors: Vec<IntegrityError>) -> Result<(), Vec<IntegrityError>> {
+    if errors.is_empty() {
+        return Ok(());
+    }
+
+    errors.sort();
+    errors.dedup();
+    Err(errors)
+}
+
+/// `NodeCountMismatch` unless `count` and `values` agree
+pub(crate) fn check_count(count: usize, values: usize, errors: &mut Vec<IntegrityError>) {
+    if count != values {
+        errors.push(IntegrityError::NodeCountMismatch { count, values });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_test() {
+        let errors = vec![
+            IntegrityError::NodeCountMismatch {
+                count: 3,
+                values: 2,
+            },
+            IntegrityError::EdgeOutOfBounds {
+                from: 0,
+                to: 9,
+                count: 3,
+            },
+            IntegrityError::TransposeMismatch { from: 1, to: 2 },
+            IntegrityError::AdjacencyMismatch { from: 2, to: 1 },
+        ];
+
+        let messages: Vec<String> = errors.iter().map(|error| error.to_string()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "3 nodes counted but 2 values stored",
+                "edge (0, 9) out of bounds for 3 nodes",
+                "edge (1, 2) differs from its transpose",
+                "edge (2, 1) differs from the neighbour lists",
+            ]
+        );
+
+        // sorted by variant, then fields, repeats dropped
+        let mut doubled = errors.clone();
+        doubled.extend(errors.iter().rev().cloned());
+        assert_eq!(into_result(doubled), Err(errors));
+        assert_eq!(into_result(Vec::new()), Ok(()));
+    }
+}
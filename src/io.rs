@@ -0,0 +1,548 @@
+use crate::bit::BitGraph;
+use crate::hash::HashGraph;
+use crate::Graph;
+use std::fmt;
+use std::io::{self, BufRead, Write};
+
+/// error from reading an edge list, `line` is 1 based
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub kind: ParseErrorKind,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    /// a field that should have been a non negative integer
+    InvalidNumber(String),
+    /// a line with a source but no destination
+    MissingDestination,
+    /// more than `from to weight` on a line
+    TooManyFields,
+    /// an index at or past the node count that was given up front
+    NodeOutOfBounds { index: usize, count: usize },
+    /// the reader failed, invalid UTF-8 included
+    Io {
+        kind: io::ErrorKind,
+        message: String,
+    },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match &self.kind {
+            ParseErrorKind::InvalidNumber(field) => write!(f, "invalid number `{}`", field),
+            ParseErrorKind::MissingDestination => write!(f, "missing destination"),
+            ParseErrorKind::TooManyFields => write!(f, "expected `from to [weight]`"),
+            ParseErrorKind::NodeOutOfBounds { index, count } => {
+                write!(f, "node {} out of bounds for {} nodes", index, count)
+            }
+            ParseErrorKind::Io { message, .. } => write!(f, "read failed: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// one parsed `from to [weight]` line
+pub type ParsedEdge = (usize, usize, Option<usize>);
+
+/// Parses one line, `None` for blank lines and `#` comments.
+fn parse_line(line: &str, number: usize) -> Result<Option<ParsedEdge>, ParseError> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return Ok(None);
+    }
+
+    let error = |kind| ParseError { line: number, kind };
+    let number_of = |field: &str| {
+        field
+            .parse::<usize>()
+            .map_err(|_| error(ParseErrorKind::InvalidNumber(field.to_string())))
+    };
+
+    let mut fields = line.split_whitespace();
+    let from = number_of(fields.next().expect("a trimmed non empty line has a field"))?;
+    let to = number_of(
+        fields
+            .next()
+            .ok_or_else(|| error(ParseErrorKind::MissingDestination))?,
+    )?;
+    let weight = fields.next().map(number_of).transpose()?;
+
+    if fields.next().is_some() {
+        return Err(error(ParseErrorKind::TooManyFields));
+    }
+
+    Ok(Some((from, to, weight)))
+}
+
+/// parses every line along with its 1 based line number
+fn parse_lines(input: &str) -> impl Iterator<Item = Result<(usize, ParsedEdge), ParseError>> + '_ {
+    input.lines().enumerate().filter_map(|(i, line)| {
+        parse_line(line, i + 1)
+            .map(|edge| edge.map(|edge| (i + 1, edge)))
+            .transpose()
+    })
+}
+
+/// First pass over the input, validates every line and returns the number of
+/// nodes needed to hold the largest index and the number of edges.
+fn scan(input: &str) -> Result<(usize, usize), ParseError> {
+    let mut node_count = 0;
+    let mut edge_count = 0;
+
+    for edge in parse_lines(input) {
+        let (_, (from, to, _)) = edge?;
+        node_count = node_count.max(from + 1).max(to + 1);
+        edge_count += 1;
+    }
+
+    Ok((node_count, edge_count))
+}
+
+/// `NodeOutOfBounds` for the first of `from` and `to` at or past `node_count`
+fn check_bounds(from: usize, to: usize, node_count: usize, line: usize) -> Result<(), ParseError> {
+    match [from, to]
+        .iter()
+        .copied()
+        .find(|index| *index >= node_count)
+    {
+        Some(index) => Err(ParseError {
+            line,
+            kind: ParseErrorKind::NodeOutOfBounds {
+                index,
+                count: node_count,
+            },
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Reads a whitespace separated `from to [weight]` edge list, the format used
+/// by the SNAP datasets, into a `BitGraph`. Blank lines and lines starting
+/// with `#` are skipped and weights are checked but dropped. The graph is
+/// sized to the largest index seen, which takes a first pass over the input.
+/// Node values are their indices.
+pub fn parse_edge_list(input: &str) -> Result<BitGraph, ParseError> {
+    let (node_count, _) = scan(input)?;
+    parse_edge_list_with_node_count(input, node_count)
+}
+
+/// Single pass version of `parse_edge_list` for when the node count is
+/// already known, indices at or past it are an error.
+pub fn parse_edge_list_with_node_count(
+    input: &str,
+    node_count: usize,
+) -> Result<BitGraph, ParseError> {
+    let mut graph = BitGraph::from_nodes(0..node_count as u64);
+
+    for edge in parse_lines(input) {
+        let (line, (from, to, _)) = edge?;
+        check_bounds(from, to, node_count, line)?;
+        graph.add_edge(from, to);
+    }
+
+    Ok(graph)
+}
+
+/// Same format as `parse_edge_list` but keeps the weights in a `HashGraph`,
+/// edges without one get weight 1.
+pub fn parse_weighted_edge_list(input: &str) -> Result<HashGraph, ParseError> {
+    let (node_count, edge_count) = scan(input)?;
+
+    let mut graph = HashGraph::with_capacity(node_count.max(edge_count));
+    for i in 0..node_count {
+        graph.push_node(i as u64);
+    }
+
+    for edge in parse_lines(input) {
+        let (_, (from, to, weight)) = edge?;
+        graph.set_edge((from, to), weight.unwrap_or(1));
+    }
+
+    Ok(graph)
+}
+
+/// Reads `from to [weight]` lines one at a time from `reader`, in the same
+/// format as `parse_edge_list`, so only the current line is ever held in
+/// memory. Each edge comes with its 1 based line number, skipped lines
+/// counted, for reporting problems found later on. Trailing whitespace and
+/// `\r\n` endings are fine.
+///
+/// A bad line is reported and reading carries on with the next one. A
+/// failed read is reported once and ends the iterator.
+pub fn read_edges_streaming<R: BufRead>(
+    reader: R,
+) -> impl Iterator<Item = Result<(usize, ParsedEdge), ParseError>> {
+    EdgeStream {
+        reader,
+        line: String::new(),
+        number: 0,
+        done: false,
+    }
+}
+
+struct EdgeStream<R> {
+    reader: R,
+    /// reused for every line
+    line: String,
+    number: usize,
+    done: bool,
+}
+
+impl<R: BufRead> Iterator for EdgeStream<R> {
+    type Item = Result<(usize, ParsedEdge), ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            self.line.clear();
+            self.number += 1;
+
+            match self.reader.read_line(&mut self.line) {
+                Ok(0) => self.done = true,
+                Ok(_) => {
+                    let number = self.number;
+                    if let Some(edge) = parse_line(&self.line, number).transpose() {
+                        return Some(edge.map(|edge| (number, edge)));
+                    }
+                }
+                Err(error) => {
+                    self.done = true;
+                    return Some(Err(ParseError {
+                        line: self.number,
+                        kind: ParseErrorKind::Io {
+                            kind: error.kind(),
+                            message: error.to_string(),
+                        },
+                    }));
+                }
+            }
+        }
+
+        None
+    }
+}
+
+/// pushes nodes valued by their index until `index` is one
+fn cover_index<W>(graph: &mut dyn Graph<u64, W>, index: usize) {
+    while graph.node_count() <= index {
+        graph.push_node(graph.node_count() as u64);
+    }
+}
+
+impl BitGraph {
+    /// Builds a graph of `node_count` nodes straight from
+    /// `read_edges_streaming` or anything else yielding parsed edges,
+    /// stopping at the first error. Like `parse_edge_list_with_node_count`,
+    /// indices at or past the count are an error rather than growing the
+    /// matrices, reported with the line number the edge came with. Node
+    /// values are their indices and weights are dropped.
+    pub fn from_edge_stream<I>(node_count: usize, edges: I) -> Result<BitGraph, ParseError>
+    where
+        I: IntoIterator<Item = Result<(usize, ParsedEdge), ParseError>>,
+    {
+        let mut graph = BitGraph::from_nodes(0..node_count as u64);
+
+        for edge in edges {
+            let (line, (from, to, _)) = edge?;
+            check_bounds(from, to, node_count, line)?;
+            graph.add_edge(from, to);
+        }
+
+        Ok(graph)
+    }
+}
+
+impl HashGraph {
+    /// `BitGraph::from_edge_stream` keeping the weights, edges without one
+    /// get weight 1. The graph grows to the largest index seen.
+    pub fn from_edge_stream<I>(edges: I) -> Result<HashGraph, ParseError>
+    where
+        I: IntoIterator<Item = Result<(usize, ParsedEdge), ParseError>>,
+    {
+        let mut graph = HashGraph::new();
+
+        for edge in edges {
+            let (_, (from, to, weight)) = edge?;
+            cover_index(&mut graph, from.max(to));
+            graph.set_edge((from, to), weight.unwrap_or(1));
+        }
+
+        Ok(graph)
+    }
+}
+
+/// Writes every edge as a `from to` line split by `delimiter`, a node at a
+/// time so the edge set is never held in memory. `header` adds a
+/// `# from to` comment line first, which the readers here skip.
+pub fn write_edge_list<T, W, O: Write>(
+    graph: &dyn Graph<T, W>,
+    out: O,
+    delimiter: u8,
+    header: bool,
+) -> io::Result<()> {
+    let columns: &[&str] = if header { &["from", "to"] } else { &[] };
+    write_lines(graph, out, delimiter, columns, |_, _, _| Ok(()))
+}
+
+/// Same as `write_edge_list` with the weight from `get_edge` as a third
+/// field, the output of a `HashGraph` reads back in with
+/// `parse_weighted_edge_list` when split by whitespace.
+pub fn write_weighted_edge_list<T, W: fmt::Display, O: Write>(
+    graph: &dyn Graph<T, W>,
+    out: O,
+    delimiter: u8,
+    header: bool,
+) -> io::Result<()> {
+    let delimiter_char = delimiter as char;
+    let columns: &[&str] = if header {
+        &["from", "to", "weight"]
+    } else {
+        &[]
+    };
+
+    write_lines(
+        graph,
+        out,
+        delimiter,
+        columns,
+        |out, from, to| match graph.get_edge(from, to) {
+            Some(edge) => write!(out, "{}{}", delimiter_char, edge.weight),
+            None => Ok(()),
+        },
+    )
+}
+
+/// writes the `columns` header if there is one, then a line per edge with
+/// `extra` adding fields after `from` and `to`
+fn write_lines<T, W, O, F>(
+    graph: &dyn Graph<T, W>,
+    out: O,
+    delimiter: u8,
+    columns: &[&str],
+    mut extra: F,
+) -> io::Result<()>
+where
+    O: Write,
+    F: FnMut(&mut io::BufWriter<O>, usize, usize) -> io::Result<()>,
+{
+    let mut out = io::BufWriter::new(out);
+    let delimiter = delimiter as char;
+
+    if !columns.is_empty() {
+        writeln!(out, "# {}", columns.join(&delimiter.to_string()))?;
+    }
+
+    for from in 0..graph.node_count() {
+        for to in graph.outgoing_edges_of(from) {
+            write!(out, "{}{}{}", from, delimiter, to)?;
+            extra(&mut out, from, to)?;
+            writeln!(out)?;
+        }
+    }
+
+    out.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    const FIXTURE: &str = "# Directed graph: fixture.txt
+# FromNodeId\tToNodeId
+0\t1
+1 2 5
+
+  # indented comment
+3 0
+70\t3 2   \n";
+
+    #[test]
+    fn parse_test() {
+        let graph = parse_edge_list(FIXTURE).unwrap();
+
+        assert_eq!(graph.node_count(), 71);
+        assert_eq!(
+            graph.all_edge_pairs(),
+            vec![(0, 1), (1, 2), (3, 0), (70, 3)]
+        );
+        assert_eq!(*graph.get_node(70), 70);
+    }
+
+    #[test]
+    fn node_count_hint_test() {
+        let graph = parse_edge_list_with_node_count(FIXTURE, 100).unwrap();
+        assert_eq!(graph.node_count(), 100);
+        assert_eq!(graph.incoming_edges_of(3), vec![70]);
+
+        let error = parse_edge_list_with_node_count(FIXTURE, 50).unwrap_err();
+        assert_eq!(
+            error,
+            ParseError {
+                line: 8,
+                kind: ParseErrorKind::NodeOutOfBounds {
+                    index: 70,
+                    count: 50
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn weighted_test() {
+        let graph = parse_weighted_edge_list(FIXTURE).unwrap();
+
+        assert_eq!(graph.node_count(), 71);
+        assert_eq!(graph.get_edge(0, 1).unwrap().weight, 1);
+        assert_eq!(graph.get_edge(1, 2).unwrap().weight, 5);
+        assert_eq!(graph.get_edge(70, 3).unwrap().weight, 2);
+        assert!(graph.get_edge(2, 1).is_none());
+    }
+
+    #[test]
+    fn bad_line_test() {
+        let input = "0 1\n# fine\n2 x\n";
+        let error = parse_edge_list(input).unwrap_err();
+        assert_eq!(error.line, 3);
+        assert_eq!(error.kind, ParseErrorKind::InvalidNumber("x".to_string()));
+        assert_eq!(error.to_string(), "line 3: invalid number `x`");
+
+        let error = parse_weighted_edge_list("0 1\n4\n").err().unwrap();
+        assert_eq!(error.line, 2);
+        assert_eq!(error.kind, ParseErrorKind::MissingDestination);
+
+        let error = parse_edge_list("0 1 2 3\n").unwrap_err();
+        assert_eq!(error.kind, ParseErrorKind::TooManyFields);
+
+        let error = parse_edge_list("0 -1\n").unwrap_err();
+        assert_eq!(error.kind, ParseErrorKind::InvalidNumber("-1".to_string()));
+    }
+
+    #[test]
+    fn streaming_test() {
+        let input = FIXTURE.replace('\n', "\r\n");
+        let edges: Vec<_> = read_edges_streaming(Cursor::new(input)).collect();
+        assert_eq!(
+            edges,
+            vec![
+                Ok((3, (0, 1, None))),
+                Ok((4, (1, 2, Some(5)))),
+                Ok((7, (3, 0, None))),
+                Ok((8, (70, 3, Some(2))))
+            ]
+        );
+
+        let graph = BitGraph::from_edge_stream(71, read_edges_streaming(Cursor::new(FIXTURE)));
+        assert_eq!(graph, parse_edge_list(FIXTURE));
+        let graph = BitGraph::from_edge_stream(100, read_edges_streaming(Cursor::new(FIXTURE)));
+        assert_eq!(graph, parse_edge_list_with_node_count(FIXTURE, 100));
+
+        // node 70 is on the eighth line, and a huge index is no different
+        let graph = BitGraph::from_edge_stream(10, read_edges_streaming(Cursor::new(FIXTURE)));
+        assert_eq!(
+            graph,
+            Err(ParseError {
+                line: 8,
+                kind: ParseErrorKind::NodeOutOfBounds {
+                    index: 70,
+                    count: 10
+                },
+            })
+        );
+        let huge = read_edges_streaming(Cursor::new("0 100000000000\n"));
+        assert_eq!(
+            BitGraph::from_edge_stream(4, huge).unwrap_err().kind,
+            ParseErrorKind::NodeOutOfBounds {
+                index: 100_000_000_000,
+                count: 4
+            }
+        );
+
+        let graph =
+            HashGraph::from_edge_stream(read_edges_streaming(Cursor::new(FIXTURE))).unwrap();
+        assert!(graph == parse_weighted_edge_list(FIXTURE).unwrap());
+    }
+
+    #[test]
+    fn streaming_bad_line_test() {
+        let input = "0 1\r\n# fine\r\n\r\n1 2 \t\r\n3 x\r\n4 5\r\n";
+
+        let edges: Vec<_> = read_edges_streaming(Cursor::new(input)).collect();
+        assert_eq!(edges.len(), 4);
+        assert_eq!(edges[1], Ok((4, (1, 2, None))));
+        assert_eq!(
+            edges[2],
+            Err(ParseError {
+                line: 5,
+                kind: ParseErrorKind::InvalidNumber("x".to_string()),
+            })
+        );
+        assert_eq!(edges[3], Ok((6, (4, 5, None))));
+
+        let error = BitGraph::from_edge_stream(10, read_edges_streaming(Cursor::new(input)));
+        assert_eq!(error.unwrap_err().line, 5);
+        let error = HashGraph::from_edge_stream(read_edges_streaming(Cursor::new(input)));
+        assert_eq!(error.err().unwrap().line, 5);
+    }
+
+    #[test]
+    fn streaming_read_error_test() {
+        // invalid UTF-8 on the second line
+        let input: &[u8] = b"0 1\n\xff\xfe\n2 3\n";
+        let edges: Vec<_> = read_edges_streaming(input).collect();
+
+        assert_eq!(edges.len(), 2);
+        assert_eq!(edges[0], Ok((1, (0, 1, None))));
+        let error = edges[1].clone().unwrap_err();
+        assert_eq!(error.line, 2);
+        assert!(matches!(
+            error.kind,
+            ParseErrorKind::Io {
+                kind: io::ErrorKind::InvalidData,
+                ..
+            }
+        ));
+        assert!(error.to_string().starts_with("line 2: read failed: "));
+    }
+
+    #[test]
+    fn empty_test() {
+        let graph = parse_edge_list("# nothing here\n\n").unwrap();
+        assert_eq!(graph.node_count(), 0);
+    }
+
+    #[test]
+    fn write_test() {
+        let graph = parse_edge_list(FIXTURE).unwrap();
+
+        let mut out = Vec::new();
+        write_edge_list(&graph, &mut out, b',', true).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "# from,to\n0,1\n1,2\n3,0\n70,3\n"
+        );
+
+        let mut out = Vec::new();
+        write_edge_list(&graph, &mut out, b'\t', false).unwrap();
+        let written = String::from_utf8(out).unwrap();
+        assert!(written.starts_with("0\t1\n"));
+        assert_eq!(parse_edge_list(&written).unwrap(), graph);
+    }
+
+    #[test]
+    fn weighted_round_trip_test() {
+        let graph = parse_weighted_edge_list(FIXTURE).unwrap();
+
+        let mut out = Vec::new();
+        write_weighted_edge_list(&graph, &mut out, b' ', true).unwrap();
+        let written = String::from_utf8(out).unwrap();
+        assert!(written.starts_with("# from to weight\n0 1 1\n1 2 5\n"));
+
+        let read = parse_weighted_edge_list(&written).unwrap();
+        assert!(read == graph);
+        assert_eq!(read.get_edge(70, 3).unwrap().weight, 2);
+    }
+}
@@ -0,0 +1,276 @@
+use crate::bit::NodeBitSet;
+use crate::hash::hash_usize;
+use crate::Graph;
+use std::num::Wrapping;
+
+/// rounds of neighbour hashing `fingerprint` runs
+const WL_ROUNDS: usize = 4;
+/// keeps outgoing and incoming neighbours from hashing the same way
+const OUT_SALT: usize = 0x9e37_79b9;
+const IN_SALT: usize = 0x85eb_ca6b;
+
+/// Structural hash that does not depend on how the nodes are numbered, for
+/// telling graphs apart cheaply before anything exact is tried.
+///
+/// Each node starts from its out and in degree, then for a few
+/// Weisfeiler–Lehman rounds takes in the multisets of its outgoing and
+/// incoming neighbours' labels. Node values and edge weights are not looked
+/// at. Isomorphic graphs always hash the same, but different graphs can too,
+/// regular graphs of the same degree are the classic case, so equal
+/// fingerprints should be confirmed with `is_isomorphic`.
+pub fn fingerprint<V, W>(graph: &dyn Graph<V, W>) -> u64 {
+    let node_count = graph.node_count();
+
+    let mut labels: Vec<Wrapping<usize>> = (0..node_count)
+        .map(|node| {
+            hash_usize(graph.out_degree(node)) + hash_usize(graph.in_degree(node) ^ IN_SALT)
+        })
+        .collect();
+
+    let mut edges = Vec::new();
+    let mut next = vec![Wrapping(0); node_count];
+    for _ in 0..WL_ROUNDS {
+        for (node, label) in next.iter_mut().enumerate() {
+            // sums so the order neighbours come out in does not matter
+            graph.outgoing_edges_into(node, &mut edges);
+            let outgoing: Wrapping<usize> = edges
+                .iter()
+                .map(|&out| hash_usize(labels[out].0 ^ OUT_SALT))
+                .sum();
+            graph.incoming_edges_into(node, &mut edges);
+            let incoming: Wrapping<usize> = edges
+                .iter()
+                .map(|&incoming| hash_usize(labels[incoming].0 ^ IN_SALT))
+                .sum();
+
+            *label = hash_usize((labels[node] + hash_usize(outgoing.0)).0) + hash_usize(incoming.0);
+        }
+        std::mem::swap(&mut labels, &mut next);
+    }
+
+    let total: Wrapping<usize> = labels.iter().map(|label| hash_usize(label.0)).sum();
+    (hash_usize(node_count) + total).0 as u64
+}
+
+/// Whether the nodes of `b` can be renumbered so it has exactly the edges of
+/// `a`, self loops included. Values and weights are not compared.
+///
+/// This is a brute force search over mappings, only trying nodes with the
+/// same degrees and checking each new pair against the ones already mapped
+/// through bit rows of both graphs. The worst case is factorial in the node
+/// count, it is meant for small graphs of around ten nodes or for
+/// confirming a `fingerprint` match.
+pub fn is_isomorphic<V, W, X, Y>(a: &dyn Graph<V, W>, b: &dyn Graph<X, Y>) -> bool {
+    let node_count = a.node_count();
+    if node_count != b.node_count() {
+        return false;
+    }
+
+    let (degrees_a, degrees_b) = (degrees(a), degrees(b));
+
+    let (mut sorted_a, mut sorted_b) = (degrees_a.clone(), degrees_b.clone());
+    sorted_a.sort_unstable();
+    sorted_b.sort_unstable();
+    if sorted_a != sorted_b {
+        return false;
+    }
+
+    let mut matcher = Matcher {
+        rows_a: rows(a),
+        rows_b: rows(b),
+        degrees_a,
+        degrees_b,
+        mapping: Vec::with_capacity(node_count),
+        used: NodeBitSet::with_capacity(node_count),
+    };
+
+    matcher.extend()
+}
+
+fn degrees<V, W>(graph: &dyn Graph<V, W>) -> Vec<(usize, usize)> {
+    (0..graph.node_count())
+        .map(|node| (graph.out_degree(node), graph.in_degree(node)))
+        .collect()
+}
+
+fn rows<V, W>(graph: &dyn Graph<V, W>) -> Vec<NodeBitSet> {
+    let mut edges = Vec::new();
+
+    (0..graph.node_count())
+        .map(|node| {
+            let mut row = NodeBitSet::with_capacity(graph.node_count());
+            graph.outgoing_edges_into(node, &mut edges);
+            edges.iter().for_each(|&out| {
+                row.insert(out);
+            });
+            row
+        })
+        .collect()
+}
+
+/// depth first search for a mapping, node `i` of `a` maps to `mapping[i]`
+struct Matcher {
+    rows_a: Vec<NodeBitSet>,
+    rows_b: Vec<NodeBitSet>,
+    degrees_a: Vec<(usize, usize)>,
+    degrees_b: Vec<(usize, usize)>,
+    mapping: Vec<usize>,
+    used: NodeBitSet,
+}
+
+impl Matcher {
+    fn extend(&mut self) -> bool {
+        let node = self.mapping.len();
+        if node == self.rows_a.len() {
+            return true;
+        }
+
+        for candidate in 0..self.rows_b.len() {
+            if self.used.contains(candidate)
+                || self.degrees_a[node] != self.degrees_b[candidate]
+                || !self.fits(node, candidate)
+            {
+                continue;
+            }
+
+            self.mapping.push(candidate);
+            self.used.insert(candidate);
+            if self.extend() {
+                return true;
+            }
+            self.used.remove(candidate);
+            self.mapping.pop();
+        }
+
+        false
+    }
+
+    /// whether `node -> candidate` agrees with every pair mapped so far
+    fn fits(&self, node: usize, candidate: usize) -> bool {
+        let (row_a, row_b) = (&self.rows_a[node], &self.rows_b[candidate]);
+
+        row_a.contains(node) == row_b.contains(candidate)
+            && self.mapping.iter().enumerate().all(|(other, &mapped)| {
+                row_a.contains(other) == row_b.contains(mapped)
+                    && self.rows_a[other].contains(node) == self.rows_b[mapped].contains(candidate)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bit::BitGraph;
+    use crate::hash::HashGraph;
+
+    fn bit(node_count: usize, edges: &[(usize, usize)]) -> BitGraph {
+        BitGraph::from_edges(node_count, edges.iter().copied()).unwrap()
+    }
+
+    fn undirected(node_count: usize, edges: &[(usize, usize)]) -> BitGraph {
+        let mut graph = bit(node_count, edges);
+        graph.symmetrize();
+        graph
+    }
+
+    fn relabeled(graph: &BitGraph, order: &[usize]) -> BitGraph {
+        let edges = graph
+            .all_edge_pairs()
+            .into_iter()
+            .map(|(from, to)| (order[from], order[to]));
+        BitGraph::from_edges(graph.node_count(), edges).unwrap()
+    }
+
+    #[test]
+    fn relabeled_test() {
+        let graph = bit(
+            7,
+            &[
+                (0, 1),
+                (1, 2),
+                (2, 0),
+                (2, 3),
+                (3, 3),
+                (4, 5),
+                (5, 6),
+                (6, 4),
+                (1, 4),
+            ],
+        );
+
+        for order in &[[6, 5, 4, 3, 2, 1, 0], [3, 0, 6, 1, 5, 2, 4]] {
+            let other = relabeled(&graph, order);
+            assert_ne!(other, graph);
+            assert_eq!(fingerprint(&other), fingerprint(&graph));
+            assert!(is_isomorphic(&graph, &other));
+            assert!(is_isomorphic(&other, &graph));
+        }
+    }
+
+    #[test]
+    fn across_graph_types_test() {
+        let graph = bit(4, &[(0, 1), (1, 2), (2, 3)]);
+
+        let mut hash = HashGraph::with_capacity(4);
+        for value in 0..4 {
+            hash.push_node(value);
+        }
+        for &(from, to) in &[(3, 1), (1, 0), (0, 2)] {
+            hash.add_edge(from, to);
+        }
+
+        assert_eq!(fingerprint(&graph), fingerprint(&hash));
+        assert!(is_isomorphic(&graph, &hash));
+    }
+
+    #[test]
+    fn same_degrees_test() {
+        // both 2-regular on six nodes, a hexagon and two triangles
+        let hexagon = undirected(6, &[(0, 1), (1, 2), (2, 3), (3, 4), (4, 5), (5, 0)]);
+        let triangles = undirected(6, &[(0, 1), (1, 2), (2, 0), (3, 4), (4, 5), (5, 3)]);
+
+        assert!(!is_isomorphic(&hexagon, &triangles));
+        // the blind spot documented on `fingerprint`
+        assert_eq!(fingerprint(&hexagon), fingerprint(&triangles));
+
+        // degrees 3, 2, 2, 1, 1, 1 in both, the two middle nodes either
+        // both hang off the centre or sit next to each other
+        let split = undirected(6, &[(0, 1), (0, 2), (2, 3), (0, 4), (4, 5)]);
+        let chained = undirected(6, &[(0, 1), (0, 2), (0, 3), (3, 4), (4, 5)]);
+        assert!(!is_isomorphic(&split, &chained));
+        assert_ne!(fingerprint(&split), fingerprint(&chained));
+        assert!(is_isomorphic(
+            &split,
+            &relabeled(&split, &[5, 4, 3, 2, 1, 0])
+        ));
+    }
+
+    #[test]
+    fn direction_test() {
+        let out_star = bit(4, &[(0, 1), (0, 2), (0, 3)]);
+        let in_star = bit(4, &[(1, 0), (2, 0), (3, 0)]);
+
+        assert!(!is_isomorphic(&out_star, &in_star));
+        assert_ne!(fingerprint(&out_star), fingerprint(&in_star));
+    }
+
+    #[test]
+    fn self_loop_test() {
+        let looped = bit(3, &[(0, 1), (1, 2), (2, 2)]);
+        let other = bit(3, &[(0, 1), (1, 2), (0, 0)]);
+
+        assert!(!is_isomorphic(&looped, &other));
+        assert_ne!(fingerprint(&looped), fingerprint(&other));
+        assert!(is_isomorphic(&looped, &relabeled(&looped, &[2, 0, 1])));
+    }
+
+    #[test]
+    fn size_mismatch_test() {
+        let small = bit(3, &[(0, 1)]);
+        let big = bit(4, &[(0, 1)]);
+
+        assert!(!is_isomorphic(&small, &big));
+        assert_ne!(fingerprint(&small), fingerprint(&big));
+        assert!(is_isomorphic(&BitGraph::new(), &BitGraph::new()));
+    }
+}
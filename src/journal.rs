@@ -0,0 +1,534 @@
+use crate::{EdgeMeta, Graph};
+use std::collections::VecDeque;
+
+/// entries kept by `JournaledGraph::new`
+const DEFAULT_LIMIT: usize = 1024;
+
+/// Wraps a graph and records every mutation that changed something, so it
+/// can be undone and redone. Calls that leave the graph as it was, adding an
+/// edge that is already there or setting a weight to what it already is,
+/// are not recorded. Past `limit` entries the oldest ones are dropped.
+///
+/// The trait methods that are built out of other trait methods, like
+/// `clear_edges_of` or `retain_edges`, go through the recorded ones and are
+/// undone one edge or node at a time. Undoing `remove_node` assumes the
+/// wrapped graph swap removes, like every graph in the crate.
+pub struct JournaledGraph<G, T, W> {
+    graph: G,
+
+    undo: VecDeque<Change<T, W>>,
+    redo: Vec<Change<T, W>>,
+    limit: usize,
+    /// entries that fell off the front of `undo`, so positions stay valid
+    dropped: usize,
+}
+
+/// Position in the journal of a `JournaledGraph`, see `rollback_to`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Checkpoint(usize);
+
+/// other end and weight of every edge on one side of a node
+type Neighbours<W> = Vec<(usize, W)>;
+
+enum Change<T, W> {
+    /// any edge change, `None` is no edge
+    Edge {
+        from: usize,
+        to: usize,
+        old: Option<W>,
+        new: Option<W>,
+    },
+    PushNode(T),
+    SetNode {
+        node: usize,
+        old: T,
+        new: T,
+    },
+    /// the value and edges of `node` before it went, the last node took its
+    /// place
+    RemoveNode {
+        node: usize,
+        value: T,
+        outgoing: Neighbours<W>,
+        incoming: Neighbours<W>,
+    },
+}
+
+impl<G, T, W> JournaledGraph<G, T, W> {
+    pub fn new(graph: G) -> Self {
+        Self::with_limit(graph, DEFAULT_LIMIT)
+    }
+
+    /// journal that keeps at most `limit` entries to undo
+    pub fn with_limit(graph: G, limit: usize) -> Self {
+        Self {
+            graph,
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            limit,
+            dropped: 0,
+        }
+    }
+
+    pub fn inner(&self) -> &G {
+        &self.graph
+    }
+
+    /// the wrapped graph, the journal is dropped
+    pub fn into_inner(self) -> G {
+        self.graph
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// the current position, to come back to with `rollback_to`
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint(self.dropped + self.undo.len())
+    }
+
+    /// forgets every entry, checkpoints taken so far can not be rolled back
+    /// to anymore
+    pub fn clear_journal(&mut self) {
+        self.dropped += self.undo.len();
+        self.undo.clear();
+        self.redo.clear();
+    }
+
+    fn record(&mut self, change: Change<T, W>) {
+        self.redo.clear();
+        self.push_undo(change);
+    }
+
+    /// adds `change` to undo, dropping the oldest entries past `limit`
+    fn push_undo(&mut self, change: Change<T, W>) {
+        self.undo.push_back(change);
+        while self.undo.len() > self.limit {
+            self.undo.pop_front();
+            self.dropped += 1;
+        }
+    }
+}
+
+impl<G, T, W> JournaledGraph<G, T, W>
+where
+    G: Graph<T, W>,
+    T: Clone + PartialEq,
+    W: Clone + PartialEq,
+{
+    /// Reverts the latest entry, false if there is none.
+    pub fn undo(&mut self) -> bool {
+        match self.undo.pop_back() {
+            Some(change) => {
+                self.revert(&change);
+                self.redo.push(change);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Applies the latest undone entry again, false if there is none. Any
+    /// new mutation after an undo clears what can be redone.
+    pub fn redo(&mut self) -> bool {
+        match self.redo.pop() {
+            Some(change) => {
+                self.replay(&change);
+                self.push_undo(change);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Undoes everything since `checkpoint`. False, with nothing undone, if
+    /// some of it already fell out of the journal or the checkpoint is ahead
+    /// of the current position.
+    pub fn rollback_to(&mut self, checkpoint: Checkpoint) -> bool {
+        if checkpoint.0 < self.dropped || checkpoint > self.checkpoint() {
+            return false;
+        }
+
+        while self.checkpoint() > checkpoint {
+            self.undo();
+        }
+        true
+    }
+
+    fn weight_of(&self, from: usize, to: usize) -> Option<W> {
+        self.graph.get_edge(from, to).map(|edge| edge.weight)
+    }
+
+    /// records the edge change if the weight before and after differ
+    fn record_edge(&mut self, from: usize, to: usize, old: Option<W>) {
+        let new = self.weight_of(from, to);
+        if old != new {
+            self.record(Change::Edge { from, to, old, new });
+        }
+    }
+
+    fn put_edge(&mut self, from: usize, to: usize, weight: &Option<W>) {
+        match weight {
+            Some(weight) => self.graph.set_edge((from, to), weight.clone()),
+            None => self.graph.remove_edge(from, to),
+        };
+    }
+
+    fn weighted_edges(&self, node: usize) -> (Neighbours<W>, Neighbours<W>) {
+        let outgoing = self
+            .graph
+            .outgoing_edges_of(node)
+            .into_iter()
+            .filter_map(|to| Some((to, self.weight_of(node, to)?)))
+            .collect();
+        let incoming = self
+            .graph
+            .incoming_edges_of(node)
+            .into_iter()
+            .filter_map(|from| Some((from, self.weight_of(from, node)?)))
+            .collect();
+
+        (outgoing, incoming)
+    }
+
+    fn revert(&mut self, change: &Change<T, W>) {
+        match change {
+            Change::Edge { from, to, old, .. } => self.put_edge(*from, *to, old),
+            Change::PushNode(_) => {
+                self.graph.remove_node(self.graph.node_count() - 1);
+            }
+            Change::SetNode { node, old, .. } => self.graph.set_node(*node, old.clone()),
+            Change::RemoveNode {
+                node,
+                value,
+                outgoing,
+                incoming,
+            } => {
+                let node = *node;
+                let last = self.graph.push_node(value.clone());
+
+                // move the node that took its place back to the end
+                if node != last {
+                    let (moved_out, moved_in) = self.weighted_edges(node);
+                    self.graph.clear_edges_of(node);
+
+                    let moved = self.graph.get_node(node).clone();
+                    self.graph.set_node(last, moved);
+                    self.graph.set_node(node, value.clone());
+
+                    let back = |other| if other == node { last } else { other };
+                    for (to, weight) in moved_out {
+                        self.graph.set_edge((last, back(to)), weight);
+                    }
+                    for (from, weight) in moved_in.into_iter().filter(|(from, _)| *from != node) {
+                        self.graph.set_edge((from, last), weight);
+                    }
+                }
+
+                for (to, weight) in outgoing {
+                    self.graph.set_edge((node, *to), weight.clone());
+                }
+                for (from, weight) in incoming.iter().filter(|(from, _)| *from != node) {
+                    self.graph.set_edge((*from, node), weight.clone());
+                }
+            }
+        }
+    }
+
+    fn replay(&mut self, change: &Change<T, W>) {
+        match change {
+            Change::Edge { from, to, new, .. } => self.put_edge(*from, *to, new),
+            Change::PushNode(value) => {
+                self.graph.push_node(value.clone());
+            }
+            Change::SetNode { node, new, .. } => self.graph.set_node(*node, new.clone()),
+            Change::RemoveNode { node, .. } => {
+                self.graph.remove_node(*node);
+            }
+        }
+    }
+}
+
+impl<G, T, W> Graph<T, W> for JournaledGraph<G, T, W>
+where
+    G: Graph<T, W>,
+    T: Clone + PartialEq,
+    W: Clone + PartialEq,
+{
+    fn add_edge(&mut self, from: usize, to: usize) -> bool {
+        let old = self.weight_of(from, to);
+        let had_edge = self.graph.add_edge(from, to);
+        self.record_edge(from, to, old);
+
+        had_edge
+    }
+
+    fn set_edge(&mut self, from_to: (usize, usize), weight: W) -> bool {
+        let (from, to) = from_to;
+        let old = self.weight_of(from, to);
+        let had_edge = self.graph.set_edge(from_to, weight);
+        self.record_edge(from, to, old);
+
+        had_edge
+    }
+
+    fn remove_edge(&mut self, from: usize, to: usize) -> bool {
+        let old = self.weight_of(from, to);
+        let had_edge = self.graph.remove_edge(from, to);
+        self.record_edge(from, to, old);
+
+        had_edge
+    }
+
+    fn has_edge(&self, from: usize, to: usize) -> bool {
+        self.graph.has_edge(from, to)
+    }
+
+    fn get_edge(&self, from: usize, to: usize) -> Option<EdgeMeta<W>> {
+        self.graph.get_edge(from, to)
+    }
+
+    fn outgoing_edges_of(&self, node_index: usize) -> Vec<usize> {
+        self.graph.outgoing_edges_of(node_index)
+    }
+
+    fn neighbors_sorted(&self, node_index: usize) -> Vec<usize> {
+        self.graph.neighbors_sorted(node_index)
+    }
+
+    fn incoming_edges_of(&self, node_index: usize) -> Vec<usize> {
+        self.graph.incoming_edges_of(node_index)
+    }
+
+    fn outgoing_edges_into(&self, node_index: usize, out: &mut Vec<usize>) {
+        self.graph.outgoing_edges_into(node_index, out);
+    }
+
+    fn incoming_edges_into(&self, node_index: usize, out: &mut Vec<usize>) {
+        self.graph.incoming_edges_into(node_index, out);
+    }
+
+    fn edges(&self) -> Box<dyn Iterator<Item = EdgeMeta<W>> + '_> {
+        self.graph.edges()
+    }
+
+    fn all_edge_pairs(&self) -> Vec<(usize, usize)> {
+        self.graph.all_edge_pairs()
+    }
+
+    fn push_node(&mut self, value: T) -> usize {
+        let node = self.graph.push_node(value.clone());
+        self.record(Change::PushNode(value));
+        node
+    }
+
+    fn set_node(&mut self, node_index: usize, value: T) {
+        let old = self.graph.get_node(node_index).clone();
+        if old != value {
+            self.graph.set_node(node_index, value.clone());
+            self.record(Change::SetNode {
+                node: node_index,
+                old,
+                new: value,
+            });
+        }
+    }
+
+    fn get_node(&self, node_index: usize) -> &T {
+        self.graph.get_node(node_index)
+    }
+
+    fn remove_node(&mut self, node_index: usize) -> T {
+        let (outgoing, incoming) = self.weighted_edges(node_index);
+        let value = self.graph.remove_node(node_index);
+        self.record(Change::RemoveNode {
+            node: node_index,
+            value: value.clone(),
+            outgoing,
+            incoming,
+        });
+
+        value
+    }
+
+    fn node_count(&self) -> usize {
+        self.graph.node_count()
+    }
+
+    fn reserve_nodes(&mut self, additional: usize) {
+        self.graph.reserve_nodes(additional);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bit::BitGraph;
+    use crate::hash::HashGraph;
+    use crate::list::ListGraph;
+    use std::fmt::Debug;
+
+    /// node values and weighted edges, for comparing whole graphs
+    fn contents<T: Clone, W>(graph: &dyn Graph<T, W>) -> (Vec<T>, Vec<(usize, usize, W)>) {
+        let nodes = graph.node_values().cloned().collect();
+        let mut edges: Vec<_> = graph
+            .edges()
+            .map(|edge| (edge.source, edge.destination, edge.weight))
+            .collect();
+        edges.sort_by_key(|&(from, to, _)| (from, to));
+
+        (nodes, edges)
+    }
+
+    /// a run of every kind of mutation on a graph of at least six nodes,
+    /// no-ops included, with `weight` for the edges that are set
+    fn mutate<G, T, W>(graph: &mut JournaledGraph<G, T, W>, value: T, weight: W)
+    where
+        G: Graph<T, W>,
+        T: Clone + PartialEq,
+        W: Clone + PartialEq,
+    {
+        graph.add_edge(0, 1);
+        graph.add_edge(0, 1);
+        graph.add_edge(5, 5);
+        graph.set_edge((2, 3), weight.clone());
+        graph.set_edge((2, 3), weight);
+        graph.remove_edge(0, 1);
+        graph.remove_edge(0, 1);
+        graph.remove_edge(4, 0);
+        graph.push_node(value.clone());
+        graph.add_edge(6, 1);
+        graph.add_edge(1, 6);
+        graph.set_node(3, value.clone());
+        graph.set_node(3, value);
+        graph.remove_node(1);
+        graph.clear_edges_of(2);
+        graph.remove_node(graph.node_count() - 1);
+    }
+
+    fn assert_undo_all<G, T, W>(graph: G, value: T, weight: W)
+    where
+        G: Graph<T, W>,
+        T: Clone + PartialEq + Debug,
+        W: Clone + PartialEq + Debug,
+    {
+        let original = contents(&graph);
+        let mut journaled = JournaledGraph::new(graph);
+        let start = journaled.checkpoint();
+
+        mutate(&mut journaled, value, weight);
+        let mutated = contents(&journaled);
+        assert_ne!(mutated, original);
+
+        while journaled.undo() {}
+        assert_eq!(contents(&journaled), original);
+        assert_eq!(journaled.checkpoint(), start);
+
+        while journaled.redo() {}
+        assert_eq!(contents(&journaled), mutated);
+
+        assert!(journaled.rollback_to(start));
+        assert_eq!(contents(&journaled.into_inner()), original);
+    }
+
+    /// six nodes with an edge out of every one
+    fn ring<G: Graph<u64, W>, W>(mut graph: G) -> G {
+        for i in 0..6 {
+            graph.push_node(i * 10);
+        }
+        for i in 0..6 {
+            graph.add_edge(i as usize, (i as usize + 1) % 6);
+        }
+        graph.add_edge(4, 0);
+        graph.add_edge(3, 3);
+        graph
+    }
+
+    #[test]
+    fn undo_all_test() {
+        assert_undo_all(ring(BitGraph::new()), 99, true);
+        assert_undo_all(ring(BitGraph::with_capacity_aligned(70)), 99, true);
+        assert_undo_all(ring(BitGraph::with_capacity_directed_only(16)), 99, true);
+        assert_undo_all(ring(HashGraph::with_default_weight(16, 1)), 99, 7);
+        assert_undo_all(ring(ListGraph::with_default_weight(16, 1)), 99, 7);
+    }
+
+    #[test]
+    fn no_op_test() {
+        let mut graph = JournaledGraph::new(ring(BitGraph::new()));
+        let start = graph.checkpoint();
+
+        assert!(graph.add_edge(0, 1));
+        assert!(!graph.remove_edge(1, 0));
+        assert!(graph.set_edge((3, 3), true));
+        assert!(!graph.set_edge((3, 2), false));
+        graph.set_node(2, 20);
+        assert!(!graph.can_undo());
+        assert_eq!(graph.checkpoint(), start);
+
+        // only the weight changes, the edge was there before
+        let mut hash = JournaledGraph::new(ring(HashGraph::with_default_weight(16, 1)));
+        assert!(hash.set_edge((0, 1), 1));
+        assert!(!hash.can_undo());
+        assert!(hash.set_edge((0, 1), 5));
+        assert!(hash.undo());
+        assert_eq!(hash.get_edge(0, 1).unwrap().weight, 1);
+    }
+
+    #[test]
+    fn checkpoint_test() {
+        let mut graph = JournaledGraph::new(ring(ListGraph::with_default_weight(16, 1)));
+        graph.add_edge(0, 2);
+        let first = graph.checkpoint();
+        let before = contents(&graph);
+
+        graph.push_node(60);
+        graph.set_edge((6, 0), 3);
+        graph.remove_node(0);
+        let second = graph.checkpoint();
+
+        assert!(graph.rollback_to(first));
+        assert_eq!(contents(&graph), before);
+        // ahead of the current position now
+        assert!(!graph.rollback_to(second));
+
+        // a new change after an undo drops what could be redone
+        assert!(graph.undo());
+        assert!(graph.can_redo());
+        graph.add_edge(5, 2);
+        assert!(!graph.can_redo());
+        assert!(!graph.redo());
+    }
+
+    #[test]
+    fn limit_test() {
+        let mut graph = JournaledGraph::with_limit(ring(BitGraph::new()), 3);
+        let start = graph.checkpoint();
+        for to in 0..6 {
+            graph.add_edge(2, to);
+        }
+
+        // five new edges, the first two fell out
+        assert!(!graph.rollback_to(start));
+        assert!(graph.has_edge(2, 0));
+        let mut undone = 0;
+        while graph.undo() {
+            undone += 1;
+        }
+        assert_eq!(undone, 3);
+        assert_eq!(graph.outgoing_edges_of(2), vec![0, 1, 3]);
+
+        // redoing refills the journal up to the limit and no further
+        while graph.redo() {}
+        assert_eq!(graph.undo.len(), 3);
+        assert_eq!(graph.checkpoint(), Checkpoint(5));
+
+        graph.clear_journal();
+        assert!(!graph.can_undo() && !graph.can_redo());
+    }
+}
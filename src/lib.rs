@@ -1,10 +1,46 @@
+#[macro_use]
+mod macros;
+
 pub mod baseline;
 pub mod bit;
+pub mod builder;
+pub mod dot;
+pub mod filtered;
+#[cfg(feature = "rand")]
+pub mod generate;
+pub mod grid;
 pub mod hash;
+pub mod hybrid;
+pub mod index;
+pub mod indexed;
+pub mod integrity;
+pub mod io;
+pub mod isomorphism;
+pub mod journal;
+pub mod list;
+pub mod logic;
+pub mod memory;
+pub mod multi;
+pub mod payload;
+#[cfg(test)]
+mod proptests;
 pub mod search;
+pub mod stats;
+pub mod undirected;
 
 pub use baseline::AdjGraph;
 pub use bit::BitGraph;
+pub use builder::GraphBuilder;
+pub use filtered::FilteredGraph;
+pub use index::{NodeIndex, TypedGraph};
+pub use indexed::IndexedGraph;
+pub use integrity::IntegrityError;
+pub use journal::JournaledGraph;
+pub use memory::MemoryBreakdown;
+pub use multi::MultiGraph;
+pub use payload::{EdgeWeight, PayloadGraph};
+
+use std::fmt;
 
 #[derive(Clone, Copy)]
 pub struct EdgeMeta<W> {
@@ -24,44 +60,315 @@ impl From<(usize, usize, usize)> for EdgeMeta<usize> {
     }
 }
 
-impl EdgeMeta<usize> {
+impl<W> EdgeMeta<W> {
     #[inline]
     pub fn key_pair(&self) -> (usize, usize) {
         (self.source, self.destination)
     }
 }
 
+impl<W: Clone> EdgeMeta<&W> {
+    /// the same edge with its weight cloned
+    pub fn cloned(&self) -> EdgeMeta<W> {
+        EdgeMeta {
+            source: self.source,
+            destination: self.destination,
+            weight: self.weight.clone(),
+        }
+    }
+}
+
+/// Compares the edge structure of two graphs of any implementation through
+/// `outgoing_edges_of`. Node values and weights are not compared since not
+/// every implementation stores them.
+pub fn graph_eq<T, W, U, V>(a: &dyn Graph<T, W>, b: &dyn Graph<U, V>) -> bool {
+    if a.node_count() != b.node_count() {
+        return false;
+    }
+
+    (0..a.node_count()).all(|node| {
+        let mut a_out = a.outgoing_edges_of(node);
+        let mut b_out = b.outgoing_edges_of(node);
+        a_out.sort_unstable();
+        b_out.sort_unstable();
+        a_out == b_out
+    })
+}
+
+/// what went wrong in a checked `try_` call on a `Graph`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GraphError {
+    /// `index` is not below the graph's `count` nodes
+    NodeOutOfBounds { index: usize, count: usize },
+    /// every edge handed to a `GraphBuilder` with an end past its `count`
+    /// nodes, in the order they were added
+    InvalidEdges {
+        edges: Vec<(usize, usize)>,
+        count: usize,
+    },
+    /// a new node would get `index`, past the largest index `max` the graph
+    /// can hold
+    IndexTooLarge { index: usize, max: usize },
+}
+
+impl fmt::Display for GraphError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GraphError::NodeOutOfBounds { index, count } => {
+                write!(f, "node {} out of bounds for {} nodes", index, count)
+            }
+            GraphError::InvalidEdges { edges, count } => {
+                write!(f, "edges {:?} out of bounds for {} nodes", edges, count)
+            }
+            GraphError::IndexTooLarge { index, max } => {
+                write!(
+                    f,
+                    "node {} does not fit the edge table, at most {}",
+                    index, max
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for GraphError {}
+
+/// `Ok` if `index` is one of `count` nodes
+#[inline]
+pub fn check_node(index: usize, count: usize) -> Result<(), GraphError> {
+    if index < count {
+        Ok(())
+    } else {
+        Err(GraphError::NodeOutOfBounds { index, count })
+    }
+}
+
+/// panics with the `GraphError` message unless `index` is one of `count`
+/// nodes, for the unchecked methods
+#[inline]
+#[track_caller]
+pub(crate) fn assert_node(index: usize, count: usize) {
+    if let Err(error) = check_node(index, count) {
+        panic!("{}", error);
+    }
+}
+
+/// node count that makes `from` and `to` both nodes, panics when one of them
+/// is `usize::MAX` since no graph can hold that many nodes
+pub(crate) fn covering_count(from: usize, to: usize) -> usize {
+    from.max(to)
+        .checked_add(1)
+        .expect("node index usize::MAX can never be a node")
+}
+
+/// pushes `T::default()` nodes until `from` and `to` are both nodes, for the
+/// `Extend` impls
+pub(crate) fn cover_nodes<T, W, G>(graph: &mut G, from: usize, to: usize)
+where
+    T: Default,
+    G: Graph<T, W> + ?Sized,
+{
+    let count = covering_count(from, to);
+    while graph.node_count() < count {
+        graph.push_node(T::default());
+    }
+}
+
+/// Self loops, edges from a node to itself, are allowed by every
+/// implementation and count once towards both degrees of their node. Each
+/// algorithm in `search` says what it does with them.
 pub trait Graph<T, W> {
     /// add a directed edge from `from` and to `to`, represent indicies in some
-    /// collection of nodes,left up to the implementation to decide. Weight set to 1
+    /// collection of nodes,left up to the implementation to decide. A new
+    /// edge gets the graph's default weight, 1 unless the graph was built
+    /// with another, and an edge already there keeps its weight. Use
+    /// `set_edge` to change it.
+    ///
+    /// Returns `true` if the edge was already there, the same goes for
+    /// `set_edge` and `remove_edge`, so `false` from `add_edge` always
+    /// means the graph changed.
     fn add_edge(&mut self, from: usize, to: usize) -> bool;
 
+    /// adds the edge or changes its weight, `true` if it was already there
     fn set_edge(&mut self, from_to: (usize, usize), weight: W) -> bool;
 
     /// remove a directed edge from `from` and to `to`, represent indicies in some
     /// collection of nodes,left up to the implementation to decide.
     fn remove_edge(&mut self, from: usize, to: usize) -> bool;
 
+    /// removes every edge going out of or coming in to `node_index`, the node
+    /// itself is kept
+    fn clear_edges_of(&mut self, node_index: usize) {
+        for to in self.outgoing_edges_of(node_index) {
+            self.remove_edge(node_index, to);
+        }
+
+        for from in self.incoming_edges_of(node_index) {
+            self.remove_edge(from, node_index);
+        }
+    }
+
+    /// removes every edge and keeps every node, the default removes them one
+    /// by one
+    fn clear_edges(&mut self) {
+        for (from, to) in self.all_edge_pairs() {
+            self.remove_edge(from, to);
+        }
+    }
+
+    /// Removes every edge and node. Implementations hold on to their
+    /// allocations where they can so the graph can be filled again cheaply,
+    /// the default removes the nodes one by one from the back.
+    fn clear(&mut self) {
+        self.clear_edges();
+        while self.node_count() > 0 {
+            self.remove_node(self.node_count() - 1);
+        }
+    }
+
     /// checks for edge between from `from` to `to` if so returns `true`, else `false`
     fn has_edge(&self, from: usize, to: usize) -> bool;
 
     /// returns edge between from `from` to `to` if exists, else None
     fn get_edge(&self, from: usize, to: usize) -> Option<EdgeMeta<W>>;
 
-    /// returns `Vec` of indicies coming out from a given node
+    /// returns `Vec` of indicies coming out from a given node, in no
+    /// particular order, see `neighbors_sorted`
     fn outgoing_edges_of(&self, node_index: usize) -> Vec<usize>;
 
+    /// `outgoing_edges_of` in ascending order, for binary searches and
+    /// merging two lists. The default sorts, implementations that already
+    /// keep their rows or lists sorted hand them over as they are.
+    fn neighbors_sorted(&self, node_index: usize) -> Vec<usize> {
+        let mut out = self.outgoing_edges_of(node_index);
+        out.sort_unstable();
+        out
+    }
+
     /// returns `Vec` of indicies coming in to a given node
     fn incoming_edges_of(&self, node_index: usize) -> Vec<usize>;
 
-    fn all_edge_pairs(&self) -> Vec<(usize, usize)> {
-        let mut out = Vec::new();
-        for index in 0..self.node_count() {
-            self.outgoing_edges_of(index)
-                .iter()
-                .for_each(|to| out.push((index, *to)))
+    /// `outgoing_edges_of` written into `out`, which is cleared first, so one
+    /// buffer can serve every call of a search loop
+    fn outgoing_edges_into(&self, node_index: usize, out: &mut Vec<usize>) {
+        out.clear();
+        out.extend(self.outgoing_edges_of(node_index));
+    }
+
+    /// `incoming_edges_of` written into `out`, which is cleared first
+    fn incoming_edges_into(&self, node_index: usize, out: &mut Vec<usize>) {
+        out.clear();
+        out.extend(self.incoming_edges_of(node_index));
+    }
+
+    /// Every edge with its weight, produced lazily one node's outgoing edges
+    /// at a time. The default goes through `outgoing_edges_of` and
+    /// `get_edge`, implementations walk their storage directly.
+    fn edges(&self) -> Box<dyn Iterator<Item = EdgeMeta<W>> + '_> {
+        Box::new((0..self.node_count()).flat_map(move |from| {
+            self.outgoing_edges_of(from)
+                .into_iter()
+                .filter_map(move |to| self.get_edge(from, to))
+        }))
+    }
+
+    /// an edge whose reverse is missing, the first one `edges` lists unless
+    /// the implementation finds one faster
+    fn find_asymmetry(&self) -> Option<(usize, usize)> {
+        self.edges()
+            .map(|edge| edge.key_pair())
+            .find(|(from, to)| !self.has_edge(*to, *from))
+    }
+
+    /// true if every edge has its reverse, as an undirected graph should
+    fn is_symmetric(&self) -> bool {
+        self.find_asymmetry().is_none()
+    }
+
+    /// adds the reverse of every edge that only goes one way, with the same
+    /// weight
+    fn symmetrize(&mut self) {
+        for (from, to) in self.all_edge_pairs() {
+            if !self.has_edge(to, from) {
+                if let Some(edge) = self.get_edge(from, to) {
+                    self.set_edge((to, from), edge.weight);
+                }
+            }
         }
-        out
+    }
+
+    /// number of edges going out of `node_index`, the default counts
+    /// `outgoing_edges_of`
+    fn out_degree(&self, node_index: usize) -> usize {
+        self.outgoing_edges_of(node_index).len()
+    }
+
+    /// number of edges coming in to `node_index`, the default counts
+    /// `incoming_edges_of`
+    fn in_degree(&self, node_index: usize) -> usize {
+        self.incoming_edges_of(node_index).len()
+    }
+
+    /// true if `node_index` has an edge to itself
+    fn has_self_loop(&self, node_index: usize) -> bool {
+        self.has_edge(node_index, node_index)
+    }
+
+    /// every node with a self loop, in ascending order
+    fn self_loops(&self) -> Box<dyn Iterator<Item = usize> + '_> {
+        Box::new((0..self.node_count()).filter(move |node| self.has_self_loop(*node)))
+    }
+
+    /// removes every self loop, returns how many there were
+    fn remove_self_loops(&mut self) -> usize {
+        let nodes: Vec<usize> = self.self_loops().collect();
+        for node in &nodes {
+            self.remove_edge(*node, *node);
+        }
+
+        nodes.len()
+    }
+
+    /// Removes every edge `pred` returns `false` for, it sees each edge once
+    /// with its weight. The default removes them one by one after collecting
+    /// them from `edges`.
+    fn retain_edges(&mut self, mut pred: impl FnMut(usize, usize, &W) -> bool)
+    where
+        Self: Sized,
+    {
+        let doomed: Vec<(usize, usize)> = self
+            .edges()
+            .filter(|edge| !pred(edge.source, edge.destination, &edge.weight))
+            .map(|edge| edge.key_pair())
+            .collect();
+
+        for (from, to) in doomed {
+            self.remove_edge(from, to);
+        }
+    }
+
+    /// Drops every node `pred` returns `false` for along with every edge
+    /// touching it. Indices are kept, nothing is compacted: a dropped node
+    /// keeps its index and value and is only left without edges, like `merge`
+    /// after `merge_nodes`. The default hands the edges to `retain_edges`.
+    fn retain_nodes(&mut self, mut pred: impl FnMut(usize, &T) -> bool)
+    where
+        Self: Sized,
+    {
+        let dropped: Vec<bool> = (0..self.node_count())
+            .map(|node| !pred(node, self.get_node(node)))
+            .collect();
+        if !dropped.contains(&true) {
+            return;
+        }
+
+        let is_dropped = |node: usize| dropped.get(node) == Some(&true);
+        self.retain_edges(|from, to, _| !is_dropped(from) && !is_dropped(to));
+    }
+
+    /// every edge's `(source, destination)`, collected from `edges`
+    fn all_edge_pairs(&self) -> Vec<(usize, usize)> {
+        self.edges().map(|edge| edge.key_pair()).collect()
     }
 
     /// appends node to graph's node storage
@@ -78,6 +385,784 @@ pub trait Graph<T, W> {
 
     fn node_count(&self) -> usize;
 
-    // :)
-    fn set_count(&mut self, count: usize);
+    /// every node's value, in index order
+    fn node_values(&self) -> Box<dyn Iterator<Item = &T> + '_> {
+        Box::new((0..self.node_count()).map(move |node| self.get_node(node)))
+    }
+
+    /// index of the first node whose value passes `pred`, a linear scan
+    fn find_node(&self, pred: impl Fn(&T) -> bool) -> Option<usize>
+    where
+        Self: Sized,
+    {
+        self.node_values().position(pred)
+    }
+
+    /// index of the first node valued `value`, a linear scan, see
+    /// `IndexedGraph` for constant time lookups
+    fn find_node_by_value(&self, value: &T) -> Option<usize>
+    where
+        T: PartialEq,
+    {
+        self.node_values().position(|other| other == value)
+    }
+
+    /// Grows or shrinks the graph to `count` nodes. Nodes at `count` and
+    /// past it go along with their edges, the others keep their index,
+    /// value and edges. New nodes are valued `fill` and have no edges. The
+    /// default removes nodes from the back one at a time and pushes new ones.
+    fn resize_nodes(&mut self, count: usize, fill: T)
+    where
+        T: Clone,
+    {
+        while self.node_count() > count {
+            self.remove_node(self.node_count() - 1);
+        }
+        while self.node_count() < count {
+            self.push_node(fill.clone());
+        }
+    }
+
+    /// Makes room for at least `additional` more nodes so pushing them does
+    /// not reallocate, the graph itself does not change. The default does
+    /// nothing.
+    fn reserve_nodes(&mut self, _additional: usize) {}
+
+    /// Used to set the node count without creating any node values, leaving
+    /// `node_count` out of step with what is stored. Now `resize_nodes` with
+    /// `T::default()` as the value of new nodes.
+    #[deprecated(note = "use `resize_nodes`, or `reserve_nodes` to only make room")]
+    fn set_count(&mut self, count: usize)
+    where
+        T: Default + Clone,
+    {
+        self.resize_nodes(count, T::default());
+    }
+
+    /// `add_edge` that checks both ends are nodes first
+    fn try_add_edge(&mut self, from: usize, to: usize) -> Result<bool, GraphError> {
+        check_node(from, self.node_count())?;
+        check_node(to, self.node_count())?;
+
+        Ok(self.add_edge(from, to))
+    }
+
+    /// `has_edge` that checks both ends are nodes first
+    fn try_has_edge(&self, from: usize, to: usize) -> Result<bool, GraphError> {
+        check_node(from, self.node_count())?;
+        check_node(to, self.node_count())?;
+
+        Ok(self.has_edge(from, to))
+    }
+
+    /// `get_node` that checks `node_index` is a node first
+    fn try_get_node(&self, node_index: usize) -> Result<&T, GraphError> {
+        check_node(node_index, self.node_count())?;
+
+        Ok(self.get_node(node_index))
+    }
+
+    /// `remove_node` that checks `node_index` is a node first
+    fn try_remove_node(&mut self, node_index: usize) -> Result<T, GraphError> {
+        check_node(node_index, self.node_count())?;
+
+        Ok(self.remove_node(node_index))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::HashGraph;
+    use crate::search::bfs::BFS;
+    use crate::search::Pathfinder;
+    use std::collections::HashSet;
+
+    /// nodes in the shared fixtures, enough to spill past one 64 bit word
+    const NODES: usize = 70;
+
+    /// pushes `NODES` nodes valued `i * scale` onto an empty graph
+    fn push_nodes<W, G: Graph<u64, W> + ?Sized>(graph: &mut G, scale: u64) {
+        for i in 0..NODES as u64 {
+            graph.push_node(i * scale);
+        }
+    }
+
+    /// runs `$check` on an empty graph of every implementation, plus the
+    /// wrappers around them, for the fixtures built by `push_nodes`
+    macro_rules! for_each_graph {
+        ($check:expr) => {{
+            $check(&mut BitGraph::with_capacity(NODES));
+            $check(&mut HashGraph::new());
+            $check(&mut AdjGraph::new());
+            $check(&mut crate::list::ListGraph::new());
+            $check(&mut crate::hybrid::HybridGraph::new());
+            $check(&mut IndexedGraph::new(HashGraph::new()));
+            $check(&mut JournaledGraph::new(crate::list::ListGraph::new()));
+        }};
+    }
+
+    /// three nodes valued 0, 1 and 2 with an edge from 0 to 1
+    fn assert_checked<W>(graph: &mut dyn Graph<u64, W>) {
+        for i in 0..3 {
+            graph.push_node(i);
+        }
+
+        let out_of_bounds = |index| GraphError::NodeOutOfBounds { index, count: 3 };
+
+        assert_eq!(graph.try_add_edge(0, 1), Ok(false));
+        assert_eq!(graph.try_add_edge(0, 3), Err(out_of_bounds(3)));
+        assert_eq!(graph.try_has_edge(0, 1), Ok(true));
+        assert_eq!(graph.try_has_edge(999, 0), Err(out_of_bounds(999)));
+        assert_eq!(graph.try_get_node(2), Ok(&2));
+        assert_eq!(graph.try_get_node(3), Err(out_of_bounds(3)));
+        assert_eq!(graph.try_remove_node(7).err(), Some(out_of_bounds(7)));
+        assert_eq!(graph.try_remove_node(2), Ok(2));
+        assert_eq!(
+            graph.try_get_node(2),
+            Err(GraphError::NodeOutOfBounds { index: 2, count: 2 })
+        );
+    }
+
+    #[test]
+    fn checked_test() {
+        for_each_graph!(assert_checked);
+        assert_checked(&mut BitGraph::new());
+    }
+
+    type UncheckedCall<W> = fn(&mut dyn Graph<u64, W>);
+
+    /// one node, every unchecked call past it panics with the message of the
+    /// `GraphError` its `try_` version would give back
+    fn assert_unchecked_message<W>(graph: &mut dyn Graph<u64, W>) {
+        graph.push_node(0);
+
+        let calls: [(&str, UncheckedCall<W>); 5] = [
+            ("has_edge", |graph| {
+                graph.has_edge(999, 0);
+            }),
+            ("add_edge", |graph| {
+                graph.add_edge(0, 999);
+            }),
+            ("outgoing_edges_of", |graph| {
+                graph.outgoing_edges_of(999);
+            }),
+            ("incoming_edges_of", |graph| {
+                graph.incoming_edges_of(999);
+            }),
+            ("remove_node", |graph| {
+                graph.remove_node(999);
+            }),
+        ];
+
+        for (name, call) in calls.iter() {
+            let panic = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| call(graph)))
+                .expect_err(name);
+            let message = panic
+                .downcast_ref::<String>()
+                .map(String::as_str)
+                .or_else(|| panic.downcast_ref::<&str>().copied());
+            assert_eq!(
+                message,
+                Some("node 999 out of bounds for 1 nodes"),
+                "{}",
+                name
+            );
+        }
+        assert_eq!(graph.node_count(), 1);
+    }
+
+    #[test]
+    fn unchecked_message_test() {
+        for_each_graph!(assert_unchecked_message);
+    }
+
+    /// four nodes valued by index, the third one changed through `IndexMut`
+    fn assert_indexing<W, G>(graph: &mut G)
+    where
+        G: Graph<u64, W> + std::ops::IndexMut<usize, Output = u64>,
+    {
+        for i in 0..4 {
+            graph.push_node(i);
+        }
+        graph.add_edge(2, 3);
+
+        graph[2] = 20;
+        graph[3] += 5;
+        assert_eq!(graph[2], 20);
+        assert_eq!(*graph.get_node(2), 20);
+        assert_eq!(*graph.get_node(3), 8);
+        assert!(graph.has_edge(2, 3));
+    }
+
+    #[test]
+    fn index_test() {
+        let mut bit = BitGraph::new();
+        assert_indexing(&mut bit);
+        bit.nodes_mut().for_each(|(i, value)| *value += i as u64);
+        assert_eq!(
+            bit.nodes().map(|(_, v)| *v).collect::<Vec<_>>(),
+            vec![0, 2, 22, 11]
+        );
+
+        let mut hash = HashGraph::new();
+        assert_indexing(&mut hash);
+        hash.nodes_mut().for_each(|(_, value)| *value *= 2);
+        assert_eq!(*hash.get_node(2), 40);
+
+        let mut adj = AdjGraph::new();
+        assert_indexing(&mut adj);
+        assert_eq!(adj.nodes().nth(3), Some((3, &8)));
+
+        let mut list = crate::list::ListGraph::new();
+        assert_indexing(&mut list);
+        for (_, value) in list.nodes_mut() {
+            *value = 0;
+        }
+        assert!(list.nodes().all(|(_, value)| *value == 0));
+    }
+
+    #[test]
+    #[should_panic(expected = "node 4 out of bounds for 4 nodes")]
+    fn index_bounds_test() {
+        let mut graph = AdjGraph::new();
+        assert_indexing(&mut graph);
+        graph[4] = 1;
+    }
+
+    #[test]
+    fn graph_eq_test() {
+        let mut bit = BitGraph::with_capacity(16);
+        let mut hash = HashGraph::with_capacity(16);
+        let mut adj = AdjGraph::with_capacity(16);
+
+        for i in 0..16 {
+            bit.push_node(i);
+            hash.push_node(i);
+            adj.push_node(i);
+        }
+
+        for (from, to) in [(0, 1), (1, 0), (15, 3), (7, 7)] {
+            bit.add_edge(from, to);
+            hash.add_edge(from, to);
+            adj.add_edge(from, to);
+        }
+
+        assert!(graph_eq(&bit, &hash));
+        assert!(graph_eq(&hash, &bit));
+
+        // weights are not part of the comparison
+        hash.set_edge((15, 3), 9);
+        adj.set_edge((15, 3), 9);
+        assert!(graph_eq(&bit, &hash));
+
+        bit.add_edge(2, 3);
+        assert!(!graph_eq(&bit, &hash));
+
+        hash.push_node(16);
+        assert!(!graph_eq(&hash, &HashGraph::new()));
+        assert!(graph_eq(&adj, &adj));
+    }
+
+    /// edges as `outgoing_edges_of` lists them, the way `all_edge_pairs`
+    /// used to collect them
+    fn pairs_by_node<T, W>(graph: &dyn Graph<T, W>) -> HashSet<(usize, usize)> {
+        (0..graph.node_count())
+            .flat_map(|from| {
+                graph
+                    .outgoing_edges_of(from)
+                    .into_iter()
+                    .map(move |to| (from, to))
+            })
+            .collect()
+    }
+
+    fn assert_edges<T, W: Clone + PartialEq + fmt::Debug>(graph: &dyn Graph<T, W>) {
+        let expected = pairs_by_node(graph);
+        let edges: Vec<EdgeMeta<W>> = graph.edges().collect();
+
+        assert_eq!(edges.len(), expected.len());
+        assert_eq!(
+            edges.iter().map(EdgeMeta::key_pair).collect::<HashSet<_>>(),
+            expected
+        );
+        for edge in &edges {
+            let (from, to) = edge.key_pair();
+            assert_eq!(
+                Some(edge.weight.clone()),
+                graph.get_edge(from, to).map(|e| e.weight)
+            );
+        }
+        assert_eq!(
+            graph.all_edge_pairs().into_iter().collect::<HashSet<_>>(),
+            expected
+        );
+    }
+
+    #[test]
+    fn edges_test() {
+        // 70 nodes so BitGraph rows straddle words
+        let mut bit = BitGraph::with_capacity(70);
+        let mut hash = HashGraph::with_capacity(70);
+        let mut list = crate::list::ListGraph::with_capacity(70);
+        let mut adj = AdjGraph::with_capacity(70);
+        for i in 0..70 {
+            bit.push_node(i);
+            hash.push_node(i);
+            list.push_node(i);
+            adj.push_node(i);
+        }
+
+        for i in 0..300 {
+            let (from, to) = ((i * 7) % 70, (i * 13 + i / 70) % 70);
+            bit.add_edge(from, to);
+            hash.set_edge((from, to), i);
+            list.set_edge((from, to), i);
+            adj.set_edge((from, to), (i % 200 + 1) as u8);
+        }
+        hash.remove_edge(0, 0);
+        list.remove_edge(7, 13);
+
+        assert_edges(&bit);
+        assert_edges(&hash);
+        assert_edges(&list);
+        assert_edges(&adj);
+
+        assert_eq!(BitGraph::new().edges().count(), 0);
+        assert_eq!(bit.edges().take(5).count(), 5);
+    }
+
+    fn assert_self_loops<W>(graph: &mut dyn Graph<u64, W>) {
+        push_nodes(graph, 1);
+        for i in (0..NODES).step_by(3) {
+            graph.add_edge(i, i);
+        }
+
+        let expected: Vec<usize> = (0..NODES).step_by(3).collect();
+        assert_eq!(graph.self_loops().collect::<Vec<_>>(), expected);
+        assert!(graph.has_self_loop(69));
+        assert!(!graph.has_self_loop(68));
+        assert_eq!(graph.outgoing_edges_of(3), vec![3]);
+        assert_eq!(graph.incoming_edges_of(3), vec![3]);
+
+        assert_eq!(graph.remove_self_loops(), expected.len());
+        assert_eq!(graph.self_loops().count(), 0);
+        assert!(graph.all_edge_pairs().is_empty());
+        assert_eq!(graph.remove_self_loops(), 0);
+    }
+
+    #[test]
+    fn self_loops_test() {
+        for_each_graph!(assert_self_loops);
+    }
+
+    fn assert_symmetry<W>(graph: &mut dyn Graph<u64, W>) {
+        push_nodes(graph, 1);
+        for i in 0..NODES {
+            graph.add_edge(i, (i * 7 + 3) % 70);
+            graph.add_edge((i * 7 + 3) % 70, i);
+        }
+        graph.add_edge(5, 5);
+        assert!(graph.is_symmetric());
+        assert_eq!(graph.find_asymmetry(), None);
+
+        graph.add_edge(64, 2);
+        assert!(!graph.is_symmetric());
+        assert_eq!(graph.find_asymmetry(), Some((64, 2)));
+
+        graph.remove_edge(64, 2);
+        graph.remove_edge(3, 0);
+        assert_eq!(graph.find_asymmetry(), Some((0, 3)));
+
+        graph.add_edge(69, 68);
+        graph.symmetrize();
+        assert!(graph.is_symmetric());
+        assert!(graph.has_edge(3, 0) && graph.has_edge(68, 69));
+        assert!(!graph.has_edge(2, 64));
+    }
+
+    #[test]
+    fn symmetry_test() {
+        for_each_graph!(assert_symmetry);
+
+        let mut weighted = HashGraph::new();
+        weighted.push_node(0);
+        weighted.push_node(1);
+        weighted.set_edge((1, 0), 6);
+        weighted.symmetrize();
+        assert_eq!(weighted.get_edge(0, 1).unwrap().weight, 6);
+    }
+
+    /// every node points at the next three, weighted by the distance
+    fn chain<G: Graph<u64, usize>>(mut graph: G) -> G {
+        push_nodes(&mut graph, 1);
+        for i in 0..NODES {
+            for step in 1..=3 {
+                graph.set_edge((i, (i + step) % 70), step);
+            }
+        }
+        graph
+    }
+
+    fn assert_retain_nodes<W, G: Graph<u64, W>>(graph: &mut G) {
+        push_nodes(graph, 10);
+        for i in 0..NODES {
+            graph.add_edge(i, (i + 1) % 70);
+            graph.add_edge(i, (i + 2) % 70);
+            graph.add_edge(i, 69 - i);
+        }
+
+        graph.retain_nodes(|node, value| node % 2 == 0 && *value == node as u64 * 10);
+
+        assert_eq!(graph.node_count(), 70);
+        assert_eq!(*graph.get_node(7), 70);
+        for node in (1..70).step_by(2) {
+            assert!(graph.outgoing_edges_of(node).is_empty());
+            assert!(graph.incoming_edges_of(node).is_empty());
+        }
+        // only the even steps survive, 69 - i is always odd for even i
+        assert_eq!(graph.outgoing_edges_of(0), vec![2]);
+        assert_eq!(graph.incoming_edges_of(64), vec![62]);
+        assert_eq!(graph.outgoing_edges_of(68), vec![0]);
+        assert_eq!(graph.all_edge_pairs().len(), 35);
+        assert!(graph
+            .edges()
+            .all(|edge| edge.source % 2 == 0 && edge.destination % 2 == 0));
+    }
+
+    #[test]
+    fn retain_test() {
+        let mut hash = chain(HashGraph::new());
+        hash.retain_edges(|_, _, weight| *weight < 3);
+        let mut list = chain(crate::list::ListGraph::new());
+        list.retain_edges(|_, _, weight| *weight < 3);
+
+        for graph in [&hash as &dyn Graph<u64, usize>, &list] {
+            assert_eq!(graph.all_edge_pairs().len(), 140);
+            assert_eq!(graph.outgoing_edges_of(68), vec![0, 69]);
+            assert_eq!(graph.incoming_edges_of(1), vec![0, 69]);
+            assert!(graph.edges().all(|edge| edge.weight < 3));
+        }
+
+        // bits have no weight to go on, filter on the pair instead
+        let mut bit = BitGraph::with_capacity(NODES);
+        push_nodes(&mut bit, 1);
+        for i in 0..NODES {
+            bit.add_edge(i, (i + 1) % 70);
+            bit.add_edge(i, (i + 3) % 70);
+        }
+        bit.retain_edges(|from, to, _| (to + 70 - from) % 70 == 1);
+        assert_eq!(bit.all_edge_pairs().len(), 70);
+        assert_eq!(bit.incoming_edges_of(2), vec![1]);
+        assert_eq!(bit.outgoing_edges_of(69), vec![0]);
+
+        for_each_graph!(assert_retain_nodes);
+    }
+
+    /// fills `graph` with a ring of 70 nodes, clears it one way or the other
+    /// and checks nothing is left
+    fn assert_cleared<W>(graph: &mut dyn Graph<u64, W>, everything: bool) {
+        push_nodes(graph, 1);
+        for i in 0..NODES {
+            graph.add_edge(i, (i + 1) % 70);
+            graph.add_edge(i, i);
+        }
+
+        if everything {
+            graph.clear();
+            assert_eq!(graph.node_count(), 0);
+            assert_eq!(graph.node_values().count(), 0);
+            for i in 0..NODES as u64 {
+                graph.push_node(i + 100);
+            }
+            assert_eq!(*graph.get_node(69), 169);
+        } else {
+            graph.clear_edges();
+            assert_eq!(graph.node_count(), 70);
+            assert_eq!(*graph.get_node(69), 69);
+        }
+
+        assert!(graph.edges().next().is_none());
+        assert!(!graph.has_edge(0, 1) && !graph.has_edge(5, 5));
+        assert!(graph.outgoing_edges_of(3).is_empty());
+        assert!(graph.incoming_edges_of(3).is_empty());
+
+        // and it fills up again as normal
+        graph.add_edge(68, 69);
+        assert_eq!(graph.all_edge_pairs(), vec![(68, 69)]);
+    }
+
+    #[test]
+    fn clear_test() {
+        for everything in [false, true] {
+            let mut bit = BitGraph::with_capacity(70);
+            assert_cleared(&mut bit, everything);
+            assert_eq!(bit.capacity(), 70);
+
+            let mut adj = AdjGraph::with_capacity(70);
+            assert_cleared(&mut adj, everything);
+            assert_eq!(adj.capacity(), 70);
+
+            let mut hash = HashGraph::new();
+            hash.push_node(0);
+            hash.add_edge(0, 0);
+            hash.remove_edge(0, 0);
+            hash.remove_node(0);
+            assert_cleared(&mut hash, everything);
+            let capacity = hash.edge_capacity();
+            hash.clear();
+            assert_eq!(hash.edge_capacity(), capacity);
+            assert_eq!(hash.edge_table().load_factor(), 0.0);
+
+            let mut list = crate::list::ListGraph::with_capacity(70);
+            assert_cleared(&mut list, everything);
+            assert_eq!(list.capacity(), 70);
+
+            assert_cleared(&mut crate::hybrid::HybridGraph::new(), everything);
+
+            let mut indexed = IndexedGraph::new(HashGraph::new());
+            assert_cleared(&mut indexed, everything);
+            assert_eq!(indexed.index_of(&169).is_some(), everything);
+        }
+    }
+
+    fn assert_edges_into<W>(graph: &mut dyn Graph<u64, W>) {
+        push_nodes(graph, 1);
+        for i in 0..69 {
+            graph.add_edge(i, (i * 5 + 1) % 69);
+            graph.add_edge(i, 68 - i);
+            graph.add_edge(i, 63);
+        }
+
+        let mut out = vec![1000, 2000];
+        for node in 0..NODES {
+            graph.outgoing_edges_into(node, &mut out);
+            assert_eq!(out, graph.outgoing_edges_of(node));
+            graph.incoming_edges_into(node, &mut out);
+            assert_eq!(out, graph.incoming_edges_of(node));
+        }
+
+        // 69 has nothing going either way
+        graph.outgoing_edges_into(63, &mut out);
+        assert!(!out.is_empty());
+        graph.outgoing_edges_into(69, &mut out);
+        assert!(out.is_empty());
+        graph.incoming_edges_into(63, &mut out);
+        assert_eq!(out.len(), 69);
+        graph.incoming_edges_into(69, &mut out);
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn edges_into_test() {
+        for_each_graph!(assert_edges_into);
+    }
+
+    /// edges added out of order, across word boundaries
+    fn assert_neighbors_sorted<W>(graph: &mut dyn Graph<u64, W>) {
+        push_nodes(graph, 1);
+        for &to in &[65, 3, 69, 0, 63, 64, 10] {
+            graph.add_edge(5, to);
+        }
+        graph.add_edge(64, 5);
+        graph.add_edge(64, 1);
+
+        let mut expected = graph.outgoing_edges_of(5);
+        expected.sort_unstable();
+        assert_eq!(expected, vec![0, 3, 10, 63, 64, 65, 69]);
+        assert_eq!(graph.neighbors_sorted(5), expected);
+        assert_eq!(graph.neighbors_sorted(64), vec![1, 5]);
+        assert!(graph.neighbors_sorted(6).is_empty());
+
+        for node in 0..graph.node_count() {
+            let sorted = graph.neighbors_sorted(node);
+            assert!(sorted.windows(2).all(|pair| pair[0] < pair[1]));
+        }
+    }
+
+    #[test]
+    fn neighbors_sorted_test() {
+        for_each_graph!(assert_neighbors_sorted);
+        assert_neighbors_sorted(&mut BitGraph::with_capacity_aligned(8));
+        assert_neighbors_sorted(&mut crate::undirected::Undirected::new(HashGraph::new()));
+
+        let mut graph = HashGraph::new();
+        assert_neighbors_sorted(&mut graph);
+        let filtered = FilteredGraph::keep_edges(&graph, |_, to| to != 63);
+        assert_eq!(filtered.neighbors_sorted(5), vec![0, 3, 10, 64, 65, 69]);
+    }
+
+    /// six nodes shrunk to three and grown back to five
+    fn assert_resize<W>(graph: &mut dyn Graph<u64, W>) {
+        for i in 0..6 {
+            graph.push_node(i * 10);
+        }
+        for &(from, to) in &[(0, 1), (1, 2), (2, 0), (0, 5), (4, 1), (2, 2), (5, 5)] {
+            graph.add_edge(from, to);
+        }
+
+        let sorted_pairs = |graph: &dyn Graph<u64, W>| {
+            let mut pairs = graph.all_edge_pairs();
+            pairs.sort_unstable();
+            pairs
+        };
+        let survivors = vec![(0, 1), (1, 2), (2, 0), (2, 2)];
+
+        graph.resize_nodes(3, 7);
+        assert_eq!(graph.node_count(), 3);
+        assert_eq!(
+            graph.node_values().copied().collect::<Vec<_>>(),
+            vec![0, 10, 20]
+        );
+        assert_eq!(sorted_pairs(graph), survivors);
+        assert_eq!(graph.incoming_edges_of(1), vec![0]);
+        // searches size themselves by the new count
+        assert_eq!(search::reachable_from(graph, 1), vec![0, 1, 2]);
+        assert_eq!(search::scc::strongly_connected_components(graph).len(), 1);
+
+        graph.resize_nodes(5, 7);
+        assert_eq!(
+            graph.node_values().copied().collect::<Vec<_>>(),
+            vec![0, 10, 20, 7, 7]
+        );
+        assert_eq!(sorted_pairs(graph), survivors);
+        assert!(graph.incoming_edges_of(4).is_empty());
+        assert!(graph.outgoing_edges_of(3).is_empty());
+        assert_eq!(search::reachable_from(graph, 3), vec![3]);
+
+        graph.add_edge(4, 3);
+        graph.reserve_nodes(100);
+        assert_eq!(graph.node_count(), 5);
+        assert!(graph.has_edge(4, 3));
+        assert_eq!(graph.get_node(3), &7);
+    }
+
+    #[test]
+    fn resize_test() {
+        for_each_graph!(assert_resize);
+        // four fit, pushing the fifth grows the matrices
+        assert_resize(&mut BitGraph::with_capacity(4));
+        assert_resize(&mut BitGraph::with_capacity_aligned(4));
+        assert_resize(&mut BitGraph::with_capacity_directed_only(4));
+        assert_resize(&mut AdjGraph::with_capacity(4));
+        assert_resize(&mut crate::hybrid::HybridGraph::with_threshold(4, 0.1));
+
+        let mut bit = BitGraph::with_capacity(4);
+        bit.reserve_nodes(100);
+        assert!(bit.capacity() >= 100);
+        bit.resize_nodes(70, 1);
+        bit.add_edge(69, 0);
+        assert_eq!(bit.validate(), Ok(()));
+        bit.resize_nodes(0, 1);
+        assert_eq!((bit.node_count(), bit.all_edge_pairs().len()), (0, 0));
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn set_count_test() {
+        let mut graph = HashGraph::new();
+        for i in 0..4 {
+            graph.push_node(i + 1);
+        }
+        graph.add_edge(0, 3);
+
+        // the values now follow the count
+        graph.set_count(2);
+        assert_eq!(graph.node_values().count(), 2);
+        assert!(graph.all_edge_pairs().is_empty());
+        graph.set_count(3);
+        assert_eq!(graph.get_node(2), &0);
+        assert_eq!(graph.validate(), Ok(()));
+    }
+
+    /// 70 nodes so the bit graphs move a node across a word
+    fn assert_remove_node<W>(graph: &mut dyn Graph<u64, W>) {
+        push_nodes(graph, 10);
+
+        graph.add_edge(0, 1);
+        graph.add_edge(1, 69);
+        graph.add_edge(69, 2);
+        graph.add_edge(3, 69);
+        graph.add_edge(69, 69);
+        graph.add_edge(64, 1);
+
+        // 69 moves into 1
+        assert_eq!(graph.remove_node(1), 10);
+        assert_eq!(graph.node_count(), 69);
+        assert_eq!(*graph.get_node(1), 690);
+        assert_eq!(graph.all_edge_pairs(), vec![(1, 1), (1, 2), (3, 1)]);
+        assert!(!graph.has_edge(0, 1));
+        assert_eq!(graph.incoming_edges_of(1), vec![1, 3]);
+        assert_eq!(graph.incoming_edges_of(2), vec![1]);
+
+        // the freed index comes back empty
+        assert_eq!(graph.push_node(700), 69);
+        assert!(graph.outgoing_edges_of(69).is_empty());
+        assert!(graph.incoming_edges_of(69).is_empty());
+        graph.add_edge(69, 0);
+        assert_eq!(graph.incoming_edges_of(0), vec![69]);
+
+        // the last node goes without moving anything
+        assert_eq!(graph.remove_node(69), 700);
+        assert_eq!(graph.all_edge_pairs(), vec![(1, 1), (1, 2), (3, 1)]);
+        assert!(graph.incoming_edges_of(0).is_empty());
+    }
+
+    #[test]
+    fn remove_node_test() {
+        for_each_graph!(assert_remove_node);
+    }
+
+    /// a chain through every 50th of 100k nodes, too big for a matrix
+    fn assert_sparse_search<W>(graph: &mut dyn Graph<u64, W>) {
+        for i in 0..100_000 {
+            graph.push_node(i);
+        }
+
+        for i in (0..100_000 - 50).step_by(50) {
+            graph.add_edge(i, i + 50);
+        }
+        graph.add_edge(99_950, 7);
+
+        let path = BFS::new(graph, 0).path_to(graph, 7).unwrap();
+        assert_eq!(path.len(), 2001);
+        assert_eq!(graph.incoming_edges_of(7), vec![99_950]);
+
+        graph.remove_edge(99_950, 7);
+        assert!(graph.outgoing_edges_of(99_950).is_empty());
+        assert!(BFS::new(graph, 0).path_to(graph, 7).is_none());
+
+        graph.remove_node(50);
+        assert!(graph.outgoing_edges_of(0).is_empty());
+        assert!(graph.incoming_edges_of(100).is_empty());
+    }
+
+    #[test]
+    fn sparse_search_test() {
+        // a neighbour query used to probe every node, which made this search
+        // take around 2 * 10^8 lookups
+        assert_sparse_search(&mut HashGraph::with_capacity(100_000));
+        assert_sparse_search(&mut crate::list::ListGraph::with_capacity(100_000));
+    }
+
+    /// `weight` must not be the graph's default weight
+    fn assert_add_keeps_weight<W: Clone + PartialEq + fmt::Debug>(
+        graph: &mut dyn Graph<u64, W>,
+        weight: W,
+    ) {
+        graph.push_node(0);
+        graph.push_node(1);
+
+        assert!(!graph.set_edge((0, 1), weight.clone()));
+        assert!(graph.add_edge(0, 1));
+        assert_eq!(graph.get_edge(0, 1).map(|edge| edge.weight), Some(weight));
+    }
+
+    #[test]
+    fn add_edge_keeps_weight_test() {
+        assert_add_keeps_weight(&mut HashGraph::new(), 5);
+        assert_add_keeps_weight(&mut AdjGraph::new(), 5);
+        assert_add_keeps_weight(&mut crate::list::ListGraph::new(), 5);
+        assert_add_keeps_weight(&mut crate::bit::LayeredBitGraph::<3>::new(), 5);
+    }
 }
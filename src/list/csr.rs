@@ -0,0 +1,105 @@
+/// Read only compressed sparse row form of a `ListGraph`, from `into_csr`.
+/// Every node's destinations sit next to each other in one flat array, node
+/// `i` owns `targets[offsets[i]..offsets[i + 1]]`.
+pub struct CsrGraph<T = u64, W = usize> {
+    nodes: Vec<T>,
+
+    /// `node_count + 1` entries, the last one is the edge count
+    offsets: Vec<usize>,
+    /// destinations, sorted within each node's range
+    targets: Vec<usize>,
+    /// weight of the edge at the same position in `targets`
+    weights: Vec<W>,
+}
+
+impl<T, W> CsrGraph<T, W> {
+    pub(super) fn from_lists(nodes: Vec<T>, lists: Vec<Vec<(usize, W)>>) -> Self {
+        let mut offsets = Vec::with_capacity(lists.len() + 1);
+        let edge_count = lists.iter().map(Vec::len).sum();
+        let mut targets = Vec::with_capacity(edge_count);
+        let mut weights = Vec::with_capacity(edge_count);
+
+        offsets.push(0);
+        for list in lists {
+            for (to, weight) in list {
+                targets.push(to);
+                weights.push(weight);
+            }
+            offsets.push(targets.len());
+        }
+
+        Self {
+            nodes,
+            offsets,
+            targets,
+            weights,
+        }
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.targets.len()
+    }
+
+    /// value of `node_index`, `None` past the last node
+    pub fn node(&self, node_index: usize) -> Option<&T> {
+        self.nodes.get(node_index)
+    }
+
+    /// destinations of `node_index`'s edges in ascending order
+    pub fn successors(&self, node_index: usize) -> &[usize] {
+        &self.targets[self.offsets[node_index]..self.offsets[node_index + 1]]
+    }
+
+    /// weights of `node_index`'s edges, lined up with `successors`
+    pub fn weights_of(&self, node_index: usize) -> &[W] {
+        &self.weights[self.offsets[node_index]..self.offsets[node_index + 1]]
+    }
+
+    pub fn has_edge(&self, from: usize, to: usize) -> bool {
+        self.weight_of(from, to).is_some()
+    }
+
+    /// weight of the edge from `from` to `to`, found with a binary search
+    pub fn weight_of(&self, from: usize, to: usize) -> Option<&W> {
+        let index = self.successors(from).binary_search(&to).ok()?;
+        Some(&self.weights_of(from)[index])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::ListGraph;
+    use crate::Graph;
+
+    #[test]
+    fn csr_test() {
+        let mut graph = ListGraph::new();
+        for i in 0..5 {
+            graph.push_node(i * 2);
+        }
+
+        graph.set_edge((0, 4), 3);
+        graph.set_edge((0, 1), 2);
+        graph.add_edge(3, 3);
+        graph.set_edge((4, 0), 9);
+        graph.remove_node(2);
+
+        let csr = graph.into_csr();
+        assert_eq!(csr.node_count(), 4);
+        assert_eq!(csr.edge_count(), 4);
+        assert_eq!(csr.node(2), Some(&8));
+
+        // 4 took over index 2
+        assert_eq!(csr.successors(0), &[1, 2]);
+        assert_eq!(csr.weights_of(0), &[2, 3]);
+        assert!(csr.successors(1).is_empty());
+        assert_eq!(csr.successors(2), &[0]);
+        assert_eq!(csr.weight_of(2, 0), Some(&9));
+        assert!(csr.has_edge(3, 3));
+        assert!(!csr.has_edge(3, 0));
+    }
+}
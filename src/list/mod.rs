@@ -0,0 +1,825 @@
+mod csr;
+
+pub use csr::CsrGraph;
+
+use crate::integrity::{check_count, into_result, IntegrityError};
+use crate::memory::{nested_vec_bytes, vec_bytes, MemoryBreakdown};
+use crate::{assert_node, cover_nodes, EdgeMeta, Graph};
+use std::iter::FromIterator;
+
+const DEFAULT_CAPACITY: usize = 16;
+
+/// Graph over adjacency lists, memory grows with the number of edges rather
+/// than the square of the node count. Node values are of type `T` and
+/// weights of type `W`, plain `ListGraph` is `u64` nodes and `usize` weights.
+pub struct ListGraph<T = u64, W = usize> {
+    count: usize,
+    nodes: Vec<T>,
+
+    /// every node's edges with their weights, kept sorted by destination.
+    /// Both list vecs can run past `count` after a `clear`, the lists there
+    /// are empty and `push_node` hands them out again.
+    outgoing: Vec<Vec<(usize, W)>>,
+    /// sources of every node's incoming edges, kept sorted
+    incoming: Vec<Vec<usize>>,
+
+    /// weight given to edges made with `add_edge`
+    default_weight: W,
+}
+
+impl Default for ListGraph {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl_node_access!(impl<T, W> for ListGraph<T, W>, T);
+
+/// Adds every `(from, to)` pair with the default weight, pushing
+/// `T::default()` nodes until both ends are nodes.
+impl<T: Default, W: Clone> Extend<(usize, usize)> for ListGraph<T, W> {
+    fn extend<I: IntoIterator<Item = (usize, usize)>>(&mut self, iter: I) {
+        for (from, to) in iter {
+            cover_nodes(self, from, to);
+            self.add_edge(from, to);
+        }
+    }
+}
+
+/// same as the unweighted `extend`, an existing edge gets the new weight
+impl<T: Default, W: Clone> Extend<(usize, usize, W)> for ListGraph<T, W> {
+    fn extend<I: IntoIterator<Item = (usize, usize, W)>>(&mut self, iter: I) {
+        for (from, to, weight) in iter {
+            cover_nodes(self, from, to);
+            self.set_edge((from, to), weight);
+        }
+    }
+}
+
+/// `add_edge` on the result gives weight 1, like `with_capacity`
+impl<T: Default, W: Clone + From<u8>> FromIterator<(usize, usize)> for ListGraph<T, W> {
+    fn from_iter<I: IntoIterator<Item = (usize, usize)>>(iter: I) -> Self {
+        let iter = iter.into_iter();
+        let size = iter.size_hint().0.max(DEFAULT_CAPACITY);
+
+        let mut graph = ListGraph::with_default_weight(size, W::from(1));
+        graph.extend(iter);
+        graph
+    }
+}
+
+/// `add_edge` on the result gives `W::default()`, every collected edge
+/// brings its own weight
+impl<T: Default, W: Clone + Default> FromIterator<(usize, usize, W)> for ListGraph<T, W> {
+    fn from_iter<I: IntoIterator<Item = (usize, usize, W)>>(iter: I) -> Self {
+        let iter = iter.into_iter();
+        let size = iter.size_hint().0.max(DEFAULT_CAPACITY);
+
+        let mut graph = ListGraph::with_default_weight(size, W::default());
+        graph.extend(iter);
+        graph
+    }
+}
+
+/// equal when node values and weighted edge sets match
+impl<T: PartialEq, W: PartialEq> PartialEq for ListGraph<T, W> {
+    fn eq(&self, other: &Self) -> bool {
+        self.count == other.count
+            && self.nodes == other.nodes
+            && self.outgoing[..self.count] == other.outgoing[..other.count]
+    }
+}
+
+impl ListGraph {
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_CAPACITY)
+    }
+
+    /// `add_edge` gives weight 1
+    pub fn with_capacity(size: usize) -> Self {
+        Self::with_default_weight(size, 1)
+    }
+}
+
+impl<T, W: Clone> ListGraph<T, W> {
+    /// empty graph with room for `size` nodes, `add_edge` gives every edge
+    /// `default_weight`
+    pub fn with_default_weight(size: usize, default_weight: W) -> Self {
+        Self {
+            count: 0,
+            nodes: Vec::with_capacity(size),
+
+            outgoing: Vec::with_capacity(size),
+            incoming: Vec::with_capacity(size),
+
+            default_weight,
+        }
+    }
+
+    /// nodes that fit before the node storage has to grow
+    pub fn capacity(&self) -> usize {
+        self.nodes.capacity()
+    }
+
+    /// the weighted outgoing lists under `edges`, the incoming ones under
+    /// `auxiliary`
+    pub fn memory_bytes(&self) -> MemoryBreakdown {
+        MemoryBreakdown {
+            nodes: vec_bytes(&self.nodes),
+            edges: nested_vec_bytes(&self.outgoing),
+            auxiliary: nested_vec_bytes(&self.incoming),
+        }
+    }
+
+    /// Checks that every outgoing edge is between two nodes and that the
+    /// outgoing and incoming lists hold the same edges.
+    pub fn validate(&self) -> Result<(), Vec<IntegrityError>> {
+        let mut errors = Vec::new();
+        check_count(self.count, self.nodes.len(), &mut errors);
+
+        let has_incoming = |to: usize, from: usize| {
+            self.incoming
+                .get(to)
+                .is_some_and(|list| list.binary_search(&from).is_ok())
+        };
+        let has_outgoing = |from: usize, to: usize| {
+            self.outgoing
+                .get(from)
+                .is_some_and(|list| list.binary_search_by_key(&to, |(next, _)| *next).is_ok())
+        };
+
+        for (from, list) in self.outgoing.iter().enumerate() {
+            for &(to, _) in list {
+                if from >= self.count || to >= self.count {
+                    errors.push(IntegrityError::EdgeOutOfBounds {
+                        from,
+                        to,
+                        count: self.count,
+                    });
+                }
+                if !has_incoming(to, from) {
+                    errors.push(IntegrityError::AdjacencyMismatch { from, to });
+                }
+            }
+        }
+        for (to, list) in self.incoming.iter().enumerate() {
+            for &from in list {
+                if !has_outgoing(from, to) {
+                    errors.push(IntegrityError::AdjacencyMismatch { from, to });
+                }
+            }
+        }
+
+        into_result(errors)
+    }
+
+    /// value of `node_index`, `None` past the last node
+    pub fn node(&self, node_index: usize) -> Option<&T> {
+        self.nodes.get(node_index)
+    }
+
+    /// number of edges
+    pub fn edge_count(&self) -> usize {
+        self.outgoing.iter().map(Vec::len).sum()
+    }
+
+    /// number of edges going out of `node_index`
+    pub fn out_degree(&self, node_index: usize) -> usize {
+        self.outgoing.get(node_index).map_or(0, Vec::len)
+    }
+
+    /// number of edges coming in to `node_index`
+    pub fn in_degree(&self, node_index: usize) -> usize {
+        self.incoming.get(node_index).map_or(0, Vec::len)
+    }
+
+    /// destinations of `node_index`'s edges with their weights, in ascending
+    /// order and without collecting, panics if `node_index` is not a node
+    pub fn successors(&self, node_index: usize) -> impl Iterator<Item = (usize, &W)> {
+        assert_node(node_index, self.count);
+        self.outgoing[node_index]
+            .iter()
+            .map(|(to, weight)| (*to, weight))
+    }
+
+    /// sources of the edges coming in to `node_index`, in ascending order,
+    /// panics if `node_index` is not a node
+    pub fn predecessors(&self, node_index: usize) -> impl Iterator<Item = usize> + '_ {
+        assert_node(node_index, self.count);
+        self.incoming[node_index].iter().copied()
+    }
+
+    /// Freezes the graph into flat arrays, see `CsrGraph`.
+    pub fn into_csr(mut self) -> CsrGraph<T, W> {
+        self.outgoing.truncate(self.count);
+        CsrGraph::from_lists(self.nodes, self.outgoing)
+    }
+
+    /// position of `to` in `from`'s edges, or where it would go
+    fn search(&self, from: usize, to: usize) -> Result<usize, usize> {
+        assert_node(from, self.count);
+        assert_node(to, self.count);
+
+        self.outgoing[from].binary_search_by_key(&to, |(next, _)| *next)
+    }
+}
+
+fn insert_sorted(list: &mut Vec<usize>, value: usize) {
+    if let Err(index) = list.binary_search(&value) {
+        list.insert(index, value);
+    }
+}
+
+fn remove_sorted(list: &mut Vec<usize>, value: usize) {
+    if let Ok(index) = list.binary_search(&value) {
+        list.remove(index);
+    }
+}
+
+impl<T, W: Clone> Graph<T, W> for ListGraph<T, W> {
+    fn add_edge(&mut self, from: usize, to: usize) -> bool {
+        if self.has_edge(from, to) {
+            return true;
+        }
+
+        self.set_edge((from, to), self.default_weight.clone())
+    }
+
+    fn set_edge(&mut self, from_to: (usize, usize), weight: W) -> bool {
+        let (from, to) = from_to;
+
+        match self.search(from, to) {
+            Ok(index) => {
+                self.outgoing[from][index].1 = weight;
+                true
+            }
+            Err(index) => {
+                self.outgoing[from].insert(index, (to, weight));
+                insert_sorted(&mut self.incoming[to], from);
+                false
+            }
+        }
+    }
+
+    fn remove_edge(&mut self, from: usize, to: usize) -> bool {
+        match self.search(from, to) {
+            Ok(index) => {
+                self.outgoing[from].remove(index);
+                remove_sorted(&mut self.incoming[to], from);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    fn has_edge(&self, from: usize, to: usize) -> bool {
+        self.search(from, to).is_ok()
+    }
+
+    fn get_edge(&self, from: usize, to: usize) -> Option<EdgeMeta<W>> {
+        let index = self.search(from, to).ok()?;
+
+        Some(EdgeMeta {
+            source: from,
+            destination: to,
+            weight: self.outgoing[from][index].1.clone(),
+        })
+    }
+
+    /// in ascending order
+    fn outgoing_edges_of(&self, node_index: usize) -> Vec<usize> {
+        self.successors(node_index).map(|(to, _)| to).collect()
+    }
+
+    /// the lists are kept sorted by destination
+    fn neighbors_sorted(&self, node_index: usize) -> Vec<usize> {
+        self.outgoing_edges_of(node_index)
+    }
+
+    /// in ascending order
+    fn incoming_edges_of(&self, node_index: usize) -> Vec<usize> {
+        self.predecessors(node_index).collect()
+    }
+
+    fn outgoing_edges_into(&self, node_index: usize, out: &mut Vec<usize>) {
+        out.clear();
+        out.extend(self.successors(node_index).map(|(to, _)| to));
+    }
+
+    fn incoming_edges_into(&self, node_index: usize, out: &mut Vec<usize>) {
+        out.clear();
+        out.extend(self.predecessors(node_index));
+    }
+
+    /// straight off the lists, sorted by source then destination
+    fn edges(&self) -> Box<dyn Iterator<Item = EdgeMeta<W>> + '_> {
+        let count = self.count;
+        Box::new(
+            self.outgoing
+                .iter()
+                .take(count)
+                .enumerate()
+                .flat_map(move |(from, list)| {
+                    list.iter()
+                        .filter(move |(to, _)| *to < count)
+                        .map(move |(to, weight)| EdgeMeta {
+                            source: from,
+                            destination: *to,
+                            weight: weight.clone(),
+                        })
+                }),
+        )
+    }
+
+    fn out_degree(&self, node_index: usize) -> usize {
+        ListGraph::out_degree(self, node_index)
+    }
+
+    fn in_degree(&self, node_index: usize) -> usize {
+        ListGraph::in_degree(self, node_index)
+    }
+
+    /// empties every list in place
+    fn clear_edges(&mut self) {
+        self.outgoing.iter_mut().for_each(Vec::clear);
+        self.incoming.iter_mut().for_each(Vec::clear);
+    }
+
+    /// keeps the node storage and every emptied list allocated for the
+    /// nodes pushed next
+    fn clear(&mut self) {
+        self.clear_edges();
+        self.nodes.clear();
+        self.count = 0;
+    }
+
+    /// filters every outgoing list in place, fixing up the incoming lists of
+    /// just the edges that go
+    fn retain_edges(&mut self, mut pred: impl FnMut(usize, usize, &W) -> bool) {
+        let incoming = &mut self.incoming;
+        for (from, list) in self.outgoing.iter_mut().enumerate() {
+            list.retain(|(to, weight)| {
+                let keep = pred(from, *to, weight);
+                if !keep {
+                    remove_sorted(&mut incoming[*to], from);
+                }
+                keep
+            });
+        }
+    }
+
+    fn push_node(&mut self, value: T) -> usize {
+        if self.outgoing.len() == self.count {
+            self.outgoing.push(Vec::new());
+            self.incoming.push(Vec::new());
+        }
+        self.count += 1;
+        self.nodes.push(value);
+        self.nodes.len() - 1
+    }
+
+    fn reserve_nodes(&mut self, additional: usize) {
+        self.nodes.reserve(additional);
+        self.outgoing.reserve(additional);
+        self.incoming.reserve(additional);
+    }
+
+    /// Panics if `node_index` is not a node.
+    fn set_node(&mut self, node_index: usize, value: T) {
+        assert_node(node_index, self.count);
+        self.nodes[node_index] = value;
+    }
+
+    /// Panics if `node_index` is not a node, see `node` for a checked lookup.
+    fn get_node(&self, node_index: usize) -> &T {
+        assert_node(node_index, self.count);
+        &self.nodes[node_index]
+    }
+
+    /// Removes the node and its edges the way `Vec::swap_remove` does, the
+    /// last node takes over `node_index` along with all of its edges. Same
+    /// as `HashGraph::remove_node`.
+    fn remove_node(&mut self, node_index: usize) -> T {
+        assert_node(node_index, self.count);
+        self.clear_edges_of(node_index);
+
+        let last = self.count - 1;
+        if node_index != last {
+            let moved = |node| if node == last { node_index } else { node };
+
+            let mut outgoing = std::mem::take(&mut self.outgoing[last]);
+            for (to, _) in outgoing.iter_mut() {
+                if *to != last {
+                    remove_sorted(&mut self.incoming[*to], last);
+                    insert_sorted(&mut self.incoming[*to], node_index);
+                }
+                *to = moved(*to);
+            }
+            outgoing.sort_unstable_by_key(|(to, _)| *to);
+
+            let mut incoming = std::mem::take(&mut self.incoming[last]);
+            for from in incoming.iter_mut() {
+                if *from != last {
+                    let list = &mut self.outgoing[*from];
+                    if let Ok(index) = list.binary_search_by_key(&last, |(to, _)| *to) {
+                        let (_, weight) = list.remove(index);
+                        let index = list
+                            .binary_search_by_key(&node_index, |(to, _)| *to)
+                            .unwrap_or_else(|index| index);
+                        list.insert(index, (node_index, weight));
+                    }
+                }
+                *from = moved(*from);
+            }
+            incoming.sort_unstable();
+
+            self.outgoing[node_index] = outgoing;
+            self.incoming[node_index] = incoming;
+        }
+
+        self.outgoing.pop();
+        self.incoming.pop();
+        self.count -= 1;
+        self.nodes.swap_remove(node_index)
+    }
+
+    fn node_count(&self) -> usize {
+        self.count
+    }
+}
+
+/// Serialized as `(node_count, nodes, default weight, weighted edges)`, the
+/// same shape as `HashGraph`.
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use super::ListGraph;
+    use crate::Graph;
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize)]
+    struct Repr<'a, T, W> {
+        node_count: usize,
+        nodes: &'a [T],
+        default_weight: &'a W,
+        edges: Vec<(usize, usize, &'a W)>,
+    }
+
+    #[derive(Deserialize)]
+    struct OwnedRepr<T, W> {
+        node_count: usize,
+        nodes: Vec<T>,
+        default_weight: W,
+        edges: Vec<(usize, usize, W)>,
+    }
+
+    impl<T: Serialize, W: Serialize + Clone> Serialize for ListGraph<T, W> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            Repr {
+                node_count: self.count,
+                nodes: &self.nodes,
+                default_weight: &self.default_weight,
+                edges: (0..self.nodes.len())
+                    .flat_map(|from| self.successors(from).map(move |(to, w)| (from, to, w)))
+                    .collect(),
+            }
+            .serialize(serializer)
+        }
+    }
+
+    impl<'de, T, W> Deserialize<'de> for ListGraph<T, W>
+    where
+        T: Deserialize<'de>,
+        W: Deserialize<'de> + Clone,
+    {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let repr = OwnedRepr::<T, W>::deserialize(deserializer)?;
+
+            if repr.nodes.len() != repr.node_count {
+                return Err(D::Error::custom(format!(
+                    "node_count is {} but there are {} nodes",
+                    repr.node_count,
+                    repr.nodes.len()
+                )));
+            }
+
+            let mut graph = ListGraph::with_default_weight(repr.node_count, repr.default_weight);
+            for value in repr.nodes {
+                graph.push_node(value);
+            }
+
+            for (from, to, weight) in repr.edges {
+                if from >= repr.node_count || to >= repr.node_count {
+                    return Err(D::Error::custom(format!(
+                        "edge ({}, {}) out of bounds for {} nodes",
+                        from, to, repr.node_count
+                    )));
+                }
+
+                graph.set_edge((from, to), weight);
+            }
+
+            Ok(graph)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_works() {
+        let mut graph = ListGraph::new();
+
+        for i in 1..16 {
+            graph.push_node(i);
+        }
+
+        graph.add_edge(0, 1);
+
+        graph.add_edge(2, 0);
+
+        assert!(graph.has_edge(2, 0));
+        assert!(!graph.has_edge(4, 3));
+    }
+
+    #[test]
+    fn outgoing_edges_test() {
+        let mut graph = ListGraph::new();
+
+        for i in 1..16 {
+            graph.push_node(i);
+        }
+
+        graph.add_edge(0, 1);
+        graph.add_edge(2, 0);
+
+        assert!(graph.outgoing_edges_of(0).len() == 1);
+        assert!(graph.outgoing_edges_of(4).is_empty());
+
+        for to in (2..10).rev() {
+            graph.add_edge(10, to);
+        }
+
+        assert_eq!(graph.outgoing_edges_of(10), vec![2, 3, 4, 5, 6, 7, 8, 9]);
+
+        graph.add_edge(10, 5);
+        graph.add_edge(10, 5);
+        assert_eq!(graph.outgoing_edges_of(10), vec![2, 3, 4, 5, 6, 7, 8, 9]);
+    }
+
+    #[test]
+    fn incoming_edges_test() {
+        let mut graph = ListGraph::new();
+
+        for i in 1..16 {
+            graph.push_node(i);
+        }
+
+        graph.add_edge(0, 1);
+        graph.add_edge(2, 0);
+
+        assert!(graph.incoming_edges_of(1).len() == 1);
+        assert!(graph.incoming_edges_of(4).is_empty());
+
+        graph.add_edge(7, 1);
+        graph.add_edge(2, 1);
+        graph.add_edge(5, 1);
+        graph.add_edge(3, 1);
+        graph.add_edge(4, 1);
+
+        assert_eq!(graph.incoming_edges_of(1), vec![0, 2, 3, 4, 5, 7]);
+        assert_eq!(graph.predecessors(1).count(), 6);
+    }
+
+    #[test]
+    fn all_edges_test() {
+        let mut graph = ListGraph::new();
+
+        for i in 0..8 {
+            graph.push_node(i);
+        }
+
+        let mut edges = vec![(0, 7), (7, 0), (3, 3), (4, 1), (4, 2), (1, 4)];
+        for &(from, to) in &edges {
+            graph.add_edge(from, to);
+        }
+
+        edges.sort_unstable();
+        assert_eq!(graph.all_edge_pairs(), edges);
+        assert_eq!(graph.edge_count(), 6);
+
+        graph.remove_edge(3, 3);
+        assert!(!graph.remove_edge(3, 3));
+        assert_eq!(graph.edge_count(), 5);
+        assert!(graph.incoming_edges_of(3).is_empty());
+    }
+
+    #[test]
+    fn weight_test() {
+        let mut graph = ListGraph::with_default_weight(4, 2.5);
+        for i in 0..4 {
+            graph.push_node(i);
+        }
+
+        graph.add_edge(0, 1);
+        assert!(!graph.set_edge((0, 3), 4.0));
+        assert!(graph.set_edge((0, 3), 1.5));
+
+        assert_eq!(graph.get_edge(0, 1).unwrap().weight, 2.5);
+        assert_eq!(graph.get_edge(0, 3).unwrap().weight, 1.5);
+        assert!(graph.get_edge(3, 0).is_none());
+
+        let successors: Vec<(usize, f64)> = graph.successors(0).map(|(to, w)| (to, *w)).collect();
+        assert_eq!(successors, vec![(1, 2.5), (3, 1.5)]);
+    }
+
+    #[test]
+    fn validate_test() {
+        let mut graph = ListGraph::new();
+        for i in 0..5 {
+            graph.push_node(i);
+        }
+        graph.add_edge(0, 1);
+        graph.set_edge((3, 0), 4);
+        graph.add_edge(2, 2);
+        assert_eq!(graph.validate(), Ok(()));
+
+        // in the outgoing list only, and to a node that is not there
+        graph.outgoing[1].push((7, 1));
+        // gone from the incoming list
+        graph.incoming[0].clear();
+
+        assert_eq!(
+            graph.validate(),
+            Err(vec![
+                IntegrityError::EdgeOutOfBounds {
+                    from: 1,
+                    to: 7,
+                    count: 5
+                },
+                IntegrityError::AdjacencyMismatch { from: 1, to: 7 },
+                IntegrityError::AdjacencyMismatch { from: 3, to: 0 },
+            ])
+        );
+    }
+
+    #[test]
+    fn remove_node_weights_test() {
+        let mut graph = ListGraph::new();
+        for i in 0..6 {
+            graph.push_node(i * 10);
+        }
+        graph.set_edge((5, 2), 7);
+        graph.set_edge((3, 5), 4);
+
+        // 5 moves into 1 and takes its weights along
+        graph.remove_node(1);
+        assert_eq!(graph.get_edge(1, 2).unwrap().weight, 7);
+        assert_eq!(graph.get_edge(3, 1).unwrap().weight, 4);
+        assert_eq!(graph.node(5), None);
+    }
+
+    #[test]
+    fn clear_keeps_lists_test() {
+        let mut graph = ListGraph::new();
+        for i in 0..4 {
+            graph.push_node(i);
+        }
+        graph.extend((1..4).map(|to| (0, to)));
+        let capacity = graph.outgoing[0].capacity();
+
+        graph.clear();
+        assert_eq!(graph.outgoing[0].capacity(), capacity);
+        assert!(graph == ListGraph::new());
+
+        // the old lists come back empty with the new nodes
+        graph.push_node(7);
+        graph.push_node(8);
+        assert_eq!(graph.outgoing[0].capacity(), capacity);
+        assert_eq!(graph.out_degree(0), 0);
+        graph.add_edge(1, 1);
+        graph.remove_node(0);
+        assert_eq!(graph.all_edge_pairs(), vec![(0, 0)]);
+        assert_eq!(graph.validate(), Ok(()));
+        assert_eq!(graph.into_csr().edge_count(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "node 2 out of bounds for 2 nodes")]
+    fn remove_node_bounds_test() {
+        let mut graph = ListGraph::new();
+        for i in 0..4 {
+            graph.push_node(i);
+        }
+        graph.clear();
+        graph.push_node(7);
+        graph.push_node(8);
+
+        // the spare list at 2 is not a node
+        graph.remove_node(2);
+    }
+
+    #[test]
+    #[should_panic(expected = "node 3 out of bounds for 1 nodes")]
+    fn successors_bounds_test() {
+        let mut graph: ListGraph<u64> = ListGraph::new();
+        for i in 0..4 {
+            graph.push_node(i);
+        }
+        graph.clear();
+        graph.push_node(7);
+        graph.successors(3).count();
+    }
+
+    #[test]
+    #[should_panic(expected = "node 3 out of bounds for 1 nodes")]
+    fn predecessors_bounds_test() {
+        let mut graph: ListGraph<u64> = ListGraph::new();
+        for i in 0..4 {
+            graph.push_node(i);
+        }
+        graph.clear();
+        graph.push_node(7);
+        graph.predecessors(3).count();
+    }
+
+    #[test]
+    fn big_outgoing_edges_test() {
+        let mut graph = ListGraph::with_capacity(100_000);
+
+        for i in 0..100_000 {
+            graph.push_node(i);
+        }
+
+        graph.add_edge(500, 402);
+        graph.add_edge(2, 0);
+
+        assert!(graph.outgoing_edges_of(500).len() == 1);
+        assert!(graph.outgoing_edges_of(4).is_empty());
+
+        for to in 2..10 {
+            graph.add_edge(99_999, to);
+        }
+        graph.add_edge(99_999, 5);
+        assert_eq!(
+            graph.outgoing_edges_of(99_999),
+            vec![2, 3, 4, 5, 6, 7, 8, 9]
+        );
+    }
+
+    #[test]
+    fn big_incoming_edges_test() {
+        let mut graph = ListGraph::with_capacity(100_000);
+
+        for i in 0..100_000 {
+            graph.push_node(i);
+        }
+
+        graph.add_edge(0, 99_999);
+        graph.add_edge(2, 0);
+
+        assert!(graph.incoming_edges_of(99_999).len() == 1);
+        assert!(graph.incoming_edges_of(4).is_empty());
+
+        for from in [2, 3, 4, 5, 7] {
+            graph.add_edge(from, 99_999);
+        }
+        assert_eq!(graph.incoming_edges_of(99_999), vec![0, 2, 3, 4, 5, 7]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip_test() {
+        let mut graph = ListGraph::with_default_weight(8, 1.5f32);
+        for i in 0..8 {
+            graph.push_node(i);
+        }
+        graph.add_edge(0, 7);
+        graph.set_edge((7, 3), 0.25);
+
+        let json = serde_json::to_string(&graph).unwrap();
+        let back: ListGraph<u64, f32> = serde_json::from_str(&json).unwrap();
+
+        assert!(back == graph);
+        assert_eq!(back.get_edge(7, 3).unwrap().weight, 0.25);
+    }
+
+    #[test]
+    fn collect_test() {
+        let edges = vec![(2, 0, 4), (0, 1, 6), (2, 1, 1)];
+        let graph: ListGraph<u64, u32> = edges.into_iter().collect();
+        assert_eq!(graph.node_count(), 3);
+        assert_eq!(graph.all_edge_pairs(), vec![(0, 1), (2, 0), (2, 1)]);
+        assert_eq!(graph.get_edge(2, 0).unwrap().weight, 4);
+
+        let mut graph: ListGraph = (0..10).filter(|i| i % 3 == 0).map(|i| (i, 0)).collect();
+        assert_eq!(graph.node_count(), 10);
+        assert_eq!(graph.incoming_edges_of(0), vec![0, 3, 6, 9]);
+
+        graph.extend(vec![(12, 1)]);
+        assert_eq!(graph.node_count(), 13);
+        assert_eq!(graph.get_edge(12, 1).unwrap().weight, 1);
+    }
+}
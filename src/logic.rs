@@ -0,0 +1,183 @@
+use crate::bit::BitGraph;
+use crate::search::scc::strongly_connected_components;
+use crate::Graph;
+
+/// 2-SAT over variables `1..=num_vars`. Literals are written the DIMACS way,
+/// `3` is variable 3 and `-3` its negation.
+///
+/// Every clause `a ∨ b` becomes the implications `¬a → b` and `¬b → a` in a
+/// `BitGraph` with a node per literal, the formula is satisfiable exactly
+/// when no variable shares a strongly connected component with its negation.
+#[derive(Clone, Debug)]
+pub struct TwoSat {
+    num_vars: usize,
+    implications: BitGraph,
+}
+
+impl TwoSat {
+    pub fn new(num_vars: usize) -> TwoSat {
+        let mut implications = BitGraph::with_capacity(2 * num_vars);
+        implications.resize_nodes(2 * num_vars, 0);
+
+        TwoSat {
+            num_vars,
+            implications,
+        }
+    }
+
+    pub fn num_vars(&self) -> usize {
+        self.num_vars
+    }
+
+    /// adds the clause `a ∨ b`, `add_clause(a, a)` forces `a` to hold
+    pub fn add_clause(&mut self, a: i64, b: i64) {
+        let (a, b) = (self.node_of(a), self.node_of(b));
+
+        self.implications.add_edge(a ^ 1, b);
+        self.implications.add_edge(b ^ 1, a);
+    }
+
+    /// A value for every variable, index 0 is variable 1, or `None` if the
+    /// clauses can not all hold.
+    ///
+    /// Components come out of Tarjan's algorithm in reverse topological
+    /// order, each variable takes whichever of its two literals comes later
+    /// in topological order so nothing true ever implies something false.
+    pub fn solve(&self) -> Option<Vec<bool>> {
+        let mut component_of = vec![0; 2 * self.num_vars];
+        for (component, members) in strongly_connected_components(&self.implications)
+            .iter()
+            .enumerate()
+        {
+            for &member in members {
+                component_of[member] = component;
+            }
+        }
+
+        (0..self.num_vars)
+            .map(|var| {
+                let (positive, negative) = (component_of[2 * var], component_of[2 * var + 1]);
+                if positive == negative {
+                    None
+                } else {
+                    Some(positive < negative)
+                }
+            })
+            .collect()
+    }
+
+    /// literal `x` is node `2(x - 1)`, `-x` is the node right after it
+    fn node_of(&self, literal: i64) -> usize {
+        let var = literal.unsigned_abs() as usize;
+        assert!(
+            var != 0 && var <= self.num_vars,
+            "literal {} is out of range for {} variables",
+            literal,
+            self.num_vars
+        );
+
+        2 * (var - 1) + (literal < 0) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn holds(assignment: &[bool], literal: i64) -> bool {
+        assignment[literal.unsigned_abs() as usize - 1] == (literal > 0)
+    }
+
+    fn assert_satisfies(assignment: &[bool], clauses: &[(i64, i64)]) {
+        for &(a, b) in clauses {
+            assert!(
+                holds(assignment, a) || holds(assignment, b),
+                "{:?} breaks {} ∨ {}",
+                assignment,
+                a,
+                b
+            );
+        }
+    }
+
+    fn with_clauses(num_vars: usize, clauses: &[(i64, i64)]) -> TwoSat {
+        let mut two_sat = TwoSat::new(num_vars);
+        for &(a, b) in clauses {
+            two_sat.add_clause(a, b);
+        }
+        two_sat
+    }
+
+    #[test]
+    fn satisfiable_test() {
+        let clauses = [(1, 2), (-1, 3), (-2, -3), (3, 4), (-4, -1), (2, 4)];
+        let assignment = with_clauses(4, &clauses).solve().unwrap();
+
+        assert_eq!(assignment.len(), 4);
+        assert_satisfies(&assignment, &clauses);
+    }
+
+    #[test]
+    fn forced_test() {
+        // x1, x1 → x2, x2 → ¬x3
+        let clauses = [(1, 1), (-1, 2), (-2, -3)];
+        let assignment = with_clauses(3, &clauses).solve().unwrap();
+
+        assert_eq!(assignment, vec![true, true, false]);
+    }
+
+    #[test]
+    fn unsatisfiable_test() {
+        assert_eq!(with_clauses(1, &[(1, 1), (-1, -1)]).solve(), None);
+
+        // x1 ↔ x2 and x1 ↔ ¬x2
+        let clauses = [(-1, 2), (1, -2), (1, 2), (-1, -2)];
+        assert_eq!(with_clauses(2, &clauses).solve(), None);
+    }
+
+    #[test]
+    fn empty_test() {
+        assert_eq!(TwoSat::new(0).solve(), Some(vec![]));
+        assert_eq!(TwoSat::new(3).solve().map(|a| a.len()), Some(3));
+    }
+
+    #[test]
+    fn planted_test() {
+        // clauses that all hold under a fixed assignment, so there is always
+        // a solution, though not necessarily that one
+        let num_vars = 300;
+        let planted: Vec<bool> = (0..num_vars).map(|var| var % 3 != 1).collect();
+        let literal = |var: usize, positive: bool| {
+            let literal = var as i64 + 1;
+            if positive {
+                literal
+            } else {
+                -literal
+            }
+        };
+
+        let clauses: Vec<(i64, i64)> = (0..4 * num_vars)
+            .map(|i: usize| {
+                let a = i.wrapping_mul(2654435761) % num_vars;
+                let b = (i * 7 + 13).wrapping_mul(40503) % num_vars;
+                // the first literal is always true, the second is random
+                (literal(a, planted[a]), literal(b, i % 4 < 2))
+            })
+            .collect();
+
+        let assignment = with_clauses(num_vars, &clauses).solve().unwrap();
+        assert_satisfies(&assignment, &clauses);
+    }
+
+    #[test]
+    #[should_panic(expected = "literal -4 is out of range for 3 variables")]
+    fn out_of_range_test() {
+        TwoSat::new(3).add_clause(1, -4);
+    }
+
+    #[test]
+    #[should_panic(expected = "literal 0 is out of range")]
+    fn zero_literal_test() {
+        TwoSat::new(3).add_clause(0, 1);
+    }
+}
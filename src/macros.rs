@@ -0,0 +1,35 @@
+/// Node value access shared by the graphs that keep their values in a
+/// `nodes: Vec<T>` with the first `count` of them live: `graph[i]` for
+/// `Index`/`IndexMut`, panicking past the last node, and the `nodes` and
+/// `nodes_mut` iterators. Called as `impl_node_access!(impl<T> for G<T>, T)`.
+macro_rules! impl_node_access {
+    (impl $(<$($param:ident),*>)? for $graph:ty, $value:ty) => {
+        impl$(<$($param),*>)? std::ops::Index<usize> for $graph {
+            type Output = $value;
+
+            fn index(&self, node_index: usize) -> &$value {
+                $crate::assert_node(node_index, self.count);
+                &self.nodes[node_index]
+            }
+        }
+
+        impl$(<$($param),*>)? std::ops::IndexMut<usize> for $graph {
+            fn index_mut(&mut self, node_index: usize) -> &mut $value {
+                $crate::assert_node(node_index, self.count);
+                &mut self.nodes[node_index]
+            }
+        }
+
+        impl$(<$($param),*>)? $graph {
+            /// every node index with its value, in order
+            pub fn nodes(&self) -> impl Iterator<Item = (usize, &$value)> {
+                self.nodes.iter().take(self.count).enumerate()
+            }
+
+            /// every node index with its value for changing in place, in order
+            pub fn nodes_mut(&mut self) -> impl Iterator<Item = (usize, &mut $value)> {
+                self.nodes.iter_mut().take(self.count).enumerate()
+            }
+        }
+    };
+}
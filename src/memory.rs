@@ -0,0 +1,137 @@
+use std::fmt;
+use std::mem::size_of;
+
+/// Heap bytes a graph holds on to, worked out from the capacities of its
+/// buffers and the sizes of what is in them. The graph struct itself and
+/// allocator overhead are not counted.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MemoryBreakdown {
+    /// node values
+    pub nodes: usize,
+    /// the main edge store, matrix words, table slots or adjacency lists
+    pub edges: usize,
+    /// anything kept next to it for faster lookups, transposes and the like
+    pub auxiliary: usize,
+}
+
+impl MemoryBreakdown {
+    pub fn total(&self) -> usize {
+        self.nodes + self.edges + self.auxiliary
+    }
+}
+
+/// one line each for nodes, edges, auxiliary and the total, in B, KiB, MiB
+/// or GiB
+impl fmt::Display for MemoryBreakdown {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "nodes:     {}", Bytes(self.nodes))?;
+        writeln!(f, "edges:     {}", Bytes(self.edges))?;
+        writeln!(f, "auxiliary: {}", Bytes(self.auxiliary))?;
+        write!(f, "total:     {}", Bytes(self.total()))
+    }
+}
+
+struct Bytes(usize);
+
+impl fmt::Display for Bytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const UNITS: [&str; 3] = ["KiB", "MiB", "GiB"];
+
+        if self.0 < 1024 {
+            return write!(f, "{} B", self.0);
+        }
+
+        let mut size = self.0 as f64 / 1024.0;
+        let mut unit = 0;
+        while size >= 1024.0 && unit < UNITS.len() - 1 {
+            size /= 1024.0;
+            unit += 1;
+        }
+
+        write!(f, "{:.1} {}", size, UNITS[unit])
+    }
+}
+
+/// heap bytes of a `Vec`, by capacity
+pub(crate) fn vec_bytes<T>(vec: &Vec<T>) -> usize {
+    vec.capacity() * size_of::<T>()
+}
+
+/// heap bytes of a `Vec` of `Vec`s, the outer one and every inner one
+pub(crate) fn nested_vec_bytes<T>(vecs: &Vec<Vec<T>>) -> usize {
+    vec_bytes(vecs) + vecs.iter().map(vec_bytes).sum::<usize>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bit::BitGraph;
+    use crate::hash::HashGraph;
+    use crate::Graph;
+
+    #[test]
+    fn bit_scales_with_dim_test() {
+        let small = BitGraph::with_capacity(128).memory_bytes();
+        let big = BitGraph::with_capacity(1024).memory_bytes();
+
+        // (dim² / 64 + 1) words per matrix
+        assert_eq!(small.edges, (128 * 128 / 64 + 1) * 8);
+        assert_eq!(big.edges, (1024 * 1024 / 64 + 1) * 8);
+        // eight times the dimension, about 64 times the bytes
+        assert!((60..=64).contains(&(big.edges / small.edges)));
+        assert_eq!(big.auxiliary, big.edges);
+        assert_eq!(big.nodes, 1024 * 8);
+
+        let mut graph = BitGraph::with_capacity(16);
+        let before = graph.memory_bytes();
+        graph.extend(vec![(0, 40)]);
+        let after = graph.memory_bytes();
+        assert!(after.edges > before.edges);
+        assert_eq!(
+            after.edges,
+            graph.capacity() * graph.capacity() / 64 * 8 + 8
+        );
+    }
+
+    #[test]
+    fn hash_scales_with_table_test() {
+        let mut graph = HashGraph::with_capacity(64);
+        for i in 0..64 {
+            graph.push_node(i);
+        }
+
+        let before = graph.memory_bytes();
+        let capacity = graph.edge_capacity();
+        let slot = before.edges / capacity;
+        assert!(slot >= 3 * 8);
+        assert_eq!(before.nodes, 64 * 8);
+
+        for i in 0..64 {
+            for j in 0..8 {
+                graph.add_edge(i, (i + j) % 64);
+            }
+        }
+
+        let after = graph.memory_bytes();
+        assert!(graph.edge_capacity() > capacity);
+        assert_eq!(after.edges, graph.edge_capacity() * slot);
+        assert!(after.auxiliary > before.auxiliary);
+    }
+
+    #[test]
+    fn display_test() {
+        let memory = MemoryBreakdown {
+            nodes: 512,
+            edges: 3 * 1024 * 1024 + 512 * 1024,
+            auxiliary: 1536,
+        };
+
+        assert_eq!(
+            memory.to_string(),
+            "nodes:     512 B\n\
+             edges:     3.5 MiB\n\
+             auxiliary: 1.5 KiB\n\
+             total:     3.5 MiB"
+        );
+    }
+}
@@ -0,0 +1,403 @@
+use crate::hash::{EdgeEntry, HashGraph, DEFAULT_CAPACITY};
+use crate::{EdgeMeta, Graph};
+
+/// A graph where the same edge can be there more than once, kept as a
+/// `HashGraph` with the number of copies of each edge as its weight.
+///
+/// `add_edge` adds one more copy and `remove_edge` takes one away, the edge
+/// is gone when the last copy is. Through `Graph` the weight of an edge is
+/// its multiplicity and neighbour lists name each neighbour once, the
+/// degrees count every copy.
+pub struct MultiGraph<T = u64> {
+    graph: HashGraph<T, usize>,
+}
+
+impl<T> Default for MultiGraph<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> MultiGraph<T> {
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_CAPACITY)
+    }
+
+    /// empty graph with room for `size` nodes and distinct edges
+    pub fn with_capacity(size: usize) -> Self {
+        MultiGraph {
+            graph: HashGraph::with_default_weight(size, 1),
+        }
+    }
+
+    /// copies of the edge from `from` to `to`, 0 if there is none
+    pub fn edge_multiplicity(&self, from: usize, to: usize) -> usize {
+        self.graph.get_edge(from, to).map_or(0, |edge| edge.weight)
+    }
+
+    /// Takes away every copy of the edge at once, returns how many there
+    /// were.
+    pub fn remove_all_edges(&mut self, from: usize, to: usize) -> usize {
+        match self.graph.edge_entry(from, to) {
+            EdgeEntry::Occupied(edge) => edge.remove(),
+            EdgeEntry::Vacant(_) => 0,
+        }
+    }
+
+    /// number of edges, every copy counted
+    pub fn edge_count(&self) -> usize {
+        self.graph.weighted_edges().map(|edge| *edge.weight).sum()
+    }
+
+    /// number of edges with copies counted once
+    pub fn distinct_edge_count(&self) -> usize {
+        self.graph.edge_count()
+    }
+
+    /// every edge once as `(from, to, multiplicity)`, in no particular order
+    pub fn edge_counts(&self) -> impl Iterator<Item = (usize, usize, usize)> + '_ {
+        self.graph
+            .weighted_edges()
+            .map(|edge| (edge.source, edge.destination, *edge.weight))
+    }
+
+    /// every copy of every edge as its own `(from, to)`, the copies of one
+    /// edge next to each other
+    pub fn expanded_edges(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.edge_counts()
+            .flat_map(|(from, to, count)| std::iter::repeat_n((from, to), count))
+    }
+
+    /// the graph underneath, weighted by multiplicity
+    pub fn as_hash_graph(&self) -> &HashGraph<T, usize> {
+        &self.graph
+    }
+}
+
+impl<T> Graph<T, usize> for MultiGraph<T> {
+    /// adds one more copy, `true` if there already was one
+    fn add_edge(&mut self, from: usize, to: usize) -> bool {
+        let count = self.graph.edge_entry(from, to).or_insert(0);
+        *count += 1;
+        *count > 1
+    }
+
+    /// Sets the multiplicity of the edge, 0 removes it. `true` if it was
+    /// there before.
+    fn set_edge(&mut self, from_to: (usize, usize), weight: usize) -> bool {
+        if weight == 0 {
+            return self.remove_all_edges(from_to.0, from_to.1) > 0;
+        }
+
+        self.graph.set_edge(from_to, weight)
+    }
+
+    /// takes away one copy, `true` if there was one
+    fn remove_edge(&mut self, from: usize, to: usize) -> bool {
+        match self.graph.edge_entry(from, to) {
+            EdgeEntry::Occupied(mut edge) if *edge.get() > 1 => {
+                *edge.get_mut() -= 1;
+                true
+            }
+            EdgeEntry::Occupied(edge) => {
+                edge.remove();
+                true
+            }
+            EdgeEntry::Vacant(_) => false,
+        }
+    }
+
+    /// every copy of every edge of the node
+    fn clear_edges_of(&mut self, node_index: usize) {
+        self.graph.clear_edges_of(node_index);
+    }
+
+    fn clear_edges(&mut self) {
+        self.graph.clear_edges();
+    }
+
+    fn clear(&mut self) {
+        self.graph.clear();
+    }
+
+    /// at least one copy
+    fn has_edge(&self, from: usize, to: usize) -> bool {
+        self.graph.has_edge(from, to)
+    }
+
+    /// the edge with its multiplicity as the weight
+    fn get_edge(&self, from: usize, to: usize) -> Option<EdgeMeta<usize>> {
+        self.graph.get_edge(from, to)
+    }
+
+    /// each neighbour once however many copies lead there
+    fn outgoing_edges_of(&self, node_index: usize) -> Vec<usize> {
+        self.graph.outgoing_edges_of(node_index)
+    }
+
+    fn neighbors_sorted(&self, node_index: usize) -> Vec<usize> {
+        self.graph.neighbors_sorted(node_index)
+    }
+
+    fn incoming_edges_of(&self, node_index: usize) -> Vec<usize> {
+        self.graph.incoming_edges_of(node_index)
+    }
+
+    fn outgoing_edges_into(&self, node_index: usize, out: &mut Vec<usize>) {
+        self.graph.outgoing_edges_into(node_index, out);
+    }
+
+    fn incoming_edges_into(&self, node_index: usize, out: &mut Vec<usize>) {
+        self.graph.incoming_edges_into(node_index, out);
+    }
+
+    /// every edge once with its multiplicity, `expanded_edges` has the copies
+    fn edges(&self) -> Box<dyn Iterator<Item = EdgeMeta<usize>> + '_> {
+        Box::new(Graph::edges(&self.graph))
+    }
+
+    /// copies of every edge going out of `node_index`
+    fn out_degree(&self, node_index: usize) -> usize {
+        self.outgoing_edges_of(node_index)
+            .into_iter()
+            .map(|to| self.edge_multiplicity(node_index, to))
+            .sum()
+    }
+
+    /// copies of every edge coming in to `node_index`
+    fn in_degree(&self, node_index: usize) -> usize {
+        self.incoming_edges_of(node_index)
+            .into_iter()
+            .map(|from| self.edge_multiplicity(from, node_index))
+            .sum()
+    }
+
+    /// every copy of every self loop, returns how many self loops there were
+    /// not counting copies
+    fn remove_self_loops(&mut self) -> usize {
+        self.graph.remove_self_loops()
+    }
+
+    /// `pred` sees each edge once with its multiplicity, a rejected edge
+    /// goes with all of its copies
+    fn retain_edges(&mut self, pred: impl FnMut(usize, usize, &usize) -> bool) {
+        self.graph.retain_edges(pred);
+    }
+
+    fn push_node(&mut self, value: T) -> usize {
+        self.graph.push_node(value)
+    }
+
+    fn set_node(&mut self, node_index: usize, value: T) {
+        self.graph.set_node(node_index, value);
+    }
+
+    fn get_node(&self, node_index: usize) -> &T {
+        self.graph.get_node(node_index)
+    }
+
+    /// like `HashGraph::remove_node`, the last node takes over `node_index`
+    /// with every copy of its edges
+    fn remove_node(&mut self, node_index: usize) -> T {
+        self.graph.remove_node(node_index)
+    }
+
+    fn node_count(&self) -> usize {
+        self.graph.node_count()
+    }
+
+    fn reserve_nodes(&mut self, additional: usize) {
+        self.graph.reserve_nodes(additional);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph(count: u64) -> MultiGraph {
+        let mut graph = MultiGraph::new();
+        for i in 0..count {
+            graph.push_node(i);
+        }
+        graph
+    }
+
+    #[test]
+    fn multiplicity_test() {
+        let mut graph = graph(4);
+
+        assert!(!graph.add_edge(0, 1));
+        assert!(graph.add_edge(0, 1));
+        assert!(graph.add_edge(0, 1));
+        assert!(!graph.add_edge(0, 2));
+        assert_eq!(graph.edge_multiplicity(0, 1), 3);
+        assert_eq!(graph.edge_multiplicity(1, 0), 0);
+
+        assert!(graph.remove_edge(0, 1));
+        assert!(graph.add_edge(0, 1));
+        assert!(graph.remove_edge(0, 1));
+        assert!(graph.remove_edge(0, 1));
+        assert_eq!(graph.edge_multiplicity(0, 1), 1);
+        assert!(graph.has_edge(0, 1));
+
+        assert!(graph.remove_edge(0, 1));
+        assert!(!graph.has_edge(0, 1));
+        assert!(!graph.remove_edge(0, 1));
+        assert!(graph.get_edge(0, 1).is_none());
+
+        assert!(!graph.add_edge(0, 1));
+        assert_eq!(graph.get_edge(0, 1).unwrap().weight, 1);
+    }
+
+    #[test]
+    fn degree_test() {
+        let mut graph = graph(5);
+        for _ in 0..3 {
+            graph.add_edge(0, 1);
+        }
+        graph.add_edge(0, 2);
+        graph.add_edge(3, 1);
+        graph.add_edge(3, 1);
+        graph.add_edge(1, 1);
+        graph.add_edge(1, 1);
+
+        assert_eq!(graph.out_degree(0), 4);
+        assert_eq!(graph.in_degree(1), 7);
+        assert_eq!(graph.out_degree(1), 2);
+        assert_eq!(graph.neighbors_sorted(0), vec![1, 2]);
+        assert_eq!(graph.neighbors_sorted(3), vec![1]);
+        assert_eq!(graph.edge_count(), 8);
+        assert_eq!(graph.distinct_edge_count(), 4);
+
+        graph.remove_edge(0, 1);
+        graph.remove_edge(3, 1);
+        assert_eq!(graph.out_degree(0), 3);
+        assert_eq!(graph.in_degree(1), 5);
+        assert_eq!(graph.edge_count(), 6);
+    }
+
+    #[test]
+    fn interleaved_test() {
+        // a counter per pair as the model, adds and removes mixed
+        let mut graph = graph(6);
+        let mut model = vec![vec![0usize; 6]; 6];
+        for i in 0..500usize {
+            let (from, to) = (i * 7 % 6, i * 11 % 5);
+            if i % 3 == 2 {
+                let had = model[from][to] > 0;
+                assert_eq!(graph.remove_edge(from, to), had);
+                model[from][to] = model[from][to].saturating_sub(1);
+            } else {
+                assert_eq!(graph.add_edge(from, to), model[from][to] > 0);
+                model[from][to] += 1;
+            }
+        }
+
+        for (from, row) in model.iter().enumerate() {
+            assert_eq!(graph.out_degree(from), row.iter().sum::<usize>());
+            for (to, &count) in row.iter().enumerate() {
+                assert_eq!(graph.edge_multiplicity(from, to), count);
+                assert_eq!(graph.has_edge(from, to), count > 0);
+            }
+        }
+        for to in 0..6 {
+            let column: usize = model.iter().map(|row| row[to]).sum();
+            assert_eq!(graph.in_degree(to), column);
+        }
+    }
+
+    #[test]
+    fn edge_iterators_test() {
+        let mut graph = graph(3);
+        graph.add_edge(0, 1);
+        graph.add_edge(0, 1);
+        graph.add_edge(2, 0);
+        graph.set_edge((1, 2), 3);
+
+        let mut counts: Vec<_> = graph.edge_counts().collect();
+        counts.sort_unstable();
+        assert_eq!(counts, vec![(0, 1, 2), (1, 2, 3), (2, 0, 1)]);
+
+        let mut collapsed: Vec<_> = graph
+            .edges()
+            .map(|edge| (edge.source, edge.destination, edge.weight))
+            .collect();
+        collapsed.sort_unstable();
+        assert_eq!(collapsed, counts);
+
+        let mut expanded: Vec<_> = graph.expanded_edges().collect();
+        expanded.sort_unstable();
+        assert_eq!(
+            expanded,
+            vec![(0, 1), (0, 1), (1, 2), (1, 2), (1, 2), (2, 0)]
+        );
+        assert_eq!(graph.all_edge_pairs().len(), 3);
+    }
+
+    #[test]
+    fn set_edge_test() {
+        let mut graph = graph(2);
+
+        assert!(!graph.set_edge((0, 1), 4));
+        assert!(graph.set_edge((0, 1), 2));
+        assert_eq!(graph.edge_multiplicity(0, 1), 2);
+
+        assert!(graph.set_edge((0, 1), 0));
+        assert!(!graph.has_edge(0, 1));
+        assert!(!graph.set_edge((0, 1), 0));
+        assert_eq!(graph.distinct_edge_count(), 0);
+    }
+
+    #[test]
+    fn bulk_removal_test() {
+        let mut graph = graph(4);
+        for _ in 0..3 {
+            graph.add_edge(1, 2);
+            graph.add_edge(2, 1);
+            graph.add_edge(3, 3);
+            graph.add_edge(0, 3);
+        }
+
+        assert_eq!(graph.remove_all_edges(0, 3), 3);
+        assert_eq!(graph.remove_all_edges(0, 3), 0);
+
+        assert_eq!(graph.remove_self_loops(), 1);
+        assert!(!graph.has_edge(3, 3));
+
+        graph.clear_edges_of(1);
+        assert_eq!(graph.edge_count(), 0);
+
+        for _ in 0..2 {
+            graph.add_edge(0, 1);
+            graph.add_edge(0, 2);
+        }
+        graph.add_edge(0, 2);
+        graph.retain_edges(|_, _, &count| count > 2);
+        assert_eq!(graph.edge_counts().collect::<Vec<_>>(), vec![(0, 2, 3)]);
+
+        graph.clear_edges();
+        assert_eq!(graph.edge_count(), 0);
+        assert_eq!(graph.node_count(), 4);
+    }
+
+    #[test]
+    fn remove_node_test() {
+        let mut graph = graph(4);
+        graph.add_edge(3, 0);
+        graph.add_edge(3, 0);
+        graph.add_edge(1, 3);
+        graph.add_edge(2, 1);
+        graph.add_edge(2, 1);
+        graph.add_edge(2, 1);
+
+        assert_eq!(graph.remove_node(2), 2);
+
+        // 3 answers to 2 now, with every copy of its edges
+        assert_eq!(*graph.get_node(2), 3);
+        assert_eq!(graph.edge_multiplicity(2, 0), 2);
+        assert_eq!(graph.edge_multiplicity(1, 2), 1);
+        assert_eq!(graph.edge_count(), 3);
+        assert_eq!(graph.in_degree(1), 0);
+    }
+}
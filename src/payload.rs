@@ -0,0 +1,417 @@
+use crate::hash::{EdgeEntry, HashGraph, DEFAULT_CAPACITY};
+use crate::{EdgeMeta, Graph};
+
+/// The number a payload stands for when a `PayloadGraph` is used through
+/// `Graph`, what the weighted searches see as the cost of its edge.
+pub trait EdgeWeight {
+    fn weight(&self) -> usize;
+
+    /// Changes whatever `weight` reads, for `Graph::set_edge`. Read only
+    /// payloads can leave it out, `set_edge` then panics on them.
+    fn set_weight(&mut self, _weight: usize) {
+        panic!("this payload has no set_weight, its weight can not be set");
+    }
+}
+
+impl EdgeWeight for usize {
+    fn weight(&self) -> usize {
+        *self
+    }
+
+    fn set_weight(&mut self, weight: usize) {
+        *self = weight;
+    }
+}
+
+/// A `HashGraph` with a value of any type `E` on each edge, road names,
+/// capacities and the like.
+///
+/// Payloads are added, borrowed and taken back out with the methods here,
+/// which work for any `E`. Through `Graph` the weight of an edge is
+/// `E::weight`, `add_edge` adds a copy of the default payload and `set_edge`
+/// calls `set_weight` on it, so the searches work on it as on any weighted
+/// graph.
+pub struct PayloadGraph<T, E> {
+    graph: HashGraph<T, E>,
+}
+
+impl<T, E: Clone + Default> Default for PayloadGraph<T, E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, E: Clone + Default> PayloadGraph<T, E> {
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_CAPACITY)
+    }
+
+    /// empty graph with room for `size` nodes and edges, `add_edge` gives
+    /// every edge `E::default()`
+    pub fn with_capacity(size: usize) -> Self {
+        Self::with_default_payload(size, E::default())
+    }
+}
+
+impl<T, E> PayloadGraph<T, E> {
+    /// empty graph with room for `size` nodes and edges, `add_edge` gives
+    /// every edge a copy of `payload`
+    pub fn with_default_payload(size: usize, payload: E) -> Self {
+        PayloadGraph {
+            graph: HashGraph::with_default_weight(size, payload),
+        }
+    }
+
+    /// `Graph::push_node`, also there for payloads that can not be cloned
+    pub fn push_node(&mut self, value: T) -> usize {
+        self.graph
+            .try_push_node(value)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Adds the edge with `payload`, or replaces the payload of the edge
+    /// already there and gives the old one back.
+    pub fn add_edge_with(&mut self, from: usize, to: usize, payload: E) -> Option<E> {
+        match self.graph.edge_entry(from, to) {
+            EdgeEntry::Occupied(mut edge) => Some(edge.insert(payload)),
+            EdgeEntry::Vacant(edge) => {
+                edge.insert(payload);
+                None
+            }
+        }
+    }
+
+    pub fn edge_payload(&self, from: usize, to: usize) -> Option<&E> {
+        self.graph.weight_of(from, to)
+    }
+
+    pub fn edge_payload_mut(&mut self, from: usize, to: usize) -> Option<&mut E> {
+        self.graph.weight_of_mut(from, to)
+    }
+
+    /// takes the edge out of the graph, returns its payload
+    pub fn remove_edge_with(&mut self, from: usize, to: usize) -> Option<E> {
+        match self.graph.edge_entry(from, to) {
+            EdgeEntry::Occupied(edge) => Some(edge.remove()),
+            EdgeEntry::Vacant(_) => None,
+        }
+    }
+
+    /// every edge with its payload, in no particular order
+    pub fn payloads(&self) -> impl Iterator<Item = EdgeMeta<&E>> {
+        self.graph.weighted_edges()
+    }
+
+    /// the graph underneath, weighted by payload
+    pub fn as_hash_graph(&self) -> &HashGraph<T, E> {
+        &self.graph
+    }
+}
+
+/// `add_edge` copies the default payload, so `E` has to be `Clone` here
+impl<T, E: EdgeWeight + Clone> Graph<T, usize> for PayloadGraph<T, E> {
+    /// adds the default payload, an edge already there keeps its own
+    fn add_edge(&mut self, from: usize, to: usize) -> bool {
+        let existed = self.graph.has_edge(from, to);
+        self.graph.edge_entry(from, to).or_default_weight();
+        existed
+    }
+
+    /// `set_weight` on the payload, the default one for a new edge
+    fn set_edge(&mut self, from_to: (usize, usize), weight: usize) -> bool {
+        let (from, to) = from_to;
+        let existed = self.graph.has_edge(from, to);
+        self.graph
+            .edge_entry(from, to)
+            .or_default_weight()
+            .set_weight(weight);
+        existed
+    }
+
+    fn remove_edge(&mut self, from: usize, to: usize) -> bool {
+        self.graph.remove_edge(from, to)
+    }
+
+    fn clear_edges(&mut self) {
+        self.graph.clear_edges();
+    }
+
+    fn clear(&mut self) {
+        self.graph.clear();
+    }
+
+    fn has_edge(&self, from: usize, to: usize) -> bool {
+        self.graph.has_edge(from, to)
+    }
+
+    /// the edge weighted by `E::weight`
+    fn get_edge(&self, from: usize, to: usize) -> Option<EdgeMeta<usize>> {
+        self.graph.weight_of(from, to).map(|payload| EdgeMeta {
+            source: from,
+            destination: to,
+            weight: payload.weight(),
+        })
+    }
+
+    fn outgoing_edges_of(&self, node_index: usize) -> Vec<usize> {
+        self.graph.outgoing_edges_of(node_index)
+    }
+
+    fn neighbors_sorted(&self, node_index: usize) -> Vec<usize> {
+        self.graph.neighbors_sorted(node_index)
+    }
+
+    fn incoming_edges_of(&self, node_index: usize) -> Vec<usize> {
+        self.graph.incoming_edges_of(node_index)
+    }
+
+    fn outgoing_edges_into(&self, node_index: usize, out: &mut Vec<usize>) {
+        self.graph.outgoing_edges_into(node_index, out);
+    }
+
+    fn incoming_edges_into(&self, node_index: usize, out: &mut Vec<usize>) {
+        self.graph.incoming_edges_into(node_index, out);
+    }
+
+    fn edges(&self) -> Box<dyn Iterator<Item = EdgeMeta<usize>> + '_> {
+        Box::new(self.graph.weighted_edges().map(|edge| EdgeMeta {
+            source: edge.source,
+            destination: edge.destination,
+            weight: edge.weight.weight(),
+        }))
+    }
+
+    fn out_degree(&self, node_index: usize) -> usize {
+        self.graph.out_degree(node_index)
+    }
+
+    fn in_degree(&self, node_index: usize) -> usize {
+        self.graph.in_degree(node_index)
+    }
+
+    /// `pred` sees the weight of each payload
+    fn retain_edges(&mut self, mut pred: impl FnMut(usize, usize, &usize) -> bool) {
+        self.graph
+            .retain_edges(|from, to, payload| pred(from, to, &payload.weight()));
+    }
+
+    fn push_node(&mut self, value: T) -> usize {
+        PayloadGraph::push_node(self, value)
+    }
+
+    fn set_node(&mut self, node_index: usize, value: T) {
+        self.graph.set_node(node_index, value);
+    }
+
+    fn get_node(&self, node_index: usize) -> &T {
+        self.graph.get_node(node_index)
+    }
+
+    /// like `HashGraph::remove_node`, the last node takes over `node_index`
+    /// with its edges and their payloads
+    fn remove_node(&mut self, node_index: usize) -> T {
+        self.graph.remove_node(node_index)
+    }
+
+    fn node_count(&self) -> usize {
+        self.graph.node_count()
+    }
+
+    fn reserve_nodes(&mut self, additional: usize) {
+        self.graph.reserve_nodes(additional);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::a_star::AStar;
+    use crate::search::Pathfinder;
+
+    #[derive(Clone, Debug, Default, PartialEq)]
+    struct Road {
+        name: String,
+        length: usize,
+        toll: bool,
+    }
+
+    impl Road {
+        fn new(name: &str, length: usize) -> Road {
+            Road {
+                name: name.to_string(),
+                length,
+                toll: false,
+            }
+        }
+    }
+
+    impl EdgeWeight for Road {
+        fn weight(&self) -> usize {
+            self.length
+        }
+
+        fn set_weight(&mut self, weight: usize) {
+            self.length = weight;
+        }
+    }
+
+    fn roads() -> PayloadGraph<&'static str, Road> {
+        let mut graph = PayloadGraph::new();
+        for name in &["depot", "mill", "bridge", "harbour"] {
+            graph.push_node(*name);
+        }
+
+        graph.add_edge_with(0, 3, Road::new("coast road", 9));
+        graph.add_edge_with(0, 1, Road::new("mill lane", 2));
+        graph.add_edge_with(1, 2, Road::new("river walk", 3));
+        graph.add_edge_with(2, 3, Road::new("quay street", 1));
+        graph
+    }
+
+    #[test]
+    fn payload_test() {
+        let mut graph = roads();
+
+        assert_eq!(graph.edge_payload(0, 1), Some(&Road::new("mill lane", 2)));
+        assert_eq!(graph.edge_payload(1, 0), None);
+
+        let road = graph.edge_payload_mut(1, 2).unwrap();
+        road.name.push_str(" north");
+        road.toll = true;
+        assert_eq!(graph.edge_payload(1, 2).unwrap().name, "river walk north");
+        assert!(graph.edge_payload(1, 2).unwrap().toll);
+        assert!(graph.edge_payload_mut(2, 1).is_none());
+
+        let old = graph.add_edge_with(0, 1, Road::new("mill road", 4));
+        assert_eq!(old, Some(Road::new("mill lane", 2)));
+        assert_eq!(graph.edge_payload(0, 1).unwrap().name, "mill road");
+
+        assert_eq!(
+            graph.remove_edge_with(2, 3),
+            Some(Road::new("quay street", 1))
+        );
+        assert_eq!(graph.remove_edge_with(2, 3), None);
+        assert!(!graph.has_edge(2, 3));
+        assert!(graph.outgoing_edges_of(2).is_empty());
+
+        let mut names: Vec<&str> = graph
+            .payloads()
+            .map(|edge| edge.weight.name.as_str())
+            .collect();
+        names.sort_unstable();
+        assert_eq!(names, vec!["coast road", "mill road", "river walk north"]);
+    }
+
+    #[test]
+    fn graph_weight_test() {
+        let mut graph = roads();
+
+        assert_eq!(graph.get_edge(0, 3).unwrap().weight, 9);
+        let mut weights: Vec<usize> = graph.edges().map(|edge| edge.weight).collect();
+        weights.sort_unstable();
+        assert_eq!(weights, vec![1, 2, 3, 9]);
+
+        // set_edge only changes what the weight reads from
+        assert!(graph.set_edge((0, 3), 5));
+        assert_eq!(graph.edge_payload(0, 3), Some(&Road::new("coast road", 5)));
+
+        // add_edge leaves a payload alone and gives new edges the default
+        assert!(graph.add_edge(0, 3));
+        assert_eq!(graph.edge_payload(0, 3).unwrap().length, 5);
+        assert!(!graph.add_edge(3, 0));
+        assert_eq!(graph.edge_payload(3, 0), Some(&Road::default()));
+        assert!(!graph.set_edge((3, 2), 4));
+        assert_eq!(graph.edge_payload(3, 2).unwrap().length, 4);
+
+        graph.retain_edges(|_, _, &weight| weight > 2);
+        let mut kept = graph.all_edge_pairs();
+        kept.sort_unstable();
+        assert_eq!(kept, vec![(0, 3), (1, 2), (3, 2)]);
+        assert_eq!(graph.edge_payload(1, 2).unwrap().name, "river walk");
+    }
+
+    #[test]
+    fn search_test() {
+        let mut graph = roads();
+
+        // the coast road is one hop but 9 long, round by the mill is 6
+        let mut astar = AStar::new(&graph, 0, 3, |_| 0).weighted();
+        assert_eq!(astar.path_to(&graph, 3).unwrap(), vec![0, 1, 2, 3]);
+        assert_eq!(astar.cost_of(3), Some(6));
+
+        graph.edge_payload_mut(0, 3).unwrap().length = 4;
+        let mut astar = AStar::new(&graph, 0, 3, |_| 0).weighted();
+        assert_eq!(astar.path_to(&graph, 3).unwrap(), vec![0, 3]);
+    }
+
+    #[test]
+    fn remove_node_test() {
+        let mut graph = roads();
+
+        // the harbour takes over index 1 with its incoming roads
+        assert_eq!(graph.remove_node(1), "mill");
+        assert_eq!(*graph.get_node(1), "harbour");
+        assert_eq!(graph.edge_payload(0, 1).unwrap().name, "coast road");
+        assert_eq!(graph.edge_payload(2, 1).unwrap().name, "quay street");
+        assert_eq!(graph.payloads().count(), 2);
+    }
+
+    #[test]
+    fn plain_weights_test() {
+        let mut graph: PayloadGraph<u64, usize> = PayloadGraph::with_default_payload(8, 1);
+        graph.push_node(0);
+        graph.push_node(1);
+
+        graph.add_edge(0, 1);
+        assert_eq!(graph.get_edge(0, 1).unwrap().weight, 1);
+        graph.set_edge((0, 1), 7);
+        assert_eq!(graph.edge_payload(0, 1), Some(&7));
+    }
+
+    /// neither `Clone` nor `set_weight`
+    #[derive(Debug, PartialEq)]
+    struct Permit(String);
+
+    impl EdgeWeight for Permit {
+        fn weight(&self) -> usize {
+            self.0.len()
+        }
+    }
+
+    #[test]
+    fn unclonable_payload_test() {
+        let mut graph = PayloadGraph::with_default_payload(4, Permit(String::new()));
+        graph.push_node("gate");
+        graph.push_node("yard");
+
+        assert_eq!(graph.add_edge_with(0, 1, Permit("day".to_string())), None);
+        let old = graph.add_edge_with(0, 1, Permit("night".to_string()));
+        assert_eq!(old, Some(Permit("day".to_string())));
+        graph.edge_payload_mut(0, 1).unwrap().0.push_str(" shift");
+        assert_eq!(graph.payloads().count(), 1);
+
+        let permit = graph.remove_edge_with(0, 1).unwrap();
+        assert_eq!(permit, Permit("night shift".to_string()));
+        assert_eq!(graph.edge_payload(0, 1), None);
+        assert_eq!(graph.as_hash_graph().edge_count(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "its weight can not be set")]
+    fn read_only_set_edge_test() {
+        #[derive(Clone, Default)]
+        struct Fixed;
+
+        impl EdgeWeight for Fixed {
+            fn weight(&self) -> usize {
+                1
+            }
+        }
+
+        let mut graph: PayloadGraph<u64, Fixed> = PayloadGraph::new();
+        graph.push_node(0);
+        graph.add_edge(0, 0);
+        assert_eq!(graph.get_edge(0, 0).unwrap().weight, 1);
+        graph.set_edge((0, 0), 2);
+    }
+}
@@ -0,0 +1,224 @@
+//! Property tests running every graph against a `HashSet` model.
+//!
+//! Inputs go up to 150 nodes so rows cross one and two word boundaries, and
+//! shrink by dropping edges and then nodes, so a failure comes back as the
+//! shortest edge list that still breaks.
+
+use crate::bit::LayeredBitGraph;
+use crate::hash::HashGraph;
+use crate::list::ListGraph;
+use crate::search::bfs::BFS;
+use crate::{AdjGraph, BitGraph, Graph};
+use proptest::prelude::*;
+use std::collections::{HashSet, VecDeque};
+
+const MAX_NODES: usize = 150;
+const MAX_EDGES: usize = 400;
+
+/// a node count and edges between those nodes, repeats allowed
+#[derive(Clone, Debug)]
+struct GraphInput {
+    node_count: usize,
+    edges: Vec<(usize, usize)>,
+}
+
+impl Arbitrary for GraphInput {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<GraphInput>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        (1..=MAX_NODES)
+            .prop_flat_map(|node_count| {
+                let edge = (0..node_count, 0..node_count);
+                prop::collection::vec(edge, 0..MAX_EDGES)
+                    .prop_map(move |edges| GraphInput { node_count, edges })
+            })
+            .boxed()
+    }
+}
+
+/// what every graph should agree with
+struct Model {
+    node_count: usize,
+    edges: HashSet<(usize, usize)>,
+}
+
+impl Model {
+    fn outgoing(&self, node: usize) -> HashSet<usize> {
+        self.edges
+            .iter()
+            .filter(|&&(from, _)| from == node)
+            .map(|&(_, to)| to)
+            .collect()
+    }
+
+    fn incoming(&self, node: usize) -> HashSet<usize> {
+        self.edges
+            .iter()
+            .filter(|&&(_, to)| to == node)
+            .map(|&(from, _)| from)
+            .collect()
+    }
+
+    /// hops from `root` by a plain queue over the edge set
+    fn distances(&self, root: usize) -> Vec<Option<usize>> {
+        let mut distances = vec![None; self.node_count];
+        distances[root] = Some(0);
+
+        let mut queue = VecDeque::from(vec![root]);
+        while let Some(node) = queue.pop_front() {
+            let mut next: Vec<usize> = self.outgoing(node).into_iter().collect();
+            next.sort_unstable();
+            for to in next {
+                if distances[to].is_none() {
+                    distances[to] = Some(distances[node].unwrap() + 1);
+                    queue.push_back(to);
+                }
+            }
+        }
+
+        distances
+    }
+}
+
+/// Adds `input` to an empty `graph` and removes every other edge again,
+/// checking it against the model after each half.
+fn check<W>(graph: &mut dyn Graph<u64, W>, input: &GraphInput) -> Result<(), TestCaseError> {
+    let mut model = Model {
+        node_count: input.node_count,
+        edges: HashSet::new(),
+    };
+
+    for i in 0..input.node_count {
+        prop_assert_eq!(graph.push_node(i as u64), i);
+    }
+
+    for &(from, to) in &input.edges {
+        let existed = !model.edges.insert((from, to));
+        prop_assert_eq!(
+            graph.add_edge(from, to),
+            existed,
+            "add_edge({}, {})",
+            from,
+            to
+        );
+        prop_assert!(
+            graph.has_edge(from, to),
+            "({}, {}) missing after add",
+            from,
+            to
+        );
+    }
+    check_edges(graph, &model)?;
+
+    for &(from, to) in input.edges.iter().step_by(2) {
+        let existed = model.edges.remove(&(from, to));
+        prop_assert_eq!(
+            graph.remove_edge(from, to),
+            existed,
+            "remove_edge({}, {})",
+            from,
+            to
+        );
+        prop_assert!(
+            !graph.has_edge(from, to),
+            "({}, {}) left after remove",
+            from,
+            to
+        );
+    }
+    check_edges(graph, &model)
+}
+
+fn check_edges<W>(graph: &dyn Graph<u64, W>, model: &Model) -> Result<(), TestCaseError> {
+    let mut incoming_total = 0;
+    for node in 0..model.node_count {
+        let outgoing = graph.outgoing_edges_of(node);
+        let incoming = graph.incoming_edges_of(node);
+        incoming_total += incoming.len();
+
+        // no repeats, and the same sets as the model
+        prop_assert_eq!(
+            outgoing.iter().collect::<HashSet<_>>().len(),
+            outgoing.len()
+        );
+        prop_assert_eq!(
+            incoming.iter().collect::<HashSet<_>>().len(),
+            incoming.len()
+        );
+        prop_assert_eq!(
+            outgoing.iter().copied().collect::<HashSet<_>>(),
+            model.outgoing(node)
+        );
+        prop_assert_eq!(
+            incoming.iter().copied().collect::<HashSet<_>>(),
+            model.incoming(node)
+        );
+
+        prop_assert_eq!(graph.out_degree(node), outgoing.len());
+        prop_assert_eq!(graph.in_degree(node), incoming.len());
+
+        // every outgoing edge shows up as incoming on the other end
+        for &to in &outgoing {
+            prop_assert!(
+                graph.incoming_edges_of(to).contains(&node),
+                "{} -> {} is not incoming on {}",
+                node,
+                to,
+                to
+            );
+        }
+    }
+    prop_assert_eq!(incoming_total, model.edges.len());
+
+    // a few roots spread over the graph are enough to reach most bugs
+    for root in (0..model.node_count).step_by(1 + model.node_count / 4) {
+        let distances = BFS::new(graph, root).distances(graph);
+        prop_assert_eq!(distances, model.distances(root), "bfs from {}", root);
+    }
+
+    Ok(())
+}
+
+proptest! {
+    #[test]
+    fn bit_graph_prop(input: GraphInput) {
+        // grown one node at a time, so every push may move the rows
+        let mut graph = BitGraph::new();
+        check(&mut graph, &input)?;
+        prop_assert_eq!(graph.validate(), Ok(()));
+    }
+
+    #[test]
+    fn bit_graph_aligned_prop(input: GraphInput) {
+        let mut graph = BitGraph::with_capacity_aligned(input.node_count);
+        check(&mut graph, &input)?;
+        prop_assert_eq!(graph.validate(), Ok(()));
+    }
+
+    #[test]
+    fn bit_graph_directed_only_prop(input: GraphInput) {
+        let mut graph = BitGraph::with_capacity_directed_only(input.node_count);
+        check(&mut graph, &input)?;
+    }
+
+    #[test]
+    fn layered_bit_graph_prop(input: GraphInput) {
+        check(&mut LayeredBitGraph::<3>::new(), &input)?;
+    }
+
+    #[test]
+    fn adj_graph_prop(input: GraphInput) {
+        check(&mut AdjGraph::new(), &input)?;
+    }
+
+    #[test]
+    fn hash_graph_prop(input: GraphInput) {
+        check(&mut HashGraph::new(), &input)?;
+    }
+
+    #[test]
+    fn list_graph_prop(input: GraphInput) {
+        check(&mut ListGraph::new(), &input)?;
+    }
+}
@@ -1,3 +1,4 @@
+use crate::bit::NodeBitSet;
 use crate::search::Pathfinder;
 use crate::Graph;
 use std::collections::binary_heap::BinaryHeap;
@@ -24,9 +25,10 @@ impl PartialOrd for HeapNode {
     }
 }
 
-/// A star using manhattan distance as heuristic
-/// indicies are assumed to be an index into a 2D Array
-pub struct AStarMH {
+/// A star with a caller supplied heuristic, `heuristic(idx)` should estimate
+/// the cost from `idx` to the goal. Every step costs 1 unless `weighted` is
+/// used. Self loops never lower a node's cost, so they are never taken.
+pub struct AStar<H> {
     root_idx: usize,
     goal_idx: usize,
 
@@ -35,153 +37,224 @@ pub struct AStarMH {
     g_score: Vec<usize>,
     f_score: Vec<usize>,
 
-    /// dims of environment
-    dim: usize,
+    heuristic: H,
+    /// step costs come from `get_edge` weights
+    weighted: bool,
+
+    /// Nodes with a live entry in `open_set`. Entries are never removed when a
+    /// node gets a better score, a new one is pushed and the old one skipped
+    /// when it comes up.
+    in_open: NodeBitSet,
+    /// nodes expanded at least once
+    discovered: NodeBitSet,
+    visited_count: usize,
+    expansions: usize,
+    /// neighbours of the node being expanded, reused across expansions
+    scratch: Vec<usize>,
 
     pub from_map: Vec<usize>,
     pub solved: bool,
 }
 
-impl AStarMH {
+impl<H: Fn(usize) -> usize> AStar<H> {
     pub fn new<V, W>(
         graph: &dyn Graph<V, W>,
         root_idx: usize,
         goal_idx: usize,
-        dim: usize,
+        heuristic: H,
     ) -> Self {
-        let mut g_score = vec![std::usize::MAX; graph.node_count()];
-        g_score[root_idx] = 0;
+        let mut astar = Self {
+            root_idx,
+            goal_idx,
+            open_set: BinaryHeap::new(),
+
+            g_score: vec![usize::MAX; graph.node_count()],
+            f_score: vec![usize::MAX; graph.node_count()],
 
-        let mut f_score = vec![std::usize::MAX; graph.node_count()];
-        f_score[root_idx] = mh_distance(root_idx, goal_idx, dim);
+            heuristic,
+            weighted: false,
+
+            in_open: NodeBitSet::with_capacity(graph.node_count()),
+            discovered: NodeBitSet::with_capacity(graph.node_count()),
+            visited_count: 0,
+            expansions: 0,
+            scratch: Vec::new(),
+
+            from_map: vec![usize::MAX; graph.node_count()],
+            solved: false,
+        };
+
+        astar.reset(root_idx);
+        astar
+    }
 
-        let mut open_set = BinaryHeap::new();
-        open_set.push(HeapNode {
+    /// Starts over from `root_idx` towards the same goal, reusing every
+    /// allocation. The graph must not have gained nodes since `new`.
+    pub fn reset(&mut self, root_idx: usize) {
+        self.root_idx = root_idx;
+
+        self.open_set.clear();
+        self.g_score
+            .iter_mut()
+            .for_each(|score| *score = usize::MAX);
+        self.f_score
+            .iter_mut()
+            .for_each(|score| *score = usize::MAX);
+        self.from_map.iter_mut().for_each(|from| *from = usize::MAX);
+
+        self.in_open.clear();
+        self.discovered.clear();
+        self.visited_count = 0;
+        self.expansions = 0;
+        self.solved = false;
+
+        self.in_open.insert(root_idx);
+        self.g_score[root_idx] = 0;
+        self.f_score[root_idx] = (self.heuristic)(root_idx);
+        self.open_set.push(HeapNode {
             index: root_idx,
-            score: f_score[root_idx],
-            h_score: f_score[root_idx],
+            score: self.f_score[root_idx],
+            h_score: self.f_score[root_idx],
         });
+    }
 
-        Self {
-            root_idx,
-            goal_idx,
-            open_set,
+    /// the node the heuristic was written for
+    pub fn goal_idx(&self) -> usize {
+        self.goal_idx
+    }
 
-            g_score,
-            f_score,
-            dim,
+    /// use the edge weights from `get_edge` as step costs instead of 1
+    pub fn weighted(mut self) -> Self {
+        self.weighted = true;
+        self
+    }
 
-            from_map: vec![std::usize::MAX; graph.node_count()],
-            solved: false,
-        }
+    /// nodes expanded so far in ascending order
+    pub fn discovered(&self) -> impl Iterator<Item = usize> + '_ {
+        self.discovered.iter()
     }
-}
 
-fn mh_distance(from: usize, to: usize, dim: usize) -> usize {
-    let (from_x, from_y) = (from / dim, from % dim);
-    let (to_x, to_y) = (to / dim, to % dim);
+    /// number of distinct nodes expanded
+    pub fn visited_count(&self) -> usize {
+        self.visited_count
+    }
 
-    let diff_x = if from_x < to_x {
-        to_x - from_x
-    } else {
-        from_x - to_x
-    };
+    /// Nodes taken off the open set. Can be more than `visited_count` when a
+    /// node is reopened because a cheaper way to it turned up later.
+    pub fn expansions(&self) -> usize {
+        self.expansions
+    }
 
-    let diff_y = if from_y < to_y {
-        to_y - from_y
-    } else {
-        from_y - to_y
-    };
+    /// Cost of the cheapest path to `idx` found so far, final once `idx` has
+    /// been expanded.
+    pub fn cost_of(&self, idx: usize) -> Option<usize> {
+        Some(self.g_score[idx]).filter(|score| *score != usize::MAX)
+    }
 
-    diff_x + diff_y
-}
+    /// where `idx` was reached from, the root is its own predecessor
+    fn predecessor_of(&self, idx: usize) -> usize {
+        if idx == self.root_idx {
+            idx
+        } else {
+            self.from_map[idx]
+        }
+    }
 
-impl<V, W> Pathfinder<V, W> for AStarMH {
-    fn next(&mut self, graph: &dyn Graph<V, W>) -> Option<(usize, usize)> {
-        let current = match self.open_set.pop() {
-            Some(inner) => inner,
-            None => return None,
-        };
+    /// pops entries off the top of the heap that were superseded by a
+    /// cheaper one for the same node
+    fn discard_stale(&mut self) {
+        while let Some(node) = self.open_set.peek() {
+            if self.in_open.contains(node.index) && node.score == self.f_score[node.index] {
+                break;
+            }
+
+            self.open_set.pop();
+        }
+    }
+
+    /// pops the best open node and relaxes its outgoing edges
+    fn step<V, W, C>(&mut self, graph: &dyn Graph<V, W>, cost: C) -> Option<usize>
+    where
+        C: Fn(usize, usize) -> usize,
+    {
+        self.discard_stale();
+        let current = self.open_set.pop()?;
+        self.in_open.remove(current.index);
+
+        self.expansions += 1;
+        if self.discovered.insert(current.index) {
+            self.visited_count += 1;
+        }
 
-        for idx in graph.outgoing_edges_of(current.index) {
-            let tenantive_g_score = self.g_score[current.index] + 1;
+        graph.outgoing_edges_into(current.index, &mut self.scratch);
+        for &idx in &self.scratch {
+            let tenantive_g_score = self.g_score[current.index] + cost(current.index, idx);
             if tenantive_g_score < self.g_score[idx] {
                 self.from_map[idx] = current.index;
                 self.g_score[idx] = tenantive_g_score;
-                self.f_score[idx] = tenantive_g_score + mh_distance(idx, self.goal_idx, self.dim);
-                let neighbor = HeapNode {
+                self.f_score[idx] = tenantive_g_score.saturating_add((self.heuristic)(idx));
+                self.in_open.insert(idx);
+                self.open_set.push(HeapNode {
                     index: idx,
                     score: self.f_score[idx],
                     h_score: self.f_score[idx] - tenantive_g_score,
-                };
-                if !self
-                    .open_set
-                    .iter()
-                    .any(|node| node.index == neighbor.index)
-                {
-                    self.open_set.push(neighbor);
-                }
+                });
             }
         }
 
-        Some((current.index, std::usize::MAX))
+        Some(current.index)
     }
 
-    fn path_to(&mut self, graph: &dyn Graph<V, W>, _to_idx: usize) -> Option<Vec<usize>> {
-        let mut out = Vec::new();
-
+    /// Expands nodes until `to_idx` is taken off the open set, `None` once
+    /// the open set runs dry. The heuristic still aims at `goal_idx`, so the
+    /// path to any other node is only as good as the heuristic is for it.
+    fn solve<V, W, C>(
+        &mut self,
+        graph: &dyn Graph<V, W>,
+        to_idx: usize,
+        cost: C,
+    ) -> Option<Vec<usize>>
+    where
+        C: Fn(usize, usize) -> usize,
+    {
         loop {
-            let current = self.open_set.pop().unwrap();
-
-            if current.index == self.goal_idx {
-                let mut from_tmp = current.index;
-                out.push(current.index);
-                loop {
-                    if from_tmp == self.root_idx {
-                        break;
-                    }
-                    let from_idx = self.from_map[from_tmp];
-                    out.push(from_idx);
-
-                    from_tmp = from_idx;
+            self.discard_stale();
+            let current = self.open_set.peek()?.index;
+
+            if current == to_idx {
+                let mut out = vec![current];
+                let mut from_tmp = current;
+                while from_tmp != self.root_idx {
+                    from_tmp = self.from_map[from_tmp];
+                    out.push(from_tmp);
                 }
 
-                break;
+                out.reverse();
+                return Some(out);
             }
 
-            for idx in graph.outgoing_edges_of(current.index) {
-                let tenantive_g_score = self.g_score[current.index] + 1;
-                if tenantive_g_score < self.g_score[idx] {
-                    self.from_map[idx] = current.index;
-                    self.g_score[idx] = tenantive_g_score;
-                    self.f_score[idx] =
-                        tenantive_g_score + mh_distance(idx, self.goal_idx, self.dim);
-                    let neighbor = HeapNode {
-                        index: idx,
-                        score: self.f_score[idx],
-                        h_score: self.f_score[idx] - tenantive_g_score,
-                    };
-                    if !self
-                        .open_set
-                        .iter()
-                        .any(|node| node.index == neighbor.index)
-                    {
-                        self.open_set.push(neighbor);
-                    }
-                }
-            }
-
-            if self.open_set.len() == 0 {
-                break;
-            }
+            self.step(graph, &cost);
         }
+    }
+}
 
-        if out.len() == 0 {
-            None
-        } else {
-            out.reverse();
-            Some(out)
-        }
+impl<V, W, H> Pathfinder<V, W> for AStar<H>
+where
+    W: Into<usize>,
+    H: Fn(usize) -> usize,
+{
+    fn next(&mut self, graph: &dyn Graph<V, W>) -> Option<(usize, usize)> {
+        let weighted = self.weighted;
+        let idx = self.step(graph, |from, to| step_cost(graph, weighted, from, to))?;
+
+        Some((idx, self.predecessor_of(idx)))
+    }
+
+    fn path_to(&mut self, graph: &dyn Graph<V, W>, to_idx: usize) -> Option<Vec<usize>> {
+        let weighted = self.weighted;
+        self.solve(graph, to_idx, |from, to| {
+            step_cost(graph, weighted, from, to)
+        })
     }
 
     fn is_solved(&self) -> bool {
@@ -195,29 +268,111 @@ impl<V, W> Pathfinder<V, W> for AStarMH {
     fn from_index_of(&self, index: usize) -> usize {
         self.from_map[index]
     }
+
+    fn cost_of(&self, index: usize) -> Option<usize> {
+        Self::cost_of(self, index)
+    }
+}
+
+pub(super) fn step_cost<V, W: Into<usize>>(
+    graph: &dyn Graph<V, W>,
+    weighted: bool,
+    from: usize,
+    to: usize,
+) -> usize {
+    if weighted {
+        graph
+            .get_edge(from, to)
+            .map_or(1, |edge| edge.weight.into())
+    } else {
+        1
+    }
+}
+
+/// A star using manhattan distance as heuristic
+/// indicies are assumed to be an index into a 2D Array, self loops are never
+/// taken as with `AStar`
+pub struct AStarMH {
+    inner: AStar<Box<dyn Fn(usize) -> usize>>,
+}
+
+impl AStarMH {
+    pub fn new<V, W>(
+        graph: &dyn Graph<V, W>,
+        root_idx: usize,
+        goal_idx: usize,
+        dim: usize,
+    ) -> Self {
+        Self {
+            inner: AStar::new(
+                graph,
+                root_idx,
+                goal_idx,
+                Box::new(move |idx| mh_distance(idx, goal_idx, dim)),
+            ),
+        }
+    }
+}
+
+impl std::ops::Deref for AStarMH {
+    type Target = AStar<Box<dyn Fn(usize) -> usize>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl std::ops::DerefMut for AStarMH {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.inner
+    }
+}
+
+fn mh_distance(from: usize, to: usize, dim: usize) -> usize {
+    let (from_x, from_y) = (from / dim, from % dim);
+    let (to_x, to_y) = (to / dim, to % dim);
+
+    from_x.abs_diff(to_x) + from_y.abs_diff(to_y)
+}
+
+impl<V, W> Pathfinder<V, W> for AStarMH {
+    fn next(&mut self, graph: &dyn Graph<V, W>) -> Option<(usize, usize)> {
+        let idx = self.inner.step(graph, |_, _| 1)?;
+
+        Some((idx, self.inner.predecessor_of(idx)))
+    }
+
+    fn path_to(&mut self, graph: &dyn Graph<V, W>, to_idx: usize) -> Option<Vec<usize>> {
+        self.inner.solve(graph, to_idx, |_, _| 1)
+    }
+
+    fn is_solved(&self) -> bool {
+        self.inner.solved
+    }
+
+    fn set_solved(&mut self) {
+        self.inner.solved = true;
+    }
+
+    fn from_index_of(&self, index: usize) -> usize {
+        self.inner.from_map[index]
+    }
+
+    fn cost_of(&self, index: usize) -> Option<usize> {
+        self.inner.cost_of(index)
+    }
 }
 
 #[cfg(test)]
 mod test_dfs {
     use super::*;
     use crate::bit::BitGraph;
+    use crate::hash::HashGraph;
+    use crate::search::test_graph;
 
     #[test]
     fn it_works() {
-        let mut graph = BitGraph::with_capacity(16);
-
-        for i in 0..15 {
-            graph.push_node(i);
-        }
-
-        graph.add_edge(0, 2);
-        graph.add_edge(0, 1);
-        graph.add_edge(2, 4);
-        graph.add_edge(3, 8);
-        graph.add_edge(8, 5);
-        graph.add_edge(1, 3);
-        graph.add_edge(3, 5);
-        graph.add_edge(5, 0);
+        let graph = test_graph(BitGraph::with_capacity(16));
 
         let mut astar = AStarMH::new(&graph, 0, 5, 16);
         let found = loop {
@@ -249,4 +404,233 @@ mod test_dfs {
         };
         assert!(not_found);
     }
+
+    fn assert_valid_path<V, W>(graph: &dyn Graph<V, W>, path: &[usize], from: usize, to: usize) {
+        assert_eq!(path.first(), Some(&from));
+        assert_eq!(path.last(), Some(&to));
+        for pair in path.windows(2) {
+            assert!(graph.has_edge(pair[0], pair[1]));
+        }
+    }
+
+    #[test]
+    fn euclidean_test() {
+        let graph = crate::grid::grid_graph(10, 10, false);
+        let goal = 99;
+        let (goal_x, goal_y) = crate::grid::coords(goal, 10);
+
+        let euclidean = |idx| {
+            let (x, y) = crate::grid::coords(idx, 10);
+            let (dx, dy) = (x.abs_diff(goal_x) as f64, y.abs_diff(goal_y) as f64);
+            (dx * dx + dy * dy).sqrt() as usize
+        };
+
+        let mut astar = AStar::new(&graph, 0, goal, euclidean);
+        let path = astar.path_to(&graph, goal).unwrap();
+
+        assert_eq!(path.len(), 19);
+        assert_valid_path(&graph, &path, 0, goal);
+    }
+
+    #[test]
+    fn zero_heuristic_test() {
+        let mut graph = HashGraph::with_capacity(16);
+        for i in 0..5 {
+            graph.push_node(i);
+        }
+
+        // direct edge is expensive, the long way round is cheaper
+        graph.set_edge((0, 4), 10);
+        graph.set_edge((0, 1), 1);
+        graph.set_edge((1, 2), 2);
+        graph.set_edge((2, 3), 3);
+        graph.set_edge((3, 4), 1);
+
+        let mut astar = AStar::new(&graph, 0, 4, |_| 0).weighted();
+        assert_eq!(astar.path_to(&graph, 4).unwrap(), vec![0, 1, 2, 3, 4]);
+
+        let mut astar = AStar::new(&graph, 0, 4, |_| 0);
+        assert_eq!(astar.path_to(&graph, 4).unwrap(), vec![0, 4]);
+
+        graph.set_edge((0, 4), 7);
+        let mut astar = AStar::new(&graph, 0, 4, |_| 0).weighted();
+        assert_eq!(astar.path_to(&graph, 4).unwrap(), vec![0, 4]);
+    }
+
+    #[test]
+    fn inadmissible_test() {
+        let blocked: Vec<usize> = (1..10).map(|y| crate::grid::index_of(4, y, 10)).collect();
+        let graph = crate::grid::with_obstacles(10, 10, &blocked);
+        let goal = crate::grid::index_of(9, 9, 10);
+
+        // wildly overestimates and prefers the wrong direction
+        let mut astar = AStar::new(&graph, 0, goal, |idx| (100 - idx) * 1000);
+        let path = astar.path_to(&graph, goal).unwrap();
+        assert_valid_path(&graph, &path, 0, goal);
+
+        let mut astar = AStar::new(&graph, 0, 45, |idx| (100 - idx) * 1000);
+        let drained = loop {
+            if astar.next(&graph).is_none() {
+                break true;
+            }
+        };
+        assert!(drained);
+    }
+
+    #[test]
+    fn weighted_bit_graph_test() {
+        let graph = crate::grid::grid_graph(5, 5, false);
+        let mut astar = AStar::new(&graph, 0, 24, |_| 0).weighted();
+        let path = astar.path_to(&graph, 24).unwrap();
+
+        assert_eq!(path.len(), 9);
+        assert_valid_path(&graph, &path, 0, 24);
+        assert_eq!(astar.from_map[24], path[7]);
+    }
+
+    #[test]
+    fn unreachable_test() {
+        let mut graph = BitGraph::with_capacity(16);
+        for i in 0..15 {
+            graph.push_node(i);
+        }
+        graph.add_edge(0, 1);
+        graph.add_edge(1, 3);
+        graph.add_edge(3, 5);
+
+        // 10 is isolated
+        let mut astar = AStarMH::new(&graph, 0, 10, 16);
+        assert_eq!(astar.path_to(&graph, 10), None);
+        // open set is empty now, asking again must not panic
+        assert_eq!(astar.path_to(&graph, 10), None);
+
+        let mut astar = AStarMH::new(&graph, 0, 5, 16);
+        while astar.next(&graph).is_some() {}
+        assert_eq!(astar.path_to(&graph, 5), None);
+    }
+
+    #[test]
+    fn to_idx_test() {
+        let graph = crate::grid::grid_graph(4, 4, false);
+
+        // goal is 15 but the path asked for is to 3
+        let mut astar = AStarMH::new(&graph, 0, 15, 4);
+        assert_eq!(astar.goal_idx(), 15);
+        let path = astar.path_to(&graph, 3).unwrap();
+        assert_valid_path(&graph, &path, 0, 3);
+
+        let mut astar = AStarMH::new(&graph, 0, 15, 4);
+        assert_eq!(astar.path_to(&graph, 0).unwrap(), vec![0]);
+    }
+
+    #[test]
+    fn iterator_test() {
+        let graph = test_graph(BitGraph::with_capacity(16));
+
+        let found = AStarMH::new(&graph, 0, 5, 16)
+            .into_iter(&graph)
+            .find(|(idx, _from)| *idx == 5);
+        assert_eq!(found, Some((5, 3)));
+        assert_eq!(AStarMH::new(&graph, 0, 5, 16).into_iter(&graph).count(), 7);
+
+        let mut astar = AStarMH::new(&graph, 0, 5, 16);
+        assert_eq!(astar.iter(&graph).next(), Some((0, 0)));
+        assert_eq!(astar.iter(&graph).count(), 6);
+    }
+
+    #[test]
+    fn stats_test() {
+        let graph = test_graph(BitGraph::with_capacity(16));
+
+        let mut astar = AStarMH::new(&graph, 0, 5, 16);
+        while astar.next(&graph).is_some() {}
+
+        assert_eq!(astar.visited_count(), 7);
+        assert_eq!(astar.expansions(), 7);
+        assert_eq!(
+            astar.discovered().collect::<Vec<_>>(),
+            vec![0, 1, 2, 3, 4, 5, 8]
+        );
+    }
+
+    #[test]
+    fn reset_test() {
+        let graph = test_graph(BitGraph::with_capacity(16));
+
+        let mut reused = AStarMH::new(&graph, 0, 4, 16);
+        let mut fresh = AStarMH::new(&graph, 0, 4, 16);
+        assert_eq!(reused.path_to(&graph, 4), fresh.path_to(&graph, 4));
+        let first: Vec<_> = reused.iter(&graph).collect();
+
+        reused.reset(3);
+        assert_eq!(reused.visited_count(), 0);
+
+        let mut fresh = AStarMH::new(&graph, 3, 4, 16);
+        assert_eq!(reused.path_to(&graph, 4), fresh.path_to(&graph, 4));
+        assert!(reused.discovered().eq(fresh.discovered()));
+        assert_eq!(reused.expansions(), fresh.expansions());
+
+        reused.reset(0);
+        let _ = reused.path_to(&graph, 4);
+        assert_eq!(reused.iter(&graph).collect::<Vec<_>>(), first);
+    }
+
+    #[test]
+    fn large_grid_test() {
+        // wall down x = 100 with a gap at the bottom row
+        let blocked: Vec<usize> = (0..199)
+            .map(|y| crate::grid::index_of(100, y, 200))
+            .collect();
+        let graph = crate::grid::with_obstacles(200, 200, &blocked);
+        let goal = crate::grid::index_of(199, 0, 200);
+
+        let mut astar = AStarMH::new(&graph, 0, goal, 200);
+        let path = astar.path_to(&graph, goal).unwrap();
+        // down 199, across 199 and back up 199
+        assert_eq!(path.len(), 3 * 199 + 1);
+        assert_valid_path(&graph, &path, 0, goal);
+
+        let mut astar = AStarMH::new(&graph, 0, goal, 200);
+        let found = astar.iter(&graph).find(|(idx, _)| *idx == goal);
+        assert!(found.is_some());
+        let mut length = 1;
+        let mut idx = goal;
+        while idx != 0 {
+            idx = astar.from_map[idx];
+            length += 1;
+        }
+        assert_eq!(length, path.len());
+    }
+
+    #[test]
+    fn path_with_cost_test() {
+        let mut graph = HashGraph::with_capacity(16);
+        for i in 0..6 {
+            graph.push_node(i);
+        }
+
+        // two hops but expensive, four hops and cheap
+        graph.set_edge((0, 5), 4);
+        graph.set_edge((5, 4), 6);
+        graph.set_edge((0, 1), 1);
+        graph.set_edge((1, 2), 2);
+        graph.set_edge((2, 3), 3);
+        graph.set_edge((3, 4), 1);
+
+        let mut astar = AStar::new(&graph, 0, 4, |_| 0).weighted();
+        assert_eq!(
+            astar.path_with_cost(&graph, 4),
+            Some((vec![0, 1, 2, 3, 4], 7))
+        );
+        assert_eq!(astar.cost_of(0), Some(0));
+        assert_eq!(astar.cost_of(5), Some(4));
+
+        let mut astar = AStar::new(&graph, 0, 4, |_| 0);
+        assert_eq!(astar.path_with_cost(&graph, 4), Some((vec![0, 5, 4], 2)));
+
+        let mut astar = AStar::new(&graph, 1, 0, |_| 0).weighted();
+        assert_eq!(astar.path_with_cost(&graph, 0), None);
+        assert_eq!(astar.cost_of(0), None);
+        assert_eq!(astar.cost_of(5), None);
+    }
 }
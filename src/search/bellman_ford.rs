@@ -0,0 +1,153 @@
+use crate::Graph;
+use std::convert::TryFrom;
+use std::fmt;
+
+/// a negative cycle reachable from the source, `node` is on it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NegativeCycle {
+    pub node: usize,
+}
+
+impl fmt::Display for NegativeCycle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "negative cycle through node {}", self.node)
+    }
+}
+
+impl std::error::Error for NegativeCycle {}
+
+/// Single source shortest paths allowing negative weights. Every edge is
+/// relaxed up to `node_count - 1` times, stopping early once nothing changes.
+///
+/// Returns the distance to every node, `None` when unreachable, and the node
+/// each one was reached from. The source is its own predecessor and
+/// unreachable nodes have `usize::MAX`. Weights too large for an `i64`
+/// saturate. A reachable self loop of negative weight is a negative cycle,
+/// any other self loop changes nothing.
+pub fn bellman_ford<V, W>(
+    graph: &dyn Graph<V, W>,
+    source: usize,
+) -> Result<(Vec<Option<i64>>, Vec<usize>), NegativeCycle>
+where
+    i64: TryFrom<W>,
+{
+    let node_count = graph.node_count();
+
+    let edges: Vec<(usize, usize, i64)> = graph
+        .all_edge_pairs()
+        .into_iter()
+        .filter_map(|(from, to)| {
+            let weight = i64::try_from(graph.get_edge(from, to)?.weight).unwrap_or(i64::MAX);
+            Some((from, to, weight))
+        })
+        .collect();
+
+    let mut distance: Vec<Option<i64>> = vec![None; node_count];
+    let mut predecessor = vec![usize::MAX; node_count];
+    distance[source] = Some(0);
+    predecessor[source] = source;
+
+    // the extra round only changes something if there is a negative cycle
+    for _ in 0..node_count {
+        let mut changed = false;
+
+        for &(from, to, weight) in &edges {
+            if let Some(from_distance) = distance[from] {
+                let tentative = from_distance.saturating_add(weight);
+                if distance[to].is_none_or(|current| tentative < current) {
+                    distance[to] = Some(tentative);
+                    predecessor[to] = from;
+                    changed = true;
+                }
+            }
+        }
+
+        if !changed {
+            return Ok((distance, predecessor));
+        }
+    }
+
+    // Something still relaxed in the last round. Following predecessors from
+    // any node that changed lands on the cycle after at most node_count steps.
+    let mut node = edges
+        .iter()
+        .find(|(from, to, weight)| {
+            distance[*from]
+                .is_some_and(|d| distance[*to].is_none_or(|t| d.saturating_add(*weight) < t))
+        })
+        .map(|(_, to, _)| *to)
+        .unwrap_or(source);
+
+    for _ in 0..node_count {
+        node = predecessor[node];
+    }
+
+    Err(NegativeCycle { node })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::HashGraph;
+
+    fn signed_graph(count: usize, edges: &[(usize, usize, i64)]) -> HashGraph<u64, i64> {
+        let mut graph = HashGraph::with_default_weight(count.max(edges.len()), 1);
+        for i in 0..count {
+            graph.push_node(i as u64);
+        }
+        for &(from, to, weight) in edges {
+            graph.set_edge((from, to), weight);
+        }
+        graph
+    }
+
+    #[test]
+    fn negative_edge_test() {
+        let graph = signed_graph(
+            6,
+            &[
+                (0, 1, 4),
+                (0, 2, 5),
+                (1, 3, 3),
+                (2, 1, -3),
+                (3, 4, 2),
+                (4, 3, 1),
+            ],
+        );
+
+        let (distance, predecessor) = bellman_ford(&graph, 0).unwrap();
+        assert_eq!(
+            distance,
+            vec![Some(0), Some(2), Some(5), Some(5), Some(7), None]
+        );
+        assert_eq!(predecessor[1], 2);
+        assert_eq!(predecessor[0], 0);
+        assert_eq!(predecessor[5], usize::MAX);
+    }
+
+    #[test]
+    fn negative_cycle_test() {
+        let graph = signed_graph(5, &[(0, 1, 1), (1, 2, 1), (2, 3, -4), (3, 1, 1), (3, 4, 1)]);
+
+        let error = bellman_ford(&graph, 0).unwrap_err();
+        assert!([1, 2, 3].contains(&error.node));
+
+        // not reachable from 4, so it does not count
+        let (distance, _) = bellman_ford(&graph, 4).unwrap();
+        assert_eq!(distance, vec![None, None, None, None, Some(0)]);
+    }
+
+    #[test]
+    fn unsigned_graph_test() {
+        let mut graph = crate::hash::HashGraph::with_capacity(8);
+        for i in 0..4 {
+            graph.push_node(i);
+        }
+        graph.set_edge((0, 1), 5);
+        graph.set_edge((0, 2), 1);
+        graph.set_edge((2, 1), 1);
+
+        let (distance, _) = bellman_ford(&graph, 0).unwrap();
+        assert_eq!(distance, vec![Some(0), Some(2), Some(1), None]);
+    }
+}
@@ -1,17 +1,22 @@
-const WORD_BYTES: usize = std::mem::size_of::<usize>();
-const WORD_BITS: usize = WORD_BYTES * 8;
-
-use crate::bit::single_bit_mask;
+use crate::bit::NodeBitSet;
 use crate::search::Pathfinder;
 use crate::Graph;
 use std::collections::VecDeque;
 
+/// Breadth first search from one or more roots. A node is queued once, so a
+/// self loop is never followed.
 pub struct BFS {
-    root_idx: usize,
-
     queue: VecDeque<(usize, usize)>,
 
-    discovered: Vec<usize>,
+    discovered: NodeBitSet,
+    visited_count: usize,
+    expansions: usize,
+
+    /// hops from the nearest root and which root that is, set on visit
+    hops: Vec<usize>,
+    origin: Vec<usize>,
+    /// neighbours of the node being visited, reused across visits
+    scratch: Vec<usize>,
 
     pub from_map: Vec<usize>,
     pub solved: bool,
@@ -20,10 +25,19 @@ pub struct BFS {
 impl<V, W> Pathfinder<V, W> for BFS {
     fn next(&mut self, graph: &dyn Graph<V, W>) -> Option<(usize, usize)> {
         while let Some((idx, from)) = self.queue.pop_front() {
+            self.expansions += 1;
+
             if self.visit_node(idx) {
+                self.visited_count += 1;
                 self.from_map[idx] = from;
+                // roots are queued as their own predecessor
+                if idx != from {
+                    self.hops[idx] = self.hops[from] + 1;
+                    self.origin[idx] = self.origin[from];
+                }
 
-                for out in graph.outgoing_edges_of(idx) {
+                graph.outgoing_edges_into(idx, &mut self.scratch);
+                for &out in &self.scratch {
                     if !self.is_discovered(out) {
                         self.queue.push_back((out, idx));
                     }
@@ -36,45 +50,23 @@ impl<V, W> Pathfinder<V, W> for BFS {
     }
 
     fn path_to(&mut self, graph: &dyn Graph<V, W>, to_idx: usize) -> Option<Vec<usize>> {
-        let mut from_map = vec![std::usize::MAX; graph.node_count()];
-        let mut out = Vec::new();
-
-        while let Some((idx, from)) = self.queue.pop_front() {
+        while let Some((idx, _from)) = self.next(graph) {
             if idx == to_idx {
-                let mut from_tmp = from;
-                out.push(idx);
-                out.push(from);
-                loop {
-                    if from_tmp == self.root_idx {
-                        break;
-                    }
-                    let from_idx = from_map[from_tmp];
-                    out.push(from_idx);
-
-                    from_tmp = from_idx;
+                let mut out = vec![idx];
+                let mut from_tmp = idx;
+                while self.from_map[from_tmp] != from_tmp {
+                    from_tmp = self.from_map[from_tmp];
+                    out.push(from_tmp);
                 }
 
-                break;
-            }
-
-            if self.visit_node(idx) {
-                from_map[idx] = from;
-
-                for out in graph.outgoing_edges_of(idx) {
-                    if !self.is_discovered(out) {
-                        self.queue.push_back((out, idx));
-                    }
-                }
+                out.reverse();
+                return Some(out);
             }
         }
 
-        if out.len() == 0 {
-            None
-        } else {
-            out.reverse();
-            Some(out)
-        }
+        None
     }
+
     fn is_solved(&self) -> bool {
         self.solved
     }
@@ -86,36 +78,96 @@ impl<V, W> Pathfinder<V, W> for BFS {
     fn from_index_of(&self, index: usize) -> usize {
         self.from_map[index]
     }
+
+    fn cost_of(&self, index: usize) -> Option<usize> {
+        Self::cost_of(self, index)
+    }
 }
 
 impl BFS {
     pub fn new<V, W>(graph: &dyn Graph<V, W>, root_idx: usize) -> Self {
+        Self::new_multi(graph, &[root_idx])
+    }
+
+    /// Searches from every node of `roots` at once, all at distance 0. Each
+    /// node is reached from the root nearest to it, ties go to the root that
+    /// comes first in `roots`.
+    pub fn new_multi<V, W>(graph: &dyn Graph<V, W>, roots: &[usize]) -> Self {
         Self {
-            root_idx,
-            discovered: vec![0; graph.node_count() / WORD_BITS + 1],
-            queue: VecDeque::from(vec![(root_idx, root_idx)]),
-            from_map: vec![std::usize::MAX; graph.node_count()],
+            discovered: NodeBitSet::with_capacity(graph.node_count()),
+            visited_count: 0,
+            expansions: 0,
+            queue: roots.iter().map(|&root| (root, root)).collect(),
+            hops: vec![0; graph.node_count()],
+            origin: (0..graph.node_count()).collect(),
+            scratch: Vec::new(),
+            from_map: vec![usize::MAX; graph.node_count()],
             solved: false,
         }
     }
-    /// Visits node, returns true if first visit, else false
-    fn visit_node(&mut self, node_idx: usize) -> bool {
-        let first_visit = self.is_discovered(node_idx);
 
-        if first_visit {
-            false
-        } else {
-            self.set_discovered(node_idx);
-            true
-        }
+    /// Starts over from `root_idx` reusing every allocation. The graph must
+    /// not have gained nodes since `new`.
+    pub fn reset(&mut self, root_idx: usize) {
+        self.queue.clear();
+        self.queue.push_back((root_idx, root_idx));
+        self.from_map.iter_mut().for_each(|from| *from = usize::MAX);
+        self.hops.iter_mut().for_each(|hops| *hops = 0);
+        self.origin
+            .iter_mut()
+            .enumerate()
+            .for_each(|(idx, origin)| *origin = idx);
+
+        self.discovered.clear();
+        self.visited_count = 0;
+        self.expansions = 0;
+        self.solved = false;
     }
 
-    fn is_discovered(&self, node_idx: usize) -> bool {
-        (self.discovered[node_idx / WORD_BITS] & single_bit_mask(node_idx % WORD_BITS)) > 0
+    /// number of hops from the nearest root to `idx`, `None` if it has not
+    /// been visited
+    pub fn cost_of(&self, idx: usize) -> Option<usize> {
+        Some(self.hops[idx]).filter(|_| self.is_discovered(idx))
+    }
+
+    /// the root `idx` was reached from, `None` if it has not been visited
+    pub fn root_of(&self, idx: usize) -> Option<usize> {
+        Some(self.origin[idx]).filter(|_| self.is_discovered(idx))
+    }
+
+    /// Runs the search until every reachable node is visited and returns the
+    /// hops from the nearest root to every node, `None` for unreachable ones.
+    /// `root_of` then gives the root each node belongs to.
+    pub fn distances<V, W>(&mut self, graph: &dyn Graph<V, W>) -> Vec<Option<usize>> {
+        while self.next(graph).is_some() {}
+
+        (0..self.hops.len())
+            .map(|idx| BFS::cost_of(self, idx))
+            .collect()
+    }
+
+    /// nodes visited so far in ascending order
+    pub fn discovered(&self) -> impl Iterator<Item = usize> + '_ {
+        self.discovered.iter()
+    }
+
+    /// number of distinct nodes visited
+    pub fn visited_count(&self) -> usize {
+        self.visited_count
     }
 
-    fn set_discovered(&mut self, node_idx: usize) {
-        self.discovered[node_idx / WORD_BITS] |= single_bit_mask(node_idx % WORD_BITS);
+    /// entries taken off the queue, including ones that were already visited
+    pub fn expansions(&self) -> usize {
+        self.expansions
+    }
+
+    /// Visits node, returns true if first visit, else false
+    fn visit_node(&mut self, node_idx: usize) -> bool {
+        self.discovered.insert(node_idx)
+    }
+
+    fn is_discovered(&self, node_idx: usize) -> bool {
+        self.discovered.contains(node_idx)
     }
 }
 
@@ -123,23 +175,12 @@ impl BFS {
 mod test_dfs {
     use super::*;
     use crate::bit::BitGraph;
+    use crate::hash::HashGraph;
+    use crate::search::test_graph;
 
     #[test]
     fn it_works() {
-        let mut graph = BitGraph::new();
-
-        for i in 0..15 {
-            graph.push_node(i);
-        }
-
-        graph.add_edge(0, 2);
-        graph.add_edge(0, 1);
-        graph.add_edge(2, 4);
-        graph.add_edge(3, 8);
-        graph.add_edge(8, 5);
-        graph.add_edge(1, 3);
-        graph.add_edge(3, 5);
-        graph.add_edge(5, 0);
+        let graph = test_graph(BitGraph::new());
 
         let mut bfs = BFS::new(&graph, 0);
         let found = loop {
@@ -171,4 +212,176 @@ mod test_dfs {
         };
         assert!(not_found);
     }
+
+    #[test]
+    fn path_to_root_and_unreachable_test() {
+        let mut graph = BitGraph::with_capacity(8);
+        for i in 0..8 {
+            graph.push_node(i);
+        }
+        graph.add_edge(0, 1);
+        graph.add_edge(1, 2);
+
+        let mut search = BFS::new(&graph, 0);
+        assert_eq!(search.path_to(&graph, 0), Some(vec![0]));
+
+        let mut search = BFS::new(&graph, 0);
+        assert_eq!(search.path_to(&graph, 2), Some(vec![0, 1, 2]));
+
+        let mut search = BFS::new(&graph, 0);
+        assert_eq!(search.path_to(&graph, 7), None);
+        assert_eq!(search.path_to(&graph, 7), None);
+    }
+
+    #[test]
+    fn no_duplicate_visits_test() {
+        // every node links to the next few so most are discovered many times
+        let mut graph = BitGraph::with_capacity(130);
+        for i in 0..130 {
+            graph.push_node(i);
+        }
+        for from in 0..130 {
+            for to in from + 1..(from + 5).min(130) {
+                graph.add_edge(from, to);
+            }
+        }
+
+        let mut search = BFS::new(&graph, 0);
+        let mut visited = Vec::new();
+        while let Some((idx, _from)) = search.next(&graph) {
+            visited.push(idx);
+        }
+
+        assert_eq!(visited.len(), 130);
+        visited.sort_unstable();
+        visited.dedup();
+        assert_eq!(visited.len(), 130);
+    }
+
+    #[test]
+    fn hash_graph_test() {
+        let graph = test_graph(HashGraph::with_capacity(32));
+
+        let mut bfs = BFS::new(&graph, 0);
+        assert_eq!(bfs.path_to(&graph, 5), Some(vec![0, 1, 3, 5]));
+
+        let mut bfs = BFS::new(&graph, 0);
+        let mut visited = Vec::new();
+        while let Some((idx, _from)) = bfs.next(&graph) {
+            visited.push(idx);
+        }
+        visited.sort_unstable();
+        assert_eq!(visited, vec![0, 1, 2, 3, 4, 5, 8]);
+
+        let mut bfs = BFS::new(&graph, 0);
+        assert_eq!(bfs.path_to(&graph, 10), None);
+        assert_eq!(bfs.cost_of(10), None);
+
+        let mut bfs = BFS::new(&graph, 0);
+        assert_eq!(bfs.path_with_cost(&graph, 5), Some((vec![0, 1, 3, 5], 3)));
+        assert_eq!(bfs.cost_of(0), Some(0));
+        assert_eq!(bfs.cost_of(8), None);
+    }
+
+    #[test]
+    fn iterator_test() {
+        let graph = test_graph(BitGraph::with_capacity(16));
+
+        let found = BFS::new(&graph, 0)
+            .into_iter(&graph)
+            .find(|(idx, _from)| *idx == 5);
+        assert_eq!(found, Some((5, 3)));
+        assert_eq!(BFS::new(&graph, 0).into_iter(&graph).count(), 7);
+        assert_eq!(BFS::new(&graph, 10).into_iter(&graph).count(), 1);
+
+        let mut bfs = BFS::new(&graph, 0);
+        let first: Vec<_> = bfs.iter(&graph).take(3).collect();
+        assert_eq!(first, vec![(0, 0), (1, 0), (2, 0)]);
+        // picks up where the borrowed iterator stopped
+        assert_eq!(bfs.iter(&graph).count(), 4);
+    }
+
+    #[test]
+    fn stats_test() {
+        let graph = test_graph(BitGraph::with_capacity(16));
+
+        let mut bfs = BFS::new(&graph, 0);
+        assert_eq!(bfs.path_to(&graph, 3), Some(vec![0, 1, 3]));
+        assert_eq!(bfs.visited_count(), 4);
+        assert_eq!(bfs.discovered().collect::<Vec<_>>(), vec![0, 1, 2, 3]);
+
+        while bfs.next(&graph).is_some() {}
+        assert_eq!(bfs.visited_count(), 7);
+        // 5 is visited before 8 so it is only ever queued once
+        assert_eq!(bfs.expansions(), 7);
+    }
+
+    #[test]
+    fn reset_test() {
+        let graph = test_graph(BitGraph::with_capacity(16));
+
+        let mut reused = BFS::new(&graph, 0);
+        let mut fresh = BFS::new(&graph, 0);
+        assert_eq!(reused.path_to(&graph, 4), fresh.path_to(&graph, 4));
+        let first: Vec<_> = reused.iter(&graph).collect();
+
+        reused.reset(3);
+        assert_eq!(reused.visited_count(), 0);
+
+        let mut fresh = BFS::new(&graph, 3);
+        assert_eq!(reused.path_to(&graph, 4), fresh.path_to(&graph, 4));
+        assert!(reused.discovered().eq(fresh.discovered()));
+        assert_eq!(reused.expansions(), fresh.expansions());
+
+        reused.reset(0);
+        let _ = reused.path_to(&graph, 4);
+        assert_eq!(reused.iter(&graph).collect::<Vec<_>>(), first);
+    }
+
+    #[test]
+    fn multi_source_test() {
+        // a path 0 - 1 - ... - 9 both ways and a separate 10 - 11
+        let mut graph = BitGraph::with_capacity(12);
+        for i in 0..12 {
+            graph.push_node(i);
+        }
+        for (from, to) in (0..9).map(|i| (i, i + 1)).chain(Some((10, 11))) {
+            graph.add_edge(from, to);
+            graph.add_edge(to, from);
+        }
+
+        let mut bfs = BFS::new_multi(&graph, &[0, 6]);
+        let distances = bfs.distances(&graph);
+        assert_eq!(
+            distances,
+            vec![
+                Some(0),
+                Some(1),
+                Some(2),
+                Some(3),
+                Some(2),
+                Some(1),
+                Some(0),
+                Some(1),
+                Some(2),
+                Some(3),
+                None,
+                None
+            ]
+        );
+
+        // 3 is as far from both, the first root wins
+        let split: Vec<_> = (0..12).map(|idx| bfs.root_of(idx)).collect();
+        assert_eq!(&split[..4], &[Some(0); 4]);
+        assert_eq!(&split[4..10], &[Some(6); 6]);
+        assert_eq!(&split[10..], &[None, None]);
+
+        assert_eq!(bfs.cost_of(9), Some(3));
+        let mut bfs = BFS::new_multi(&graph, &[0, 6]);
+        assert_eq!(bfs.path_to(&graph, 9), Some(vec![6, 7, 8, 9]));
+        assert_eq!(bfs.path_to(&graph, 11), None);
+
+        let mut bfs = BFS::new_multi(&graph, &[]);
+        assert_eq!(bfs.distances(&graph), vec![None; 12]);
+    }
 }
@@ -0,0 +1,212 @@
+use crate::bit::NodeBitSet;
+use crate::Graph;
+
+/// Bidirectional breadth first search, grows one frontier out of the source
+/// along outgoing edges and one out of the target along incoming edges, always
+/// advancing the smaller one a whole level, until they meet. Self loops are
+/// never followed.
+pub struct BiBFS {
+    forward_seen: NodeBitSet,
+    backward_seen: NodeBitSet,
+
+    /// node each forward node was reached from
+    forward_from: Vec<usize>,
+    /// node each backward node leads to on the way to the target
+    backward_to: Vec<usize>,
+
+    forward_frontier: Vec<usize>,
+    backward_frontier: Vec<usize>,
+    next_frontier: Vec<usize>,
+}
+
+impl BiBFS {
+    pub fn new<V, W>(graph: &dyn Graph<V, W>) -> Self {
+        Self {
+            forward_seen: NodeBitSet::with_capacity(graph.node_count()),
+            backward_seen: NodeBitSet::with_capacity(graph.node_count()),
+
+            forward_from: vec![usize::MAX; graph.node_count()],
+            backward_to: vec![usize::MAX; graph.node_count()],
+
+            forward_frontier: Vec::new(),
+            backward_frontier: Vec::new(),
+            next_frontier: Vec::new(),
+        }
+    }
+
+    /// Shortest path from `from` to `to` including both ends, `None` if there
+    /// is none. Can be called any number of times, the graph must not have
+    /// gained nodes since `new`.
+    pub fn path_to<V, W>(
+        &mut self,
+        graph: &dyn Graph<V, W>,
+        from: usize,
+        to: usize,
+    ) -> Option<Vec<usize>> {
+        if from == to {
+            return Some(vec![from]);
+        }
+
+        self.forward_seen.clear();
+        self.backward_seen.clear();
+        self.forward_frontier.clear();
+        self.backward_frontier.clear();
+
+        self.forward_seen.insert(from);
+        self.forward_frontier.push(from);
+        self.backward_seen.insert(to);
+        self.backward_frontier.push(to);
+
+        while !self.forward_frontier.is_empty() && !self.backward_frontier.is_empty() {
+            let meeting = if self.forward_frontier.len() <= self.backward_frontier.len() {
+                self.advance_forward(graph)
+            } else {
+                self.advance_backward(graph)
+            };
+
+            if let Some(meeting) = meeting {
+                return Some(self.stitch(from, to, meeting));
+            }
+        }
+
+        None
+    }
+
+    /// expands the forward frontier one level, returns the node where it ran
+    /// into the backward search
+    fn advance_forward<V, W>(&mut self, graph: &dyn Graph<V, W>) -> Option<usize> {
+        self.next_frontier.clear();
+
+        for &node in &self.forward_frontier {
+            for out in graph.outgoing_edges_of(node) {
+                if self.forward_seen.insert(out) {
+                    self.forward_from[out] = node;
+                    if self.backward_seen.contains(out) {
+                        return Some(out);
+                    }
+                    self.next_frontier.push(out);
+                }
+            }
+        }
+
+        std::mem::swap(&mut self.forward_frontier, &mut self.next_frontier);
+        None
+    }
+
+    fn advance_backward<V, W>(&mut self, graph: &dyn Graph<V, W>) -> Option<usize> {
+        self.next_frontier.clear();
+
+        for &node in &self.backward_frontier {
+            for incoming in graph.incoming_edges_of(node) {
+                if self.backward_seen.insert(incoming) {
+                    self.backward_to[incoming] = node;
+                    if self.forward_seen.contains(incoming) {
+                        return Some(incoming);
+                    }
+                    self.next_frontier.push(incoming);
+                }
+            }
+        }
+
+        std::mem::swap(&mut self.backward_frontier, &mut self.next_frontier);
+        None
+    }
+
+    /// joins the forward half ending at `meeting` with the backward half
+    /// starting there
+    fn stitch(&self, from: usize, to: usize, meeting: usize) -> Vec<usize> {
+        let mut out = vec![meeting];
+        let mut node = meeting;
+        while node != from {
+            node = self.forward_from[node];
+            out.push(node);
+        }
+        out.reverse();
+
+        let mut node = meeting;
+        while node != to {
+            node = self.backward_to[node];
+            out.push(node);
+        }
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bit::BitGraph;
+    use crate::search::bfs::BFS;
+    use crate::search::Pathfinder;
+
+    fn chain(length: usize) -> BitGraph {
+        let mut graph = BitGraph::with_capacity(16);
+        for i in 0..10 {
+            graph.push_node(i);
+        }
+        for i in 0..length {
+            graph.add_edge(i, i + 1);
+        }
+
+        graph
+    }
+
+    #[test]
+    fn same_node_test() {
+        let graph = chain(3);
+        let mut search = BiBFS::new(&graph);
+
+        assert_eq!(search.path_to(&graph, 2, 2), Some(vec![2]));
+        assert_eq!(search.path_to(&graph, 9, 9), Some(vec![9]));
+    }
+
+    #[test]
+    fn unreachable_test() {
+        let graph = chain(3);
+        let mut search = BiBFS::new(&graph);
+
+        assert_eq!(search.path_to(&graph, 0, 9), None);
+        // edges only go one way
+        assert_eq!(search.path_to(&graph, 3, 0), None);
+    }
+
+    #[test]
+    fn odd_and_even_test() {
+        let graph = chain(6);
+        let mut search = BiBFS::new(&graph);
+
+        assert_eq!(search.path_to(&graph, 0, 1), Some(vec![0, 1]));
+        assert_eq!(search.path_to(&graph, 0, 2), Some(vec![0, 1, 2]));
+        assert_eq!(search.path_to(&graph, 0, 5), Some(vec![0, 1, 2, 3, 4, 5]));
+        assert_eq!(
+            search.path_to(&graph, 0, 6),
+            Some(vec![0, 1, 2, 3, 4, 5, 6])
+        );
+        assert_eq!(search.path_to(&graph, 2, 5), Some(vec![2, 3, 4, 5]));
+    }
+
+    #[test]
+    fn matches_bfs_test() {
+        let blocked: Vec<usize> = (0..9)
+            .map(|y| crate::grid::index_of(4, y, 10))
+            .chain((1..10).map(|y| crate::grid::index_of(7, y, 10)))
+            .collect();
+        let graph = crate::grid::with_obstacles(10, 10, &blocked);
+        let mut search = BiBFS::new(&graph);
+
+        for &(from, to) in &[(0, 99), (0, 9), (55, 3), (90, 22), (0, 4)] {
+            let expected = BFS::new(&graph, from).path_to(&graph, to);
+            let path = search.path_to(&graph, from, to);
+
+            assert_eq!(path.as_ref().map(Vec::len), expected.as_ref().map(Vec::len));
+            if let Some(path) = path {
+                assert_eq!(path.first(), Some(&from));
+                assert_eq!(path.last(), Some(&to));
+                for pair in path.windows(2) {
+                    assert!(graph.has_edge(pair[0], pair[1]));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use crate::Graph;
+use std::collections::VecDeque;
+
+/// Colors every node 0 or 1 so no edge joins two nodes of the same color,
+/// edges count in both directions. `None` if the graph has an odd cycle, see
+/// `odd_cycle` for one. Isolated nodes get 0.
+pub fn two_color<V, W>(graph: &dyn Graph<V, W>) -> Option<Vec<u8>> {
+    color(graph).ok()
+}
+
+/// An odd cycle that keeps the graph from being bipartite, `None` if it is.
+/// Consecutive nodes, and the last and first, are joined by an edge in at
+/// least one direction. A self loop is a cycle of one node.
+pub fn odd_cycle<V, W>(graph: &dyn Graph<V, W>) -> Option<Vec<usize>> {
+    color(graph).err()
+}
+
+/// breadth first coloring, on a conflict returns the cycle through the edge
+/// and the two tree paths up to where they meet
+fn color<V, W>(graph: &dyn Graph<V, W>) -> Result<Vec<u8>, Vec<usize>> {
+    let node_count = graph.node_count();
+
+    let mut colors = vec![u8::MAX; node_count];
+    let mut from_map = vec![usize::MAX; node_count];
+    let mut depth = vec![0; node_count];
+    let mut queue = VecDeque::new();
+
+    for root in 0..node_count {
+        if colors[root] != u8::MAX {
+            continue;
+        }
+
+        colors[root] = 0;
+        from_map[root] = root;
+        queue.push_back(root);
+
+        while let Some(node) = queue.pop_front() {
+            let mut neighbours = graph.outgoing_edges_of(node);
+            neighbours.extend(graph.incoming_edges_of(node));
+
+            for next in neighbours {
+                if colors[next] == u8::MAX {
+                    colors[next] = 1 - colors[node];
+                    from_map[next] = node;
+                    depth[next] = depth[node] + 1;
+                    queue.push_back(next);
+                } else if colors[next] == colors[node] {
+                    return Err(cycle_through(&from_map, &depth, node, next));
+                }
+            }
+        }
+    }
+
+    Ok(colors)
+}
+
+/// the cycle closed by the edge `a`, `b` in the BFS tree
+fn cycle_through(from_map: &[usize], depth: &[usize], a: usize, b: usize) -> Vec<usize> {
+    let (mut a_side, mut b_side) = (vec![a], vec![b]);
+    let (mut a, mut b) = (a, b);
+
+    while a != b {
+        if depth[a] >= depth[b] {
+            a = from_map[a];
+            a_side.push(a);
+        } else {
+            b = from_map[b];
+            b_side.push(b);
+        }
+    }
+
+    // a up to the common ancestor and back down to b, which links to a
+    b_side.pop();
+    b_side.reverse();
+    a_side.extend(b_side);
+
+    a_side
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::baseline::AdjGraph;
+    use crate::bit::BitGraph;
+    use crate::hash::HashGraph;
+
+    fn cycle<G: Graph<u64, W>, W>(mut graph: G, length: usize) -> G {
+        for i in 0..length {
+            graph.push_node(i as u64);
+        }
+        for i in 0..length {
+            graph.add_edge(i, (i + 1) % length);
+        }
+
+        graph
+    }
+
+    fn assert_odd_cycle<V, W>(graph: &dyn Graph<V, W>, cycle: &[usize]) {
+        assert_eq!(cycle.len() % 2, 1);
+        for i in 0..cycle.len() {
+            let (a, b) = (cycle[i], cycle[(i + 1) % cycle.len()]);
+            assert!(graph.has_edge(a, b) || graph.has_edge(b, a));
+        }
+    }
+
+    #[test]
+    fn even_cycle_test() {
+        let graph = cycle(BitGraph::with_capacity(16), 6);
+        let colors = two_color(&graph).unwrap();
+
+        assert_eq!(colors, vec![0, 1, 0, 1, 0, 1]);
+        assert_eq!(odd_cycle(&graph), None);
+
+        let graph = cycle(HashGraph::with_capacity(16), 8);
+        let colors = two_color(&graph).unwrap();
+        for (from, to) in graph.all_edge_pairs() {
+            assert_ne!(colors[from], colors[to]);
+        }
+
+        let graph = cycle(AdjGraph::with_capacity(16), 4);
+        assert!(two_color(&graph).is_some());
+    }
+
+    #[test]
+    fn odd_cycle_test() {
+        for &length in &[3, 5, 9] {
+            let graph = cycle(BitGraph::with_capacity(16), length);
+            assert_eq!(two_color(&graph), None);
+
+            let cycle = odd_cycle(&graph).unwrap();
+            assert_odd_cycle(&graph, &cycle);
+            assert_eq!(cycle.len(), length);
+        }
+
+        let graph = cycle(HashGraph::with_capacity(16), 7);
+        assert_odd_cycle(&graph, &odd_cycle(&graph).unwrap());
+    }
+
+    #[test]
+    fn odd_cycle_with_tail_test() {
+        // a tail 0-1-2 into a triangle 3-4-5, plus an isolated node
+        let edges = [(0, 1), (1, 2), (2, 3), (3, 4), (4, 5), (5, 3)];
+        let graph = BitGraph::from_edges(7, edges.iter().copied()).unwrap();
+
+        let cycle = odd_cycle(&graph).unwrap();
+        assert_odd_cycle(&graph, &cycle);
+        assert_eq!(cycle.len(), 3);
+
+        let self_loop = BitGraph::from_edges(2, vec![(0, 1), (1, 1)]).unwrap();
+        assert_eq!(odd_cycle(&self_loop), Some(vec![1]));
+    }
+}
@@ -0,0 +1,133 @@
+use crate::bit::NodeBitSet;
+use crate::Graph;
+
+/// Colors the nodes one by one in `order`, each taking the smallest color no
+/// neighbour already has. Edges count in both directions and self loops are
+/// ignored. Without an order nodes go by degree, largest first, ties by index.
+/// Nodes left out of `order` are colored after it by index, a node listed
+/// twice keeps the color it got first.
+///
+/// Returns the color of every node, colors are numbered from 0.
+pub fn greedy_coloring<V, W>(graph: &dyn Graph<V, W>, order: Option<&[usize]>) -> Vec<usize> {
+    let node_count = graph.node_count();
+
+    let neighbours: Vec<Vec<usize>> = (0..node_count)
+        .map(|node| {
+            let mut all = graph.outgoing_edges_of(node);
+            all.extend(graph.incoming_edges_of(node));
+            all.sort_unstable();
+            all.dedup();
+            all.retain(|other| *other != node);
+            all
+        })
+        .collect();
+
+    let by_degree;
+    let order = match order {
+        Some(order) => order,
+        None => {
+            let mut nodes: Vec<usize> = (0..node_count).collect();
+            nodes.sort_by_key(|node| std::cmp::Reverse(neighbours[*node].len()));
+            by_degree = nodes;
+            &by_degree
+        }
+    };
+
+    let mut colors = vec![usize::MAX; node_count];
+    // a node can never need more colors than it has neighbours
+    let mut used = NodeBitSet::with_capacity(node_count + 1);
+
+    for node in order.iter().copied().chain(0..node_count) {
+        if colors[node] != usize::MAX {
+            continue;
+        }
+
+        let taken: Vec<usize> = neighbours[node]
+            .iter()
+            .map(|other| colors[*other])
+            .filter(|color| *color != usize::MAX)
+            .collect();
+
+        taken.iter().for_each(|color| {
+            used.insert(*color);
+        });
+        colors[node] = (0..)
+            .find(|color| !used.contains(*color))
+            .expect("fewer colors are taken than there are nodes");
+        taken.iter().for_each(|color| {
+            used.remove(*color);
+        });
+    }
+
+    colors
+}
+
+/// number of colors in the output of `greedy_coloring`
+pub fn color_count(colors: &[usize]) -> usize {
+    colors.iter().max().map_or(0, |max| max + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bit::BitGraph;
+    use crate::hash::HashGraph;
+
+    fn assert_valid<V, W>(graph: &dyn Graph<V, W>, colors: &[usize]) {
+        for (from, to) in graph.all_edge_pairs() {
+            if from != to {
+                assert_ne!(colors[from], colors[to]);
+            }
+        }
+    }
+
+    #[test]
+    fn bipartite_test() {
+        // every node of 0..4 linked to every node of 4..8, edges one way
+        let edges = (0..4).flat_map(|i| (4..8).map(move |j| (i, j)));
+        let graph = BitGraph::from_edges(8, edges).unwrap();
+
+        let colors = greedy_coloring(&graph, None);
+        assert_valid(&graph, &colors);
+        assert_eq!(color_count(&colors), 2);
+    }
+
+    #[test]
+    fn complete_test() {
+        let mut graph = HashGraph::with_capacity(32);
+        for i in 0..4 {
+            graph.push_node(i);
+        }
+        for i in 0..4 {
+            for j in i + 1..4 {
+                graph.add_edge(i, j);
+            }
+        }
+        graph.add_edge(2, 2);
+
+        let colors = greedy_coloring(&graph, None);
+        assert_valid(&graph, &colors);
+        assert_eq!(color_count(&colors), 4);
+    }
+
+    #[test]
+    fn order_test() {
+        // a path 0-1-2-3 colored ends first needs 3 colors, in order needs 2
+        let graph = BitGraph::from_edges(4, vec![(0, 1), (1, 2), (2, 3)]).unwrap();
+
+        let colors = greedy_coloring(&graph, Some(&[0, 3, 1, 2]));
+        assert_valid(&graph, &colors);
+        assert_eq!(colors, vec![0, 1, 2, 0]);
+
+        let colors = greedy_coloring(&graph, Some(&[0, 1, 2, 3]));
+        assert_eq!(colors, vec![0, 1, 0, 1]);
+
+        // 1 and 2 are missing and come after 3, 3 is only colored once
+        let colors = greedy_coloring(&graph, Some(&[3, 0, 3]));
+        assert_valid(&graph, &colors);
+        assert_eq!(colors, vec![0, 1, 2, 0]);
+        assert_eq!(color_count(&colors), 3);
+
+        assert_eq!(color_count(&greedy_coloring(&BitGraph::new(), None)), 0);
+    }
+}
@@ -0,0 +1,97 @@
+use crate::search::union_find::UnionFind;
+use crate::Graph;
+
+/// Weakly connected components, every edge counts in both directions.
+/// Returns the component id of every node, ids are numbered from 0 in order
+/// of the lowest node in each component so isolated nodes, even those with a
+/// self loop, get their own.
+///
+/// Takes a single `all_edge_pairs` pass and a union find over the edges, no
+/// per node searches.
+pub fn weak_components<V, W>(graph: &dyn Graph<V, W>) -> Vec<usize> {
+    let node_count = graph.node_count();
+
+    let mut sets = UnionFind::new(node_count);
+    for (from, to) in graph.all_edge_pairs() {
+        sets.union(from, to);
+    }
+
+    let mut id_of_root = vec![usize::MAX; node_count];
+    let mut next_id = 0;
+
+    (0..node_count)
+        .map(|node| {
+            let root = sets.find(node);
+            if id_of_root[root] == usize::MAX {
+                id_of_root[root] = next_id;
+                next_id += 1;
+            }
+
+            id_of_root[root]
+        })
+        .collect()
+}
+
+/// number of components in the output of `weak_components`
+pub fn component_count(components: &[usize]) -> usize {
+    components.iter().max().map_or(0, |max| max + 1)
+}
+
+/// node indices of each component, in component id order
+pub fn group_by_component(components: &[usize]) -> impl Iterator<Item = Vec<usize>> {
+    let mut groups = vec![Vec::new(); component_count(components)];
+    for (node, component) in components.iter().enumerate() {
+        groups[*component].push(node);
+    }
+
+    groups.into_iter()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bit::BitGraph;
+    use crate::hash::HashGraph;
+
+    #[test]
+    fn islands_test() {
+        // 0-1-2-3 a chain with edges both ways, 4-5 and 6 alone, 7-8-9 a
+        // directed star pointing in
+        let edges = [(0, 1), (2, 1), (3, 2), (4, 5), (7, 9), (8, 9)];
+        let graph = BitGraph::from_edges(10, edges.iter().copied()).unwrap();
+
+        let components = weak_components(&graph);
+        assert_eq!(components, vec![0, 0, 0, 0, 1, 1, 2, 3, 3, 3]);
+        assert_eq!(component_count(&components), 4);
+
+        let groups: Vec<_> = group_by_component(&components).collect();
+        assert_eq!(
+            groups,
+            vec![vec![0, 1, 2, 3], vec![4, 5], vec![6], vec![7, 8, 9]]
+        );
+    }
+
+    #[test]
+    fn sparse_test() {
+        let mut graph = HashGraph::with_capacity(4096);
+        for i in 0..2000 {
+            graph.push_node(i);
+        }
+        graph.add_edge(1999, 0);
+        graph.add_edge(500, 1000);
+
+        let components = weak_components(&graph);
+        assert_eq!(component_count(&components), 1998);
+        assert_eq!(components[0], components[1999]);
+        assert_eq!(components[500], components[1000]);
+        assert_ne!(components[0], components[500]);
+    }
+
+    #[test]
+    fn empty_test() {
+        let graph = BitGraph::new();
+        assert!(weak_components(&graph).is_empty());
+        assert_eq!(component_count(&[]), 0);
+        assert_eq!(group_by_component(&[]).count(), 0);
+    }
+}
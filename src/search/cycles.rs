@@ -0,0 +1,118 @@
+use crate::Graph;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Color {
+    /// not reached yet
+    White,
+    /// on the current DFS path
+    Gray,
+    /// fully explored
+    Black,
+}
+
+/// Finds one directed cycle with an iterative three color DFS. The cycle is
+/// returned in edge order without repeating the first node at the end, so a
+/// self loop on `n` comes back as `[n]`.
+pub fn find_cycle<V, W>(graph: &dyn Graph<V, W>) -> Option<Vec<usize>> {
+    let node_count = graph.node_count();
+    let mut color = vec![Color::White; node_count];
+
+    // (node, its outgoing edges, next edge to look at), the gray path
+    let mut call_stack: Vec<(usize, Vec<usize>, usize)> = Vec::new();
+
+    for root in 0..node_count {
+        if color[root] != Color::White {
+            continue;
+        }
+
+        color[root] = Color::Gray;
+        call_stack.push((root, graph.outgoing_edges_of(root), 0));
+
+        while let Some((node, edges, position)) = call_stack.last_mut() {
+            let node = *node;
+
+            if let Some(&next) = edges.get(*position) {
+                *position += 1;
+
+                match color[next] {
+                    Color::White => {
+                        color[next] = Color::Gray;
+                        call_stack.push((next, graph.outgoing_edges_of(next), 0));
+                    }
+                    Color::Gray => {
+                        let start = call_stack
+                            .iter()
+                            .position(|(on_path, _, _)| *on_path == next)
+                            .expect("gray nodes are on the call stack");
+
+                        return Some(call_stack[start..].iter().map(|(n, _, _)| *n).collect());
+                    }
+                    Color::Black => {}
+                }
+
+                continue;
+            }
+
+            color[node] = Color::Black;
+            call_stack.pop();
+        }
+    }
+
+    None
+}
+
+/// true if the graph has no directed cycle, self loops included
+pub fn is_dag<V, W>(graph: &dyn Graph<V, W>) -> bool {
+    find_cycle(graph).is_none()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bit::BitGraph;
+
+    fn assert_cycle<V, W>(graph: &dyn Graph<V, W>, cycle: &[usize]) {
+        assert!(!cycle.is_empty());
+        for i in 0..cycle.len() {
+            assert!(graph.has_edge(cycle[i], cycle[(i + 1) % cycle.len()]));
+        }
+
+        let mut unique = cycle.to_vec();
+        unique.sort_unstable();
+        unique.dedup();
+        assert_eq!(unique.len(), cycle.len());
+    }
+
+    #[test]
+    fn dag_test() {
+        let edges = [(0, 1), (0, 2), (1, 3), (2, 3), (3, 4), (5, 4)];
+        let graph = BitGraph::from_edges(7, edges.iter().copied()).unwrap();
+
+        assert_eq!(find_cycle(&graph), None);
+        assert!(is_dag(&graph));
+        assert!(is_dag(&BitGraph::new()));
+    }
+
+    #[test]
+    fn self_loop_test() {
+        let graph = BitGraph::from_edges(3, vec![(0, 1), (1, 2), (2, 2)]).unwrap();
+
+        assert_eq!(find_cycle(&graph), Some(vec![2]));
+        assert!(!is_dag(&graph));
+    }
+
+    #[test]
+    fn long_cycle_test() {
+        // a tail leading into a 50 node cycle plus a chord that skips ahead
+        let mut edges: Vec<(usize, usize)> = (0..10).map(|i| (i, i + 1)).collect();
+        edges.extend((10..59).map(|i| (i, i + 1)));
+        edges.push((59, 10));
+        edges.push((20, 40));
+        let graph = BitGraph::from_edges(64, edges).unwrap();
+
+        let cycle = find_cycle(&graph).unwrap();
+        assert_cycle(&graph, &cycle);
+        assert!(cycle.iter().all(|node| (10..60).contains(node)));
+        assert!(!is_dag(&graph));
+    }
+}
@@ -0,0 +1,126 @@
+use crate::search::topo::{topological_sort, CycleError};
+use crate::Graph;
+
+/// Most expensive path in a DAG, the critical path of a project network.
+/// Edge costs are their weights, which is 1 per edge for a `BitGraph`. Starts
+/// at `source`, or at any node without incoming edges when `None`.
+///
+/// Returns the path and its cost, ties go to the lowest end node. A self loop
+/// is a cycle.
+pub fn longest_path<V, W>(
+    graph: &dyn Graph<V, W>,
+    source: Option<usize>,
+) -> Result<(Vec<usize>, usize), CycleError>
+where
+    W: Into<usize>,
+{
+    let order = topological_sort(graph)?;
+    let node_count = graph.node_count();
+
+    let mut cost: Vec<Option<usize>> = vec![None; node_count];
+    let mut from_map = vec![usize::MAX; node_count];
+
+    match source {
+        Some(source) => cost[source] = Some(0),
+        None => (0..node_count)
+            .filter(|node| graph.incoming_edges_of(*node).is_empty())
+            .for_each(|node| cost[node] = Some(0)),
+    }
+
+    for node in order {
+        let node_cost = match cost[node] {
+            Some(node_cost) => node_cost,
+            None => continue,
+        };
+
+        for next in graph.outgoing_edges_of(node) {
+            let weight = graph
+                .get_edge(node, next)
+                .map_or(1, |edge| edge.weight.into());
+            let tentative = node_cost + weight;
+
+            if cost[next].is_none_or(|current| tentative > current) {
+                cost[next] = Some(tentative);
+                from_map[next] = node;
+            }
+        }
+    }
+
+    let end = (0..node_count)
+        .filter(|node| cost[*node].is_some())
+        .max_by_key(|node| (cost[*node], std::cmp::Reverse(*node)));
+
+    let end = match end {
+        Some(end) => end,
+        None => return Ok((Vec::new(), 0)),
+    };
+
+    let mut path = vec![end];
+    let mut node = end;
+    while from_map[node] != usize::MAX {
+        node = from_map[node];
+        path.push(node);
+    }
+    path.reverse();
+
+    Ok((
+        path,
+        cost[end].expect("only nodes with a cost are picked as the end"),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bit::BitGraph;
+    use crate::hash::HashGraph;
+
+    fn project() -> HashGraph {
+        let mut graph = HashGraph::with_capacity(32);
+        for i in 0..7 {
+            graph.push_node(i);
+        }
+
+        // task durations on the edges, 6 is an unrelated task
+        for &(from, to, weight) in &[
+            (0, 1, 3),
+            (0, 2, 2),
+            (1, 3, 4),
+            (2, 3, 2),
+            (1, 4, 1),
+            (3, 5, 3),
+            (4, 5, 5),
+        ] {
+            graph.set_edge((from, to), weight);
+        }
+
+        graph
+    }
+
+    #[test]
+    fn critical_path_test() {
+        let graph = project();
+
+        assert_eq!(longest_path(&graph, None), Ok((vec![0, 1, 3, 5], 10)));
+        assert_eq!(longest_path(&graph, Some(2)), Ok((vec![2, 3, 5], 5)));
+        assert_eq!(longest_path(&graph, Some(6)), Ok((vec![6], 0)));
+    }
+
+    #[test]
+    fn unweighted_test() {
+        let edges = [(0, 1), (1, 2), (0, 2), (2, 3), (4, 3)];
+        let graph = BitGraph::from_edges(5, edges.iter().copied()).unwrap();
+
+        assert_eq!(longest_path(&graph, None), Ok((vec![0, 1, 2, 3], 3)));
+        assert_eq!(longest_path(&BitGraph::new(), None), Ok((Vec::new(), 0)));
+    }
+
+    #[test]
+    fn cycle_test() {
+        let mut graph = project();
+        graph.add_edge(5, 1);
+
+        assert!(longest_path(&graph, None).is_err());
+        assert!(longest_path(&graph, Some(0)).is_err());
+    }
+}
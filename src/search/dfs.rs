@@ -1,17 +1,20 @@
-const WORD_BYTES: usize = std::mem::size_of::<usize>();
-const WORD_BITS: usize = WORD_BYTES * 8;
-
-use crate::bit::single_bit_mask;
+use crate::bit::NodeBitSet;
 use crate::Graph;
 
 use crate::search::Pathfinder;
 
+/// Depth first search from a root. A node is visited once, so a self loop is
+/// never followed.
 pub struct DFS {
     root_idx: usize,
 
     stack: Vec<(usize, usize)>,
+    /// neighbours of the node being visited, reused across visits
+    scratch: Vec<usize>,
 
-    discovered: Vec<usize>,
+    discovered: NodeBitSet,
+    visited_count: usize,
+    expansions: usize,
 
     pub from_map: Vec<usize>,
     pub solved: bool,
@@ -20,10 +23,14 @@ pub struct DFS {
 impl<V, W> Pathfinder<V, W> for DFS {
     fn next(&mut self, graph: &dyn Graph<V, W>) -> Option<(usize, usize)> {
         while let Some((idx, from)) = self.stack.pop() {
+            self.expansions += 1;
+
             if self.visit_node(idx) {
+                self.visited_count += 1;
                 self.from_map[idx] = from;
 
-                for out in graph.outgoing_edges_of(idx) {
+                graph.outgoing_edges_into(idx, &mut self.scratch);
+                for &out in &self.scratch {
                     if !self.is_discovered(out) {
                         self.stack.push((out, idx));
                     }
@@ -36,45 +43,21 @@ impl<V, W> Pathfinder<V, W> for DFS {
     }
 
     fn path_to(&mut self, graph: &dyn Graph<V, W>, to_idx: usize) -> Option<Vec<usize>> {
-        let mut from_map = vec![std::usize::MAX; graph.node_count()];
-        let mut out = Vec::new();
-
-        while let Some((idx, from)) = self.stack.pop() {
+        while let Some((idx, _from)) = self.next(graph) {
             if idx == to_idx {
-                let mut from_tmp = from;
-                out.push(idx);
-                out.push(from);
-                loop {
-                    if from_tmp == self.root_idx {
-                        break;
-                    }
-
-                    let from_idx = from_map[from_tmp];
-                    out.push(from_idx);
-
-                    from_tmp = from_idx;
+                let mut out = vec![idx];
+                let mut from_tmp = idx;
+                while from_tmp != self.root_idx {
+                    from_tmp = self.from_map[from_tmp];
+                    out.push(from_tmp);
                 }
 
-                break;
-            }
-
-            if self.visit_node(idx) {
-                from_map[idx] = from;
-
-                for out in graph.outgoing_edges_of(idx) {
-                    if !self.is_discovered(out) {
-                        self.stack.push((out, idx));
-                    }
-                }
+                out.reverse();
+                return Some(out);
             }
         }
 
-        if out.len() == 0 {
-            None
-        } else {
-            out.reverse();
-            Some(out)
-        }
+        None
     }
 
     fn is_solved(&self) -> bool {
@@ -88,61 +71,195 @@ impl<V, W> Pathfinder<V, W> for DFS {
     fn from_index_of(&self, index: usize) -> usize {
         self.from_map[index]
     }
+
+    fn cost_of(&self, index: usize) -> Option<usize> {
+        Self::cost_of(self, index)
+    }
 }
 
 impl DFS {
     pub fn new<V, W>(graph: &dyn Graph<V, W>, root_idx: usize) -> Self {
         Self {
             root_idx,
-            discovered: vec![0; graph.node_count() / WORD_BITS + 1],
+            discovered: NodeBitSet::with_capacity(graph.node_count()),
+            visited_count: 0,
+            expansions: 0,
             stack: vec![(root_idx, root_idx)],
-            from_map: vec![std::usize::MAX; graph.node_count()],
+            scratch: Vec::new(),
+            from_map: vec![usize::MAX; graph.node_count()],
             solved: false,
         }
     }
 
-    /// Visits node, returns true if first visit, else false
-    fn visit_node(&mut self, node_idx: usize) -> bool {
-        let first_visit = self.is_discovered(node_idx);
+    /// Starts over from `root_idx` reusing every allocation. The graph must
+    /// not have gained nodes since `new`.
+    pub fn reset(&mut self, root_idx: usize) {
+        self.root_idx = root_idx;
 
-        if first_visit {
-            false
-        } else {
-            self.set_discovered(node_idx);
-            true
+        self.stack.clear();
+        self.stack.push((root_idx, root_idx));
+        self.from_map.iter_mut().for_each(|from| *from = usize::MAX);
+
+        self.discovered.clear();
+        self.visited_count = 0;
+        self.expansions = 0;
+        self.solved = false;
+    }
+
+    /// number of hops from the root to `idx` in the search tree, `None` if
+    /// it has not been visited
+    pub fn cost_of(&self, idx: usize) -> Option<usize> {
+        if !self.is_discovered(idx) {
+            return None;
         }
+
+        let mut hops = 0;
+        let mut from_tmp = idx;
+        while from_tmp != self.root_idx {
+            from_tmp = self.from_map[from_tmp];
+            hops += 1;
+        }
+
+        Some(hops)
+    }
+
+    /// nodes visited so far in ascending order
+    pub fn discovered(&self) -> impl Iterator<Item = usize> + '_ {
+        self.discovered.iter()
+    }
+
+    /// number of distinct nodes visited
+    pub fn visited_count(&self) -> usize {
+        self.visited_count
+    }
+
+    /// entries taken off the stack, including ones that were already visited
+    pub fn expansions(&self) -> usize {
+        self.expansions
+    }
+
+    /// Visits node, returns true if first visit, else false
+    fn visit_node(&mut self, node_idx: usize) -> bool {
+        self.discovered.insert(node_idx)
     }
 
     fn is_discovered(&self, node_idx: usize) -> bool {
-        (self.discovered[node_idx / WORD_BITS] & single_bit_mask(node_idx % WORD_BITS)) > 0
+        self.discovered.contains(node_idx)
     }
+}
+
+/// how an edge relates to the DFS tree
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EdgeKind {
+    /// first reaches its destination
+    Tree,
+    /// to a node still on the current path, closes a cycle
+    Back,
+    /// to an already finished descendant
+    Forward,
+    /// to an already finished node in another branch
+    Cross,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DfsEvent {
+    Discover(usize),
+    Finish(usize),
+    Edge(usize, usize, EdgeKind),
+}
 
-    fn set_discovered(&mut self, node_idx: usize) {
-        self.discovered[node_idx / WORD_BITS] |= single_bit_mask(node_idx % WORD_BITS);
+/// Depth first traversal from a root as a stream of events. A tree edge is
+/// followed by the discovery of its destination and a node finishes after
+/// all of its edges were reported. A self loop is a `Back` edge.
+pub struct DfsEvents<'a, V, W> {
+    graph: &'a dyn Graph<V, W>,
+
+    /// (node, its outgoing edges, next edge to look at)
+    stack: Vec<(usize, Vec<usize>, usize)>,
+    discovered_at: Vec<usize>,
+    finished: NodeBitSet,
+    time: usize,
+
+    pending: Option<DfsEvent>,
+}
+
+impl<'a, V, W> DfsEvents<'a, V, W> {
+    pub fn new(graph: &'a dyn Graph<V, W>, root_idx: usize) -> Self {
+        let mut events = Self {
+            graph,
+            stack: Vec::new(),
+            discovered_at: vec![usize::MAX; graph.node_count()],
+            finished: NodeBitSet::with_capacity(graph.node_count()),
+            time: 0,
+            pending: None,
+        };
+
+        events.discover(root_idx);
+        events
+    }
+
+    fn discover(&mut self, node: usize) {
+        self.discovered_at[node] = self.time;
+        self.time += 1;
+        self.stack
+            .push((node, self.graph.outgoing_edges_of(node), 0));
+        self.pending = Some(DfsEvent::Discover(node));
     }
 }
 
+impl<'a, V, W> Iterator for DfsEvents<'a, V, W> {
+    type Item = DfsEvent;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(event) = self.pending.take() {
+            return Some(event);
+        }
+
+        let (node, edges, position) = self.stack.last_mut()?;
+        let node = *node;
+
+        if let Some(&to) = edges.get(*position) {
+            *position += 1;
+
+            let kind = if self.discovered_at[to] == usize::MAX {
+                self.discover(to);
+                EdgeKind::Tree
+            } else if !self.finished.contains(to) {
+                EdgeKind::Back
+            } else if self.discovered_at[node] < self.discovered_at[to] {
+                EdgeKind::Forward
+            } else {
+                EdgeKind::Cross
+            };
+
+            return Some(DfsEvent::Edge(node, to, kind));
+        }
+
+        self.stack.pop();
+        self.finished.insert(node);
+        Some(DfsEvent::Finish(node))
+    }
+}
+
+/// nodes reachable from `root_idx` in the order they finish
+pub fn post_order<V, W>(graph: &dyn Graph<V, W>, root_idx: usize) -> Vec<usize> {
+    DfsEvents::new(graph, root_idx)
+        .filter_map(|event| match event {
+            DfsEvent::Finish(node) => Some(node),
+            _ => None,
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod test_dfs {
     use super::*;
     use crate::bit::BitGraph;
+    use crate::search::test_graph;
 
     #[test]
     fn it_works() {
-        let mut graph = BitGraph::new();
-
-        for i in 0..15 {
-            graph.push_node(i);
-        }
-
-        graph.add_edge(0, 2);
-        graph.add_edge(0, 1);
-        graph.add_edge(2, 4);
-        graph.add_edge(3, 8);
-        graph.add_edge(8, 5);
-        graph.add_edge(1, 3);
-        graph.add_edge(3, 5);
-        graph.add_edge(5, 0);
+        let graph = test_graph(BitGraph::new());
 
         let mut dfs = DFS::new(&graph, 0);
         let found = loop {
@@ -174,4 +291,132 @@ mod test_dfs {
         };
         assert!(not_found);
     }
+
+    #[test]
+    fn path_to_root_and_unreachable_test() {
+        let mut graph = BitGraph::with_capacity(8);
+        for i in 0..8 {
+            graph.push_node(i);
+        }
+        graph.add_edge(0, 1);
+        graph.add_edge(1, 2);
+
+        let mut search = DFS::new(&graph, 0);
+        assert_eq!(search.path_to(&graph, 0), Some(vec![0]));
+
+        let mut search = DFS::new(&graph, 0);
+        assert_eq!(search.path_to(&graph, 2), Some(vec![0, 1, 2]));
+
+        let mut search = DFS::new(&graph, 0);
+        assert_eq!(search.path_to(&graph, 7), None);
+        assert_eq!(search.path_to(&graph, 7), None);
+    }
+
+    #[test]
+    fn no_duplicate_visits_test() {
+        // every node links to the next few so most are discovered many times
+        let mut graph = BitGraph::with_capacity(130);
+        for i in 0..130 {
+            graph.push_node(i);
+        }
+        for from in 0..130 {
+            for to in from + 1..(from + 5).min(130) {
+                graph.add_edge(from, to);
+            }
+        }
+
+        let mut search = DFS::new(&graph, 0);
+        let mut visited = Vec::new();
+        while let Some((idx, _from)) = search.next(&graph) {
+            visited.push(idx);
+        }
+
+        assert_eq!(visited.len(), 130);
+        visited.sort_unstable();
+        visited.dedup();
+        assert_eq!(visited.len(), 130);
+    }
+
+    #[test]
+    fn iterator_test() {
+        let graph = test_graph(BitGraph::with_capacity(16));
+
+        let found = DFS::new(&graph, 0)
+            .into_iter(&graph)
+            .find(|(idx, _from)| *idx == 5);
+        assert_eq!(found, Some((5, 8)));
+        assert_eq!(DFS::new(&graph, 0).into_iter(&graph).count(), 7);
+
+        let mut dfs = DFS::new(&graph, 0);
+        assert_eq!(dfs.iter(&graph).take(2).count(), 2);
+        assert_eq!(dfs.iter(&graph).count(), 5);
+    }
+
+    #[test]
+    fn stats_test() {
+        let graph = test_graph(BitGraph::with_capacity(16));
+
+        let mut dfs = DFS::new(&graph, 0);
+        while dfs.next(&graph).is_some() {}
+
+        assert_eq!(dfs.visited_count(), 7);
+        assert_eq!(
+            dfs.discovered().collect::<Vec<_>>(),
+            vec![0, 1, 2, 3, 4, 5, 8]
+        );
+        assert!(dfs.expansions() >= dfs.visited_count());
+    }
+
+    #[test]
+    fn reset_test() {
+        let graph = test_graph(BitGraph::with_capacity(16));
+
+        let mut reused = DFS::new(&graph, 0);
+        let mut fresh = DFS::new(&graph, 0);
+        assert_eq!(reused.path_to(&graph, 4), fresh.path_to(&graph, 4));
+        let first: Vec<_> = reused.iter(&graph).collect();
+
+        reused.reset(3);
+        assert_eq!(reused.visited_count(), 0);
+
+        let mut fresh = DFS::new(&graph, 3);
+        assert_eq!(reused.path_to(&graph, 4), fresh.path_to(&graph, 4));
+        assert!(reused.discovered().eq(fresh.discovered()));
+        assert_eq!(reused.expansions(), fresh.expansions());
+
+        reused.reset(0);
+        let _ = reused.path_to(&graph, 4);
+        assert_eq!(reused.iter(&graph).collect::<Vec<_>>(), first);
+    }
+
+    #[test]
+    fn events_test() {
+        let edges = [(0, 1), (1, 2), (2, 0), (0, 2), (0, 3), (3, 2)];
+        let graph = BitGraph::from_edges(5, edges.iter().copied()).unwrap();
+
+        let events: Vec<_> = DfsEvents::new(&graph, 0).collect();
+        assert_eq!(
+            events,
+            vec![
+                DfsEvent::Discover(0),
+                DfsEvent::Edge(0, 1, EdgeKind::Tree),
+                DfsEvent::Discover(1),
+                DfsEvent::Edge(1, 2, EdgeKind::Tree),
+                DfsEvent::Discover(2),
+                DfsEvent::Edge(2, 0, EdgeKind::Back),
+                DfsEvent::Finish(2),
+                DfsEvent::Finish(1),
+                DfsEvent::Edge(0, 2, EdgeKind::Forward),
+                DfsEvent::Edge(0, 3, EdgeKind::Tree),
+                DfsEvent::Discover(3),
+                DfsEvent::Edge(3, 2, EdgeKind::Cross),
+                DfsEvent::Finish(3),
+                DfsEvent::Finish(0),
+            ]
+        );
+
+        assert_eq!(post_order(&graph, 0), vec![2, 1, 3, 0]);
+        assert_eq!(post_order(&graph, 3), vec![1, 0, 2, 3]);
+        assert_eq!(post_order(&graph, 4), vec![4]);
+    }
 }
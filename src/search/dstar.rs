@@ -0,0 +1,418 @@
+use crate::bit::NodeBitSet;
+use crate::search::a_star::step_cost;
+use crate::search::Pathfinder;
+use crate::Graph;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+/// (cost with heuristic, cost without)
+type Key = (usize, usize);
+
+/// D* Lite for a fixed start, which is Lifelong Planning A*. Keeps its
+/// scores between calls to `path_to` so after the graph changes only the
+/// nodes whose cost actually moved are expanded again. Takes the same
+/// heuristic as `AStar`, `heuristic(idx)` estimates the cost from `idx` to
+/// the goal and has to be consistent. Self loops are never taken.
+///
+/// The graph must not gain nodes after `new`, and every `add_edge` or
+/// `remove_edge` on it has to be reported through `update_edge`.
+pub struct DStarLite<H> {
+    root_idx: usize,
+    goal_idx: usize,
+
+    /// cost of the cheapest path to every node as of its last expansion
+    g_score: Vec<usize>,
+    /// one step lookahead, cheapest `g_score` of a predecessor plus the step
+    rhs: Vec<usize>,
+
+    /// Entries of nodes whose `g_score` and `rhs` differ. Stale entries are
+    /// skipped when they come up, like in `AStar`.
+    open_set: BinaryHeap<Reverse<(Key, usize)>>,
+    in_open: NodeBitSet,
+    keys: Vec<Key>,
+
+    /// edges reported by `update_edge` and not handled yet
+    changes: Vec<(usize, usize, bool)>,
+
+    heuristic: H,
+    /// step costs come from `get_edge` weights
+    weighted: bool,
+
+    expansions: usize,
+
+    /// the predecessor `rhs` was taken from
+    pub from_map: Vec<usize>,
+    pub solved: bool,
+}
+
+impl<H: Fn(usize) -> usize> DStarLite<H> {
+    pub fn new<V, W>(
+        graph: &dyn Graph<V, W>,
+        root_idx: usize,
+        goal_idx: usize,
+        heuristic: H,
+    ) -> Self {
+        let node_count = graph.node_count();
+        let mut dstar = Self {
+            root_idx,
+            goal_idx,
+
+            g_score: vec![usize::MAX; node_count],
+            rhs: vec![usize::MAX; node_count],
+
+            open_set: BinaryHeap::new(),
+            in_open: NodeBitSet::with_capacity(node_count),
+            keys: vec![(usize::MAX, usize::MAX); node_count],
+
+            changes: Vec::new(),
+
+            heuristic,
+            weighted: false,
+
+            expansions: 0,
+
+            from_map: vec![usize::MAX; node_count],
+            solved: false,
+        };
+
+        dstar.rhs[root_idx] = 0;
+        dstar.queue(root_idx);
+        dstar
+    }
+
+    /// use the edge weights from `get_edge` as step costs instead of 1
+    pub fn weighted(mut self) -> Self {
+        self.weighted = true;
+        self
+    }
+
+    pub fn goal_idx(&self) -> usize {
+        self.goal_idx
+    }
+
+    /// Reports that the edge from `from` to `to` was added to or removed from
+    /// the graph, or had its weight changed which counts as added. The work
+    /// happens in the next `path_to` or `next`.
+    pub fn update_edge(&mut self, from: usize, to: usize, added: bool) {
+        self.changes.push((from, to, added));
+    }
+
+    /// Nodes taken off the open set since `new`, counting a node again every
+    /// time a change makes it expand again.
+    pub fn expansions(&self) -> usize {
+        self.expansions
+    }
+
+    /// cost of the cheapest path to `idx` as far as the search has got
+    pub fn cost_of(&self, idx: usize) -> Option<usize> {
+        Some(self.g_score[idx]).filter(|score| *score != usize::MAX)
+    }
+
+    fn key_of(&self, idx: usize) -> Key {
+        let cost = self.g_score[idx].min(self.rhs[idx]);
+        (cost.saturating_add((self.heuristic)(idx)), cost)
+    }
+
+    /// puts `idx` on the open set if it is inconsistent, takes it off if not
+    fn queue(&mut self, idx: usize) {
+        if self.g_score[idx] == self.rhs[idx] {
+            self.in_open.remove(idx);
+            return;
+        }
+
+        let key = self.key_of(idx);
+        self.keys[idx] = key;
+        self.in_open.insert(idx);
+        self.open_set.push(Reverse((key, idx)));
+    }
+
+    /// recomputes `rhs` of `idx` from all of its predecessors
+    fn update_node<C>(&mut self, incoming: Vec<usize>, idx: usize, cost: &C)
+    where
+        C: Fn(usize, usize) -> usize,
+    {
+        if idx != self.root_idx {
+            let best = incoming
+                .into_iter()
+                .map(|from| (self.g_score[from].saturating_add(cost(from, idx)), from))
+                .min();
+
+            self.rhs[idx] = best.map_or(usize::MAX, |(rhs, _)| rhs);
+            self.from_map[idx] = best.map_or(usize::MAX, |(_, from)| from);
+        }
+
+        self.queue(idx);
+    }
+
+    /// handles the edges given to `update_edge`
+    fn apply_changes<V, W, C>(&mut self, graph: &dyn Graph<V, W>, cost: &C)
+    where
+        C: Fn(usize, usize) -> usize,
+    {
+        for (from, to, added) in std::mem::take(&mut self.changes) {
+            if to == self.root_idx {
+                continue;
+            }
+
+            if self.from_map[to] == from {
+                // the step `rhs` came from may have got more expensive
+                self.update_node(graph.incoming_edges_of(to), to, cost);
+            } else if added {
+                let rhs = self.g_score[from].saturating_add(cost(from, to));
+                if rhs < self.rhs[to] {
+                    self.rhs[to] = rhs;
+                    self.from_map[to] = from;
+                    self.queue(to);
+                }
+            }
+        }
+    }
+
+    /// best live entry on the open set
+    fn peek(&mut self) -> Option<(Key, usize)> {
+        while let Some(Reverse((key, idx))) = self.open_set.peek().copied() {
+            if self.in_open.contains(idx) && key == self.keys[idx] {
+                return Some((key, idx));
+            }
+
+            self.open_set.pop();
+        }
+
+        None
+    }
+
+    /// takes the best node off the open set and makes it consistent
+    fn step<V, W, C>(&mut self, graph: &dyn Graph<V, W>, cost: &C) -> Option<usize>
+    where
+        C: Fn(usize, usize) -> usize,
+    {
+        let (_, current) = self.peek()?;
+        self.open_set.pop();
+        self.in_open.remove(current);
+        self.expansions += 1;
+
+        if self.g_score[current] > self.rhs[current] {
+            self.g_score[current] = self.rhs[current];
+        } else {
+            // got more expensive, everything through it has to be redone
+            self.g_score[current] = usize::MAX;
+            self.update_node(graph.incoming_edges_of(current), current, cost);
+        }
+
+        for next in graph.outgoing_edges_of(current) {
+            self.update_node(graph.incoming_edges_of(next), next, cost);
+        }
+
+        Some(current)
+    }
+
+    /// Expands nodes until nothing on the open set can change the cost of
+    /// `to_idx`, then walks `from_map` back to the root.
+    fn solve<V, W, C>(
+        &mut self,
+        graph: &dyn Graph<V, W>,
+        to_idx: usize,
+        cost: &C,
+    ) -> Option<Vec<usize>>
+    where
+        C: Fn(usize, usize) -> usize,
+    {
+        self.apply_changes(graph, cost);
+
+        while let Some((key, _)) = self.peek() {
+            if key >= self.key_of(to_idx) && self.rhs[to_idx] == self.g_score[to_idx] {
+                break;
+            }
+
+            self.step(graph, cost);
+        }
+
+        if self.g_score[to_idx] == usize::MAX {
+            return None;
+        }
+
+        let mut out = vec![to_idx];
+        let mut from_tmp = to_idx;
+        while from_tmp != self.root_idx {
+            from_tmp = self.from_map[from_tmp];
+            out.push(from_tmp);
+        }
+
+        out.reverse();
+        Some(out)
+    }
+}
+
+impl<V, W, H> Pathfinder<V, W> for DStarLite<H>
+where
+    W: Into<usize>,
+    H: Fn(usize) -> usize,
+{
+    fn next(&mut self, graph: &dyn Graph<V, W>) -> Option<(usize, usize)> {
+        let weighted = self.weighted;
+        let cost = |from, to| step_cost(graph, weighted, from, to);
+
+        self.apply_changes(graph, &cost);
+        let idx = self.step(graph, &cost)?;
+
+        if idx == self.root_idx {
+            Some((idx, idx))
+        } else {
+            Some((idx, self.from_map[idx]))
+        }
+    }
+
+    fn path_to(&mut self, graph: &dyn Graph<V, W>, to_idx: usize) -> Option<Vec<usize>> {
+        let weighted = self.weighted;
+        self.solve(graph, to_idx, &|from, to| {
+            step_cost(graph, weighted, from, to)
+        })
+    }
+
+    fn is_solved(&self) -> bool {
+        self.solved
+    }
+
+    fn set_solved(&mut self) {
+        self.solved = true;
+    }
+
+    fn from_index_of(&self, index: usize) -> usize {
+        self.from_map[index]
+    }
+
+    fn cost_of(&self, index: usize) -> Option<usize> {
+        Self::cost_of(self, index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bit::BitGraph;
+    use crate::grid::{coords, grid_graph, index_of, with_obstacles};
+    use crate::hash::HashGraph;
+    use crate::search::a_star::AStar;
+
+    const WIDTH: usize = 12;
+
+    fn manhattan(goal: usize) -> impl Fn(usize) -> usize {
+        move |idx| {
+            let (x, y) = coords(idx, WIDTH);
+            let (goal_x, goal_y) = coords(goal, WIDTH);
+            x.abs_diff(goal_x) + y.abs_diff(goal_y)
+        }
+    }
+
+    fn assert_valid_path(graph: &BitGraph, path: &[usize], from: usize, to: usize) {
+        assert_eq!(path.first(), Some(&from));
+        assert_eq!(path.last(), Some(&to));
+        assert!(path.windows(2).all(|step| graph.has_edge(step[0], step[1])));
+    }
+
+    #[test]
+    fn replan_test() {
+        // a wall down the middle with a gap at the top and one at the bottom
+        let wall: Vec<usize> = (1..WIDTH - 1).map(|y| index_of(6, y, WIDTH)).collect();
+        let mut graph = with_obstacles(WIDTH, WIDTH, &wall);
+        let (root, goal) = (index_of(0, 2, WIDTH), index_of(11, 2, WIDTH));
+
+        let mut dstar = DStarLite::new(&graph, root, goal, manhattan(goal));
+        let path = dstar.path_to(&graph, goal).unwrap();
+        assert_valid_path(&graph, &path, root, goal);
+        assert_eq!(path.len() - 1, 15);
+
+        // close the top gap, somewhere along the found path
+        let top_gap = index_of(6, 0, WIDTH);
+        assert!(path.contains(&top_gap));
+        let (from, to) = (index_of(5, 0, WIDTH), top_gap);
+        graph.remove_edge(from, to);
+        graph.remove_edge(to, from);
+        dstar.update_edge(from, to, false);
+        dstar.update_edge(to, from, false);
+
+        let before = dstar.expansions();
+        let path = dstar.path_to(&graph, goal).unwrap();
+        let replan_expansions = dstar.expansions() - before;
+
+        assert_valid_path(&graph, &path, root, goal);
+        assert!(!path.windows(2).any(|step| step == [from, to]));
+        assert_eq!(path.len() - 1, 29);
+
+        let mut astar = AStar::new(&graph, root, goal, manhattan(goal));
+        let fresh_path = astar.path_to(&graph, goal).unwrap();
+        assert_eq!(fresh_path.len(), path.len());
+
+        let mut fresh = DStarLite::new(&graph, root, goal, manhattan(goal));
+        fresh.path_to(&graph, goal).unwrap();
+        assert!(
+            replan_expansions < fresh.expansions(),
+            "{} >= {}",
+            replan_expansions,
+            fresh.expansions()
+        );
+    }
+
+    #[test]
+    fn added_edge_test() {
+        let wall: Vec<usize> = (0..WIDTH - 1).map(|y| index_of(6, y, WIDTH)).collect();
+        let mut graph = with_obstacles(WIDTH, WIDTH, &wall);
+        let (root, goal) = (index_of(0, 0, WIDTH), index_of(11, 0, WIDTH));
+
+        let mut dstar = DStarLite::new(&graph, root, goal, manhattan(goal));
+        assert_eq!(dstar.path_to(&graph, goal).unwrap().len() - 1, 33);
+
+        // open a cell of the wall again
+        let cell = index_of(6, 0, WIDTH);
+        for (from, to) in [(index_of(5, 0, WIDTH), cell), (cell, index_of(7, 0, WIDTH))] {
+            graph.add_edge(from, to);
+            dstar.update_edge(from, to, true);
+        }
+
+        let path = dstar.path_to(&graph, goal).unwrap();
+        assert_valid_path(&graph, &path, root, goal);
+        assert_eq!(path.len() - 1, 11);
+        assert_eq!(dstar.cost_of(goal), Some(11));
+
+        // and close it, back to the long way around
+        graph.remove_edge(index_of(5, 0, WIDTH), cell);
+        dstar.update_edge(index_of(5, 0, WIDTH), cell, false);
+        assert_eq!(dstar.path_to(&graph, goal).unwrap().len() - 1, 33);
+    }
+
+    #[test]
+    fn unreachable_test() {
+        let mut graph = grid_graph(WIDTH, WIDTH, false);
+        let (root, goal) = (0, WIDTH * WIDTH - 1);
+        let mut dstar = DStarLite::new(&graph, root, goal, manhattan(goal));
+        assert!(dstar.path_to(&graph, goal).is_some());
+
+        for from in graph.incoming_edges_of(goal) {
+            graph.remove_edge(from, goal);
+            dstar.update_edge(from, goal, false);
+        }
+
+        assert_eq!(dstar.path_to(&graph, goal), None);
+        assert_eq!(dstar.cost_of(goal), None);
+    }
+
+    #[test]
+    fn weighted_test() {
+        let mut graph = HashGraph::with_capacity(16);
+        for i in 0..4 {
+            graph.push_node(i);
+        }
+        graph.set_edge((0, 1), 1);
+        graph.set_edge((1, 3), 1);
+        graph.set_edge((0, 2), 1);
+        graph.set_edge((2, 3), 3);
+
+        let mut dstar = DStarLite::new(&graph, 0, 3, |_| 0).weighted();
+        assert_eq!(dstar.path_to(&graph, 3), Some(vec![0, 1, 3]));
+
+        graph.set_edge((1, 3), 5);
+        dstar.update_edge(1, 3, true);
+        assert_eq!(dstar.path_to(&graph, 3), Some(vec![0, 2, 3]));
+        assert_eq!(dstar.cost_of(3), Some(4));
+    }
+}
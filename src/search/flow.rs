@@ -0,0 +1,191 @@
+use crate::hash::{HashGraph, PairHashTable};
+use crate::Graph;
+use std::collections::VecDeque;
+
+/// Maximum flow from `source` to `sink` with Edmonds–Karp, edge weights are
+/// capacities. Returns the flow value and a graph with every edge of `graph`
+/// weighted by the flow sent along it. Node values of the flow graph are
+/// their indices. Self loops are ignored and a `source` equal to `sink`
+/// carries no flow.
+pub fn max_flow<V>(graph: &dyn Graph<V, usize>, source: usize, sink: usize) -> (usize, HashGraph) {
+    let node_count = graph.node_count();
+    let edges: Vec<(usize, usize, usize)> = graph
+        .all_edge_pairs()
+        .into_iter()
+        .filter(|(from, to)| from != to)
+        .filter_map(|(from, to)| Some((from, to, graph.get_edge(from, to)?.weight)))
+        .collect();
+
+    // residual capacity of every edge and its reverse, antiparallel edges
+    // share one pair of entries
+    let mut residual = PairHashTable::with_capacity(edges.len() * 4 + 1);
+    let mut neighbours = vec![Vec::new(); node_count];
+    for &(from, to, capacity) in &edges {
+        if residual.get((from, to)).is_none() && residual.get((to, from)).is_none() {
+            neighbours[from].push(to);
+            neighbours[to].push(from);
+            residual.insert((to, from), 0);
+        }
+        let current = residual.get((from, to)).map_or(0, |edge| *edge.weight);
+        residual.insert((from, to), current + capacity);
+    }
+
+    let capacity_of = |residual: &PairHashTable, from: usize, to: usize| {
+        residual.get((from, to)).map_or(0, |edge| *edge.weight)
+    };
+
+    let mut total = 0;
+    let mut from_map = vec![usize::MAX; node_count];
+    let mut queue = VecDeque::new();
+
+    loop {
+        // shortest augmenting path
+        from_map.iter_mut().for_each(|from| *from = usize::MAX);
+        from_map[source] = source;
+        queue.clear();
+        queue.push_back(source);
+
+        while let Some(node) = queue.pop_front() {
+            if node == sink {
+                break;
+            }
+
+            for &next in &neighbours[node] {
+                if from_map[next] == usize::MAX && capacity_of(&residual, node, next) > 0 {
+                    from_map[next] = node;
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        if from_map[sink] == usize::MAX || source == sink {
+            break;
+        }
+
+        let mut bottleneck = usize::MAX;
+        let mut node = sink;
+        while node != source {
+            let from = from_map[node];
+            bottleneck = bottleneck.min(capacity_of(&residual, from, node));
+            node = from;
+        }
+
+        let mut node = sink;
+        while node != source {
+            let from = from_map[node];
+            let forward = capacity_of(&residual, from, node);
+            let backward = capacity_of(&residual, node, from);
+            residual.insert((from, node), forward - bottleneck);
+            residual.insert((node, from), backward + bottleneck);
+            node = from;
+        }
+
+        total += bottleneck;
+    }
+
+    let mut flow = HashGraph::with_capacity(edges.len() * 2 + 1);
+    for i in 0..node_count {
+        flow.push_node(i as u64);
+    }
+
+    // whatever is missing from the forward capacity is the net flow, when
+    // it runs the other way there is nothing missing
+    for &(from, to, capacity) in &edges {
+        flow.set_edge(
+            (from, to),
+            capacity.saturating_sub(capacity_of(&residual, from, to)),
+        );
+    }
+
+    (total, flow)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph_of(node_count: usize, edges: &[(usize, usize, usize)]) -> HashGraph {
+        let mut graph = HashGraph::with_capacity(64);
+        for i in 0..node_count {
+            graph.push_node(i as u64);
+        }
+        for &(from, to, weight) in edges {
+            graph.set_edge((from, to), weight);
+        }
+
+        graph
+    }
+
+    fn assert_valid_flow(
+        graph: &HashGraph,
+        flow: &HashGraph,
+        source: usize,
+        sink: usize,
+        value: usize,
+    ) {
+        let mut net = vec![0i64; graph.node_count()];
+
+        for (from, to) in graph.all_edge_pairs() {
+            let capacity = graph.get_edge(from, to).unwrap().weight;
+            let sent = flow.get_edge(from, to).map_or(0, |edge| edge.weight);
+            assert!(sent <= capacity);
+
+            net[from] -= sent as i64;
+            net[to] += sent as i64;
+        }
+
+        for (node, balance) in net.iter().enumerate() {
+            if node != source && node != sink {
+                assert_eq!(*balance, 0, "flow not conserved at {}", node);
+            }
+        }
+        assert_eq!(net[sink], value as i64);
+        assert_eq!(net[source], -(value as i64));
+    }
+
+    #[test]
+    fn textbook_test() {
+        // the usual six node network, 1 and 2 have edges both ways
+        let edges = [
+            (0, 1, 16),
+            (0, 2, 13),
+            (1, 2, 10),
+            (2, 1, 4),
+            (1, 3, 12),
+            (3, 2, 9),
+            (2, 4, 14),
+            (4, 3, 7),
+            (3, 5, 20),
+            (4, 5, 4),
+        ];
+        let graph = graph_of(6, &edges);
+
+        let (value, flow) = max_flow(&graph, 0, 5);
+        assert_eq!(value, 23);
+        assert_valid_flow(&graph, &flow, 0, 5, value);
+    }
+
+    #[test]
+    fn zero_capacity_test() {
+        let graph = graph_of(4, &[(0, 1, 0), (0, 2, 5), (2, 3, 3), (1, 3, 10), (3, 3, 7)]);
+
+        let (value, flow) = max_flow(&graph, 0, 3);
+        assert_eq!(value, 3);
+        assert_eq!(flow.get_edge(0, 1).unwrap().weight, 0);
+        assert_valid_flow(&graph, &flow, 0, 3, value);
+    }
+
+    #[test]
+    fn disconnected_test() {
+        let graph = graph_of(4, &[(0, 1, 5), (2, 3, 5), (3, 0, 5)]);
+
+        let (value, flow) = max_flow(&graph, 0, 3);
+        assert_eq!(value, 0);
+        assert!(flow
+            .all_edge_pairs()
+            .iter()
+            .all(|(from, to)| { flow.get_edge(*from, *to).unwrap().weight == 0 }));
+
+        assert_eq!(max_flow(&graph, 2, 2).0, 0);
+    }
+}
@@ -0,0 +1,165 @@
+use crate::bit::NodeBitSet;
+use crate::{assert_node, Graph};
+
+/// Nodes grouped by their hop count from the nearest of `roots`, layer 0 is
+/// the roots themselves. Every layer is in ascending order and unreachable
+/// nodes are left out.
+pub fn bfs_layers<V, W>(graph: &dyn Graph<V, W>, roots: &[usize]) -> Vec<Vec<usize>> {
+    let mut seen = NodeBitSet::with_capacity(graph.node_count());
+    let mut frontier = first_layer(graph.node_count(), roots, &mut seen);
+
+    let mut layers = Vec::new();
+    let mut next = NodeBitSet::with_capacity(graph.node_count());
+    let mut scratch = Vec::new();
+    while !frontier.is_empty() {
+        for &node in &frontier {
+            graph.outgoing_edges_into(node, &mut scratch);
+            for &out in &scratch {
+                if !seen.contains(out) {
+                    next.insert(out);
+                }
+            }
+        }
+
+        let layer = std::mem::replace(&mut frontier, next.iter().collect());
+        layers.push(layer);
+        next.clear();
+
+        for &node in &frontier {
+            seen.insert(node);
+        }
+    }
+
+    layers
+}
+
+/// `bfs_layers` with each frontier spread over the rayon pool. Threads OR
+/// what they find into a shared bitset of atomic words, which is read back
+/// in ascending order, so the layers are the same as the serial ones.
+#[cfg(feature = "rayon")]
+pub fn parallel_bfs_layers<V, W>(
+    graph: &(dyn Graph<V, W> + Sync),
+    roots: &[usize],
+) -> Vec<Vec<usize>> {
+    use rayon::prelude::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    const WORD_BITS: usize = usize::BITS as usize;
+
+    let mut seen = NodeBitSet::with_capacity(graph.node_count());
+    let mut frontier = first_layer(graph.node_count(), roots, &mut seen);
+
+    let mut layers = Vec::new();
+    let next: Vec<AtomicUsize> = (0..graph.node_count() / WORD_BITS + 1)
+        .map(|_| AtomicUsize::new(0))
+        .collect();
+    while !frontier.is_empty() {
+        frontier
+            .par_iter()
+            .for_each_init(Vec::new, |scratch, &node| {
+                graph.outgoing_edges_into(node, scratch);
+                for &out in scratch.iter() {
+                    if !seen.contains(out) {
+                        next[out / WORD_BITS].fetch_or(1 << (out % WORD_BITS), Ordering::Relaxed);
+                    }
+                }
+            });
+
+        // the pool has joined, every OR is visible here
+        let mut found = Vec::new();
+        for (i, word) in next.iter().enumerate() {
+            let mut bits = word.swap(0, Ordering::Relaxed);
+            while bits != 0 {
+                found.push(i * WORD_BITS + bits.trailing_zeros() as usize);
+                bits &= bits - 1;
+            }
+        }
+
+        layers.push(std::mem::replace(&mut frontier, found));
+        for &node in &frontier {
+            seen.insert(node);
+        }
+    }
+
+    layers
+}
+
+/// the roots, sorted and without repeats, marked as seen
+fn first_layer(count: usize, roots: &[usize], seen: &mut NodeBitSet) -> Vec<usize> {
+    for &root in roots {
+        assert_node(root, count);
+        seen.insert(root);
+    }
+
+    seen.iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bit::BitGraph;
+    use crate::hash::HashGraph;
+
+    #[test]
+    fn layers_test() {
+        // two roots, 5 is reached from both at the same depth, 7 from nothing
+        let edges = vec![(0, 2), (1, 3), (2, 5), (3, 5), (5, 6), (6, 0), (7, 1)];
+        let graph = BitGraph::from_edges(8, edges).unwrap();
+
+        assert_eq!(
+            bfs_layers(&graph, &[1, 0, 1]),
+            vec![vec![0, 1], vec![2, 3], vec![5], vec![6]]
+        );
+        assert_eq!(
+            bfs_layers(&graph, &[7]),
+            vec![
+                vec![7],
+                vec![1],
+                vec![3],
+                vec![5],
+                vec![6],
+                vec![0],
+                vec![2]
+            ]
+        );
+        assert!(bfs_layers(&graph, &[]).is_empty());
+
+        let mut loops = HashGraph::with_capacity(4);
+        loops.push_node(0);
+        loops.push_node(1);
+        loops.add_edge(0, 0);
+        assert_eq!(bfs_layers(&loops, &[0]), vec![vec![0]]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn root_out_of_range_test() {
+        bfs_layers(&BitGraph::from_edges(2, vec![(0, 1)]).unwrap(), &[2]);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn parallel_matches_serial_test() {
+        for &count in &[1, 64, 65, 500] {
+            let edges = (0..count).flat_map(move |from| {
+                (0..count)
+                    .filter(move |to| (from * 11 + to * 5) % 97 < 2)
+                    .map(move |to| (from, to))
+            });
+            let graph = BitGraph::from_edges(count, edges).unwrap();
+
+            for roots in &[
+                vec![0],
+                vec![count - 1, 0],
+                (0..count).step_by(37).collect(),
+            ] {
+                let serial = bfs_layers(&graph, roots);
+                assert_eq!(parallel_bfs_layers(&graph, roots), serial);
+            }
+
+            let mut reached: Vec<usize> = bfs_layers(&graph, &[0]).concat();
+            reached.sort_unstable();
+            assert_eq!(reached, crate::search::reachable_from(&graph, 0));
+        }
+    }
+}
@@ -0,0 +1,211 @@
+use crate::Graph;
+use std::collections::VecDeque;
+use std::fmt;
+
+/// the graph is not a tree under the root, `node` can be reached from the
+/// root but has `parents` incoming edges instead of one, or any for the root
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TreeError {
+    pub node: usize,
+    pub parents: usize,
+}
+
+impl fmt::Display for TreeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "node {} has {} incoming edges in the tree",
+            self.node, self.parents
+        )
+    }
+}
+
+impl std::error::Error for TreeError {}
+
+/// Lowest common ancestor queries on a tree stored as parent to child edges,
+/// binary lifting so building is O(n log n) and every query O(log n). Nodes
+/// that can not be reached from the root are left out of the index.
+pub struct LcaIndex {
+    root: usize,
+    /// `None` for nodes that are not under the root
+    depth: Vec<Option<usize>>,
+    /// `ancestors[k][node]` is the `2^k`th ancestor, the root is its own
+    ancestors: Vec<Vec<usize>>,
+}
+
+impl LcaIndex {
+    /// Walks the tree from `root`, fails if any node it reaches has a number
+    /// of incoming edges other than one, or the root has any. A self loop
+    /// counts as an incoming edge.
+    pub fn new<V, W>(graph: &dyn Graph<V, W>, root: usize) -> Result<Self, TreeError> {
+        let node_count = graph.node_count();
+        let mut depth = vec![None; node_count];
+        let mut parent: Vec<usize> = (0..node_count).collect();
+
+        depth[root] = Some(0);
+        let mut queue = VecDeque::new();
+        queue.push_back(root);
+
+        while let Some(node) = queue.pop_front() {
+            let parents = graph.incoming_edges_of(node).len();
+            if parents != (node != root) as usize {
+                return Err(TreeError { node, parents });
+            }
+
+            let child_depth = depth[node].map(|depth| depth + 1);
+            for child in graph.outgoing_edges_of(node) {
+                // every node checked here has one parent, so a node seen
+                // twice is caught when its in degree is checked
+                if depth[child].is_none() && child != root {
+                    depth[child] = child_depth;
+                    parent[child] = node;
+                    queue.push_back(child);
+                }
+            }
+        }
+
+        let levels = (usize::BITS - node_count.leading_zeros()).max(1) as usize;
+        let mut ancestors = vec![parent];
+        for k in 1..levels {
+            let below = &ancestors[k - 1];
+            let level = below.iter().map(|&middle| below[middle]).collect();
+            ancestors.push(level);
+        }
+
+        Ok(Self {
+            root,
+            depth,
+            ancestors,
+        })
+    }
+
+    pub fn root(&self) -> usize {
+        self.root
+    }
+
+    /// edges from the root down to `node`, `None` if it is not in the tree
+    pub fn depth(&self, node: usize) -> Option<usize> {
+        self.depth.get(node).copied().flatten()
+    }
+
+    /// deepest node that has both `u` and `v` below it or is one of them,
+    /// `None` if either is not in the tree
+    pub fn lca(&self, u: usize, v: usize) -> Option<usize> {
+        let (mut deep, mut shallow) = (u, v);
+        let (mut deep_depth, shallow_depth) = (self.depth(u)?, self.depth(v)?);
+        if deep_depth < shallow_depth {
+            std::mem::swap(&mut deep, &mut shallow);
+            deep_depth = shallow_depth;
+        }
+
+        let mut climb = deep_depth - self.depth(shallow)?;
+        let mut k = 0;
+        while climb > 0 {
+            if climb & 1 == 1 {
+                deep = self.ancestors[k][deep];
+            }
+            climb >>= 1;
+            k += 1;
+        }
+
+        if deep == shallow {
+            return Some(deep);
+        }
+
+        for level in self.ancestors.iter().rev() {
+            if level[deep] != level[shallow] {
+                deep = level[deep];
+                shallow = level[shallow];
+            }
+        }
+
+        Some(self.ancestors[0][deep])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bit::BitGraph;
+
+    /// heap layout binary tree of depth 5 under 0, plus a node 63 on its own
+    fn binary_tree() -> BitGraph {
+        let edges = (1..63).map(|child| ((child - 1) / 2, child));
+        BitGraph::from_edges(64, edges).unwrap()
+    }
+
+    #[test]
+    fn lca_test() {
+        let index = LcaIndex::new(&binary_tree(), 0).unwrap();
+
+        let expected = [
+            (0, 0, 0),
+            (1, 2, 0),
+            (3, 4, 1),
+            (3, 5, 0),
+            (7, 8, 3),
+            (7, 14, 0),
+            (31, 32, 15),
+            (31, 62, 0),
+            (33, 40, 1),
+            (44, 45, 10),
+            (35, 36, 17),
+            (44, 21, 21),
+            (21, 44, 21),
+            (47, 48, 23),
+            (59, 60, 29),
+            (59, 6, 6),
+            (62, 2, 2),
+            (9, 9, 9),
+        ];
+        for &(u, v, lca) in &expected {
+            assert_eq!(index.lca(u, v), Some(lca), "lca of {} and {}", u, v);
+        }
+
+        assert_eq!(index.depth(0), Some(0));
+        assert_eq!(index.depth(62), Some(5));
+        assert_eq!(index.root(), 0);
+    }
+
+    #[test]
+    fn subtree_root_test() {
+        let index = LcaIndex::new(&binary_tree(), 0).unwrap();
+        assert_eq!(index.lca(63, 0), None);
+        assert_eq!(index.lca(5, 63), None);
+        assert_eq!(index.depth(63), None);
+
+        // the root's parent is outside of the tree
+        assert_eq!(
+            LcaIndex::new(&binary_tree(), 2).err(),
+            Some(TreeError {
+                node: 2,
+                parents: 1
+            })
+        );
+    }
+
+    #[test]
+    fn not_a_tree_test() {
+        // 3 has two parents
+        let graph = BitGraph::from_edges(4, vec![(0, 1), (0, 2), (1, 3), (2, 3)]).unwrap();
+        let error = LcaIndex::new(&graph, 0).err().unwrap();
+        assert_eq!(
+            error,
+            TreeError {
+                node: 3,
+                parents: 2
+            }
+        );
+        assert_eq!(error.to_string(), "node 3 has 2 incoming edges in the tree");
+
+        // a cycle back to the root
+        let graph = BitGraph::from_edges(3, vec![(0, 1), (1, 2), (2, 0)]).unwrap();
+        assert_eq!(
+            LcaIndex::new(&graph, 0).err(),
+            Some(TreeError {
+                node: 0,
+                parents: 1
+            })
+        );
+    }
+}
@@ -1,13 +1,295 @@
 pub mod a_star;
+pub mod bellman_ford;
 pub mod bfs;
+pub mod bidirectional;
+pub mod bipartite;
+pub mod coloring;
+pub mod components;
+pub mod cycles;
+pub mod dag;
 pub mod dfs;
+pub mod dstar;
+pub mod flow;
+pub mod layers;
+pub mod lca;
+pub mod mst;
+pub mod order;
+pub mod scc;
+pub mod topo;
+mod union_find;
 
-use crate::Graph;
+pub use bellman_ford::{bellman_ford, NegativeCycle};
+
+use crate::{Graph, NodeIndex};
+use bfs::BFS;
 
 pub trait Pathfinder<V, W> {
     fn next(&mut self, graph: &dyn Graph<V, W>) -> Option<(usize, usize)>;
+    /// path from the root to `to_idx` including both ends, `None` if it can
+    /// not be reached
     fn path_to(&mut self, graph: &dyn Graph<V, W>, to_idx: usize) -> Option<Vec<usize>>;
     fn is_solved(&self) -> bool;
     fn set_solved(&mut self);
+    #[allow(clippy::wrong_self_convention)]
     fn from_index_of(&self, index: usize) -> usize;
+    /// cost of the best path to `index` found so far, `None` if the search
+    /// has not reached it or does not keep costs
+    fn cost_of(&self, _index: usize) -> Option<usize> {
+        None
+    }
+
+    /// `path_to` with `NodeIndex` ends
+    fn path_to_ix(&mut self, graph: &dyn Graph<V, W>, to: NodeIndex) -> Option<Vec<NodeIndex>> {
+        let path = self.path_to(graph, to.0)?;
+        Some(path.into_iter().map(NodeIndex).collect())
+    }
+
+    /// `from_index_of` with `NodeIndex` in and out
+    #[allow(clippy::wrong_self_convention)]
+    fn from_index_of_ix(&self, index: NodeIndex) -> NodeIndex {
+        NodeIndex(self.from_index_of(index.0))
+    }
+
+    /// `path_to` along with the cost of that path, `None` for a search that
+    /// does not keep costs
+    fn path_with_cost(
+        &mut self,
+        graph: &dyn Graph<V, W>,
+        to_idx: usize,
+    ) -> Option<(Vec<usize>, usize)> {
+        let path = self.path_to(graph, to_idx)?;
+        let cost = self.cost_of(to_idx)?;
+
+        Some((path, cost))
+    }
+
+    /// iterator over `next`, borrowing the search so it can be picked up
+    /// again afterwards
+    fn iter<'a>(&'a mut self, graph: &'a dyn Graph<V, W>) -> Iter<'a, &'a mut Self, V, W>
+    where
+        Self: Sized,
+    {
+        Iter {
+            search: self,
+            graph,
+        }
+    }
+
+    /// iterator over `next` that owns the search
+    fn into_iter(self, graph: &dyn Graph<V, W>) -> Iter<'_, Self, V, W>
+    where
+        Self: Sized,
+    {
+        Iter {
+            search: self,
+            graph,
+        }
+    }
+}
+
+impl<V, W, P: Pathfinder<V, W>> Pathfinder<V, W> for &mut P {
+    fn next(&mut self, graph: &dyn Graph<V, W>) -> Option<(usize, usize)> {
+        (**self).next(graph)
+    }
+
+    fn path_to(&mut self, graph: &dyn Graph<V, W>, to_idx: usize) -> Option<Vec<usize>> {
+        (**self).path_to(graph, to_idx)
+    }
+
+    fn is_solved(&self) -> bool {
+        (**self).is_solved()
+    }
+
+    fn set_solved(&mut self) {
+        (**self).set_solved()
+    }
+
+    fn from_index_of(&self, index: usize) -> usize {
+        (**self).from_index_of(index)
+    }
+
+    fn cost_of(&self, index: usize) -> Option<usize> {
+        (**self).cost_of(index)
+    }
+}
+
+/// Yields `(node, predecessor)` in the order the search visits nodes, the
+/// root is its own predecessor.
+pub struct Iter<'a, P, V, W> {
+    search: P,
+    graph: &'a dyn Graph<V, W>,
+}
+
+impl<'a, P: Pathfinder<V, W>, V, W> Iterator for Iter<'a, P, V, W> {
+    type Item = (usize, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.search.next(self.graph)
+    }
+}
+
+/// every node reachable from `root`, including `root`, in ascending order
+pub fn reachable_from<V, W>(graph: &dyn Graph<V, W>, root: usize) -> Vec<usize> {
+    let mut bfs = BFS::new(graph, root);
+    while bfs.next(graph).is_some() {}
+
+    bfs.discovered().collect()
+}
+
+/// nodes both `a` and `b` have an edge to, in ascending order, found by
+/// merging their `neighbors_sorted` lists
+pub fn common_neighbors<V, W>(graph: &dyn Graph<V, W>, a: usize, b: usize) -> Vec<usize> {
+    let (left, right) = (graph.neighbors_sorted(a), graph.neighbors_sorted(b));
+
+    let mut common = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < left.len() && j < right.len() {
+        match left[i].cmp(&right[j]) {
+            std::cmp::Ordering::Less => i += 1,
+            std::cmp::Ordering::Greater => j += 1,
+            std::cmp::Ordering::Equal => {
+                common.push(left[i]);
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+
+    common
+}
+
+/// 15 nodes valued by index, 0 reaches 1 to 5 and 8 and the rest are
+/// isolated, shared by the search tests
+#[cfg(test)]
+pub(crate) fn test_graph<G: Graph<u64, W>, W>(mut graph: G) -> G {
+    for i in 0..15 {
+        graph.push_node(i);
+    }
+
+    graph.add_edge(0, 2);
+    graph.add_edge(0, 1);
+    graph.add_edge(2, 4);
+    graph.add_edge(3, 8);
+    graph.add_edge(8, 5);
+    graph.add_edge(1, 3);
+    graph.add_edge(3, 5);
+    graph.add_edge(5, 0);
+    graph
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bit::BitGraph;
+    use crate::hash::HashGraph;
+
+    #[test]
+    fn reachable_from_test() {
+        let graph = test_graph(BitGraph::new());
+
+        assert_eq!(reachable_from(&graph, 0), vec![0, 1, 2, 3, 4, 5, 8]);
+        assert_eq!(reachable_from(&graph, 8), vec![0, 1, 2, 3, 4, 5, 8]);
+        assert_eq!(reachable_from(&graph, 4), vec![4]);
+        assert_eq!(reachable_from(&graph, 14), vec![14]);
+    }
+
+    #[test]
+    fn common_neighbors_test() {
+        let mut graph = HashGraph::with_capacity(8);
+        for i in 0..70 {
+            graph.push_node(i);
+        }
+        for &to in &[69, 3, 64, 1, 40] {
+            graph.add_edge(0, to);
+        }
+        for &to in &[40, 2, 69, 0, 3, 65] {
+            graph.add_edge(7, to);
+        }
+
+        assert_eq!(common_neighbors(&graph, 0, 7), vec![3, 40, 69]);
+        assert_eq!(common_neighbors(&graph, 7, 0), vec![3, 40, 69]);
+        assert!(common_neighbors(&graph, 0, 5).is_empty());
+
+        let bit = BitGraph::from_edges(70, graph.all_edge_pairs()).unwrap();
+        assert_eq!(common_neighbors(&bit, 0, 7), vec![3, 40, 69]);
+    }
+
+    /// four nodes, each with a self loop and no other edge
+    fn loops_only() -> HashGraph {
+        let mut graph = HashGraph::with_capacity(8);
+        for i in 0..4 {
+            graph.push_node(i);
+            graph.set_edge((i as usize, i as usize), 2);
+        }
+        graph
+    }
+
+    #[test]
+    fn self_loops_only_test() {
+        let graph = loops_only();
+
+        // searches do not queue a node again through its self loop
+        let mut bfs = BFS::new(&graph, 0);
+        assert_eq!(bfs.iter(&graph).collect::<Vec<_>>(), vec![(0, 0)]);
+        assert_eq!(bfs.visited_count(), 1);
+        assert_eq!(reachable_from(&graph, 2), vec![2]);
+        assert_eq!(BFS::new(&graph, 0).path_to(&graph, 0), Some(vec![0]));
+
+        let mut dfs = dfs::DFS::new(&graph, 1);
+        assert_eq!(dfs.iter(&graph).collect::<Vec<_>>(), vec![(1, 1)]);
+        assert_eq!(dfs.visited_count(), 1);
+
+        use dfs::{DfsEvent, DfsEvents, EdgeKind};
+        assert_eq!(
+            DfsEvents::new(&graph, 3).collect::<Vec<_>>(),
+            vec![
+                DfsEvent::Discover(3),
+                DfsEvent::Edge(3, 3, EdgeKind::Back),
+                DfsEvent::Finish(3)
+            ]
+        );
+
+        let mut bibfs = bidirectional::BiBFS::new(&graph);
+        assert_eq!(bibfs.path_to(&graph, 0, 0), Some(vec![0]));
+        assert_eq!(bibfs.path_to(&graph, 0, 1), None);
+
+        let mut astar = a_star::AStar::new(&graph, 0, 1, |_| 0).weighted();
+        assert_eq!(astar.path_to(&graph, 1), None);
+        let mut astar = a_star::AStar::new(&graph, 0, 0, |_| 0).weighted();
+        assert_eq!(astar.path_with_cost(&graph, 0), Some((vec![0], 0)));
+
+        let mut dstar = dstar::DStarLite::new(&graph, 0, 0, |_| 0).weighted();
+        assert_eq!(dstar.path_to(&graph, 0), Some(vec![0]));
+
+        // a self loop is a cycle of one node
+        assert_eq!(cycles::find_cycle(&graph), Some(vec![0]));
+        assert!(!cycles::is_dag(&graph));
+        assert!(topo::topological_sort(&graph).is_err());
+        assert!(dag::longest_path(&graph, None).is_err());
+        assert_eq!(bipartite::odd_cycle(&graph), Some(vec![0]));
+        assert!(bipartite::two_color(&graph).is_none());
+        assert!(lca::LcaIndex::new(&graph, 0).is_err());
+
+        // but does not join or split anything
+        assert_eq!(
+            scc::strongly_connected_components(&graph).len(),
+            graph.node_count()
+        );
+        let (condensed, _) = scc::condensation(&graph);
+        assert_eq!(condensed.self_loops().count(), 0);
+        assert_eq!(components::weak_components(&graph), vec![0, 1, 2, 3]);
+        assert_eq!(coloring::greedy_coloring(&graph, None), vec![0; 4]);
+        assert!(mst::kruskal(&graph).is_empty());
+        assert_eq!(flow::max_flow(&graph, 0, 1).0, 0);
+
+        let (distances, _) = bellman_ford(&graph, 0).unwrap();
+        assert_eq!(distances, vec![Some(0), None, None, None]);
+
+        let mut negative: HashGraph<u64, i64> = HashGraph::with_default_weight(4, 1);
+        negative.push_node(0);
+        negative.push_node(1);
+        negative.set_edge((0, 1), 3);
+        negative.set_edge((1, 1), -1);
+        assert_eq!(bellman_ford(&negative, 0), Err(NegativeCycle { node: 1 }));
+    }
 }
@@ -0,0 +1,103 @@
+use crate::search::union_find::UnionFind;
+use crate::Graph;
+
+/// Minimum spanning forest with Kruskal's algorithm, edges are treated as
+/// undirected. If both directions of an edge exist the cheaper one counts and
+/// self loops are ignored. Returns the chosen `(from, to, weight)` edges with
+/// `from < to`, cheapest first.
+pub fn kruskal<V>(graph: &dyn Graph<V, usize>) -> Vec<(usize, usize, usize)> {
+    let mut edges: Vec<(usize, usize, usize)> = graph
+        .all_edge_pairs()
+        .into_iter()
+        .filter(|(from, to)| from != to)
+        .filter_map(|(from, to)| {
+            let weight = graph.get_edge(from, to)?.weight;
+            Some((from.min(to), from.max(to), weight))
+        })
+        .collect();
+
+    // cheapest copy of each undirected edge first so dedup keeps it
+    edges.sort_unstable_by_key(|(from, to, weight)| (*from, *to, *weight));
+    edges.dedup_by_key(|(from, to, _)| (*from, *to));
+    edges.sort_unstable_by_key(|(from, to, weight)| (*weight, *from, *to));
+
+    let mut sets = UnionFind::new(graph.node_count());
+    edges
+        .into_iter()
+        .filter(|(from, to, _)| sets.union(*from, *to))
+        .collect()
+}
+
+/// sum of the weights of a `kruskal` result
+pub fn total_weight(edges: &[(usize, usize, usize)]) -> usize {
+    edges.iter().map(|(_, _, weight)| weight).sum()
+}
+
+/// Number of trees in a spanning forest over `node_count` nodes, 1 means the
+/// graph was connected and the forest is a spanning tree.
+pub fn tree_count(node_count: usize, edges: &[(usize, usize, usize)]) -> usize {
+    node_count - edges.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::HashGraph;
+
+    fn graph_of(node_count: usize, edges: &[(usize, usize, usize)]) -> HashGraph {
+        let mut graph = HashGraph::with_capacity(64);
+        for i in 0..node_count {
+            graph.push_node(i as u64);
+        }
+        for &(from, to, weight) in edges {
+            graph.set_edge((from, to), weight);
+        }
+
+        graph
+    }
+
+    #[test]
+    fn classic_test() {
+        // A..G from the usual textbook example, MST weight 39
+        let (a, b, c, d, e, f, g) = (0, 1, 2, 3, 4, 5, 6);
+        let graph = graph_of(
+            7,
+            &[
+                (a, b, 7),
+                (a, d, 5),
+                (b, c, 8),
+                (b, d, 9),
+                (b, e, 7),
+                (c, e, 5),
+                (d, e, 15),
+                (d, f, 6),
+                (e, f, 8),
+                (e, g, 9),
+                (f, g, 11),
+            ],
+        );
+
+        let tree = kruskal(&graph);
+        assert_eq!(total_weight(&tree), 39);
+        assert_eq!(tree.len(), 6);
+        assert_eq!(tree_count(7, &tree), 1);
+        assert_eq!(tree[0], (a, d, 5));
+    }
+
+    #[test]
+    fn disconnected_test() {
+        let graph = graph_of(6, &[(0, 1, 3), (1, 2, 1), (2, 0, 1), (3, 4, 2), (4, 4, 0)]);
+
+        let forest = kruskal(&graph);
+        assert_eq!(forest, vec![(0, 2, 1), (1, 2, 1), (3, 4, 2)]);
+        assert_eq!(total_weight(&forest), 4);
+        // {0, 1, 2}, {3, 4} and {5}
+        assert_eq!(tree_count(6, &forest), 3);
+    }
+
+    #[test]
+    fn both_directions_test() {
+        let graph = graph_of(2, &[(0, 1, 9), (1, 0, 4)]);
+        assert_eq!(kruskal(&graph), vec![(0, 1, 4)]);
+    }
+}
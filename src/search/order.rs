@@ -0,0 +1,162 @@
+use crate::bit::NodeBitSet;
+use crate::{assert_node, Graph};
+use std::collections::VecDeque;
+
+/// New index of every node when they are numbered in breadth first order
+/// from `root` along outgoing edges, ready for `BitGraph::permute`. Nodes
+/// `root` can not reach follow, each unreached node starting a new search
+/// from the lowest index.
+pub fn bfs_order<V, W>(graph: &dyn Graph<V, W>, root: usize) -> Vec<usize> {
+    let node_count = graph.node_count();
+    assert_node(root, node_count);
+
+    let mut seen = NodeBitSet::with_capacity(node_count);
+    let mut queue = VecDeque::new();
+    let mut sequence = Vec::with_capacity(node_count);
+    let mut edges = Vec::new();
+    for root in std::iter::once(root).chain(0..node_count) {
+        if !seen.insert(root) {
+            continue;
+        }
+
+        queue.push_back(root);
+        while let Some(node) = queue.pop_front() {
+            sequence.push(node);
+            graph.outgoing_edges_into(node, &mut edges);
+            for &out in &edges {
+                if seen.insert(out) {
+                    queue.push_back(out);
+                }
+            }
+        }
+    }
+
+    into_permutation(&sequence)
+}
+
+/// New index of every node under reverse Cuthill–McKee, ready for
+/// `BitGraph::permute`. Edges are taken as undirected. Numbering nodes this
+/// way keeps edges close to the diagonal of the matrix, so neighbours tend
+/// to share words and searches touch less memory.
+///
+/// Each component is searched breadth first from a node of lowest degree,
+/// visiting neighbours lowest degree first, and the order of the whole
+/// search is reversed at the end. Ties go to the lower index.
+pub fn rcm_order<V, W>(graph: &dyn Graph<V, W>) -> Vec<usize> {
+    let node_count = graph.node_count();
+
+    let neighbours: Vec<Vec<usize>> = (0..node_count)
+        .map(|node| {
+            let mut neighbours = graph.outgoing_edges_of(node);
+            neighbours.extend(graph.incoming_edges_of(node));
+            neighbours.sort_unstable();
+            neighbours.dedup();
+            neighbours.retain(|&other| other != node);
+            neighbours
+        })
+        .collect();
+    let degree = |node: usize| neighbours[node].len();
+
+    let mut by_degree: Vec<usize> = (0..node_count).collect();
+    by_degree.sort_by_key(|&node| (degree(node), node));
+
+    let mut seen = NodeBitSet::with_capacity(node_count);
+    let mut queue = VecDeque::new();
+    let mut sequence = Vec::with_capacity(node_count);
+    let mut next = Vec::new();
+    for &root in &by_degree {
+        if !seen.insert(root) {
+            continue;
+        }
+
+        queue.push_back(root);
+        while let Some(node) = queue.pop_front() {
+            sequence.push(node);
+
+            next.clear();
+            next.extend(
+                neighbours[node]
+                    .iter()
+                    .copied()
+                    .filter(|&other| seen.insert(other)),
+            );
+            next.sort_by_key(|&other| (degree(other), other));
+            queue.extend(&next);
+        }
+    }
+
+    sequence.reverse();
+    into_permutation(&sequence)
+}
+
+/// `sequence[i]` is the node that goes to index `i`, turned around
+fn into_permutation(sequence: &[usize]) -> Vec<usize> {
+    let mut perm = vec![0; sequence.len()];
+    for (new, &old) in sequence.iter().enumerate() {
+        perm[old] = new;
+    }
+    perm
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bit::BitGraph;
+
+    /// widest gap between the ends of an edge once renumbered by `perm`
+    fn bandwidth(graph: &BitGraph, perm: &[usize]) -> usize {
+        graph
+            .all_edge_pairs()
+            .into_iter()
+            .map(|(from, to)| (perm[from] as isize - perm[to] as isize).unsigned_abs())
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn is_permutation(perm: &[usize]) -> bool {
+        let mut sorted = perm.to_vec();
+        sorted.sort_unstable();
+        sorted.into_iter().eq(0..perm.len())
+    }
+
+    #[test]
+    fn bfs_order_test() {
+        // 0 -> 3 -> {1, 4}, 2 and 5 hang off nothing reachable
+        let graph = BitGraph::from_edges(6, vec![(0, 3), (3, 4), (3, 1), (5, 2)]).unwrap();
+
+        assert_eq!(bfs_order(&graph, 0), vec![0, 2, 4, 1, 3, 5]);
+        // from 3 first, then 0, 2 and 5 as new roots
+        assert_eq!(bfs_order(&graph, 3), vec![3, 1, 4, 0, 2, 5]);
+    }
+
+    #[test]
+    fn rcm_order_test() {
+        // a path 0 - 1 - ... - 99 scattered over the indices
+        let count = 100;
+        let label = |i: usize| i * 37 % count;
+        let edges = (0..count - 1).map(|i| (label(i), label(i + 1)));
+        let mut graph = BitGraph::from_edges(count, edges).unwrap();
+        graph.symmetrize();
+
+        let identity: Vec<usize> = (0..count).collect();
+        assert!(bandwidth(&graph, &identity) > 50);
+
+        let perm = rcm_order(&graph);
+        assert!(is_permutation(&perm));
+        assert_eq!(bandwidth(&graph, &perm), 1);
+
+        let permuted = graph.permute(&perm).unwrap();
+        assert_eq!(bandwidth(&permuted, &identity), 1);
+    }
+
+    #[test]
+    fn rcm_components_test() {
+        // a star around 4 and a separate edge, directions do not matter
+        let graph = BitGraph::from_edges(7, vec![(4, 0), (1, 4), (4, 5), (2, 6)]).unwrap();
+
+        let perm = rcm_order(&graph);
+        assert!(is_permutation(&perm));
+        // visited 3 on its own, then 0, 4, 1, 5, then 2, 6, and reversed
+        assert_eq!(perm, vec![5, 3, 1, 6, 4, 2, 0]);
+    }
+}
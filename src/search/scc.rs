@@ -0,0 +1,197 @@
+use crate::bit::{BitGraph, NodeBitSet};
+use crate::Graph;
+
+/// Strongly connected components with Tarjan's algorithm, run with an
+/// explicit stack so deep graphs can not overflow the call stack.
+///
+/// Components come out in reverse topological order of the condensation, no
+/// edge goes from a component to one listed after it. Self loops do not
+/// change the components.
+pub fn strongly_connected_components<V, W>(graph: &dyn Graph<V, W>) -> Vec<Vec<usize>> {
+    let node_count = graph.node_count();
+
+    let mut index = vec![usize::MAX; node_count];
+    let mut low_link = vec![usize::MAX; node_count];
+    let mut on_stack = NodeBitSet::with_capacity(node_count);
+    let mut stack = Vec::new();
+    let mut next_index = 0;
+
+    // (node, its outgoing edges, next edge to look at)
+    let mut call_stack: Vec<(usize, Vec<usize>, usize)> = Vec::new();
+    let mut components = Vec::new();
+
+    for root in 0..node_count {
+        if index[root] != usize::MAX {
+            continue;
+        }
+
+        index[root] = next_index;
+        low_link[root] = next_index;
+        next_index += 1;
+        stack.push(root);
+        on_stack.insert(root);
+        call_stack.push((root, graph.outgoing_edges_of(root), 0));
+
+        while let Some((node, edges, position)) = call_stack.last_mut() {
+            let node = *node;
+
+            if let Some(&next) = edges.get(*position) {
+                *position += 1;
+
+                if index[next] == usize::MAX {
+                    index[next] = next_index;
+                    low_link[next] = next_index;
+                    next_index += 1;
+                    stack.push(next);
+                    on_stack.insert(next);
+                    call_stack.push((next, graph.outgoing_edges_of(next), 0));
+                } else if on_stack.contains(next) {
+                    low_link[node] = low_link[node].min(index[next]);
+                }
+
+                continue;
+            }
+
+            call_stack.pop();
+
+            if low_link[node] == index[node] {
+                let mut component = Vec::new();
+                while let Some(member) = stack.pop() {
+                    on_stack.remove(member);
+                    component.push(member);
+                    if member == node {
+                        break;
+                    }
+                }
+                components.push(component);
+            }
+
+            if let Some((parent, _, _)) = call_stack.last() {
+                low_link[*parent] = low_link[*parent].min(low_link[node]);
+            }
+        }
+    }
+
+    components
+}
+
+/// Collapses every strongly connected component into one node. Returns the
+/// component DAG, where node `i` is the `i`th component of
+/// `strongly_connected_components` and has value `i`, along with the
+/// component of every node of `graph`. Edges inside a component, self loops
+/// included, are left out so the DAG has no self loops.
+pub fn condensation<V, W>(graph: &dyn Graph<V, W>) -> (BitGraph, Vec<usize>) {
+    let components = strongly_connected_components(graph);
+
+    let mut component_of = vec![0; graph.node_count()];
+    for (component, members) in components.iter().enumerate() {
+        for &member in members {
+            component_of[member] = component;
+        }
+    }
+
+    let edges = graph
+        .all_edge_pairs()
+        .into_iter()
+        .map(|(from, to)| (component_of[from], component_of[to]))
+        .filter(|(from, to)| from != to);
+
+    let dag = BitGraph::from_nodes_and_edges(0..components.len() as u64, edges)
+        .expect("component indices are under the component count");
+
+    (dag, component_of)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::topo::topological_sort;
+
+    fn sorted(mut components: Vec<Vec<usize>>) -> Vec<Vec<usize>> {
+        components
+            .iter_mut()
+            .for_each(|component| component.sort_unstable());
+        components
+    }
+
+    /// no edge may point at a component listed later
+    fn assert_reverse_topological<V, W>(graph: &dyn Graph<V, W>, components: &[Vec<usize>]) {
+        let mut component_of = vec![0; graph.node_count()];
+        for (component, members) in components.iter().enumerate() {
+            for &member in members {
+                component_of[member] = component;
+            }
+        }
+
+        for (from, to) in graph.all_edge_pairs() {
+            assert!(component_of[to] <= component_of[from]);
+        }
+    }
+
+    #[test]
+    fn nested_cycles_test() {
+        let edges = [
+            (0, 1),
+            (1, 2),
+            (2, 0),
+            (1, 3),
+            (3, 4),
+            (4, 1),
+            (4, 5),
+            (5, 6),
+            (6, 5),
+            (6, 7),
+        ];
+        let graph = BitGraph::from_edges(9, edges.iter().copied()).unwrap();
+
+        let components = strongly_connected_components(&graph);
+        assert_reverse_topological(&graph, &components);
+
+        let mut components = sorted(components);
+        components.sort();
+        assert_eq!(
+            components,
+            vec![vec![0, 1, 2, 3, 4], vec![5, 6], vec![7], vec![8]]
+        );
+    }
+
+    #[test]
+    fn dag_test() {
+        let edges = [(0, 1), (0, 2), (1, 3), (2, 3), (3, 4), (5, 4)];
+        let graph = BitGraph::from_edges(6, edges.iter().copied()).unwrap();
+
+        let components = strongly_connected_components(&graph);
+        assert_eq!(components.len(), 6);
+        assert!(components.iter().all(|component| component.len() == 1));
+        assert_reverse_topological(&graph, &components);
+    }
+
+    #[test]
+    fn deep_test() {
+        // one long cycle, deep enough to overflow a recursive version
+        let count = 20_000;
+        let edges = (0..count).map(|i| (i, (i + 1) % count));
+        let graph = BitGraph::from_edges(count, edges).unwrap();
+
+        let components = strongly_connected_components(&graph);
+        assert_eq!(components.len(), 1);
+        assert_eq!(components[0].len(), count);
+    }
+
+    #[test]
+    fn condensation_test() {
+        let edges = [(0, 1), (1, 0), (1, 2), (2, 3), (3, 2), (0, 4), (4, 3)];
+        let graph = BitGraph::from_edges(5, edges.iter().copied()).unwrap();
+
+        let (dag, component_of) = condensation(&graph);
+        assert_eq!(dag.node_count(), 3);
+        assert_eq!(component_of[0], component_of[1]);
+        assert_eq!(component_of[2], component_of[3]);
+
+        let (a, b, c) = (component_of[0], component_of[4], component_of[2]);
+        let mut expected = vec![(a, b), (a, c), (b, c)];
+        expected.sort_unstable();
+        assert_eq!(dag.all_edge_pairs(), expected);
+        assert!(topological_sort(&dag).is_ok());
+    }
+}
@@ -0,0 +1,175 @@
+use crate::Graph;
+use std::collections::VecDeque;
+use std::fmt;
+
+/// the graph has a cycle, `node` is on it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CycleError {
+    pub node: usize,
+}
+
+impl fmt::Display for CycleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "graph has a cycle through node {}", self.node)
+    }
+}
+
+impl std::error::Error for CycleError {}
+
+/// Orders the nodes so every edge goes from an earlier node to a later one,
+/// Kahn's algorithm. Nodes without edges are included, a self loop is a
+/// cycle.
+pub fn topological_sort<V, W>(graph: &dyn Graph<V, W>) -> Result<Vec<usize>, CycleError> {
+    TopoOrder::new(graph).collect()
+}
+
+/// Lazy topological order, yields `Ok(node)` until every node is out and if
+/// a cycle stops that early a single `Err` last.
+pub struct TopoOrder<'a, V, W> {
+    graph: &'a dyn Graph<V, W>,
+
+    /// in degree among the nodes not yielded yet
+    in_degree: Vec<usize>,
+    ready: VecDeque<usize>,
+
+    yielded: usize,
+    done: bool,
+}
+
+impl<'a, V, W> TopoOrder<'a, V, W> {
+    pub fn new(graph: &'a dyn Graph<V, W>) -> Self {
+        let in_degree: Vec<usize> = (0..graph.node_count())
+            .map(|node| graph.incoming_edges_of(node).len())
+            .collect();
+
+        let ready = in_degree
+            .iter()
+            .enumerate()
+            .filter(|(_, degree)| **degree == 0)
+            .map(|(node, _)| node)
+            .collect();
+
+        Self {
+            graph,
+            in_degree,
+            ready,
+            yielded: 0,
+            done: false,
+        }
+    }
+
+    /// Every node left has an incoming edge from another node left, so
+    /// walking those edges backwards has to come round to a node twice.
+    fn find_cycle_node(&self) -> usize {
+        let mut node = (0..self.in_degree.len())
+            .find(|node| self.in_degree[*node] > 0)
+            .expect("a node is left with an incoming edge");
+
+        for _ in 0..self.in_degree.len() {
+            node = self
+                .graph
+                .incoming_edges_of(node)
+                .into_iter()
+                .find(|from| self.in_degree[*from] > 0)
+                .unwrap_or(node);
+        }
+
+        node
+    }
+}
+
+impl<'a, V, W> Iterator for TopoOrder<'a, V, W> {
+    type Item = Result<usize, CycleError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let node = match self.ready.pop_front() {
+            Some(node) => node,
+            None => {
+                self.done = true;
+                if self.yielded == self.in_degree.len() {
+                    return None;
+                }
+
+                return Some(Err(CycleError {
+                    node: self.find_cycle_node(),
+                }));
+            }
+        };
+
+        for out in self.graph.outgoing_edges_of(node) {
+            self.in_degree[out] -= 1;
+            if self.in_degree[out] == 0 {
+                self.ready.push_back(out);
+            }
+        }
+
+        self.yielded += 1;
+        Some(Ok(node))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bit::BitGraph;
+
+    fn graph_of(node_count: usize, edges: &[(usize, usize)]) -> BitGraph {
+        BitGraph::from_edges(node_count, edges.iter().copied()).unwrap()
+    }
+
+    #[test]
+    fn dag_test() {
+        let edges = [(5, 2), (5, 0), (4, 0), (4, 1), (2, 3), (3, 1), (6, 0)];
+        let graph = graph_of(8, &edges);
+
+        let order = topological_sort(&graph).unwrap();
+        assert_eq!(order.len(), 8);
+
+        let mut position = [0; 8];
+        for (i, node) in order.iter().enumerate() {
+            position[*node] = i;
+        }
+        for (from, to) in graph.all_edge_pairs() {
+            assert!(position[from] < position[to]);
+        }
+        // 7 has no edges at all
+        assert!(order.contains(&7));
+    }
+
+    #[test]
+    fn cycle_test() {
+        let graph = graph_of(5, &[(3, 0), (0, 1), (1, 2), (2, 0), (2, 4)]);
+
+        let error = topological_sort(&graph).unwrap_err();
+        assert!([0, 1, 2].contains(&error.node));
+        assert_eq!(
+            error.to_string(),
+            format!("graph has a cycle through node {}", error.node)
+        );
+
+        let self_loop = graph_of(2, &[(0, 1), (1, 1)]);
+        assert_eq!(topological_sort(&self_loop), Err(CycleError { node: 1 }));
+    }
+
+    #[test]
+    fn lazy_test() {
+        let graph = graph_of(4, &[(0, 1), (1, 2), (2, 3)]);
+        let mut order = TopoOrder::new(&graph);
+
+        assert_eq!(order.next(), Some(Ok(0)));
+        assert_eq!(order.next(), Some(Ok(1)));
+        assert_eq!(order.by_ref().count(), 2);
+        assert_eq!(order.next(), None);
+
+        // the part before the cycle still comes out
+        let graph = graph_of(4, &[(0, 1), (1, 2), (2, 3), (3, 1)]);
+        let items: Vec<_> = TopoOrder::new(&graph).collect();
+        assert_eq!(items[0], Ok(0));
+        assert_eq!(items.len(), 2);
+        assert!(items[1].is_err());
+    }
+}
@@ -0,0 +1,62 @@
+/// Disjoint sets over `0..size` with union by size and path halving.
+pub(crate) struct UnionFind {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+}
+
+impl UnionFind {
+    pub(crate) fn new(size: usize) -> Self {
+        Self {
+            parent: (0..size).collect(),
+            size: vec![1; size],
+        }
+    }
+
+    /// representative of the set holding `node`
+    pub(crate) fn find(&mut self, mut node: usize) -> usize {
+        while self.parent[node] != node {
+            self.parent[node] = self.parent[self.parent[node]];
+            node = self.parent[node];
+        }
+
+        node
+    }
+
+    /// merges the sets of `a` and `b`, returns false if they already were one
+    pub(crate) fn union(&mut self, a: usize, b: usize) -> bool {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+
+        if self.size[a] < self.size[b] {
+            std::mem::swap(&mut a, &mut b);
+        }
+        self.parent[b] = a;
+        self.size[a] += self.size[b];
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn union_find_test() {
+        let mut sets = UnionFind::new(8);
+
+        assert!(sets.union(0, 1));
+        assert!(sets.union(2, 3));
+        assert!(sets.union(1, 3));
+        assert!(!sets.union(0, 2));
+        assert!(sets.union(5, 6));
+
+        assert_eq!(sets.find(0), sets.find(3));
+        assert_eq!(sets.find(5), sets.find(6));
+        assert_ne!(sets.find(0), sets.find(5));
+        assert_eq!(sets.find(7), 7);
+        assert_eq!(sets.find(4), 4);
+    }
+}
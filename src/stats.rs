@@ -0,0 +1,301 @@
+use crate::Graph;
+
+/// Degree summary of a graph, see `stats`. Means and density are 0 for a
+/// graph without nodes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GraphStats {
+    pub node_count: usize,
+    pub edge_count: usize,
+
+    pub min_out_degree: usize,
+    pub max_out_degree: usize,
+    pub mean_out_degree: f64,
+
+    pub min_in_degree: usize,
+    pub max_in_degree: usize,
+    pub mean_in_degree: f64,
+
+    /// nodes without any edge, a self loop counts as an edge
+    pub isolated: usize,
+    pub self_loops: usize,
+    /// fraction of all node pairs, self loops included, that have an edge
+    pub density: f64,
+}
+
+/// Everything in `GraphStats` in one pass over the nodes, only asking each
+/// one for its degrees and self loop. `BitGraph` answers those with a
+/// popcount of a row and a single bit, no neighbour lists are built.
+pub fn stats<T, W>(graph: &dyn Graph<T, W>) -> GraphStats {
+    (0..graph.node_count())
+        .map(|node| Tally::of(graph, node))
+        .fold(Tally::EMPTY, Tally::merge)
+        .finish(graph.node_count())
+}
+
+/// `stats` with the nodes spread over the rayon pool. Only integer counts
+/// are gathered in parallel, so the result is the same as `stats` down to
+/// the last bit of the means.
+#[cfg(feature = "rayon")]
+pub fn par_stats<T, W>(graph: &(dyn Graph<T, W> + Sync)) -> GraphStats {
+    use rayon::prelude::*;
+
+    (0..graph.node_count())
+        .into_par_iter()
+        .map(|node| Tally::of(graph, node))
+        .reduce(|| Tally::EMPTY, Tally::merge)
+        .finish(graph.node_count())
+}
+
+/// `histogram[d]` is the number of nodes with `d` edges, in and out added
+/// together so a self loop counts twice. Ends at the highest degree.
+pub fn degree_histogram<T, W>(graph: &dyn Graph<T, W>) -> Vec<usize> {
+    let mut histogram = Vec::new();
+    for node in 0..graph.node_count() {
+        count_degree(
+            &mut histogram,
+            graph.out_degree(node) + graph.in_degree(node),
+        );
+    }
+
+    histogram
+}
+
+/// `degree_histogram` with the nodes spread over the rayon pool
+#[cfg(feature = "rayon")]
+pub fn par_degree_histogram<T, W>(graph: &(dyn Graph<T, W> + Sync)) -> Vec<usize> {
+    use rayon::prelude::*;
+
+    (0..graph.node_count())
+        .into_par_iter()
+        .fold(Vec::new, |mut histogram, node| {
+            count_degree(
+                &mut histogram,
+                graph.out_degree(node) + graph.in_degree(node),
+            );
+            histogram
+        })
+        .reduce(Vec::new, |mut histogram, other| {
+            if histogram.len() < other.len() {
+                histogram.resize(other.len(), 0);
+            }
+            histogram
+                .iter_mut()
+                .zip(other)
+                .for_each(|(count, other)| *count += other);
+            histogram
+        })
+}
+
+fn count_degree(histogram: &mut Vec<usize>, degree: usize) {
+    if histogram.len() <= degree {
+        histogram.resize(degree + 1, 0);
+    }
+    histogram[degree] += 1;
+}
+
+/// the integer parts of `GraphStats` for some set of nodes, merging is
+/// order independent
+#[derive(Clone, Copy)]
+struct Tally {
+    out_total: usize,
+    in_total: usize,
+
+    min_out: usize,
+    max_out: usize,
+    min_in: usize,
+    max_in: usize,
+
+    isolated: usize,
+    self_loops: usize,
+}
+
+impl Tally {
+    const EMPTY: Tally = Tally {
+        out_total: 0,
+        in_total: 0,
+
+        min_out: usize::MAX,
+        max_out: 0,
+        min_in: usize::MAX,
+        max_in: 0,
+
+        isolated: 0,
+        self_loops: 0,
+    };
+
+    fn of<T, W>(graph: &dyn Graph<T, W>, node: usize) -> Tally {
+        let out_degree = graph.out_degree(node);
+        let in_degree = graph.in_degree(node);
+        let isolated = out_degree == 0 && in_degree == 0;
+
+        Tally {
+            out_total: out_degree,
+            in_total: in_degree,
+
+            min_out: out_degree,
+            max_out: out_degree,
+            min_in: in_degree,
+            max_in: in_degree,
+
+            isolated: isolated as usize,
+            self_loops: (!isolated && graph.has_self_loop(node)) as usize,
+        }
+    }
+
+    fn merge(self, other: Tally) -> Tally {
+        Tally {
+            out_total: self.out_total + other.out_total,
+            in_total: self.in_total + other.in_total,
+
+            min_out: self.min_out.min(other.min_out),
+            max_out: self.max_out.max(other.max_out),
+            min_in: self.min_in.min(other.min_in),
+            max_in: self.max_in.max(other.max_in),
+
+            isolated: self.isolated + other.isolated,
+            self_loops: self.self_loops + other.self_loops,
+        }
+    }
+
+    fn finish(self, node_count: usize) -> GraphStats {
+        let mut stats = GraphStats {
+            node_count,
+            edge_count: self.out_total,
+
+            min_out_degree: self.min_out,
+            max_out_degree: self.max_out,
+            mean_out_degree: 0.0,
+
+            min_in_degree: self.min_in,
+            max_in_degree: self.max_in,
+            mean_in_degree: 0.0,
+
+            isolated: self.isolated,
+            self_loops: self.self_loops,
+            density: 0.0,
+        };
+
+        if node_count == 0 {
+            stats.min_out_degree = 0;
+            stats.min_in_degree = 0;
+        } else {
+            let count = node_count as f64;
+            stats.mean_out_degree = stats.edge_count as f64 / count;
+            stats.mean_in_degree = self.in_total as f64 / count;
+            stats.density = stats.edge_count as f64 / (count * count);
+        }
+
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bit::BitGraph;
+    use crate::hash::HashGraph;
+    use crate::list::ListGraph;
+
+    /// 0 points at 1 to 5, 6 only has a self loop and 7 nothing
+    fn star(graph: &mut dyn Graph<u64, usize>) {
+        for i in 0..8 {
+            graph.push_node(i);
+        }
+        for i in 1..=5 {
+            graph.add_edge(0, i);
+        }
+        graph.add_edge(6, 6);
+    }
+
+    #[test]
+    fn star_test() {
+        let mut hash = HashGraph::new();
+        star(&mut hash);
+        let mut list = ListGraph::new();
+        star(&mut list);
+        let bit =
+            BitGraph::from_edges(8, vec![(0, 1), (0, 2), (0, 3), (0, 4), (0, 5), (6, 6)]).unwrap();
+
+        let expected = GraphStats {
+            node_count: 8,
+            edge_count: 6,
+
+            min_out_degree: 0,
+            max_out_degree: 5,
+            mean_out_degree: 0.75,
+
+            min_in_degree: 0,
+            max_in_degree: 1,
+            mean_in_degree: 0.75,
+
+            isolated: 1,
+            self_loops: 1,
+            density: 6.0 / 64.0,
+        };
+
+        assert_eq!(stats(&hash), expected);
+        assert_eq!(stats(&list), expected);
+        assert_eq!(stats(&bit), expected);
+
+        // 7, then 1 to 5, then 6, then 0
+        let histogram = vec![1, 5, 1, 0, 0, 1];
+        assert_eq!(degree_histogram(&hash), histogram);
+        assert_eq!(degree_histogram(&bit), histogram);
+    }
+
+    #[test]
+    fn complete_test() {
+        // rows straddle words
+        let count = 70;
+        let edges = (0..count).flat_map(|from| {
+            (0..count)
+                .filter(move |to| *to != from)
+                .map(move |to| (from, to))
+        });
+        let bit = BitGraph::from_edges(count, edges).unwrap();
+
+        let stats = stats(&bit);
+        assert_eq!(stats.edge_count, 70 * 69);
+        assert_eq!((stats.min_out_degree, stats.max_out_degree), (69, 69));
+        assert_eq!((stats.min_in_degree, stats.max_in_degree), (69, 69));
+        assert_eq!(stats.mean_in_degree, 69.0);
+        assert_eq!((stats.isolated, stats.self_loops), (0, 0));
+        assert_eq!(stats.density, 69.0 / 70.0);
+
+        let mut histogram = vec![0; 139];
+        histogram[138] = 70;
+        assert_eq!(degree_histogram(&bit), histogram);
+
+        let empty = super::stats(&BitGraph::new());
+        assert_eq!((empty.min_out_degree, empty.density), (0, 0.0));
+        assert!(degree_histogram(&HashGraph::new()).is_empty());
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn parallel_matches_serial_test() {
+        let mut hash = HashGraph::new();
+        star(&mut hash);
+        assert_eq!(par_stats(&hash), stats(&hash));
+        assert_eq!(par_degree_histogram(&hash), degree_histogram(&hash));
+
+        for &count in &[0, 1, 65, 300] {
+            let edges = (0..count).flat_map(move |from| {
+                (0..count)
+                    .filter(move |to| (from * 7 + to * 13) % 29 < from % 5)
+                    .map(move |to| (from, to))
+            });
+            let bit = BitGraph::from_edges(count, edges).unwrap();
+
+            let serial = stats(&bit);
+            let parallel = par_stats(&bit);
+            assert_eq!(parallel, serial);
+            assert_eq!(
+                parallel.mean_in_degree.to_bits(),
+                serial.mean_in_degree.to_bits()
+            );
+            assert_eq!(parallel.density.to_bits(), serial.density.to_bits());
+            assert_eq!(par_degree_histogram(&bit), degree_histogram(&bit));
+        }
+    }
+}
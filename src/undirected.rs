@@ -0,0 +1,291 @@
+use crate::{EdgeMeta, Graph};
+
+/// Wraps any graph so every edge goes both ways, each mutation is mirrored
+/// onto the reverse edge. A self loop is stored once.
+pub struct Undirected<G> {
+    graph: G,
+}
+
+impl<G> Undirected<G> {
+    /// Wraps `graph`, adding the reverse of every edge that only goes one
+    /// way with the same weight.
+    pub fn new<T, W: Clone>(mut graph: G) -> Self
+    where
+        G: Graph<T, W>,
+    {
+        graph.symmetrize();
+
+        Self { graph }
+    }
+
+    /// the wrapped graph, with both directions of every edge
+    pub fn inner(&self) -> &G {
+        &self.graph
+    }
+
+    pub fn into_inner(self) -> G {
+        self.graph
+    }
+
+    /// every node sharing an edge with `node_index`, in the order the
+    /// wrapped graph lists its outgoing edges
+    pub fn neighbors_of<T, W>(&self, node_index: usize) -> Vec<usize>
+    where
+        G: Graph<T, W>,
+    {
+        self.graph.outgoing_edges_of(node_index)
+    }
+
+    /// number of undirected edges, half the directed count with self loops
+    /// counted once
+    pub fn edge_count<T, W>(&self) -> usize
+    where
+        G: Graph<T, W>,
+    {
+        self.graph
+            .all_edge_pairs()
+            .into_iter()
+            .filter(|(from, to)| from <= to)
+            .count()
+    }
+}
+
+impl<T, W: Clone, G: Graph<T, W>> Graph<T, W> for Undirected<G> {
+    /// returns `true` if the edge was already there
+    fn add_edge(&mut self, from: usize, to: usize) -> bool {
+        let had_edge = self.graph.add_edge(from, to);
+        if from != to {
+            self.graph.add_edge(to, from);
+        }
+
+        had_edge
+    }
+
+    fn set_edge(&mut self, from_to: (usize, usize), weight: W) -> bool {
+        let (from, to) = from_to;
+        if from != to {
+            self.graph.set_edge((to, from), weight.clone());
+        }
+
+        self.graph.set_edge(from_to, weight)
+    }
+
+    fn remove_edge(&mut self, from: usize, to: usize) -> bool {
+        let had_edge = self.graph.remove_edge(from, to);
+        if from != to {
+            self.graph.remove_edge(to, from);
+        }
+
+        had_edge
+    }
+
+    fn clear_edges_of(&mut self, node_index: usize) {
+        self.graph.clear_edges_of(node_index);
+    }
+
+    fn clear_edges(&mut self) {
+        self.graph.clear_edges();
+    }
+
+    fn clear(&mut self) {
+        self.graph.clear();
+    }
+
+    fn has_edge(&self, from: usize, to: usize) -> bool {
+        self.graph.has_edge(from, to)
+    }
+
+    fn get_edge(&self, from: usize, to: usize) -> Option<EdgeMeta<W>> {
+        self.graph.get_edge(from, to)
+    }
+
+    /// same as `neighbors_of`
+    fn outgoing_edges_of(&self, node_index: usize) -> Vec<usize> {
+        self.graph.outgoing_edges_of(node_index)
+    }
+
+    fn neighbors_sorted(&self, node_index: usize) -> Vec<usize> {
+        self.graph.neighbors_sorted(node_index)
+    }
+
+    /// same as `neighbors_of`
+    fn incoming_edges_of(&self, node_index: usize) -> Vec<usize> {
+        self.graph.outgoing_edges_of(node_index)
+    }
+
+    fn edges(&self) -> Box<dyn Iterator<Item = EdgeMeta<W>> + '_> {
+        self.graph.edges()
+    }
+
+    /// both directions of every edge
+    fn all_edge_pairs(&self) -> Vec<(usize, usize)> {
+        self.graph.all_edge_pairs()
+    }
+
+    fn push_node(&mut self, value: T) -> usize {
+        self.graph.push_node(value)
+    }
+
+    fn set_node(&mut self, node_index: usize, value: T) {
+        self.graph.set_node(node_index, value);
+    }
+
+    fn get_node(&self, node_index: usize) -> &T {
+        self.graph.get_node(node_index)
+    }
+
+    fn remove_node(&mut self, node_index: usize) -> T {
+        self.graph.remove_node(node_index)
+    }
+
+    fn node_count(&self) -> usize {
+        self.graph.node_count()
+    }
+
+    fn resize_nodes(&mut self, count: usize, fill: T)
+    where
+        T: Clone,
+    {
+        self.graph.resize_nodes(count, fill);
+    }
+
+    fn reserve_nodes(&mut self, additional: usize) {
+        self.graph.reserve_nodes(additional);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bit::BitGraph;
+    use crate::hash::HashGraph;
+    use crate::search::bipartite::{odd_cycle, two_color};
+    use crate::search::mst::{kruskal, total_weight};
+
+    fn assert_symmetric<T, W>(graph: &dyn Graph<T, W>) {
+        for from in 0..graph.node_count() {
+            for to in 0..graph.node_count() {
+                assert_eq!(graph.has_edge(from, to), graph.has_edge(to, from));
+            }
+        }
+    }
+
+    #[test]
+    fn mutation_test() {
+        let mut graph = Undirected::new(HashGraph::with_capacity(16));
+        for i in 0..12 {
+            graph.push_node(i);
+        }
+
+        // a fixed pseudo random sequence of adds, removes and node removals
+        let mut state = 12345usize;
+        let mut next = |bound: usize| {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (state >> 33) % bound
+        };
+
+        for step in 0..400 {
+            let count = graph.node_count();
+            let (from, to) = (next(count), next(count));
+
+            match next(10) {
+                0..=4 => {
+                    graph.add_edge(from, to);
+                }
+                5..=6 => {
+                    graph.set_edge((from, to), step);
+                }
+                7..=8 => {
+                    graph.remove_edge(to, from);
+                }
+                _ if count > 4 => {
+                    graph.remove_node(from);
+                    graph.push_node(step as u64);
+                }
+                _ => {}
+            }
+
+            assert_symmetric(&graph);
+            if graph.has_edge(from, to) {
+                assert_eq!(
+                    graph.get_edge(from, to).unwrap().weight,
+                    graph.get_edge(to, from).unwrap().weight
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn edge_count_test() {
+        let mut directed = BitGraph::with_capacity(8);
+        for i in 0..8 {
+            directed.push_node(i);
+        }
+        directed.add_edge(0, 1);
+        directed.add_edge(2, 1);
+        directed.add_edge(1, 2);
+        directed.add_edge(3, 3);
+
+        let mut graph = Undirected::new(directed);
+        assert_symmetric(&graph);
+        assert_eq!(graph.edge_count(), 3);
+        assert_eq!(graph.neighbors_of(1), vec![0, 2]);
+        assert_eq!(graph.incoming_edges_of(1), vec![0, 2]);
+
+        graph.remove_edge(1, 0);
+        graph.add_edge(5, 5);
+        assert_eq!(graph.edge_count(), 3);
+        assert_eq!(graph.neighbors_of(1), vec![2]);
+        assert_eq!(graph.inner().all_edge_pairs().len(), 4);
+    }
+
+    #[test]
+    fn mst_test() {
+        let mut graph = Undirected::new(HashGraph::with_capacity(8));
+        for i in 0..5 {
+            graph.push_node(i);
+        }
+        for &(from, to, weight) in &[
+            (0, 1, 4),
+            (2, 1, 1),
+            (2, 0, 3),
+            (3, 2, 6),
+            (4, 3, 2),
+            (1, 4, 9),
+        ] {
+            graph.set_edge((from, to), weight);
+        }
+
+        let tree = kruskal(&graph);
+        assert_eq!(tree.len(), 4);
+        assert_eq!(total_weight(&tree), 12);
+    }
+
+    #[test]
+    fn bipartite_test() {
+        let mut graph = Undirected::new(BitGraph::with_capacity(8));
+        for i in 0..6 {
+            graph.push_node(i);
+        }
+
+        // a 6 cycle added in alternating directions
+        for i in 0..6 {
+            if i % 2 == 0 {
+                graph.add_edge(i, (i + 1) % 6);
+            } else {
+                graph.add_edge((i + 1) % 6, i);
+            }
+        }
+
+        let colors = two_color(&graph).unwrap();
+        for (from, to) in graph.all_edge_pairs() {
+            assert_ne!(colors[from], colors[to]);
+        }
+
+        graph.add_edge(0, 2);
+        assert!(two_color(&graph).is_none());
+        assert_eq!(odd_cycle(&graph).unwrap().len() % 2, 1);
+    }
+}